@@ -1,8 +1,9 @@
-use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_primitives::{Address, B256, U256};
 use argus_core::types::{AccessEntry, AccessMode, StorageLocation};
 use argus_core::{AccessList, Transaction};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use smallvec::SmallVec;
+use std::sync::Arc;
 
 // ---------------------------------------------------------------------------
 // Helpers
@@ -13,9 +14,8 @@ fn make_tx(i: u64) -> Transaction {
         hash: B256::from(U256::from(i)),
         from: Address::from_word(B256::from(U256::from(i * 1000))),
         to: Some(Address::from_word(B256::from(U256::from(i * 2000)))),
-        input: Bytes::new(),
-        value: U256::ZERO,
         gas: 100_000,
+        ..Default::default()
     }
 }
 
@@ -39,11 +39,17 @@ fn make_access_list(tx_idx: u64, n_entries: usize, overlap_ratio: f64) -> Access
             } else {
                 AccessMode::Read
             },
+            count: 1,
         });
     }
     AccessList {
         tx_hash: B256::from(U256::from(tx_idx)),
         entries,
+        transient: SmallVec::new(),
+        logs: Vec::new(),
+        outcome: Default::default(),
+        gas_used: 0,
+        writes: Vec::new(),
     }
 }
 
@@ -56,10 +62,10 @@ fn bench_simulate_batch(c: &mut Criterion) {
 
     let mut group = c.benchmark_group("simulate_batch");
     for count in [10, 50, 100] {
-        let txs: Vec<Transaction> = (0..count).map(make_tx).collect();
+        let txs: Arc<[Transaction]> = (0..count).map(make_tx).collect();
         group.bench_with_input(BenchmarkId::from_parameter(count), &txs, |b, txs| {
             b.to_async(&rt).iter(|| {
-                let txs = txs.clone();
+                let txs = Arc::clone(txs);
                 async move {
                     black_box(
                         argus_analyzer::simulator::simulate_batch(txs)
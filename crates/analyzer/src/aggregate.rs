@@ -0,0 +1,278 @@
+//! Rolling-window statistics over streams of per-block results.
+//!
+//! Keeps the last `window` blocks in memory and answers rollup queries
+//! (per-protocol conflict share, density percentiles, parallelism score
+//! trend) without a warehouse. Emits an [`AggregateRow`] every
+//! `dump_every` blocks for periodic sink output.
+//!
+//! ```ignore
+//! let mut agg = RollingAggregator::new(100).with_dump_every(10);
+//! if let Some(row) = agg.push(&report, &graph) {
+//!     sink.write_aggregates(&[row])?;
+//! }
+//! ```
+
+use crate::reporter::Report;
+use crate::sink::{AggregateRow, ProtocolShare};
+use argus_core::ConflictGraph;
+use std::collections::{HashMap, VecDeque};
+
+/// Compact per-block statistics retained in the window.
+#[derive(Debug, Clone)]
+pub struct BlockStats {
+    pub block_number: u64,
+    pub total_txs: usize,
+    pub total_conflicts: usize,
+    /// Conflict edges per protocol label.
+    pub protocol_conflicts: HashMap<String, usize>,
+    /// Conflict density of every contention event in the block.
+    pub densities: Vec<f64>,
+    /// Fraction of txs with no conflict edge (1.0 = fully parallel).
+    pub parallelism_score: f64,
+}
+
+impl BlockStats {
+    pub fn from_report(report: &Report, graph: &ConflictGraph) -> Self {
+        let mut protocol_conflicts: HashMap<String, usize> = HashMap::new();
+        for g in &report.groups {
+            *protocol_conflicts.entry(g.protocol.clone()).or_default() += g.conflict_count;
+        }
+
+        let densities = report
+            .to_contention_events(graph)
+            .iter()
+            .map(|ev| ev.conflict_density)
+            .collect();
+
+        let parallelism_score = if report.total_txs == 0 {
+            1.0
+        } else {
+            1.0 - graph.adjacency.len() as f64 / report.total_txs as f64
+        };
+
+        BlockStats {
+            block_number: report.block_number,
+            total_txs: report.total_txs,
+            total_conflicts: report.total_conflicts,
+            protocol_conflicts,
+            densities,
+            parallelism_score,
+        }
+    }
+}
+
+/// In-process rollups over the most recent `window` blocks.
+pub struct RollingAggregator {
+    window: usize,
+    dump_every: Option<usize>,
+    since_dump: usize,
+    blocks: VecDeque<BlockStats>,
+}
+
+impl RollingAggregator {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            dump_every: None,
+            since_dump: 0,
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Emit a snapshot row every `n` pushed blocks.
+    pub fn with_dump_every(mut self, n: usize) -> Self {
+        self.dump_every = Some(n.max(1));
+        self
+    }
+
+    /// Add one block's results. Returns a snapshot when a dump is due.
+    pub fn push(&mut self, report: &Report, graph: &ConflictGraph) -> Option<AggregateRow> {
+        self.push_stats(BlockStats::from_report(report, graph))
+    }
+
+    /// Add precomputed block statistics. Returns a snapshot when a dump is due.
+    pub fn push_stats(&mut self, stats: BlockStats) -> Option<AggregateRow> {
+        if self.blocks.len() == self.window {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(stats);
+
+        let every = self.dump_every?;
+        self.since_dump += 1;
+        if self.since_dump >= every {
+            self.since_dump = 0;
+            Some(self.snapshot())
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Share of all conflict edges in the window per protocol, descending.
+    pub fn protocol_share(&self) -> Vec<(String, f64)> {
+        let mut totals: HashMap<&str, usize> = HashMap::new();
+        for b in &self.blocks {
+            for (protocol, n) in &b.protocol_conflicts {
+                *totals.entry(protocol.as_str()).or_default() += n;
+            }
+        }
+
+        let sum: usize = totals.values().sum();
+        if sum == 0 {
+            return Vec::new();
+        }
+
+        let mut shares: Vec<(String, f64)> = totals
+            .into_iter()
+            .map(|(p, n)| (p.to_string(), n as f64 / sum as f64))
+            .collect();
+        shares.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        shares
+    }
+
+    /// Nearest-rank percentile (`p` in 0..=100) of contention densities.
+    pub fn density_percentile(&self, p: f64) -> f64 {
+        let mut all: Vec<f64> = self
+            .blocks
+            .iter()
+            .flat_map(|b| b.densities.iter().copied())
+            .collect();
+        if all.is_empty() {
+            return 0.0;
+        }
+        all.sort_by(f64::total_cmp);
+
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * all.len() as f64).ceil() as usize;
+        all[rank.saturating_sub(1).min(all.len() - 1)]
+    }
+
+    /// `(block_number, parallelism_score)` for each block in the window.
+    pub fn parallelism_scores(&self) -> Vec<(u64, f64)> {
+        self.blocks
+            .iter()
+            .map(|b| (b.block_number, b.parallelism_score))
+            .collect()
+    }
+
+    /// Least-squares slope of the parallelism score per block.
+    /// Negative means parallelism is degrading.
+    pub fn parallelism_trend(&self) -> f64 {
+        let n = self.blocks.len() as f64;
+        if n < 2.0 {
+            return 0.0;
+        }
+
+        let xs = (0..self.blocks.len()).map(|i| i as f64);
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.mean_parallelism_score();
+
+        let (num, den) = xs
+            .zip(self.blocks.iter().map(|b| b.parallelism_score))
+            .fold((0.0, 0.0), |(num, den), (x, y)| {
+                (
+                    num + (x - mean_x) * (y - mean_y),
+                    den + (x - mean_x) * (x - mean_x),
+                )
+            });
+        num / den
+    }
+
+    pub fn mean_parallelism_score(&self) -> f64 {
+        if self.blocks.is_empty() {
+            return 1.0;
+        }
+        self.blocks.iter().map(|b| b.parallelism_score).sum::<f64>() / self.blocks.len() as f64
+    }
+
+    /// Flatten the current window into a sink-ready row.
+    pub fn snapshot(&self) -> AggregateRow {
+        let round = |v: f64| (v * 10_000.0).round() / 10_000.0;
+
+        AggregateRow {
+            window_start: self.blocks.front().map_or(0, |b| b.block_number),
+            window_end: self.blocks.back().map_or(0, |b| b.block_number),
+            blocks: self.blocks.len() as u32,
+            total_txs: self.blocks.iter().map(|b| b.total_txs as u64).sum(),
+            total_conflicts: self.blocks.iter().map(|b| b.total_conflicts as u64).sum(),
+            density_p50: round(self.density_percentile(50.0)),
+            density_p90: round(self.density_percentile(90.0)),
+            density_p99: round(self.density_percentile(99.0)),
+            mean_parallelism_score: round(self.mean_parallelism_score()),
+            parallelism_trend: round(self.parallelism_trend()),
+            protocol_shares: self
+                .protocol_share()
+                .into_iter()
+                .map(|(protocol, share)| ProtocolShare {
+                    protocol,
+                    share: round(share),
+                })
+                .collect(),
+            created_at: crate::sink::chrono_now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(block: u64, score: f64, densities: Vec<f64>, protocol: &str, n: usize) -> BlockStats {
+        BlockStats {
+            block_number: block,
+            total_txs: 10,
+            total_conflicts: n,
+            protocol_conflicts: HashMap::from([(protocol.to_string(), n)]),
+            densities,
+            parallelism_score: score,
+        }
+    }
+
+    #[test]
+    fn window_evicts_oldest() {
+        let mut agg = RollingAggregator::new(2);
+        agg.push_stats(stats(1, 1.0, vec![], "A", 1));
+        agg.push_stats(stats(2, 1.0, vec![], "A", 1));
+        agg.push_stats(stats(3, 1.0, vec![], "A", 1));
+
+        assert_eq!(agg.len(), 2);
+        let row = agg.snapshot();
+        assert_eq!(row.window_start, 2);
+        assert_eq!(row.window_end, 3);
+    }
+
+    #[test]
+    fn protocol_share_and_percentiles() {
+        let mut agg = RollingAggregator::new(10);
+        agg.push_stats(stats(1, 1.0, vec![1.0, 2.0], "A", 3));
+        agg.push_stats(stats(2, 1.0, vec![3.0, 4.0], "B", 1));
+
+        let shares = agg.protocol_share();
+        assert_eq!(shares[0], ("A".to_string(), 0.75));
+        assert_eq!(agg.density_percentile(50.0), 2.0);
+        assert_eq!(agg.density_percentile(100.0), 4.0);
+    }
+
+    #[test]
+    fn trend_detects_degradation() {
+        let mut agg = RollingAggregator::new(10);
+        for (i, s) in [0.9, 0.8, 0.7].into_iter().enumerate() {
+            agg.push_stats(stats(i as u64, s, vec![], "A", 1));
+        }
+        assert!((agg.parallelism_trend() + 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dumps_periodically() {
+        let mut agg = RollingAggregator::new(10).with_dump_every(2);
+        assert!(agg.push_stats(stats(1, 1.0, vec![], "A", 1)).is_none());
+        assert!(agg.push_stats(stats(2, 1.0, vec![], "A", 1)).is_some());
+        assert!(agg.push_stats(stats(3, 1.0, vec![], "A", 1)).is_none());
+    }
+}
@@ -0,0 +1,700 @@
+//! Block-STM emulation: optimistic parallel execution of a real block.
+//!
+//! Where [`crate::concurrency`] estimates Block-STM from the conflict graph,
+//! this runs it. Worker threads execute transactions out of order against a
+//! multi-version memory, which answers every read with the latest write of
+//! a lower transaction (or the warm state). Each execution records where its
+//! reads came from; validation re-resolves them, and a transaction whose
+//! reads changed is aborted, its writes marked as estimates, and run again.
+//! A transaction that reads an estimate waits for that transaction to
+//! re-execute. When the run ends every transaction has committed what a
+//! serial run would have, so the access lists match
+//! [`simulate_batch_sequential`](crate::simulator::simulate_batch_sequential).
+//!
+//! Simplifications: an execution that reads an estimate runs to completion
+//! before being discarded (Block-STM suspends it right away), storage wiped
+//! by a self-destruct is not versioned, and the block beneficiary's balance
+//! is read from the warm state, since every fee payment writes it.
+//!
+//! ```ignore
+//! let (lists, report) = execute_block_stm(&warm_db, &txs, None, None, 8, None)?;
+//! println!("{} re-executions, {:.2}x", report.reexecutions, report.speedup);
+//! ```
+
+use crate::plugin::ReportSection;
+use crate::simulator::{block_env, execute_tx};
+use alloy_primitives::{Address, B256, U256};
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::{AccessList, BlockContext, Transaction};
+use revm::database::CacheDB;
+use revm::database_interface::DatabaseRef;
+use revm::primitives::hardfork::SpecId;
+use revm::state::{AccountInfo, EvmState};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Transactions listed in [`BlockStmReport::most_reexecuted`].
+pub const TOP_REEXECUTED: usize = 5;
+
+/// What one Block-STM run of a block took.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStmReport {
+    pub threads: usize,
+    pub total_txs: usize,
+    /// Incarnations run, aborted ones included.
+    pub executions: usize,
+    /// Executions beyond the first of each transaction.
+    pub reexecutions: usize,
+    /// Incarnations whose reads changed after they ran.
+    pub validation_aborts: usize,
+    /// Executions that read an aborted transaction's pending write and had
+    /// to wait for its next incarnation.
+    pub dependency_waits: usize,
+    pub validations: usize,
+    /// Transactions executed more than once, most first; at most
+    /// [`TOP_REEXECUTED`].
+    pub most_reexecuted: Vec<(B256, usize)>,
+    /// Execution time of the committed incarnations: what a serial run costs.
+    pub serial_ms: f64,
+    /// Execution time across all workers, aborted incarnations included.
+    pub work_ms: f64,
+    pub wall_ms: f64,
+    /// Serial time over wall time.
+    pub speedup: f64,
+}
+
+impl BlockStmReport {
+    /// Report section with the run's counters.
+    pub fn section(&self) -> ReportSection {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} txs on {} threads: {:.2}x ({:.1} ms serial, {:.1} ms wall)",
+            self.total_txs, self.threads, self.speedup, self.serial_ms, self.wall_ms
+        );
+        let _ = writeln!(
+            out,
+            "{} executions ({} re-executions) | {} validation aborts | {} dependency waits",
+            self.executions, self.reexecutions, self.validation_aborts, self.dependency_waits
+        );
+        for (hash, runs) in &self.most_reexecuted {
+            let _ = writeln!(out, "{}…  {runs} runs", &format!("{hash}")[..18]);
+        }
+        let mut section = ReportSection::new(format!("Block-STM ({} threads)", self.threads));
+        for line in out.lines() {
+            section = section.line(line);
+        }
+        section.with_data(serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+/// Run `transactions` under Block-STM on `threads` workers over `warm_db`,
+/// returning each transaction's committed access list and the run's
+/// counters. Once `cancel` fires the run stops with
+/// [`ArgusError::Cancelled`].
+pub fn execute_block_stm<ExtDB>(
+    warm_db: &CacheDB<ExtDB>,
+    transactions: &[Transaction],
+    spec: Option<SpecId>,
+    block: Option<&BlockContext>,
+    threads: usize,
+    cancel: Option<&CancellationToken>,
+) -> ArgusResult<(Vec<AccessList>, BlockStmReport)>
+where
+    ExtDB: DatabaseRef + Sync,
+    ExtDB::Error: core::fmt::Debug,
+{
+    let threads = threads.max(1);
+    let n = transactions.len();
+    tracing::info!(txs = n, threads, "block-stm execution");
+
+    let beneficiary = block.map(|b| b.coinbase).unwrap_or_default();
+    let block = block.map(|block| block_env(block, spec));
+    let memory = MvMemory::new(n);
+    let scheduler = Scheduler::new(n);
+    let committed: Vec<Mutex<Option<(AccessList, Duration)>>> =
+        (0..n).map(|_| Mutex::new(None)).collect();
+
+    let started = Instant::now();
+    let worker = || -> ArgusResult<()> {
+        while let Some(task) = scheduler.next_task(cancel) {
+            match task {
+                Task::Execute(i, incarnation) => {
+                    let tx = &transactions[i];
+                    let db = MvDb::new(warm_db, &memory, i, beneficiary);
+                    let began = Instant::now();
+                    let result = execute_tx(tx, &db, spec, block.as_ref(), &[], None);
+                    let elapsed = began.elapsed();
+                    let (list, _, state) = match result.for_tx(tx.hash) {
+                        Ok(done) => done,
+                        Err(e) => {
+                            scheduler.fail();
+                            return Err(e);
+                        }
+                    };
+                    if let Some(dep) = db.blocked_on.get() {
+                        scheduler.wait_for(i, dep, elapsed);
+                        continue;
+                    }
+                    let writes = state
+                        .as_ref()
+                        .map(|state| db.write_set(state))
+                        .unwrap_or_default();
+                    let reads = db.reads.into_inner();
+                    let new_location = memory.record(i, incarnation, reads, writes);
+                    *lock(&committed[i]) = Some((list, elapsed));
+                    scheduler.finish_execution(i, new_location, elapsed);
+                }
+                Task::Validate(i, incarnation) => {
+                    let valid = memory.validate(i);
+                    scheduler.finish_validation(i, incarnation, valid, &memory);
+                }
+            }
+        }
+        Ok(())
+    };
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+        workers.into_iter().try_for_each(|w| {
+            w.join()
+                .map_err(|_| ArgusError::Internal("block-stm worker panicked".into()))?
+        })
+    })?;
+    if cancel.is_some_and(|c| c.is_cancelled()) {
+        return Err(ArgusError::Cancelled);
+    }
+    let wall = started.elapsed();
+
+    let mut access_lists = Vec::with_capacity(n);
+    let mut serial = Duration::ZERO;
+    for (i, slot) in committed.into_iter().enumerate() {
+        let (list, elapsed) = slot
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .ok_or_else(|| ArgusError::Internal(format!("block-stm left tx {i} uncommitted")))?;
+        serial += elapsed;
+        access_lists.push(list);
+    }
+
+    let state = scheduler.into_state();
+    let mut most_reexecuted: Vec<(B256, usize)> = state
+        .incarnation
+        .iter()
+        .zip(transactions)
+        .filter(|(incarnation, _)| **incarnation > 0)
+        .map(|(&incarnation, tx)| (tx.hash, incarnation + 1))
+        .collect();
+    most_reexecuted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    most_reexecuted.truncate(TOP_REEXECUTED);
+
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let report = BlockStmReport {
+        threads,
+        total_txs: n,
+        executions: state.executions,
+        reexecutions: state.executions.saturating_sub(n),
+        validation_aborts: state.validation_aborts,
+        dependency_waits: state.dependency_waits,
+        validations: state.validations,
+        most_reexecuted,
+        serial_ms: ms(serial),
+        work_ms: ms(state.work),
+        wall_ms: ms(wall),
+        speedup: if wall.is_zero() {
+            1.0
+        } else {
+            serial.as_secs_f64() / wall.as_secs_f64()
+        },
+    };
+    tracing::info!(
+        executions = report.executions,
+        aborts = report.validation_aborts,
+        waits = report.dependency_waits,
+        speedup = report.speedup,
+        "block-stm complete"
+    );
+    Ok((access_lists, report))
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ---------------------------------------------------------------------------
+// Multi-version memory
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Account(Address),
+    Slot(Address, U256),
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    /// `None` once the account self-destructed.
+    Account(Option<AccountInfo>),
+    Slot(U256),
+}
+
+#[derive(Debug, Clone)]
+enum Version {
+    Written {
+        incarnation: usize,
+        value: Value,
+    },
+    /// Written by an incarnation since aborted; likely rewritten.
+    Estimate,
+}
+
+/// Where a read was answered from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    Base,
+    Tx {
+        index: usize,
+        incarnation: usize,
+    },
+    /// An estimate of `index`; never valid.
+    Estimate(usize),
+}
+
+/// Every transaction's writes, versioned by block position.
+struct MvMemory {
+    data: Mutex<HashMap<Key, BTreeMap<usize, Version>>>,
+    /// Keys each transaction's last incarnation wrote.
+    written: Vec<Mutex<Vec<Key>>>,
+    /// Reads of each transaction's last incarnation.
+    reads: Vec<Mutex<Vec<(Key, Origin)>>>,
+}
+
+impl MvMemory {
+    fn new(n: usize) -> Self {
+        Self {
+            data: Mutex::default(),
+            written: (0..n).map(|_| Mutex::default()).collect(),
+            reads: (0..n).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Latest write below `tx`, or `None` for the warm state.
+    fn read(&self, key: &Key, tx: usize) -> (Origin, Option<Value>) {
+        let data = lock(&self.data);
+        let latest = data
+            .get(key)
+            .and_then(|versions| versions.range(..tx).next_back());
+        match latest {
+            None => (Origin::Base, None),
+            Some((&index, Version::Estimate)) => (Origin::Estimate(index), None),
+            Some((&index, Version::Written { incarnation, value })) => (
+                Origin::Tx {
+                    index,
+                    incarnation: *incarnation,
+                },
+                Some(value.clone()),
+            ),
+        }
+    }
+
+    /// Publish an incarnation's reads and writes, dropping writes of the
+    /// previous incarnation it no longer makes. True if it wrote a key the
+    /// previous one did not.
+    fn record(
+        &self,
+        tx: usize,
+        incarnation: usize,
+        reads: Vec<(Key, Origin)>,
+        writes: Vec<(Key, Value)>,
+    ) -> bool {
+        *lock(&self.reads[tx]) = reads;
+        let mut written = lock(&self.written[tx]);
+        let mut data = lock(&self.data);
+        let keys: Vec<Key> = writes.iter().map(|(key, _)| key.clone()).collect();
+        for stale in written.iter().filter(|key| !keys.contains(key)) {
+            if let Some(versions) = data.get_mut(stale) {
+                versions.remove(&tx);
+            }
+        }
+        let new_location = keys.iter().any(|key| !written.contains(key));
+        for (key, value) in writes {
+            data.entry(key)
+                .or_default()
+                .insert(tx, Version::Written { incarnation, value });
+        }
+        *written = keys;
+        new_location
+    }
+
+    /// Turn `tx`'s writes into estimates after it aborted.
+    fn mark_estimates(&self, tx: usize) {
+        let written = lock(&self.written[tx]);
+        let mut data = lock(&self.data);
+        for key in written.iter() {
+            if let Some(version) = data.get_mut(key).and_then(|v| v.get_mut(&tx)) {
+                *version = Version::Estimate;
+            }
+        }
+    }
+
+    /// Whether every read of `tx`'s last incarnation would still resolve to
+    /// the same version.
+    fn validate(&self, tx: usize) -> bool {
+        let reads = lock(&self.reads[tx]);
+        reads.iter().all(|(key, origin)| {
+            !matches!(origin, Origin::Estimate(_)) && self.read(key, tx).0 == *origin
+        })
+    }
+}
+
+/// One incarnation's view of the block: multi-version memory over the warm
+/// state, noting where each read came from.
+struct MvDb<'a, ExtDB> {
+    base: &'a CacheDB<ExtDB>,
+    memory: &'a MvMemory,
+    tx: usize,
+    beneficiary: Address,
+    reads: RefCell<Vec<(Key, Origin)>>,
+    /// Accounts as this incarnation saw them, to tell real writes from
+    /// accounts that were merely loaded.
+    accounts: RefCell<HashMap<Address, Option<AccountInfo>>>,
+    /// First transaction whose estimate this incarnation read.
+    blocked_on: Cell<Option<usize>>,
+}
+
+impl<'a, ExtDB> MvDb<'a, ExtDB> {
+    fn new(
+        base: &'a CacheDB<ExtDB>,
+        memory: &'a MvMemory,
+        tx: usize,
+        beneficiary: Address,
+    ) -> Self {
+        Self {
+            base,
+            memory,
+            tx,
+            beneficiary,
+            reads: RefCell::default(),
+            accounts: RefCell::default(),
+            blocked_on: Cell::new(None),
+        }
+    }
+
+    fn read(&self, key: Key) -> Option<Value> {
+        let (origin, value) = self.memory.read(&key, self.tx);
+        if let Origin::Estimate(index) = origin {
+            if self.blocked_on.get().is_none() {
+                self.blocked_on.set(Some(index));
+            }
+        }
+        self.reads.borrow_mut().push((key, origin));
+        value
+    }
+
+    /// Accounts and slots `state` changed relative to what was read.
+    fn write_set(&self, state: &EvmState) -> Vec<(Key, Value)> {
+        let accounts = self.accounts.borrow();
+        let mut writes = Vec::new();
+        for (&address, account) in state {
+            if address == self.beneficiary {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                writes.push((Key::Account(address), Value::Account(None)));
+                continue;
+            }
+            let unchanged = match accounts.get(&address) {
+                Some(Some(read)) => {
+                    read.balance == account.info.balance
+                        && read.nonce == account.info.nonce
+                        && read.code_hash == account.info.code_hash
+                }
+                _ => account.info.is_empty(),
+            };
+            if !unchanged {
+                writes.push((
+                    Key::Account(address),
+                    Value::Account(Some(account.info.clone())),
+                ));
+            }
+            for (&index, slot) in &account.storage {
+                if slot.present_value != slot.original_value {
+                    writes.push((Key::Slot(address, index), Value::Slot(slot.present_value)));
+                }
+            }
+        }
+        writes
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for MvDb<'_, ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let versioned = if address == self.beneficiary {
+            None
+        } else {
+            self.read(Key::Account(address))
+        };
+        let info = match versioned {
+            Some(Value::Account(info)) => info,
+            _ => self.base.basic_ref(address)?,
+        };
+        self.accounts.borrow_mut().insert(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<revm::state::Bytecode, Self::Error> {
+        self.base.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.read(Key::Slot(address, index)) {
+            Some(Value::Slot(value)) => Ok(value),
+            _ => self.base.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.base.block_hash_ref(number)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scheduler
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy)]
+enum Task {
+    Execute(usize, usize),
+    Validate(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ready,
+    Executing,
+    Executed,
+    /// Waiting for another transaction to re-execute.
+    Waiting,
+}
+
+struct SchedulerState {
+    status: Vec<Status>,
+    incarnation: Vec<usize>,
+    ready: BTreeSet<usize>,
+    /// Executed transactions whose reads need checking.
+    validate: BTreeSet<usize>,
+    /// Transactions waiting on each transaction's next execution.
+    dependents: Vec<Vec<usize>>,
+    /// Tasks handed out and not yet finished.
+    active: usize,
+    failed: bool,
+    executions: usize,
+    validations: usize,
+    validation_aborts: usize,
+    dependency_waits: usize,
+    work: Duration,
+}
+
+/// Hands out the lowest-indexed pending task, validation or execution, as
+/// Block-STM does. One lock guards it all; execution dominates anyway.
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+    wake: Condvar,
+}
+
+impl Scheduler {
+    fn new(n: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                status: vec![Status::Ready; n],
+                incarnation: vec![0; n],
+                ready: (0..n).collect(),
+                validate: BTreeSet::new(),
+                dependents: vec![Vec::new(); n],
+                active: 0,
+                failed: false,
+                executions: 0,
+                validations: 0,
+                validation_aborts: 0,
+                dependency_waits: 0,
+                work: Duration::ZERO,
+            }),
+            wake: Condvar::new(),
+        }
+    }
+
+    fn into_state(self) -> SchedulerState {
+        self.state.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Next task, blocking while others are in flight; `None` once every
+    /// transaction has executed and validated, or the run was stopped.
+    fn next_task(&self, cancel: Option<&CancellationToken>) -> Option<Task> {
+        let mut s = lock(&self.state);
+        loop {
+            if s.failed || cancel.is_some_and(|c| c.is_cancelled()) {
+                s.failed = true;
+                self.wake.notify_all();
+                return None;
+            }
+            let validate = s.validate.first().copied();
+            let execute = s.ready.first().copied();
+            let task = match (validate, execute) {
+                (Some(v), Some(e)) if e < v => Some(Task::Execute(e, s.incarnation[e])),
+                (Some(v), _) => Some(Task::Validate(v, s.incarnation[v])),
+                (None, Some(e)) => Some(Task::Execute(e, s.incarnation[e])),
+                (None, None) => None,
+            };
+            match task {
+                Some(Task::Execute(i, _)) => {
+                    s.ready.remove(&i);
+                    s.status[i] = Status::Executing;
+                }
+                Some(Task::Validate(i, _)) => {
+                    s.validate.remove(&i);
+                }
+                None if s.active == 0 => {
+                    self.wake.notify_all();
+                    return None;
+                }
+                None => {
+                    s = self.wake.wait(s).unwrap_or_else(|e| e.into_inner());
+                    continue;
+                }
+            }
+            s.active += 1;
+            return task;
+        }
+    }
+
+    fn fail(&self) {
+        lock(&self.state).failed = true;
+        self.wake.notify_all();
+    }
+
+    fn finish_execution(&self, tx: usize, new_location: bool, elapsed: Duration) {
+        let mut s = lock(&self.state);
+        s.executions += 1;
+        s.work += elapsed;
+        s.status[tx] = Status::Executed;
+        for dep in std::mem::take(&mut s.dependents[tx]) {
+            s.incarnation[dep] += 1;
+            s.status[dep] = Status::Ready;
+            s.ready.insert(dep);
+        }
+        s.validate.insert(tx);
+        if new_location {
+            // Later transactions may have missed the new write.
+            Self::revalidate_after(&mut s, tx);
+        }
+        s.active -= 1;
+        self.wake.notify_all();
+    }
+
+    /// `tx` read an estimate of `dep`: run it again once `dep` has.
+    fn wait_for(&self, tx: usize, dep: usize, elapsed: Duration) {
+        let mut s = lock(&self.state);
+        s.executions += 1;
+        s.work += elapsed;
+        s.dependency_waits += 1;
+        if s.status[dep] == Status::Executed {
+            s.incarnation[tx] += 1;
+            s.status[tx] = Status::Ready;
+            s.ready.insert(tx);
+        } else {
+            s.status[tx] = Status::Waiting;
+            s.dependents[dep].push(tx);
+        }
+        s.active -= 1;
+        self.wake.notify_all();
+    }
+
+    fn finish_validation(&self, tx: usize, incarnation: usize, valid: bool, memory: &MvMemory) {
+        let mut s = lock(&self.state);
+        s.validations += 1;
+        // A stale validation (the incarnation already aborted) is moot.
+        if !valid && s.status[tx] == Status::Executed && s.incarnation[tx] == incarnation {
+            s.validation_aborts += 1;
+            memory.mark_estimates(tx);
+            s.incarnation[tx] += 1;
+            s.status[tx] = Status::Ready;
+            s.ready.insert(tx);
+            Self::revalidate_after(&mut s, tx);
+        }
+        s.active -= 1;
+        self.wake.notify_all();
+    }
+
+    fn revalidate_after(s: &mut SchedulerState, tx: usize) {
+        let executed: Vec<usize> = (tx + 1..s.status.len())
+            .filter(|&i| s.status[i] == Status::Executed)
+            .collect();
+        s.validate.extend(executed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::tests::deploy;
+    use crate::simulator::WarmCacheDB;
+    use alloy_primitives::Bytes;
+
+    /// Transactions from distinct senders that each add one to slot 0 of
+    /// the same counter contract.
+    fn counter_block(n: u8) -> (WarmCacheDB, Vec<Transaction>) {
+        // PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE STOP
+        let code =
+            Bytes::from_static(&[0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, 0x00]);
+        let (target, warm) = deploy(code);
+        let txs = (1..=n)
+            .map(|i| Transaction {
+                hash: B256::with_last_byte(i),
+                from: Address::with_last_byte(0x80 + i),
+                to: Some(target),
+                gas: 100_000,
+                ..Default::default()
+            })
+            .collect();
+        (warm, txs)
+    }
+
+    #[test]
+    fn commits_serial_results() {
+        let (warm, txs) = counter_block(6);
+        let (lists, report) = execute_block_stm(&warm, &txs, None, None, 4, None).unwrap();
+        assert_eq!(lists.len(), 6);
+        for (i, list) in lists.iter().enumerate() {
+            assert_eq!(list.tx_hash, txs[i].hash);
+            let write = &list.writes[0];
+            assert_eq!(write.original, B256::from(U256::from(i)));
+            assert_eq!(write.value, B256::from(U256::from(i + 1)));
+        }
+        assert_eq!(report.executions, 6 + report.reexecutions);
+        assert!(report.validations >= 6);
+    }
+
+    #[test]
+    fn one_thread_never_reexecutes() {
+        let (warm, txs) = counter_block(4);
+        let (_, report) = execute_block_stm(&warm, &txs, None, None, 1, None).unwrap();
+        assert_eq!(report.executions, 4);
+        assert_eq!(report.reexecutions, 0);
+        assert!(report.most_reexecuted.is_empty());
+    }
+
+    #[test]
+    fn cancelled_run_stops() {
+        let (warm, txs) = counter_block(2);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = execute_block_stm(&warm, &txs, None, None, 2, Some(&cancel));
+        assert!(matches!(result, Err(ArgusError::Cancelled)));
+    }
+}
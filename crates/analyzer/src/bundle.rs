@@ -0,0 +1,207 @@
+//! Bundle inclusion simulation for searcher tooling.
+//!
+//! Simulates an ordered candidate bundle against a head-state snapshot and
+//! classifies the resulting conflict edges: within the bundle, against a
+//! set of competing txs, and on hot pools the bundle would serialize.
+//!
+//! ```ignore
+//! let analysis = simulate_bundle(&warm_db, &bundle, &competing)?;
+//! if analysis.forces_serialization {
+//!     // bundle writes a pool that many competing txs also touch
+//! }
+//! ```
+
+use crate::graph::build_conflict_graph;
+use crate::simulator::{simulate_batch_with_state, WarmCacheDB};
+use alloy_primitives::B256;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, AccessMode, Conflict, StorageLocation, Transaction};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Minimum competing accessors on a bundle-written location for it to count
+/// as a hot pool the bundle would serialize.
+pub const DEFAULT_HOT_POOL_THRESHOLD: usize = 3;
+
+/// A location written by the bundle and contended by competing txs.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotPool {
+    pub location: StorageLocation,
+    /// Bundle txs writing this location.
+    pub bundle_writers: usize,
+    /// Competing txs reading or writing this location.
+    pub competing_accessors: usize,
+}
+
+/// Result of simulating a candidate bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleAnalysis {
+    /// Per-tx access lists, in bundle order.
+    pub access_lists: Vec<AccessList>,
+    /// Edges where both endpoints are bundle txs.
+    pub intra_conflicts: Vec<Conflict>,
+    /// Edges between a bundle tx and a competing tx.
+    pub competing_conflicts: Vec<Conflict>,
+    /// Competing txs conflicting with at least one bundle tx.
+    pub conflicting_competitors: usize,
+    /// Sorted by competing accessors, hottest first.
+    pub hot_pools: Vec<HotPool>,
+    /// True if the bundle writes at least one hot pool.
+    pub forces_serialization: bool,
+}
+
+/// Simulate `bundle` against `state` and classify its conflicts against
+/// `competing`, using [`DEFAULT_HOT_POOL_THRESHOLD`].
+///
+/// Each tx runs in its own overlay over `state`, so access lists reflect
+/// head state rather than the bundle's own prior writes.
+pub fn simulate_bundle(
+    state: &WarmCacheDB,
+    bundle: &[Transaction],
+    competing: &[Transaction],
+) -> ArgusResult<BundleAnalysis> {
+    simulate_bundle_with_threshold(state, bundle, competing, DEFAULT_HOT_POOL_THRESHOLD)
+}
+
+/// Like [`simulate_bundle`] with an explicit hot-pool threshold.
+pub fn simulate_bundle_with_threshold(
+    state: &WarmCacheDB,
+    bundle: &[Transaction],
+    competing: &[Transaction],
+    hot_pool_threshold: usize,
+) -> ArgusResult<BundleAnalysis> {
+    let bundle_lists = simulate_batch_with_state(state, bundle)?;
+    let competing_lists = simulate_batch_with_state(state, competing)?;
+    Ok(classify(bundle_lists, &competing_lists, hot_pool_threshold))
+}
+
+/// Classify conflicts for already-simulated bundle and competing access lists.
+pub fn classify(
+    bundle_lists: Vec<AccessList>,
+    competing_lists: &[AccessList],
+    hot_pool_threshold: usize,
+) -> BundleAnalysis {
+    let bundle_hashes: HashSet<B256> = bundle_lists.iter().map(|al| al.tx_hash).collect();
+
+    let mut all = bundle_lists.clone();
+    all.extend(competing_lists.iter().cloned());
+    let graph = build_conflict_graph(&all);
+
+    let mut intra_conflicts = Vec::new();
+    let mut competing_conflicts = Vec::new();
+    let mut competitors = HashSet::new();
+
+    for c in graph.conflicts {
+        match (
+            bundle_hashes.contains(&c.tx_a),
+            bundle_hashes.contains(&c.tx_b),
+        ) {
+            (true, true) => intra_conflicts.push(c),
+            (true, false) => {
+                competitors.insert(c.tx_b);
+                competing_conflicts.push(c);
+            }
+            (false, true) => {
+                competitors.insert(c.tx_a);
+                competing_conflicts.push(c);
+            }
+            (false, false) => {}
+        }
+    }
+
+    // Hot pools: bundle-written locations with many competing accessors.
+    let mut writers: HashMap<&StorageLocation, usize> = HashMap::new();
+    for al in &bundle_lists {
+        for e in al.entries.iter().filter(|e| e.mode == AccessMode::Write) {
+            *writers.entry(&e.location).or_default() += 1;
+        }
+    }
+
+    let mut accessors: HashMap<&StorageLocation, usize> = HashMap::new();
+    for al in competing_lists {
+        for e in &al.entries {
+            if writers.contains_key(&e.location) {
+                *accessors.entry(&e.location).or_default() += 1;
+            }
+        }
+    }
+
+    let mut hot_pools: Vec<HotPool> = accessors
+        .into_iter()
+        .filter(|(_, n)| *n >= hot_pool_threshold)
+        .map(|(loc, n)| HotPool {
+            location: loc.clone(),
+            bundle_writers: writers[loc],
+            competing_accessors: n,
+        })
+        .collect();
+    hot_pools.sort_by(|a, b| b.competing_accessors.cmp(&a.competing_accessors));
+
+    BundleAnalysis {
+        forces_serialization: !hot_pools.is_empty(),
+        conflicting_competitors: competitors.len(),
+        access_lists: bundle_lists,
+        intra_conflicts,
+        competing_conflicts,
+        hot_pools,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use argus_core::AccessEntry;
+    use smallvec::SmallVec;
+
+    fn list(tx: u64, entries: &[(u64, AccessMode)]) -> AccessList {
+        AccessList {
+            tx_hash: B256::from(U256::from(tx)),
+            entries: entries
+                .iter()
+                .map(|&(slot, mode)| AccessEntry {
+                    location: StorageLocation {
+                        address: Address::ZERO,
+                        slot: B256::from(U256::from(slot)),
+                    },
+                    mode,
+                    count: 1,
+                })
+                .collect::<SmallVec<_>>(),
+            transient: SmallVec::new(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn splits_intra_and_competing_edges() {
+        let bundle = vec![
+            list(1, &[(1, AccessMode::Write)]),
+            list(2, &[(1, AccessMode::Read)]),
+        ];
+        let competing = vec![list(10, &[(1, AccessMode::Read)])];
+
+        let a = classify(bundle, &competing, DEFAULT_HOT_POOL_THRESHOLD);
+        assert_eq!(a.intra_conflicts.len(), 1);
+        assert_eq!(a.competing_conflicts.len(), 1);
+        assert_eq!(a.conflicting_competitors, 1);
+        assert!(!a.forces_serialization);
+    }
+
+    #[test]
+    fn detects_hot_pool() {
+        let bundle = vec![list(1, &[(5, AccessMode::Write)])];
+        let competing: Vec<_> = (10..13)
+            .map(|i| list(i, &[(5, AccessMode::Read)]))
+            .collect();
+
+        let a = classify(bundle, &competing, 3);
+        assert!(a.forces_serialization);
+        assert_eq!(a.hot_pools.len(), 1);
+        assert_eq!(a.hot_pools[0].competing_accessors, 3);
+        assert_eq!(a.hot_pools[0].bundle_writers, 1);
+    }
+}
@@ -0,0 +1,162 @@
+//! On-disk cache of per-block analysis results.
+//!
+//! Simulation is the expensive stage and is deterministic for a given
+//! block, so access lists and conflict graphs are stored as JSON keyed by
+//! `(chain_id, block_hash, analyzer_version, mode)`. Keying by hash rather
+//! than number means a reorged block never serves stale results.
+//!
+//! Layout: `<dir>/<chain_id>/<analyzer_version>/<mode>/<block_hash>.json`.
+
+use alloy_primitives::B256;
+use argus_core::error::ArgusResult;
+use argus_core::json_file;
+use argus_core::{AccessList, ConflictGraph};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Bumped with the crate version so format or semantics changes never mix.
+pub const ANALYZER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Identity of a cached analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub chain_id: u64,
+    pub block_hash: B256,
+    pub analyzer_version: &'static str,
+    /// Simulation mode, e.g. `"warm"` or `"dry"`, and anything else the
+    /// result depends on.
+    pub mode: String,
+}
+
+impl CacheKey {
+    pub fn new(chain_id: u64, block_hash: B256, mode: impl Into<String>) -> Self {
+        Self {
+            chain_id,
+            block_hash,
+            analyzer_version: ANALYZER_VERSION,
+            mode: mode.into(),
+        }
+    }
+}
+
+/// Cached simulation output for one block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub block_number: u64,
+    /// Header timestamp, when it was known (absent in older entries).
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    pub access_lists: Vec<AccessList>,
+    pub graph: ConflictGraph,
+}
+
+/// Filesystem-backed result cache.
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/argus`, falling back to `$HOME/.cache/argus`.
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))
+            .map(|base| base.join("argus"))
+    }
+
+    pub fn path_for(&self, key: &CacheKey) -> PathBuf {
+        self.dir
+            .join(key.chain_id.to_string())
+            .join(key.analyzer_version)
+            .join(&key.mode)
+            .join(format!("{}.json", key.block_hash))
+    }
+
+    /// Returns `Ok(None)` on a miss. A corrupt entry is treated as a miss.
+    pub fn get(&self, key: &CacheKey) -> ArgusResult<Option<CachedAnalysis>> {
+        json_file::read(&self.path_for(key), "cache entry")
+    }
+
+    /// Store an entry atomically (see [`json_file`]).
+    pub fn put(&self, key: &CacheKey, value: &CachedAnalysis) -> ArgusResult<()> {
+        json_file::write(&self.path_for(key), value, "cache entry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use argus_core::{AccessEntry, AccessMode, StorageLocation};
+    use smallvec::smallvec;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("argus-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn roundtrip_and_miss() {
+        let dir = temp_dir("roundtrip");
+        let cache = ResultCache::new(&dir);
+        let key = CacheKey::new(1, B256::from(U256::from(42)), "warm");
+
+        assert!(cache.get(&key).unwrap().is_none());
+
+        let lists = vec![AccessList {
+            tx_hash: B256::from(U256::from(7)),
+            entries: smallvec![AccessEntry {
+                location: StorageLocation {
+                    address: Address::ZERO,
+                    slot: B256::ZERO,
+                },
+                mode: AccessMode::Write,
+                count: 1,
+            }],
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }];
+        let value = CachedAnalysis {
+            block_number: 100,
+            timestamp: Some(1_200),
+            graph: crate::graph::build_conflict_graph(&lists),
+            access_lists: lists,
+        };
+        cache.put(&key, &value).unwrap();
+
+        let hit = cache.get(&key).unwrap().unwrap();
+        assert_eq!(hit.block_number, 100);
+        assert_eq!(hit.access_lists[0].entries.len(), 1);
+
+        // Different mode is a different entry.
+        assert!(cache
+            .get(&CacheKey::new(1, key.block_hash, "dry"))
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_entry_is_a_miss() {
+        let dir = temp_dir("corrupt");
+        let cache = ResultCache::new(&dir);
+        let key = CacheKey::new(1, B256::ZERO, "warm");
+
+        let path = cache.path_for(&key);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"not json").unwrap();
+
+        assert!(cache.get(&key).unwrap().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
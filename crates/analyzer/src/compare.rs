@@ -0,0 +1,363 @@
+//! Accuracy comparison between dry-run and warm-state simulation.
+//!
+//! Diffs the access lists and conflict graphs produced by the `EmptyDB`
+//! path and the prefetched-state path for the same block, quantifying how
+//! much state prefetching changes what the simulator observes.
+
+use alloy_primitives::B256;
+use argus_core::{AccessList, ConflictGraph, ConflictKind, StorageLocation};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Per-transaction access list delta (warm relative to dry-run).
+#[derive(Debug, Clone, Serialize)]
+pub struct TxAccessDiff {
+    pub tx_hash: B256,
+    /// Locations only seen with warm state.
+    pub added: usize,
+    /// Locations only seen against `EmptyDB`.
+    pub removed: usize,
+    /// Locations seen by both but with a different mode.
+    pub mode_changed: usize,
+}
+
+impl TxAccessDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.mode_changed == 0
+    }
+}
+
+/// Block-level accuracy comparison between the two simulation modes.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccuracyReport {
+    pub block_number: u64,
+    pub total_txs: usize,
+    pub dry_entries: usize,
+    pub warm_entries: usize,
+    pub added_entries: usize,
+    pub removed_entries: usize,
+    pub mode_changed_entries: usize,
+    pub changed_txs: usize,
+    pub dry_conflicts: usize,
+    pub warm_conflicts: usize,
+    pub added_conflicts: usize,
+    pub removed_conflicts: usize,
+    /// Only transactions whose access list changed, sorted by total delta.
+    pub per_tx: Vec<TxAccessDiff>,
+}
+
+impl AccuracyReport {
+    /// Compare dry-run and warm-state results for the same block.
+    ///
+    /// Access lists are matched by `tx_hash`; both inputs are expected to be
+    /// the deduplicated, location-sorted output of the simulator.
+    pub fn build(
+        block_number: u64,
+        dry: &[AccessList],
+        warm: &[AccessList],
+        dry_graph: &ConflictGraph,
+        warm_graph: &ConflictGraph,
+    ) -> Self {
+        let dry_by_tx: std::collections::HashMap<B256, &AccessList> =
+            dry.iter().map(|al| (al.tx_hash, al)).collect();
+
+        let mut per_tx = Vec::new();
+        let mut added_entries = 0;
+        let mut removed_entries = 0;
+        let mut mode_changed_entries = 0;
+
+        for w in warm {
+            let diff = match dry_by_tx.get(&w.tx_hash) {
+                Some(d) => diff_entries(w.tx_hash, d, w),
+                None => TxAccessDiff {
+                    tx_hash: w.tx_hash,
+                    added: w.entries.len(),
+                    removed: 0,
+                    mode_changed: 0,
+                },
+            };
+            added_entries += diff.added;
+            removed_entries += diff.removed;
+            mode_changed_entries += diff.mode_changed;
+            if !diff.is_unchanged() {
+                per_tx.push(diff);
+            }
+        }
+
+        // Transactions only the dry run saw lost every entry.
+        let warm_txs: HashSet<B256> = warm.iter().map(|al| al.tx_hash).collect();
+        for d in dry.iter().filter(|d| !warm_txs.contains(&d.tx_hash)) {
+            let diff = TxAccessDiff {
+                tx_hash: d.tx_hash,
+                added: 0,
+                removed: d.entries.len(),
+                mode_changed: 0,
+            };
+            removed_entries += diff.removed;
+            if !diff.is_unchanged() {
+                per_tx.push(diff);
+            }
+        }
+
+        per_tx.sort_by(|a, b| {
+            (b.added + b.removed + b.mode_changed).cmp(&(a.added + a.removed + a.mode_changed))
+        });
+
+        let dry_edges = edge_set(dry_graph);
+        let warm_edges = edge_set(warm_graph);
+
+        AccuracyReport {
+            block_number,
+            total_txs: warm.len(),
+            dry_entries: dry.iter().map(|al| al.entries.len()).sum(),
+            warm_entries: warm.iter().map(|al| al.entries.len()).sum(),
+            added_entries,
+            removed_entries,
+            mode_changed_entries,
+            changed_txs: per_tx.len(),
+            dry_conflicts: dry_graph.len(),
+            warm_conflicts: warm_graph.len(),
+            added_conflicts: warm_edges.difference(&dry_edges).count(),
+            removed_conflicts: dry_edges.difference(&warm_edges).count(),
+            per_tx,
+        }
+    }
+
+    /// Fraction of warm-state entries the dry run already found unchanged
+    /// (1.0 = dry run is as good as prefetching).
+    pub fn entry_agreement(&self) -> f64 {
+        if self.warm_entries == 0 {
+            return 1.0;
+        }
+        let unchanged = self.warm_entries - self.added_entries - self.mode_changed_entries;
+        unchanged as f64 / self.warm_entries as f64
+    }
+
+    /// Render the comparison as a formatted string.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push('\n');
+        out.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+        out.push_str("║              ARGUS DRY-RUN vs WARM-STATE REPORT             ║\n");
+        out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+        out.push_str(&format!(
+            "║  Block:              {:>38} ║\n",
+            self.block_number
+        ));
+        out.push_str(&format!(
+            "║  Transactions:       {:>38} ║\n",
+            self.total_txs
+        ));
+        out.push_str(&format!(
+            "║  Changed txs:        {:>38} ║\n",
+            self.changed_txs
+        ));
+        out.push_str(&format!(
+            "║  Entries (dry/warm): {:>38} ║\n",
+            format!("{} / {}", self.dry_entries, self.warm_entries)
+        ));
+        out.push_str(&format!(
+            "║  Entries +/-/~:      {:>38} ║\n",
+            format!(
+                "+{} / -{} / ~{}",
+                self.added_entries, self.removed_entries, self.mode_changed_entries
+            )
+        ));
+        out.push_str(&format!(
+            "║  Conflicts (dry/warm):{:>37} ║\n",
+            format!("{} / {}", self.dry_conflicts, self.warm_conflicts)
+        ));
+        out.push_str(&format!(
+            "║  Conflicts +/-:      {:>38} ║\n",
+            format!("+{} / -{}", self.added_conflicts, self.removed_conflicts)
+        ));
+        out.push_str(&format!(
+            "║  Entry agreement:    {:>37.1}% ║\n",
+            self.entry_agreement() * 100.0
+        ));
+
+        if !self.per_tx.is_empty() {
+            out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+            out.push_str("║  MOST AFFECTED TRANSACTIONS                                 ║\n");
+            out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+            for d in self.per_tx.iter().take(10) {
+                let row = format!(
+                    "{}…  +{} -{} ~{}",
+                    &format!("{}", d.tx_hash)[..18],
+                    d.added,
+                    d.removed,
+                    d.mode_changed
+                );
+                out.push_str(&format!("║  {row:<58} ║\n"));
+            }
+        }
+
+        out.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+        out
+    }
+}
+
+/// Merge-walk two location-sorted entry lists.
+fn diff_entries(tx_hash: B256, dry: &AccessList, warm: &AccessList) -> TxAccessDiff {
+    let (mut i, mut j) = (0, 0);
+    let (mut added, mut removed, mut mode_changed) = (0, 0, 0);
+
+    while i < dry.entries.len() && j < warm.entries.len() {
+        let (d, w) = (&dry.entries[i], &warm.entries[j]);
+        match d.location.cmp(&w.location) {
+            Ordering::Less => {
+                removed += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                added += 1;
+                j += 1;
+            }
+            Ordering::Equal => {
+                if d.mode != w.mode {
+                    mode_changed += 1;
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    removed += dry.entries.len() - i;
+    added += warm.entries.len() - j;
+
+    TxAccessDiff {
+        tx_hash,
+        added,
+        removed,
+        mode_changed,
+    }
+}
+
+type EdgeKey = (B256, B256, StorageLocation, bool);
+
+/// Order-independent edge identity: `(min tx, max tx, location, is_ww)`.
+fn edge_set(graph: &ConflictGraph) -> HashSet<EdgeKey> {
+    graph
+        .conflicts
+        .iter()
+        .map(|c| {
+            let (a, b) = if c.tx_a <= c.tx_b {
+                (c.tx_a, c.tx_b)
+            } else {
+                (c.tx_b, c.tx_a)
+            };
+            (a, b, c.location.clone(), c.kind == ConflictKind::WriteWrite)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use argus_core::{AccessEntry, AccessMode};
+    use smallvec::SmallVec;
+
+    fn entry(slot: u64, mode: AccessMode) -> AccessEntry {
+        AccessEntry {
+            location: StorageLocation {
+                address: Address::ZERO,
+                slot: B256::from(U256::from(slot)),
+            },
+            mode,
+            count: 1,
+        }
+    }
+
+    fn list(tx: u64, entries: Vec<AccessEntry>) -> AccessList {
+        AccessList {
+            tx_hash: B256::from(U256::from(tx)),
+            entries: SmallVec::from_vec(entries),
+            transient: SmallVec::new(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn identical_runs_agree() {
+        let lists = vec![list(1, vec![entry(1, AccessMode::Read)])];
+        let graph = crate::graph::build_conflict_graph(&lists);
+        let report = AccuracyReport::build(1, &lists, &lists, &graph, &graph);
+        assert_eq!(report.changed_txs, 0);
+        assert_eq!(report.entry_agreement(), 1.0);
+    }
+
+    #[test]
+    fn counts_added_removed_and_mode_changes() {
+        let dry = vec![list(
+            1,
+            vec![entry(1, AccessMode::Read), entry(2, AccessMode::Read)],
+        )];
+        let warm = vec![list(
+            1,
+            vec![entry(2, AccessMode::Write), entry(3, AccessMode::Read)],
+        )];
+        let empty = ConflictGraph::new();
+        let report = AccuracyReport::build(1, &dry, &warm, &empty, &empty);
+
+        assert_eq!(report.changed_txs, 1);
+        assert_eq!(report.added_entries, 1);
+        assert_eq!(report.removed_entries, 1);
+        assert_eq!(report.mode_changed_entries, 1);
+        assert_eq!(report.entry_agreement(), 0.0);
+    }
+
+    #[test]
+    fn dry_only_txs_count_as_removed() {
+        let dry = vec![
+            list(1, vec![entry(1, AccessMode::Read)]),
+            list(
+                2,
+                vec![entry(1, AccessMode::Read), entry(2, AccessMode::Read)],
+            ),
+        ];
+        let warm = vec![list(1, vec![entry(1, AccessMode::Read)])];
+        let empty = ConflictGraph::new();
+        let report = AccuracyReport::build(1, &dry, &warm, &empty, &empty);
+
+        assert_eq!(report.removed_entries, 2);
+        assert_eq!(report.changed_txs, 1);
+        assert_eq!(report.per_tx[0].tx_hash, B256::from(U256::from(2)));
+    }
+
+    #[test]
+    fn per_tx_rows_close_the_box() {
+        let dry = vec![list(1, vec![entry(1, AccessMode::Read)])];
+        let empty = ConflictGraph::new();
+        let report = AccuracyReport::build(1, &dry, &[], &empty, &empty);
+        let rendered = report.render();
+        let width = |line: &str| line.chars().count();
+        let block = rendered.lines().find(|l| l.contains("Block:")).unwrap();
+        let row = rendered.lines().find(|l| l.contains('…')).unwrap();
+        assert!(row.ends_with(" ║"));
+        assert_eq!(width(row), width(block));
+    }
+
+    #[test]
+    fn conflict_edges_diffed_order_independent() {
+        let dry = vec![
+            list(1, vec![entry(1, AccessMode::Write)]),
+            list(2, vec![entry(1, AccessMode::Write)]),
+        ];
+        let warm = vec![
+            list(2, vec![entry(1, AccessMode::Write)]),
+            list(1, vec![entry(1, AccessMode::Write)]),
+        ];
+        let dry_graph = crate::graph::build_conflict_graph(&dry);
+        let warm_graph = crate::graph::build_conflict_graph(&warm);
+        let report = AccuracyReport::build(1, &dry, &warm, &dry_graph, &warm_graph);
+
+        assert_eq!(report.added_conflicts, 0);
+        assert_eq!(report.removed_conflicts, 0);
+    }
+}
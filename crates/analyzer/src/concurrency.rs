@@ -0,0 +1,316 @@
+//! Analytical comparison of parallel-execution concurrency control.
+//!
+//! Estimates makespan, aborts and speedup on `threads` workers for one block
+//! under three schemes, using simulated gas used (else gas limits) as
+//! execution-time weights and the conflict graph (plus same-sender nonce
+//! chains) as true dependencies:
+//!
+//! - **Block-STM** (optimistic): workers take transactions in block order and
+//!   execute immediately. A transaction started before a dependency finished
+//!   aborts once, then re-executes after it; the first attempt is wasted.
+//! - **Pessimistic** (lock per slot): workers take transactions in block
+//!   order and block, holding the worker, until conflicting predecessors
+//!   release their locks. No aborts, but idle workers.
+//! - **Static** (access lists known up front): a list scheduler hands each
+//!   free worker the ready transaction that can start earliest.
+//!
+//! These are first-order models meant for comparing schemes on real
+//! conflict structure, not cycle-accurate predictions.
+
+use crate::plugin::{BlockAnalysisInput, BlockAnalysisPlugin, ReportSection};
+use crate::reorder::BlockDeps;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, ConflictGraph, Transaction};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write;
+
+/// Default worker count for comparisons.
+pub const DEFAULT_THREADS: usize = 8;
+
+/// Estimated behaviour of one scheme.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemeEstimate {
+    pub scheme: String,
+    /// Gas-time until the last transaction commits.
+    pub makespan_gas: u64,
+    /// Sequential gas over makespan.
+    pub speedup: f64,
+    pub aborts: usize,
+    /// Aborts per transaction.
+    pub abort_rate: f64,
+    /// Gas spent on aborted executions.
+    pub wasted_gas: u64,
+}
+
+/// Side-by-side estimates for one block.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcurrencyComparison {
+    pub block_number: u64,
+    pub threads: usize,
+    pub total_txs: usize,
+    pub total_gas: u64,
+    pub schemes: Vec<SchemeEstimate>,
+}
+
+impl ConcurrencyComparison {
+    pub fn build(
+        block_number: u64,
+        transactions: &[Transaction],
+        access_lists: &[AccessList],
+        graph: &ConflictGraph,
+        threads: usize,
+    ) -> Self {
+        let deps = BlockDeps::new(transactions, access_lists, graph);
+        let threads = threads.max(1);
+        let total_gas = deps.total_gas();
+        let n = deps.tx_count();
+
+        let estimate = |scheme: &str, makespan: u64, aborts: usize, wasted: u64| SchemeEstimate {
+            scheme: scheme.to_string(),
+            makespan_gas: makespan,
+            speedup: if makespan == 0 {
+                1.0
+            } else {
+                total_gas as f64 / makespan as f64
+            },
+            aborts,
+            abort_rate: if n == 0 {
+                0.0
+            } else {
+                aborts as f64 / n as f64
+            },
+            wasted_gas: wasted,
+        };
+
+        let (stm, aborts, wasted) = block_stm(&deps, threads);
+        Self {
+            block_number,
+            threads,
+            total_txs: n,
+            total_gas,
+            schemes: vec![
+                estimate("sequential", total_gas, 0, 0),
+                estimate("block-stm", stm, aborts, wasted),
+                estimate("pessimistic", pessimistic(&deps, threads), 0, 0),
+                estimate("static", static_schedule(&deps, threads), 0, 0),
+            ],
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<12} {:>14} {:>8} {:>7} {:>10}",
+            "scheme", "makespan gas", "speedup", "aborts", "abort rate"
+        );
+        for s in &self.schemes {
+            let _ = writeln!(
+                out,
+                "{:<12} {:>14} {:>7.2}x {:>7} {:>9.1}%",
+                s.scheme,
+                s.makespan_gas,
+                s.speedup,
+                s.aborts,
+                s.abort_rate * 100.0
+            );
+        }
+        out
+    }
+}
+
+/// Dependencies of `i` that precede it in block order.
+fn preds(deps: &BlockDeps, i: usize) -> impl Iterator<Item = usize> + '_ {
+    deps.neighbors[i]
+        .iter()
+        .copied()
+        .filter(move |&j| j < i)
+        .chain(deps.sender_prev[i])
+}
+
+/// Earliest-free worker pool over gas-time.
+struct Workers(BinaryHeap<Reverse<u64>>);
+
+impl Workers {
+    fn new(threads: usize) -> Self {
+        Self((0..threads).map(|_| Reverse(0)).collect())
+    }
+
+    fn take(&mut self) -> u64 {
+        self.0.pop().map_or(0, |Reverse(t)| t)
+    }
+
+    fn release(&mut self, at: u64) {
+        self.0.push(Reverse(at));
+    }
+}
+
+/// Returns `(makespan, aborts, wasted_gas)`.
+fn block_stm(deps: &BlockDeps, threads: usize) -> (u64, usize, u64) {
+    let mut workers = Workers::new(threads);
+    let mut finish = vec![0u64; deps.tx_count()];
+    let (mut aborts, mut wasted) = (0, 0);
+    for i in 0..deps.tx_count() {
+        let start = workers.take();
+        let ready = preds(deps, i).map(|j| finish[j]).max().unwrap_or(0);
+        let gas = deps.gas[i];
+        finish[i] = if ready > start {
+            // Speculative run read stale state; validation fails after it
+            // completes and the re-execution waits for the dependency.
+            aborts += 1;
+            wasted += gas;
+            (start + gas).max(ready) + gas
+        } else {
+            start + gas
+        };
+        workers.release(finish[i]);
+    }
+    (finish.into_iter().max().unwrap_or(0), aborts, wasted)
+}
+
+fn pessimistic(deps: &BlockDeps, threads: usize) -> u64 {
+    let mut workers = Workers::new(threads);
+    let mut finish = vec![0u64; deps.tx_count()];
+    for i in 0..deps.tx_count() {
+        let start = workers.take();
+        let ready = preds(deps, i).map(|j| finish[j]).max().unwrap_or(0);
+        finish[i] = start.max(ready) + deps.gas[i];
+        workers.release(finish[i]);
+    }
+    finish.into_iter().max().unwrap_or(0)
+}
+
+fn static_schedule(deps: &BlockDeps, threads: usize) -> u64 {
+    let n = deps.tx_count();
+    let mut workers = Workers::new(threads);
+    let mut finish: Vec<Option<u64>> = vec![None; n];
+    let mut remaining: Vec<usize> = (0..n).collect();
+
+    while !remaining.is_empty() {
+        let free = workers.take();
+        // Among transactions whose dependencies are all scheduled, pick the
+        // one that can start soonest (ties: block order).
+        let (pos, start) = remaining
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, &i)| {
+                preds(deps, i)
+                    .map(|j| finish[j])
+                    .try_fold(0u64, |acc, f| f.map(|f| acc.max(f)))
+                    .map(|ready| (pos, ready.max(free)))
+            })
+            .min_by_key(|&(pos, start)| (start, remaining[pos]))
+            .expect("block order is a topological order");
+        let i = remaining.remove(pos);
+        let end = start + deps.gas[i];
+        finish[i] = Some(end);
+        workers.release(end);
+    }
+    finish.into_iter().flatten().max().unwrap_or(0)
+}
+
+/// Adds a concurrency-control comparison table to the report.
+pub struct ConcurrencyPlugin {
+    pub threads: usize,
+}
+
+impl Default for ConcurrencyPlugin {
+    fn default() -> Self {
+        Self {
+            threads: DEFAULT_THREADS,
+        }
+    }
+}
+
+impl BlockAnalysisPlugin for ConcurrencyPlugin {
+    fn name(&self) -> &str {
+        "concurrency"
+    }
+
+    fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection> {
+        let c = ConcurrencyComparison::build(
+            input.block_number,
+            input.transactions,
+            input.access_lists,
+            input.graph,
+            self.threads,
+        );
+        let mut section =
+            ReportSection::new(format!("Concurrency control ({} threads)", c.threads));
+        for line in c.render().lines() {
+            section = section.line(line);
+        }
+        Ok(section.with_data(serde_json::to_value(&c).unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use argus_core::{Conflict, ConflictKind, StorageLocation};
+
+    fn txs(n: u8) -> Vec<Transaction> {
+        (1..=n)
+            .map(|i| Transaction {
+                hash: B256::with_last_byte(i),
+                from: Address::with_last_byte(i),
+                gas: 100,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn graph(edges: &[(u8, u8)]) -> ConflictGraph {
+        let mut g = ConflictGraph::new();
+        for &(a, b) in edges {
+            g.add_conflict(Conflict {
+                tx_a: B256::with_last_byte(a),
+                tx_b: B256::with_last_byte(b),
+                location: StorageLocation {
+                    address: Address::ZERO,
+                    slot: B256::ZERO,
+                },
+                kind: ConflictKind::WriteWrite,
+            });
+        }
+        g
+    }
+
+    fn scheme<'a>(c: &'a ConcurrencyComparison, name: &str) -> &'a SchemeEstimate {
+        c.schemes.iter().find(|s| s.scheme == name).unwrap()
+    }
+
+    #[test]
+    fn independent_txs_scale_with_threads() {
+        let c = ConcurrencyComparison::build(1, &txs(8), &[], &ConflictGraph::new(), 4);
+        for name in ["block-stm", "pessimistic", "static"] {
+            let s = scheme(&c, name);
+            assert_eq!(s.makespan_gas, 200, "{name}");
+            assert_eq!(s.aborts, 0);
+        }
+        assert!((scheme(&c, "static").speedup - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hot_slot_aborts_under_block_stm() {
+        // 1 conflicts with 2 and 3; 4 is independent.
+        let c = ConcurrencyComparison::build(1, &txs(4), &[], &graph(&[(1, 2), (1, 3)]), 4);
+        let stm = scheme(&c, "block-stm");
+        assert_eq!(stm.aborts, 2);
+        assert_eq!(stm.wasted_gas, 200);
+        assert_eq!(stm.makespan_gas, 200);
+        assert_eq!(scheme(&c, "pessimistic").makespan_gas, 200);
+    }
+
+    #[test]
+    fn static_fills_idle_workers() {
+        // 2 threads: 2 waits on 1, pinning a worker under in-order dispatch;
+        // the static scheduler runs 3 alongside 1 instead.
+        let c = ConcurrencyComparison::build(1, &txs(4), &[], &graph(&[(1, 2)]), 2);
+        assert_eq!(scheme(&c, "pessimistic").makespan_gas, 300);
+        assert_eq!(scheme(&c, "static").makespan_gas, 200);
+    }
+}
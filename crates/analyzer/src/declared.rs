@@ -0,0 +1,249 @@
+//! Accuracy of declared EIP-2930 access lists.
+//!
+//! Type-1 and later transactions may declare the storage they will touch.
+//! Matching each declared list against the slots simulation actually
+//! touched shows how well wallets and searchers predict their own
+//! footprint: **coverage** is the share of touched slots that were
+//! declared, **extra** slots were declared but never touched. Account
+//! pseudo-slots and address-only entries are left out; only storage keys
+//! are compared.
+
+use crate::plugin::{BlockAnalysisInput, BlockAnalysisPlugin, ReportSection};
+use alloy_primitives::B256;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, StorageLocation, Transaction};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Declared vs simulated storage for one transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeclaredAccessDiff {
+    pub tx_hash: B256,
+    pub declared: usize,
+    pub simulated: usize,
+    /// Simulated slots that were declared.
+    pub covered: usize,
+    /// Declared slots simulation never touched.
+    pub extra: usize,
+}
+
+impl DeclaredAccessDiff {
+    /// Simulated slots that were not declared.
+    pub fn missing(&self) -> usize {
+        self.simulated - self.covered
+    }
+}
+
+/// Block-level comparison over the transactions that declared a list.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeclaredAccessReport {
+    pub block_number: u64,
+    pub total_txs: usize,
+    /// Transactions carrying a non-empty access list.
+    pub declaring_txs: usize,
+    pub declared_slots: usize,
+    pub simulated_slots: usize,
+    pub covered_slots: usize,
+    pub extra_slots: usize,
+    /// Declaring transactions, worst first (missing + extra).
+    pub per_tx: Vec<DeclaredAccessDiff>,
+}
+
+impl DeclaredAccessReport {
+    /// Compare each transaction's declared list with its simulated access
+    /// list, matched by hash. Transactions without a declared list, or
+    /// without a simulated one, are skipped.
+    pub fn build(
+        block_number: u64,
+        transactions: &[Transaction],
+        access_lists: &[AccessList],
+    ) -> Self {
+        let simulated: HashMap<B256, &AccessList> =
+            access_lists.iter().map(|al| (al.tx_hash, al)).collect();
+
+        let mut per_tx: Vec<DeclaredAccessDiff> = transactions
+            .iter()
+            .filter(|tx| !tx.access_list.is_empty())
+            .filter_map(|tx| Some(diff(tx, simulated.get(&tx.hash)?)))
+            .collect();
+        per_tx.sort_by(|a, b| {
+            (b.missing() + b.extra)
+                .cmp(&(a.missing() + a.extra))
+                .then(a.tx_hash.cmp(&b.tx_hash))
+        });
+
+        Self {
+            block_number,
+            total_txs: transactions.len(),
+            declaring_txs: per_tx.len(),
+            declared_slots: per_tx.iter().map(|d| d.declared).sum(),
+            simulated_slots: per_tx.iter().map(|d| d.simulated).sum(),
+            covered_slots: per_tx.iter().map(|d| d.covered).sum(),
+            extra_slots: per_tx.iter().map(|d| d.extra).sum(),
+            per_tx,
+        }
+    }
+
+    /// Share of touched slots that were declared (1.0 with nothing touched).
+    pub fn coverage(&self) -> f64 {
+        if self.simulated_slots == 0 {
+            return 1.0;
+        }
+        self.covered_slots as f64 / self.simulated_slots as f64
+    }
+
+    /// Share of declared slots that were touched (1.0 with nothing declared).
+    pub fn precision(&self) -> f64 {
+        if self.declared_slots == 0 {
+            return 1.0;
+        }
+        (self.declared_slots - self.extra_slots) as f64 / self.declared_slots as f64
+    }
+}
+
+fn diff(tx: &Transaction, simulated: &AccessList) -> DeclaredAccessDiff {
+    let declared: HashSet<StorageLocation> = tx
+        .access_list
+        .iter()
+        .flat_map(|item| {
+            item.storage_keys.iter().map(|&slot| StorageLocation {
+                address: item.address,
+                slot,
+            })
+        })
+        .collect();
+    let touched: HashSet<&StorageLocation> = simulated
+        .entries
+        .iter()
+        .map(|e| &e.location)
+        .filter(|loc| loc.account_field().is_none())
+        .collect();
+    let covered = touched.iter().filter(|loc| declared.contains(*loc)).count();
+
+    DeclaredAccessDiff {
+        tx_hash: tx.hash,
+        declared: declared.len(),
+        simulated: touched.len(),
+        covered,
+        extra: declared.len() - covered,
+    }
+}
+
+/// Adds declared-vs-simulated access list accuracy to the report.
+pub struct DeclaredAccessPlugin;
+
+impl BlockAnalysisPlugin for DeclaredAccessPlugin {
+    fn name(&self) -> &str {
+        "declared-access"
+    }
+
+    fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection> {
+        let r =
+            DeclaredAccessReport::build(input.block_number, input.transactions, input.access_lists);
+        let mut section = ReportSection::new("Declared access lists")
+            .line(format!(
+                "{} of {} txs declared {} slots",
+                r.declaring_txs, r.total_txs, r.declared_slots
+            ))
+            .line(format!(
+                "Coverage {:.1}% ({} of {} touched slots declared) | {} declared but untouched",
+                r.coverage() * 100.0,
+                r.covered_slots,
+                r.simulated_slots,
+                r.extra_slots
+            ));
+        for d in r
+            .per_tx
+            .iter()
+            .take(5)
+            .filter(|d| d.missing() + d.extra > 0)
+        {
+            section = section.line(format!(
+                "{}…  {} missing, {} extra",
+                &format!("{}", d.tx_hash)[..18],
+                d.missing(),
+                d.extra
+            ));
+        }
+        Ok(section.with_data(serde_json::to_value(&r).unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+    use argus_core::{AccessEntry, AccessMode, AccountField, DeclaredAccess};
+
+    fn slot(n: u8) -> StorageLocation {
+        StorageLocation {
+            address: Address::with_last_byte(1),
+            slot: B256::with_last_byte(n),
+        }
+    }
+
+    fn tx(n: u8, declared: &[u8]) -> Transaction {
+        Transaction {
+            hash: B256::with_last_byte(n),
+            from: Address::with_last_byte(n),
+            to: Some(Address::with_last_byte(1)),
+            gas: 100_000,
+            access_list: if declared.is_empty() {
+                Vec::new()
+            } else {
+                vec![DeclaredAccess {
+                    address: Address::with_last_byte(1),
+                    storage_keys: declared.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                }]
+            },
+            ..Default::default()
+        }
+    }
+
+    fn list(n: u8, touched: &[u8]) -> AccessList {
+        let mut entries: smallvec::SmallVec<[AccessEntry; 32]> = touched
+            .iter()
+            .map(|&s| AccessEntry {
+                location: slot(s),
+                mode: AccessMode::Read,
+                count: 1,
+            })
+            .collect();
+        // Pseudo-slots never count against the declared list.
+        entries.push(AccessEntry {
+            location: StorageLocation::account(Address::with_last_byte(n), AccountField::Nonce),
+            mode: AccessMode::Write,
+            count: 1,
+        });
+        AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries,
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn coverage_and_extra_slots() {
+        // Tx 1 declared 1, 2, 3 and touched 1, 2, 4; tx 2 declared nothing.
+        let txs = [tx(1, &[1, 2, 3]), tx(2, &[])];
+        let lists = [list(1, &[1, 2, 4]), list(2, &[5])];
+        let r = DeclaredAccessReport::build(9, &txs, &lists);
+        assert_eq!(r.declaring_txs, 1);
+        assert_eq!((r.declared_slots, r.simulated_slots), (3, 3));
+        assert_eq!((r.covered_slots, r.extra_slots), (2, 1));
+        assert_eq!(r.per_tx[0].missing(), 1);
+        assert!((r.coverage() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((r.precision() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_declared_lists_is_perfect() {
+        let r = DeclaredAccessReport::build(9, &[tx(1, &[])], &[list(1, &[1])]);
+        assert_eq!(r.declaring_txs, 0);
+        assert_eq!(r.coverage(), 1.0);
+    }
+}
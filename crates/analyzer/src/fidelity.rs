@@ -0,0 +1,226 @@
+//! Simulation fidelity against mined receipts.
+//!
+//! Each transaction's simulated gas used and status are compared with its
+//! receipt. Where they differ, simulation saw something the block did not:
+//! state the prefetch missed, or, in isolated mode, the absence of the
+//! earlier transactions' writes. The divergence rate tells how far a
+//! block's access lists can be trusted. Access lists without simulated gas
+//! (e.g. taken from node traces) are not compared.
+
+use crate::plugin::{BlockAnalysisInput, BlockAnalysisPlugin, ReportSection};
+use alloy_primitives::B256;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, TxReceipt};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Relative gas difference still counted as a match.
+pub const DEFAULT_GAS_TOLERANCE: f64 = 0.01;
+
+/// One transaction whose simulation disagreed with its receipt.
+#[derive(Debug, Clone, Serialize)]
+pub struct TxDivergence {
+    pub tx_hash: B256,
+    pub simulated_gas: u64,
+    pub mined_gas: u64,
+    pub simulated_success: bool,
+    pub mined_success: bool,
+}
+
+impl TxDivergence {
+    /// Simulated minus mined gas.
+    pub fn gas_delta(&self) -> i64 {
+        self.simulated_gas as i64 - self.mined_gas as i64
+    }
+
+    pub fn status_mismatch(&self) -> bool {
+        self.simulated_success != self.mined_success
+    }
+}
+
+/// Simulated vs mined outcome over a block.
+#[derive(Debug, Clone, Serialize)]
+pub struct GasValidationReport {
+    pub block_number: u64,
+    /// Transactions with both simulated gas and a receipt.
+    pub compared: usize,
+    /// Gas off by more than `tolerance`.
+    pub gas_mismatches: usize,
+    /// Succeeded on one side, failed on the other.
+    pub status_mismatches: usize,
+    pub tolerance: f64,
+    /// Diverging transactions, largest gas difference first.
+    pub per_tx: Vec<TxDivergence>,
+}
+
+impl GasValidationReport {
+    /// Match access lists to receipts by hash; a transaction diverges when
+    /// its status differs or its gas is off by more than `tolerance` of
+    /// the mined gas.
+    pub fn build(
+        block_number: u64,
+        access_lists: &[AccessList],
+        receipts: &[TxReceipt],
+        tolerance: f64,
+    ) -> Self {
+        let mined: HashMap<B256, &TxReceipt> = receipts.iter().map(|r| (r.tx_hash, r)).collect();
+        let mut compared = 0;
+        let mut gas_mismatches = 0;
+        let mut status_mismatches = 0;
+        let mut per_tx = Vec::new();
+        for list in access_lists.iter().filter(|al| al.gas_used > 0) {
+            let Some(receipt) = mined.get(&list.tx_hash) else {
+                continue;
+            };
+            compared += 1;
+            let d = TxDivergence {
+                tx_hash: list.tx_hash,
+                simulated_gas: list.gas_used,
+                mined_gas: receipt.gas_used,
+                simulated_success: list.outcome.is_success(),
+                mined_success: receipt.success,
+            };
+            let gas_off = d.gas_delta().unsigned_abs() as f64 > receipt.gas_used as f64 * tolerance;
+            gas_mismatches += gas_off as usize;
+            status_mismatches += d.status_mismatch() as usize;
+            if gas_off || d.status_mismatch() {
+                per_tx.push(d);
+            }
+        }
+        per_tx.sort_by(|a, b| {
+            b.gas_delta()
+                .unsigned_abs()
+                .cmp(&a.gas_delta().unsigned_abs())
+                .then(a.tx_hash.cmp(&b.tx_hash))
+        });
+
+        Self {
+            block_number,
+            compared,
+            gas_mismatches,
+            status_mismatches,
+            tolerance,
+            per_tx,
+        }
+    }
+
+    /// Share of compared transactions that diverged (0.0 when none were
+    /// compared).
+    pub fn divergence_rate(&self) -> f64 {
+        if self.compared == 0 {
+            return 0.0;
+        }
+        self.per_tx.len() as f64 / self.compared as f64
+    }
+}
+
+/// Adds the simulated-vs-mined comparison to the report. Needs receipts.
+pub struct GasValidationPlugin {
+    pub tolerance: f64,
+}
+
+impl Default for GasValidationPlugin {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_GAS_TOLERANCE,
+        }
+    }
+}
+
+impl BlockAnalysisPlugin for GasValidationPlugin {
+    fn name(&self) -> &str {
+        "gas-validation"
+    }
+
+    fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection> {
+        let section = ReportSection::new("Simulation fidelity");
+        if input.receipts.is_empty() {
+            return Ok(section.line("No receipts fetched; nothing to compare"));
+        }
+        let r = GasValidationReport::build(
+            input.block_number,
+            input.access_lists,
+            input.receipts,
+            self.tolerance,
+        );
+        let mut section = section
+            .line(format!(
+                "{} of {} txs diverged ({:.1}%)",
+                r.per_tx.len(),
+                r.compared,
+                r.divergence_rate() * 100.0
+            ))
+            .line(format!(
+                "{} gas off by more than {:.1}% | {} status mismatches",
+                r.gas_mismatches,
+                r.tolerance * 100.0,
+                r.status_mismatches
+            ));
+        for d in r.per_tx.iter().take(5) {
+            section = section.line(format!(
+                "{}…  gas {} vs {} mined{}",
+                &format!("{}", d.tx_hash)[..18],
+                d.simulated_gas,
+                d.mined_gas,
+                if d.status_mismatch() {
+                    ", status differs"
+                } else {
+                    ""
+                }
+            ));
+        }
+        Ok(section.with_data(serde_json::to_value(&r).unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argus_core::ExecutionOutcome;
+
+    fn list(n: u8, gas_used: u64, outcome: ExecutionOutcome) -> AccessList {
+        AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: Default::default(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome,
+            gas_used,
+            writes: Vec::new(),
+        }
+    }
+
+    fn receipt(n: u8, gas_used: u64, success: bool) -> TxReceipt {
+        TxReceipt {
+            tx_hash: B256::with_last_byte(n),
+            gas_used,
+            success,
+        }
+    }
+
+    #[test]
+    fn counts_gas_and_status_divergence() {
+        let revert = ExecutionOutcome::Revert { reason: None };
+        let lists = [
+            list(1, 50_000, ExecutionOutcome::Success),
+            list(2, 50_200, ExecutionOutcome::Success),
+            list(3, 30_000, ExecutionOutcome::Success),
+            list(4, 21_000, revert),
+            // From a trace: no simulated gas, not compared.
+            list(5, 0, ExecutionOutcome::Success),
+        ];
+        let receipts = [
+            receipt(1, 50_000, true),
+            receipt(2, 50_000, true),
+            receipt(3, 90_000, true),
+            receipt(4, 21_000, true),
+            receipt(5, 21_000, true),
+        ];
+        let r = GasValidationReport::build(9, &lists, &receipts, DEFAULT_GAS_TOLERANCE);
+        assert_eq!(r.compared, 4);
+        assert_eq!((r.gas_mismatches, r.status_mismatches), (1, 1));
+        assert_eq!(r.per_tx.len(), 2);
+        assert_eq!(r.per_tx[0].gas_delta(), -60_000);
+        assert!((r.divergence_rate() - 0.5).abs() < 1e-9);
+    }
+}
@@ -1,25 +1,89 @@
 //! Conflict graph construction from per-transaction access lists.
 
-use alloy_primitives::B256;
-use argus_core::{AccessList, AccessMode, Conflict, ConflictGraph, ConflictKind, StorageLocation};
-use std::collections::HashMap;
+use alloy_primitives::{Address, B256};
+use argus_core::{
+    AccessEntry, AccessList, AccessMode, AccountField, Conflict, ConflictGraph, ConflictKind,
+    FastHashMap, SlotWrite, StorageLocation,
+};
+use std::hash::BuildHasher;
 
-/// Builds a [`ConflictGraph`] from a slice of access lists.
+fn conflict_kind(a: AccessMode, b: AccessMode) -> Option<ConflictKind> {
+    match (a, b) {
+        (AccessMode::Write, AccessMode::Write) => Some(ConflictKind::WriteWrite),
+        (AccessMode::Write, AccessMode::Read) | (AccessMode::Read, AccessMode::Write) => {
+            Some(ConflictKind::ReadWrite)
+        }
+        (AccessMode::Read, AccessMode::Read) => None,
+    }
+}
+
+/// Sorted fingerprints of every location written in a batch.
+///
+/// A location nobody writes can't produce an edge, so it is screened out
+/// before indexing. Fingerprint collisions only let extra read-only
+/// locations through; they never drop a conflict.
+pub(crate) struct WriteFilter {
+    fingerprints: Vec<u64>,
+}
+
+impl WriteFilter {
+    pub fn new(access_lists: &[AccessList]) -> Self {
+        let mut fingerprints: Vec<u64> = access_lists
+            .iter()
+            .flat_map(|al| &al.entries)
+            .filter(|e| e.mode == AccessMode::Write)
+            .map(|e| fingerprint(&e.location))
+            .collect();
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+        Self { fingerprints }
+    }
+
+    /// Whether `location` may be written by some transaction in the batch.
+    pub fn may_conflict(&self, location: &StorageLocation) -> bool {
+        self.fingerprints
+            .binary_search(&fingerprint(location))
+            .is_ok()
+    }
+}
+
+fn fingerprint(location: &StorageLocation) -> u64 {
+    rustc_hash::FxBuildHasher.hash_one(location)
+}
+
+/// Builds a [`ConflictGraph`] from a slice of access lists. See
+/// [`for_each_conflict`].
+pub fn build_conflict_graph(access_lists: &[AccessList]) -> ConflictGraph {
+    let mut graph = ConflictGraph::new();
+    for_each_conflict(access_lists, |conflict| graph.add_conflict(conflict));
+    graph
+}
+
+/// Hands every conflict edge between `access_lists` to `emit`, without
+/// collecting them.
 ///
-/// Uses a two-phase approach:
-///   1. Reverse-index every `(location -> [(tx, mode)])` using borrowed keys.
-///   2. For each location with 2+ accessors, emit conflict edges where at
+/// Uses a three-phase approach:
+///   1. Fingerprint every written location ([`WriteFilter`]).
+///   2. Reverse-index `(location -> [(tx, mode)])` using borrowed keys,
+///      skipping locations no transaction writes.
+///   3. For each location with 2+ accessors, emit conflict edges where at
 ///      least one side is a write.
 ///
+/// Self-destructs then add their edges ([`destruction_conflicts`]).
 /// Location clones only happen for actual conflicts (cold path).
-pub fn build_conflict_graph(access_lists: &[AccessList]) -> ConflictGraph {
-    let mut graph = ConflictGraph::new();
+pub fn for_each_conflict(access_lists: &[AccessList], mut emit: impl FnMut(Conflict)) {
+    let writes = WriteFilter::new(access_lists);
 
     // Reverse index: &StorageLocation -> [(tx_hash, mode)].
-    let mut location_index: HashMap<&StorageLocation, Vec<(B256, AccessMode)>> = HashMap::new();
+    let mut location_index: FastHashMap<&StorageLocation, Vec<(B256, AccessMode)>> =
+        FastHashMap::default();
 
     for al in access_lists {
-        for entry in &al.entries {
+        for entry in al
+            .entries
+            .iter()
+            .filter(|e| writes.may_conflict(&e.location))
+        {
             location_index
                 .entry(&entry.location)
                 .or_default()
@@ -29,31 +93,426 @@ pub fn build_conflict_graph(access_lists: &[AccessList]) -> ConflictGraph {
 
     // Pair-wise conflict detection at each shared location.
     for (location, accessors) in &location_index {
-        if accessors.len() < 2 {
-            continue;
+        location_conflicts(location, accessors, &mut emit);
+    }
+    destruction_conflicts(access_lists, &mut emit);
+}
+
+fn is_destruction(entry: &AccessEntry) -> bool {
+    entry.mode == AccessMode::Write
+        && entry.location.account_field() == Some(AccountField::Existence)
+}
+
+/// Accounts `list` self-destructs.
+fn destroyed_accounts(list: &AccessList) -> impl Iterator<Item = Address> + '_ {
+    list.entries
+        .iter()
+        .filter(|e| is_destruction(e))
+        .map(|e| e.location.address)
+}
+
+/// The strongest access `list` makes to each account `wanted` accepts,
+/// leaving out accounts it destroys itself (those meet other destroyers on
+/// the existence pseudo-slot like any shared location).
+fn account_touches(
+    list: &AccessList,
+    wanted: impl Fn(&Address) -> bool,
+) -> FastHashMap<Address, AccessMode> {
+    let mut touched: FastHashMap<Address, AccessMode> = FastHashMap::default();
+    for entry in list.entries.iter().filter(|e| wanted(&e.location.address)) {
+        let mode = touched.entry(entry.location.address).or_insert(entry.mode);
+        *mode = (*mode).max(entry.mode);
+    }
+    for address in destroyed_accounts(list) {
+        touched.remove(&address);
+    }
+    touched
+}
+
+/// The edge between a transaction accessing `address` with `mode` and one
+/// destroying it, each given as `(block position, hash)`.
+fn destruction_conflict(
+    toucher: (usize, B256),
+    destroyer: (usize, B256),
+    address: Address,
+    mode: AccessMode,
+) -> Conflict {
+    let (tx_a, tx_b) = if toucher.0 < destroyer.0 {
+        (toucher.1, destroyer.1)
+    } else {
+        (destroyer.1, toucher.1)
+    };
+    Conflict {
+        tx_a,
+        tx_b,
+        location: StorageLocation::account(address, AccountField::Existence),
+        kind: match mode {
+            AccessMode::Write => ConflictKind::WriteWrite,
+            AccessMode::Read => ConflictKind::ReadWrite,
+        },
+    }
+}
+
+/// Edges from self-destructs. Destroying an account writes all of it, so
+/// the destroyer conflicts with every other transaction touching the
+/// account at all; one edge per pair, on the existence pseudo-slot.
+pub(crate) fn destruction_conflicts(access_lists: &[AccessList], emit: &mut impl FnMut(Conflict)) {
+    let mut destroyers: FastHashMap<Address, Vec<usize>> = FastHashMap::default();
+    for (i, al) in access_lists.iter().enumerate() {
+        for address in destroyed_accounts(al) {
+            destroyers.entry(address).or_default().push(i);
+        }
+    }
+    if destroyers.is_empty() {
+        return;
+    }
+
+    for (i, al) in access_lists.iter().enumerate() {
+        for (address, mode) in account_touches(al, |a| destroyers.contains_key(a)) {
+            for &d in &destroyers[&address] {
+                emit(destruction_conflict(
+                    (i, al.tx_hash),
+                    (d, access_lists[d].tx_hash),
+                    address,
+                    mode,
+                ));
+            }
         }
+    }
+}
 
-        for i in 0..accessors.len() {
-            for j in (i + 1)..accessors.len() {
-                let (tx_a, mode_a) = &accessors[i];
-                let (tx_b, mode_b) = &accessors[j];
+/// Drop W-W edges that order nothing: both transactions leave the slot
+/// with the same value, or either one writes it and sets it back. Needs the
+/// values simulation records in [`AccessList::writes`]; edges without them
+/// are kept. Returns how many edges were dropped.
+pub fn prune_same_value_writes(graph: &mut ConflictGraph, access_lists: &[AccessList]) -> usize {
+    let writes: FastHashMap<(B256, &StorageLocation), &SlotWrite> = access_lists
+        .iter()
+        .flat_map(|al| al.writes.iter().map(|w| ((al.tx_hash, &w.location), w)))
+        .collect();
+    if writes.is_empty() {
+        return 0;
+    }
 
-                let kind = match (mode_a, mode_b) {
-                    (AccessMode::Write, AccessMode::Write) => ConflictKind::WriteWrite,
-                    (AccessMode::Write, AccessMode::Read)
-                    | (AccessMode::Read, AccessMode::Write) => ConflictKind::ReadWrite,
-                    (AccessMode::Read, AccessMode::Read) => continue,
-                };
+    let before = graph.len();
+    graph.retain(|c| {
+        if c.kind != ConflictKind::WriteWrite {
+            return true;
+        }
+        let (Some(a), Some(b)) = (
+            writes.get(&(c.tx_a, &c.location)),
+            writes.get(&(c.tx_b, &c.location)),
+        ) else {
+            return true;
+        };
+        !(a.value == b.value || a.is_restore() || b.is_restore())
+    });
+    before - graph.len()
+}
+
+/// Distinct locations with at least one conflict, sorted.
+pub fn contested_locations(graph: &ConflictGraph) -> Vec<StorageLocation> {
+    let mut locations: Vec<StorageLocation> =
+        graph.conflicts.iter().map(|c| c.location.clone()).collect();
+    locations.sort_unstable();
+    locations.dedup();
+    locations
+}
 
-                graph.add_conflict(Conflict {
-                    tx_a: *tx_a,
-                    tx_b: *tx_b,
-                    location: (*location).clone(),
+/// Emit the edges among one location's accessors (in block order).
+pub(crate) fn location_conflicts(
+    location: &StorageLocation,
+    accessors: &[(B256, AccessMode)],
+    emit: &mut impl FnMut(Conflict),
+) {
+    if accessors.len() < 2 {
+        return;
+    }
+
+    for i in 0..accessors.len() {
+        for j in (i + 1)..accessors.len() {
+            let (tx_a, mode_a) = &accessors[i];
+            let (tx_b, mode_b) = &accessors[j];
+
+            let Some(kind) = conflict_kind(*mode_a, *mode_b) else {
+                continue;
+            };
+
+            emit(Conflict {
+                tx_a: *tx_a,
+                tx_b: *tx_b,
+                location: location.clone(),
+                kind,
+            });
+        }
+    }
+}
+
+/// Conflict detection for access lists that arrive one at a time, in any
+/// order (e.g. as parallel simulation completes).
+///
+/// Each [`add`](Self::add) returns only the new edges; across a whole block
+/// they are the same edges [`build_conflict_graph`] finds, with the
+/// earlier-in-block transaction as `tx_a`.
+#[derive(Debug, Default)]
+pub struct IncrementalGraph {
+    location_index: FastHashMap<StorageLocation, Vec<(usize, B256, AccessMode)>>,
+    destroyers: FastHashMap<Address, Vec<(usize, B256)>>,
+}
+
+impl IncrementalGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the access list of the transaction at block position `index`.
+    pub fn add(&mut self, index: usize, access_list: &AccessList) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        for entry in &access_list.entries {
+            let accessors = self
+                .location_index
+                .entry(entry.location.clone())
+                .or_default();
+            for &(other, other_hash, other_mode) in accessors.iter() {
+                if other == index {
+                    continue;
+                }
+                let Some(kind) = conflict_kind(other_mode, entry.mode) else {
+                    continue;
+                };
+                let (tx_a, tx_b) = if other < index {
+                    (other_hash, access_list.tx_hash)
+                } else {
+                    (access_list.tx_hash, other_hash)
+                };
+                conflicts.push(Conflict {
+                    tx_a,
+                    tx_b,
+                    location: entry.location.clone(),
                     kind,
                 });
             }
+            accessors.push((index, access_list.tx_hash, entry.mode));
+        }
+
+        let this = (index, access_list.tx_hash);
+        let touched = account_touches(access_list, |a| self.destroyers.contains_key(a));
+        for (address, mode) in touched {
+            for &destroyer in &self.destroyers[&address] {
+                conflicts.push(destruction_conflict(this, destroyer, address, mode));
+            }
         }
+        for address in destroyed_accounts(access_list) {
+            // Destructions are rare, so scan for earlier arrivals touching
+            // the account instead of indexing every access by address.
+            let destroyers = self.destroyers.entry(address).or_default();
+            let mut touchers: FastHashMap<usize, (B256, AccessMode)> = FastHashMap::default();
+            for (location, accessors) in &self.location_index {
+                if location.address != address {
+                    continue;
+                }
+                for &(other, other_hash, other_mode) in accessors {
+                    if other == index || destroyers.iter().any(|&(d, _)| d == other) {
+                        continue;
+                    }
+                    let touch = touchers.entry(other).or_insert((other_hash, other_mode));
+                    touch.1 = touch.1.max(other_mode);
+                }
+            }
+            for (other, (other_hash, mode)) in touchers {
+                conflicts.push(destruction_conflict(
+                    (other, other_hash),
+                    this,
+                    address,
+                    mode,
+                ));
+            }
+            destroyers.push(this);
+        }
+        conflicts
     }
 
-    graph
+    /// Forget all locations, e.g. between blocks.
+    pub fn clear(&mut self) {
+        self.location_index.clear();
+        self.destroyers.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+    use argus_core::AccessEntry;
+
+    fn list(n: u8, accesses: &[(u8, AccessMode)]) -> AccessList {
+        AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: accesses
+                .iter()
+                .map(|&(slot, mode)| AccessEntry {
+                    location: StorageLocation {
+                        address: Address::ZERO,
+                        slot: B256::with_last_byte(slot),
+                    },
+                    mode,
+                    count: 1,
+                })
+                .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn same_value_writes_are_pruned() {
+        // All four write slot 1 from 0: txs 1 and 2 to 5, tx 3 to 7, tx 4
+        // back to 0.
+        let lists: Vec<AccessList> = [5u8, 5, 7, 0]
+            .iter()
+            .zip(1u8..)
+            .map(|(&value, n)| {
+                let mut al = list(n, &[(1, AccessMode::Write)]);
+                al.writes.push(SlotWrite {
+                    location: al.entries[0].location.clone(),
+                    original: B256::ZERO,
+                    value: B256::with_last_byte(value),
+                });
+                al
+            })
+            .collect();
+        let mut graph = build_conflict_graph(&lists);
+        assert_eq!(graph.len(), 6);
+
+        assert_eq!(prune_same_value_writes(&mut graph, &lists), 4);
+        assert!(graph.has_conflict(&B256::with_last_byte(1), &B256::with_last_byte(3)));
+        assert!(graph.has_conflict(&B256::with_last_byte(2), &B256::with_last_byte(3)));
+        assert!(!graph.has_conflict(&B256::with_last_byte(1), &B256::with_last_byte(2)));
+        assert!(!graph.has_conflict(&B256::with_last_byte(4), &B256::with_last_byte(3)));
+    }
+
+    #[test]
+    fn read_only_locations_are_screened_out() {
+        use AccessMode::{Read, Write};
+        let lists = vec![
+            list(1, &[(1, Read), (2, Read)]),
+            list(2, &[(1, Read), (2, Write)]),
+        ];
+        let writes = WriteFilter::new(&lists);
+        assert!(writes.may_conflict(&lists[1].entries[1].location));
+        assert!(!writes.may_conflict(&lists[0].entries[0].location));
+
+        let graph = build_conflict_graph(&lists);
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph.conflicts[0].location.slot, B256::with_last_byte(2));
+    }
+
+    #[test]
+    fn incremental_matches_batch_in_any_order() {
+        use AccessMode::{Read, Write};
+        let lists = vec![
+            list(1, &[(1, Write), (2, Read)]),
+            list(2, &[(1, Read)]),
+            list(3, &[(2, Write), (1, Write)]),
+            list(4, &[(2, Read)]),
+        ];
+        let key = |c: &Conflict| (c.tx_a, c.tx_b, c.location.slot, c.kind as u8);
+
+        let mut expected: Vec<_> = build_conflict_graph(&lists)
+            .conflicts
+            .iter()
+            .map(key)
+            .collect();
+        expected.sort();
+
+        let mut inc = IncrementalGraph::new();
+        let mut streamed: Vec<_> = [2, 0, 3, 1]
+            .into_iter()
+            .flat_map(|i| inc.add(i, &lists[i]))
+            .map(|c| key(&c))
+            .collect();
+        streamed.sort();
+
+        assert_eq!(streamed, expected);
+        assert_eq!(streamed.len(), 5);
+    }
+
+    #[test]
+    fn self_destruct_conflicts_with_any_access_to_the_account() {
+        use argus_core::AccountField;
+        let destroy = AccessList {
+            tx_hash: B256::with_last_byte(2),
+            entries: [AccessEntry {
+                location: StorageLocation::account(Address::ZERO, AccountField::Existence),
+                mode: AccessMode::Write,
+                count: 1,
+            }]
+            .into_iter()
+            .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+        let lists = vec![
+            list(1, &[(4, AccessMode::Read)]),
+            destroy,
+            list(3, &[(5, AccessMode::Write), (4, AccessMode::Read)]),
+        ];
+
+        let graph = build_conflict_graph(&lists);
+        assert_eq!(graph.len(), 2);
+        assert!(graph
+            .conflicts
+            .iter()
+            .all(|c| { c.location.account_field() == Some(AccountField::Existence) }));
+        let kind = |a: u8, b: u8| {
+            graph
+                .conflicts
+                .iter()
+                .find(|c| c.tx_a == B256::with_last_byte(a) && c.tx_b == B256::with_last_byte(b))
+                .map(|c| c.kind)
+        };
+        assert_eq!(kind(1, 2), Some(ConflictKind::ReadWrite));
+        assert_eq!(kind(2, 3), Some(ConflictKind::WriteWrite));
+
+        for order in [[0, 1, 2], [2, 1, 0]] {
+            let mut inc = IncrementalGraph::new();
+            let streamed: usize = order.into_iter().map(|i| inc.add(i, &lists[i]).len()).sum();
+            assert_eq!(streamed, 2);
+        }
+    }
+
+    #[test]
+    fn shared_sender_nonce_conflicts() {
+        use argus_core::AccountField;
+        let sender = Address::repeat_byte(0x22);
+        let nonce = |n: u8| AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: [AccessEntry {
+                location: StorageLocation::account(sender, AccountField::Nonce),
+                mode: AccessMode::Write,
+                count: 1,
+            }]
+            .into_iter()
+            .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+
+        let graph = build_conflict_graph(&[nonce(1), nonce(2), list(3, &[(0, AccessMode::Write)])]);
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph.conflicts[0].kind, ConflictKind::WriteWrite);
+        assert_eq!(
+            graph.conflicts[0].location.account_field(),
+            Some(AccountField::Nonce)
+        );
+    }
 }
@@ -0,0 +1,314 @@
+//! Persistent cross-block leaderboard of contended storage slots.
+//!
+//! Every analyzed block adds its conflict edges per `(contract, slot)` to a
+//! decayed score: a conflict `h` blocks old weighs `0.5^(h / half_life)`.
+//! Chronically hot slots stay on top while one-off spikes fade. The store is
+//! a small JSON file; entries whose score decays below [`PRUNE_BELOW`] are
+//! dropped on save so it stays bounded.
+//!
+//! ```ignore
+//! let mut board = HotSlotLeaderboard::load_or_new(path, 7200.0)?;
+//! board.record(block_number, &graph);
+//! board.save(path)?;
+//! for slot in board.top(20) { ... }
+//! ```
+
+use alloy_primitives::{Address, B256};
+use argus_core::chain::ChainConfig;
+use argus_core::error::ArgusResult;
+use argus_core::json_file;
+use argus_core::{ConflictGraph, StorageLocation};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Default half-life: roughly one day of Ethereum blocks.
+pub const DEFAULT_HALF_LIFE_BLOCKS: f64 = 7200.0;
+
+/// Entries below this decayed score are pruned on save.
+pub const PRUNE_BELOW: f64 = 0.01;
+
+/// Recently recorded blocks remembered to avoid double counting.
+const RECENT_BLOCKS: usize = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SlotStats {
+    /// Decayed score as of block `scored_at`.
+    score: f64,
+    scored_at: u64,
+    total_conflicts: u64,
+    blocks_seen: u64,
+    last_seen: u64,
+}
+
+/// One ranked `(contract, slot)`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotSlot {
+    pub address: Address,
+    pub slot: B256,
+    /// Decay-weighted conflict count as of the leaderboard head.
+    pub score: f64,
+    pub total_conflicts: u64,
+    pub blocks_seen: u64,
+    pub last_seen: u64,
+}
+
+/// Decay-weighted contention totals across all analyzed blocks.
+#[derive(Debug, Clone)]
+pub struct HotSlotLeaderboard {
+    half_life_blocks: f64,
+    /// Highest block recorded so far; scores are reported as of this block.
+    head: u64,
+    recent: VecDeque<u64>,
+    slots: HashMap<StorageLocation, SlotStats>,
+}
+
+/// On-disk form: JSON objects can't key by `StorageLocation`.
+#[derive(Serialize, Deserialize)]
+struct Persisted {
+    half_life_blocks: f64,
+    head: u64,
+    recent: VecDeque<u64>,
+    slots: Vec<(StorageLocation, SlotStats)>,
+}
+
+impl HotSlotLeaderboard {
+    pub fn new(half_life_blocks: f64) -> Self {
+        Self {
+            half_life_blocks: half_life_blocks.max(1.0),
+            head: 0,
+            recent: VecDeque::new(),
+            slots: HashMap::new(),
+        }
+    }
+
+    /// Load `path`, or start empty with `half_life_blocks` if it doesn't
+    /// exist or doesn't parse. A stored half-life takes precedence so scores
+    /// stay consistent.
+    pub fn load_or_new(path: &Path, half_life_blocks: f64) -> ArgusResult<Self> {
+        let Some(p) = json_file::read::<Persisted>(path, "hotspot store")? else {
+            return Ok(Self::new(half_life_blocks));
+        };
+        Ok(Self {
+            half_life_blocks: p.half_life_blocks,
+            head: p.head,
+            recent: p.recent,
+            slots: p.slots.into_iter().collect(),
+        })
+    }
+
+    /// Prune faded entries and write atomically (see [`json_file`]).
+    pub fn save(&mut self, path: &Path) -> ArgusResult<()> {
+        self.prune();
+        let persisted = Persisted {
+            half_life_blocks: self.half_life_blocks,
+            head: self.head,
+            recent: self.recent.clone(),
+            slots: self
+                .slots
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        };
+        json_file::write(path, &persisted, "hotspot store")
+    }
+
+    pub fn half_life_blocks(&self) -> f64 {
+        self.half_life_blocks
+    }
+
+    /// Highest block recorded so far.
+    pub fn head(&self) -> u64 {
+        self.head
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Add one block's conflicts. Returns `false` (and changes nothing) if
+    /// the block was already recorded recently.
+    pub fn record(&mut self, block_number: u64, graph: &ConflictGraph) -> bool {
+        if self.recent.contains(&block_number) {
+            return false;
+        }
+        self.recent.push_back(block_number);
+        if self.recent.len() > RECENT_BLOCKS {
+            self.recent.pop_front();
+        }
+        self.head = self.head.max(block_number);
+
+        let mut per_slot: HashMap<&StorageLocation, u64> = HashMap::new();
+        for c in &graph.conflicts {
+            *per_slot.entry(&c.location).or_default() += 1;
+        }
+
+        for (location, count) in per_slot {
+            let stats = self.slots.entry(location.clone()).or_insert(SlotStats {
+                score: 0.0,
+                scored_at: block_number,
+                total_conflicts: 0,
+                blocks_seen: 0,
+                last_seen: block_number,
+            });
+            // Bring both the stored score and this block's weight to the
+            // later of the two blocks, so out-of-order blocks add correctly.
+            let at = stats.scored_at.max(block_number);
+            stats.score = stats.score * decay(self.half_life_blocks, at - stats.scored_at)
+                + count as f64 * decay(self.half_life_blocks, at - block_number);
+            stats.scored_at = at;
+            stats.total_conflicts += count;
+            stats.blocks_seen += 1;
+            stats.last_seen = stats.last_seen.max(block_number);
+        }
+        true
+    }
+
+    /// The `n` highest-scoring slots as of [`head`](Self::head).
+    pub fn top(&self, n: usize) -> Vec<HotSlot> {
+        let mut ranked: Vec<HotSlot> = self
+            .slots
+            .iter()
+            .map(|(loc, s)| HotSlot {
+                address: loc.address,
+                slot: loc.slot,
+                score: self.score_at_head(s),
+                total_conflicts: s.total_conflicts,
+                blocks_seen: s.blocks_seen,
+                last_seen: s.last_seen,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Text table of the top `n` slots, labeled for `chain`.
+    pub fn render(&self, chain: &ChainConfig, n: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Hot slots as of block {} (half-life {} blocks, {} tracked)",
+            self.head,
+            self.half_life_blocks,
+            self.slots.len()
+        );
+        for (i, h) in self.top(n).iter().enumerate() {
+            let (protocol, name) = crate::reporter::group_label(chain, &h.address);
+            let _ = writeln!(
+                out,
+                "{:>3}. {:>9.2}  {protocol} {name}  slot {}  ({} conflicts over {} blocks, last {})",
+                i + 1,
+                h.score,
+                h.slot,
+                h.total_conflicts,
+                h.blocks_seen,
+                h.last_seen
+            );
+        }
+        out
+    }
+
+    fn score_at_head(&self, s: &SlotStats) -> f64 {
+        s.score * decay(self.half_life_blocks, self.head.saturating_sub(s.scored_at))
+    }
+
+    fn prune(&mut self) {
+        let before = self.slots.len();
+        let (half_life, head) = (self.half_life_blocks, self.head);
+        self.slots.retain(|_, s| {
+            s.score * decay(half_life, head.saturating_sub(s.scored_at)) >= PRUNE_BELOW
+        });
+        if self.slots.len() < before {
+            tracing::debug!(pruned = before - self.slots.len(), "pruned faded hotspots");
+        }
+    }
+}
+
+fn decay(half_life_blocks: f64, age: u64) -> f64 {
+    0.5f64.powf(age as f64 / half_life_blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argus_core::{Conflict, ConflictKind};
+
+    fn graph(slots: &[(u8, u8)]) -> ConflictGraph {
+        let mut g = ConflictGraph::new();
+        for (i, &(addr, slot)) in slots.iter().enumerate() {
+            g.add_conflict(Conflict {
+                tx_a: B256::with_last_byte(2 * i as u8),
+                tx_b: B256::with_last_byte(2 * i as u8 + 1),
+                location: StorageLocation {
+                    address: Address::with_last_byte(addr),
+                    slot: B256::with_last_byte(slot),
+                },
+                kind: ConflictKind::WriteWrite,
+            });
+        }
+        g
+    }
+
+    #[test]
+    fn scores_decay_by_half_life() {
+        let mut board = HotSlotLeaderboard::new(10.0);
+        board.record(100, &graph(&[(1, 1), (1, 1), (1, 1), (1, 1)]));
+        board.record(110, &graph(&[(2, 2)]));
+
+        let top = board.top(10);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].address, Address::with_last_byte(1));
+        assert!(
+            (top[0].score - 2.0).abs() < 1e-9,
+            "4 conflicts, one half-life ago"
+        );
+        assert!((top[1].score - 1.0).abs() < 1e-9);
+        assert_eq!(top[0].total_conflicts, 4);
+    }
+
+    #[test]
+    fn out_of_order_blocks_match_in_order() {
+        let mut a = HotSlotLeaderboard::new(5.0);
+        a.record(1, &graph(&[(1, 1)]));
+        a.record(6, &graph(&[(1, 1)]));
+        let mut b = HotSlotLeaderboard::new(5.0);
+        b.record(6, &graph(&[(1, 1)]));
+        b.record(1, &graph(&[(1, 1)]));
+        assert!((a.top(1)[0].score - b.top(1)[0].score).abs() < 1e-9);
+        assert!((a.top(1)[0].score - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repeated_block_is_ignored() {
+        let mut board = HotSlotLeaderboard::new(10.0);
+        assert!(board.record(5, &graph(&[(1, 1)])));
+        assert!(!board.record(5, &graph(&[(1, 1)])));
+        assert_eq!(board.top(1)[0].total_conflicts, 1);
+    }
+
+    #[test]
+    fn persists_and_prunes() {
+        let path = std::env::temp_dir().join(format!("argus-hotspots-{}.json", std::process::id()));
+        let mut board = HotSlotLeaderboard::new(1.0);
+        board.record(1, &graph(&[(1, 1)]));
+        board.record(100, &graph(&[(2, 2)]));
+        board.save(&path).unwrap();
+
+        let loaded = HotSlotLeaderboard::load_or_new(&path, 50.0).unwrap();
+        assert_eq!(loaded.half_life_blocks(), 1.0);
+        assert_eq!(loaded.head(), 100);
+        assert_eq!(loaded.len(), 1, "block-1 slot decayed away");
+
+        // A torn or foreign file starts the board over.
+        std::fs::write(&path, b"not json").unwrap();
+        let fresh = HotSlotLeaderboard::load_or_new(&path, 50.0).unwrap();
+        assert_eq!((fresh.half_life_blocks(), fresh.len()), (50.0, 0));
+        std::fs::remove_file(&path).ok();
+    }
+}
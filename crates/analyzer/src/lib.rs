@@ -1,9 +1,37 @@
 //! EVM simulation engine, conflict graph builder, report generator, and data sinks.
+//!
+//! Graph construction, reporting, and sinks are pure and build for
+//! `wasm32-unknown-unknown` with `--no-default-features`. Everything that
+//! executes transactions sits behind the default `simulator` feature.
 
+pub mod aggregate;
+#[cfg(feature = "simulator")]
+pub mod blockstm;
+#[cfg(feature = "simulator")]
+pub mod bundle;
+pub mod cache;
+pub mod compare;
+pub mod concurrency;
+pub mod declared;
+pub mod fidelity;
 pub mod graph;
+pub mod hotspots;
+#[cfg(feature = "simulator")]
+pub mod mempool;
+#[cfg(feature = "simulator")]
+pub mod ordering;
+mod par;
+pub mod plugin;
+pub mod reorder;
 pub mod reporter;
+pub mod severity;
+#[cfg(feature = "simulator")]
 pub mod simulator;
 pub mod sink;
+pub mod spill;
+pub mod whatif;
 
+#[cfg(feature = "simulator")]
 pub use simulator::AccessListInspector;
+#[cfg(feature = "simulator")]
 pub use simulator::{simulate_batch_with_state, WarmCacheDB};
@@ -0,0 +1,311 @@
+//! Real-time conflict predictor for pending transactions.
+//!
+//! Scores each incoming pending tx against the current pending set and the
+//! latest block's hotspots ("this tx will conflict with ~14 pending txs on
+//! USDC slot X"). The streaming counterpart to per-block analysis.
+
+use crate::sink::PendingScoreRow;
+use alloy_primitives::B256;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, AccessMode, ConflictGraph, StorageLocation};
+use argus_provider::DataProvider;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Predicted contention for one pending transaction.
+#[derive(Debug, Clone)]
+pub struct PendingTxScore {
+    pub tx_hash: B256,
+    /// Distinct pending txs this tx would conflict with.
+    pub pending_conflicts: usize,
+    /// Location contributing the most pending conflicts, with its count.
+    pub top_location: Option<(StorageLocation, usize)>,
+    /// Touched locations that were hotspots in the latest analyzed block.
+    pub hotspot_hits: usize,
+    /// Highest conflict density among the hit hotspots.
+    pub max_hotspot_density: f64,
+}
+
+impl PendingTxScore {
+    /// Flatten into a sink-ready row.
+    pub fn to_row(&self) -> PendingScoreRow {
+        let (address, protocol, name, slot, top_count) = match &self.top_location {
+            Some((loc, n)) => {
+                let (protocol, name) = match argus_core::labels::lookup(&loc.address) {
+                    Some(l) => (l.protocol.to_string(), l.name.to_string()),
+                    None => ("Unknown".into(), format!("{}", loc.address)),
+                };
+                (
+                    Some(format!("{}", loc.address)),
+                    Some(protocol),
+                    Some(name),
+                    Some(format!("{}", loc.slot)),
+                    *n as u32,
+                )
+            }
+            None => (None, None, None, None, 0),
+        };
+
+        PendingScoreRow {
+            tx_hash: format!("{}", self.tx_hash),
+            pending_conflicts: self.pending_conflicts as u32,
+            top_contract_address: address,
+            top_contract_protocol: protocol,
+            top_contract_name: name,
+            top_slot: slot,
+            top_slot_conflicts: top_count,
+            hotspot_hits: self.hotspot_hits as u32,
+            max_hotspot_density: (self.max_hotspot_density * 100.0).round() / 100.0,
+            created_at: crate::sink::chrono_now(),
+        }
+    }
+}
+
+/// Maintains the pending set and scores new arrivals against it.
+#[derive(Default)]
+pub struct MempoolPredictor {
+    pending: HashMap<B256, AccessList>,
+    index: HashMap<StorageLocation, Vec<(B256, AccessMode)>>,
+    /// Conflict density per location from the latest analyzed block.
+    hotspots: HashMap<StorageLocation, f64>,
+}
+
+impl MempoolPredictor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn contains(&self, tx_hash: &B256) -> bool {
+        self.pending.contains_key(tx_hash)
+    }
+
+    /// Replace the hotspot table with per-location densities from `graph`.
+    pub fn set_hotspots(&mut self, graph: &ConflictGraph) {
+        let mut buckets: HashMap<&StorageLocation, (HashSet<B256>, usize)> = HashMap::new();
+        for c in &graph.conflicts {
+            let bucket = buckets.entry(&c.location).or_default();
+            bucket.0.insert(c.tx_a);
+            bucket.0.insert(c.tx_b);
+            bucket.1 += 1;
+        }
+
+        self.hotspots = buckets
+            .into_iter()
+            .map(|(loc, (txs, count))| (loc.clone(), count as f64 / txs.len() as f64))
+            .collect();
+    }
+
+    /// Score `al` against the current pending set without inserting it.
+    pub fn score(&self, al: &AccessList) -> PendingTxScore {
+        let mut conflicting: HashSet<B256> = HashSet::new();
+        let mut top_location: Option<(StorageLocation, usize)> = None;
+        let mut hotspot_hits = 0;
+        let mut max_hotspot_density = 0.0f64;
+
+        for entry in &al.entries {
+            if let Some(&density) = self.hotspots.get(&entry.location) {
+                hotspot_hits += 1;
+                max_hotspot_density = max_hotspot_density.max(density);
+            }
+
+            let Some(accessors) = self.index.get(&entry.location) else {
+                continue;
+            };
+
+            let mut here = 0;
+            for (other, mode) in accessors {
+                if *other == al.tx_hash {
+                    continue;
+                }
+                if entry.mode == AccessMode::Write || *mode == AccessMode::Write {
+                    conflicting.insert(*other);
+                    here += 1;
+                }
+            }
+
+            if here > top_location.as_ref().map_or(0, |(_, n)| *n) {
+                top_location = Some((entry.location.clone(), here));
+            }
+        }
+
+        PendingTxScore {
+            tx_hash: al.tx_hash,
+            pending_conflicts: conflicting.len(),
+            top_location,
+            hotspot_hits,
+            max_hotspot_density,
+        }
+    }
+
+    /// Score `al` and add it to the pending set.
+    pub fn insert(&mut self, al: AccessList) -> PendingTxScore {
+        let score = self.score(&al);
+        self.remove(&al.tx_hash);
+        for entry in &al.entries {
+            self.index
+                .entry(entry.location.clone())
+                .or_default()
+                .push((al.tx_hash, entry.mode));
+        }
+        self.pending.insert(al.tx_hash, al);
+        score
+    }
+
+    /// Drop a tx (mined or evicted) from the pending set.
+    pub fn remove(&mut self, tx_hash: &B256) -> Option<AccessList> {
+        let al = self.pending.remove(tx_hash)?;
+        for entry in &al.entries {
+            if let Some(accessors) = self.index.get_mut(&entry.location) {
+                accessors.retain(|(h, _)| h != tx_hash);
+                if accessors.is_empty() {
+                    self.index.remove(&entry.location);
+                }
+            }
+        }
+        Some(al)
+    }
+
+    /// Keep only txs that are still pending.
+    pub fn retain_pending(&mut self, still_pending: &HashSet<B256>) {
+        let gone: Vec<B256> = self
+            .pending
+            .keys()
+            .filter(|h| !still_pending.contains(*h))
+            .copied()
+            .collect();
+        for h in gone {
+            self.remove(&h);
+        }
+    }
+
+    /// Poll `provider` for pending txs every `interval`, scoring new arrivals
+    /// and sending each score on `scores`. Returns when the receiver is dropped.
+    ///
+    /// New txs are simulated against `EmptyDB` to keep the loop cheap.
+    pub async fn run<P: DataProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+        interval: Duration,
+        scores: mpsc::Sender<PendingTxScore>,
+    ) -> ArgusResult<()> {
+        loop {
+            let txs = provider.get_pending_transactions().await?;
+            let hashes: HashSet<B256> = txs.iter().map(|tx| tx.hash).collect();
+            self.retain_pending(&hashes);
+
+            let fresh: Vec<_> = txs
+                .into_iter()
+                .filter(|tx| !self.contains(&tx.hash))
+                .collect();
+            if !fresh.is_empty() {
+                tracing::debug!(
+                    fresh = fresh.len(),
+                    pending = self.len(),
+                    "scoring pending txs"
+                );
+                let access_lists = crate::simulator::simulate_batch(fresh).await?;
+                for al in access_lists {
+                    let score = self.insert(al);
+                    if scores.send(score).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use argus_core::{AccessEntry, Conflict, ConflictKind};
+    use smallvec::SmallVec;
+
+    fn loc(slot: u64) -> StorageLocation {
+        StorageLocation {
+            address: Address::ZERO,
+            slot: B256::from(U256::from(slot)),
+        }
+    }
+
+    fn list(tx: u64, entries: &[(u64, AccessMode)]) -> AccessList {
+        AccessList {
+            tx_hash: B256::from(U256::from(tx)),
+            entries: entries
+                .iter()
+                .map(|&(slot, mode)| AccessEntry {
+                    location: loc(slot),
+                    mode,
+                    count: 1,
+                })
+                .collect::<SmallVec<_>>(),
+            transient: SmallVec::new(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn scores_against_pending_set() {
+        let mut p = MempoolPredictor::new();
+        p.insert(list(1, &[(1, AccessMode::Write)]));
+        p.insert(list(2, &[(1, AccessMode::Read)]));
+        p.insert(list(3, &[(2, AccessMode::Read)]));
+
+        let score = p.insert(list(4, &[(1, AccessMode::Write), (2, AccessMode::Read)]));
+        assert_eq!(score.pending_conflicts, 2);
+        assert_eq!(score.top_location, Some((loc(1), 2)));
+    }
+
+    #[test]
+    fn read_read_is_not_a_conflict() {
+        let mut p = MempoolPredictor::new();
+        p.insert(list(1, &[(1, AccessMode::Read)]));
+        let score = p.score(&list(2, &[(1, AccessMode::Read)]));
+        assert_eq!(score.pending_conflicts, 0);
+        assert!(score.top_location.is_none());
+    }
+
+    #[test]
+    fn retain_drops_mined_txs() {
+        let mut p = MempoolPredictor::new();
+        p.insert(list(1, &[(1, AccessMode::Write)]));
+        p.insert(list(2, &[(1, AccessMode::Write)]));
+        p.retain_pending(&HashSet::from([B256::from(U256::from(2))]));
+
+        assert_eq!(p.len(), 1);
+        let score = p.score(&list(3, &[(1, AccessMode::Write)]));
+        assert_eq!(score.pending_conflicts, 1);
+    }
+
+    #[test]
+    fn hotspots_from_graph() {
+        let mut graph = ConflictGraph::new();
+        graph.add_conflict(Conflict {
+            tx_a: B256::from(U256::from(10)),
+            tx_b: B256::from(U256::from(11)),
+            location: loc(7),
+            kind: ConflictKind::WriteWrite,
+        });
+
+        let mut p = MempoolPredictor::new();
+        p.set_hotspots(&graph);
+        let score = p.score(&list(1, &[(7, AccessMode::Write)]));
+        assert_eq!(score.hotspot_hits, 1);
+        assert_eq!(score.max_hotspot_density, 0.5);
+    }
+}
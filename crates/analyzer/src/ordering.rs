@@ -0,0 +1,260 @@
+//! Ordering sensitivity: re-simulating a block in other transaction orders.
+//!
+//! [`crate::reorder`] proposes orderings from one simulation's conflict
+//! graph, assuming each transaction touches the same state whatever runs
+//! before it. This puts that to the test. Every candidate ordering is
+//! executed sequentially over the warm state, its conflict graph rebuilt
+//! and its access lists compared with those of block order. Transactions
+//! that branch on what earlier ones wrote (a swap reverting once the price
+//! moved, a mint failing once sold out) show up as changed access lists or
+//! outcomes; an insensitive block only reshuffles the same edges.
+//!
+//! ```ignore
+//! let orders = candidate_orderings(&txs, &lists, &graph);
+//! let r = resimulate_orderings(&warm_db, block_number, &txs, &options, &orders, None)?;
+//! for run in &r.alternatives {
+//!     println!("{}: {} conflicts, {} lists changed", run.strategy, run.conflicts, run.changed_access_lists);
+//! }
+//! ```
+
+use crate::graph::build_conflict_graph;
+use crate::plugin::ReportSection;
+use crate::reorder::{BlockDeps, OrderingStats};
+use crate::simulator::{simulate_batch_sequential, BatchOptions};
+use alloy_primitives::B256;
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{AccessList, Transaction};
+use revm::database::CacheDB;
+use revm::database_interface::DatabaseRef;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+
+/// One sequential run of the block in a given order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderingRun {
+    pub strategy: String,
+    /// Execution order as transaction hashes.
+    pub order: Vec<B256>,
+    /// Edges in the conflict graph rebuilt from this run.
+    pub conflicts: usize,
+    /// Parallelism of this order, measured on its own conflict graph.
+    pub stats: OrderingStats,
+    /// Transactions whose accessed locations or modes differ from block
+    /// order's run.
+    pub changed_access_lists: usize,
+    /// Transactions that ended differently (e.g. reverted) than in block
+    /// order.
+    pub changed_outcomes: usize,
+}
+
+/// Block order against each re-simulated alternative.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderingSensitivityReport {
+    pub block_number: u64,
+    pub total_txs: usize,
+    pub baseline: OrderingRun,
+    pub alternatives: Vec<OrderingRun>,
+}
+
+impl OrderingSensitivityReport {
+    /// Share of transactions whose access list changed under the most
+    /// disruptive alternative (0.0 without alternatives).
+    pub fn sensitivity(&self) -> f64 {
+        let worst = self
+            .alternatives
+            .iter()
+            .map(|run| run.changed_access_lists)
+            .max()
+            .unwrap_or(0);
+        if self.total_txs == 0 {
+            return 0.0;
+        }
+        worst as f64 / self.total_txs as f64
+    }
+
+    pub fn section(&self) -> ReportSection {
+        let line = |run: &OrderingRun| {
+            format!(
+                "{:<30} {} conflicts, {} rounds, {:.2}x | {} lists, {} outcomes changed",
+                run.strategy,
+                run.conflicts,
+                run.stats.rounds,
+                run.stats.speedup,
+                run.changed_access_lists,
+                run.changed_outcomes
+            )
+        };
+        let mut section = ReportSection::new("Ordering sensitivity")
+            .line(format!(
+                "{} txs re-simulated in {} alternative orders: {:.1}% of access lists change",
+                self.total_txs,
+                self.alternatives.len(),
+                self.sensitivity() * 100.0
+            ))
+            .line(line(&self.baseline));
+        for run in &self.alternatives {
+            section = section.line(line(run));
+        }
+        section.with_data(serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+/// Simulate `transactions` sequentially in block order and in each of
+/// `orderings` (block positions, e.g. from
+/// [`candidate_orderings`](crate::reorder::candidate_orderings)), each run
+/// starting over `warm_db` and executing as `options` describe, so fee
+/// accounting and exclusions match the block's own simulation; their
+/// thread settings have nothing to do here. An ordering that is not a
+/// permutation of the block fails with [`ArgusError::InvalidInput`].
+pub fn resimulate_orderings<ExtDB>(
+    warm_db: &CacheDB<ExtDB>,
+    block_number: u64,
+    transactions: &[Transaction],
+    options: &BatchOptions,
+    orderings: &[(String, Vec<usize>)],
+    cancel: Option<&CancellationToken>,
+) -> ArgusResult<OrderingSensitivityReport>
+where
+    ExtDB: DatabaseRef,
+    ExtDB::Error: core::fmt::Debug,
+{
+    for (name, order) in orderings {
+        let mut seen = vec![false; transactions.len()];
+        let valid = order.len() == transactions.len()
+            && order
+                .iter()
+                .all(|&i| i < seen.len() && !std::mem::replace(&mut seen[i], true));
+        if !valid {
+            return Err(ArgusError::InvalidInput(format!(
+                "ordering {name:?} is not a permutation of the block's {} txs",
+                transactions.len()
+            )));
+        }
+    }
+
+    let run = |order: &[usize]| -> ArgusResult<(Vec<Transaction>, Vec<AccessList>)> {
+        let txs: Vec<Transaction> = order.iter().map(|&i| transactions[i].clone()).collect();
+        let (lists, _, _) = simulate_batch_sequential(warm_db, &txs, &[], None, cancel, options)?;
+        Ok((txs, lists))
+    };
+    let summarize = |strategy: &str, txs: &[Transaction], lists: &[AccessList]| {
+        let graph = build_conflict_graph(lists);
+        let identity: Vec<usize> = (0..txs.len()).collect();
+        OrderingRun {
+            strategy: strategy.to_string(),
+            order: txs.iter().map(|tx| tx.hash).collect(),
+            conflicts: graph.conflicts.len(),
+            stats: BlockDeps::new(txs, lists, &graph).evaluate(&identity),
+            changed_access_lists: 0,
+            changed_outcomes: 0,
+        }
+    };
+
+    let identity: Vec<usize> = (0..transactions.len()).collect();
+    let (txs, baseline_lists) = run(&identity)?;
+    let baseline = summarize("block order", &txs, &baseline_lists);
+    let by_hash: HashMap<B256, &AccessList> =
+        baseline_lists.iter().map(|al| (al.tx_hash, al)).collect();
+
+    let mut alternatives = Vec::with_capacity(orderings.len());
+    for (name, order) in orderings {
+        let (txs, lists) = run(order)?;
+        let mut r = summarize(name, &txs, &lists);
+        for list in &lists {
+            let Some(base) = by_hash.get(&list.tx_hash) else {
+                continue;
+            };
+            let footprint = |al: &AccessList| {
+                al.entries
+                    .iter()
+                    .map(|e| (e.location.clone(), e.mode))
+                    .collect::<Vec<_>>()
+            };
+            r.changed_access_lists += (footprint(list) != footprint(base)) as usize;
+            r.changed_outcomes += (list.outcome != base.outcome) as usize;
+        }
+        alternatives.push(r);
+    }
+
+    Ok(OrderingSensitivityReport {
+        block_number,
+        total_txs: transactions.len(),
+        baseline,
+        alternatives,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::tests::deploy;
+    use crate::simulator::{FeeAccounting, WarmCacheDB};
+    use alloy_primitives::{Address, Bytes};
+
+    /// Tx 1 sets slot 0 of a switch contract; the others read slot 1 while
+    /// it is unset and slot 2 once it is.
+    fn switch_block() -> (WarmCacheDB, Vec<Transaction>) {
+        // CALLDATASIZE PUSH1 14 JUMPI
+        // PUSH1 0 SLOAD PUSH1 21 JUMPI PUSH1 1 SLOAD STOP
+        // JUMPDEST(14) PUSH1 1 PUSH1 0 SSTORE STOP
+        // JUMPDEST(21) PUSH1 2 SLOAD STOP
+        let code = Bytes::from_static(&[
+            0x36, 0x60, 0x0e, 0x57, 0x60, 0x00, 0x54, 0x60, 0x15, 0x57, 0x60, 0x01, 0x54, 0x00,
+            0x5b, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00, 0x5b, 0x60, 0x02, 0x54, 0x00,
+        ]);
+        let (target, warm) = deploy(code);
+        let txs = (1..=3u8)
+            .map(|n| Transaction {
+                hash: B256::with_last_byte(n),
+                from: Address::with_last_byte(0x80 + n),
+                to: Some(target),
+                input: if n == 1 {
+                    Bytes::from_static(&[1])
+                } else {
+                    Bytes::new()
+                },
+                gas: 100_000,
+                ..Default::default()
+            })
+            .collect();
+        (warm, txs)
+    }
+
+    #[test]
+    fn moving_the_writer_changes_readers() {
+        let (warm, txs) = switch_block();
+        let orders = [("writer last".to_string(), vec![1, 2, 0])];
+        let options = BatchOptions::default();
+        let r = resimulate_orderings(&warm, 9, &txs, &options, &orders, None).unwrap();
+        assert_eq!(r.baseline.order, [txs[0].hash, txs[1].hash, txs[2].hash]);
+        let alt = &r.alternatives[0];
+        assert_eq!(alt.order[2], txs[0].hash);
+        // Both readers take the other branch; the writer's footprint stays.
+        assert_eq!(alt.changed_access_lists, 2);
+        assert_eq!(alt.changed_outcomes, 0);
+        assert!((r.sensitivity() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn strict_fees_reach_every_run() {
+        let (warm, txs) = switch_block();
+        let orders = [("writer last".to_string(), vec![1, 2, 0])];
+        let relaxed = BatchOptions::default();
+        let relaxed = resimulate_orderings(&warm, 9, &txs, &relaxed, &orders, None).unwrap();
+        let strict = BatchOptions::default().with_fees(FeeAccounting::Strict);
+        let strict = resimulate_orderings(&warm, 9, &txs, &strict, &orders, None).unwrap();
+        // Every transaction now writes the coinbase, so the readers conflict too.
+        assert!(strict.baseline.conflicts > relaxed.baseline.conflicts);
+        assert!(strict.alternatives[0].conflicts > relaxed.alternatives[0].conflicts);
+    }
+
+    #[test]
+    fn rejects_non_permutations() {
+        let (warm, txs) = switch_block();
+        let orders = [("dup".to_string(), vec![0, 0, 1])];
+        let options = BatchOptions::default();
+        let err = resimulate_orderings(&warm, 9, &txs, &options, &orders, None).unwrap_err();
+        assert!(matches!(err, ArgusError::InvalidInput(_)));
+    }
+}
@@ -0,0 +1,79 @@
+//! Keyed aggregation that fans out over rayon for large inputs.
+
+use argus_core::FastHashMap;
+use std::hash::Hash;
+
+/// Below this many items the serial path beats thread fan-out.
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+/// Fold `items` into one accumulator per key.
+///
+/// With the `rayon` feature and a large input, each worker folds its share
+/// into a private map and the shards are merged pairwise; otherwise a single
+/// map is built serially. `add` folds one item into an accumulator; `merge`
+/// combines two accumulators for the same key and must be commutative.
+#[cfg_attr(not(feature = "rayon"), allow(unused_variables))]
+pub(crate) fn group_by<T, K, A>(
+    items: &[T],
+    key: impl Fn(&T) -> K + Sync + Send,
+    add: impl Fn(&mut A, &T) + Sync + Send,
+    merge: impl Fn(&mut A, A) + Sync + Send,
+) -> FastHashMap<K, A>
+where
+    T: Sync,
+    K: Eq + Hash + Send,
+    A: Default + Send,
+{
+    let fold = |mut map: FastHashMap<K, A>, item: &T| {
+        add(map.entry(key(item)).or_default(), item);
+        map
+    };
+
+    #[cfg(feature = "rayon")]
+    if items.len() >= PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+        use std::collections::hash_map::Entry;
+        return items.par_iter().fold(FastHashMap::default, &fold).reduce(
+            FastHashMap::default,
+            |mut a, mut b| {
+                if a.len() < b.len() {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                for (k, v) in b {
+                    match a.entry(k) {
+                        Entry::Occupied(mut e) => merge(e.get_mut(), v),
+                        Entry::Vacant(e) => {
+                            e.insert(v);
+                        }
+                    }
+                }
+                a
+            },
+        );
+    }
+
+    items.iter().fold(FastHashMap::default(), &fold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_and_small_inputs_agree() {
+        for n in [100u32, 100_000] {
+            let items: Vec<u32> = (0..n).collect();
+            let groups = group_by(
+                &items,
+                |i| i % 7,
+                |sum: &mut u64, i| *sum += *i as u64,
+                |a, b| *a += b,
+            );
+            assert_eq!(groups.len(), 7);
+            assert_eq!(groups.values().sum::<u64>(), (n as u64 - 1) * n as u64 / 2);
+            let expected: u64 = (0..n).filter(|i| i % 7 == 3).map(u64::from).sum();
+            assert_eq!(groups[&3], expected);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Extension points for out-of-tree capture logic and report sections.
+//!
+//! - [`TxInspectorPlugin`] observes EVM execution of every transaction,
+//!   alongside the built-in access-list inspector.
+//! - [`BlockAnalysisPlugin`] runs after the conflict graph is built and
+//!   contributes a [`ReportSection`] to the rendered report.
+//! - [`SimulationObserver`] receives each transaction's access list as soon
+//!   as it finishes simulating, for incremental output.
+//!
+//! Both are registered on the `argus` facade builder and run inside the
+//! standard pipeline, so protocol-specific analyses need no fork.
+
+use alloy_primitives::{Address, B256, U256};
+use argus_core::chain::ChainConfig;
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, ConflictGraph, Transaction, TxReceipt};
+use serde::Serialize;
+
+/// Factory for per-transaction capture state.
+///
+/// Simulation is parallel, so the plugin itself is shared and each
+/// transaction gets its own [`TxCapture`].
+pub trait TxInspectorPlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn begin_tx(&self, tx: &Transaction) -> Box<dyn TxCapture>;
+}
+
+/// Per-transaction hooks. All methods except `finish` default to no-ops.
+pub trait TxCapture: Send {
+    /// Called before every opcode. `stack` is bottom-to-top.
+    fn step(&mut self, _address: Address, _opcode: u8, _stack: &[U256]) {}
+
+    /// Called when execution enters a call frame targeting `target`.
+    fn enter_call(&mut self, _target: Address) {}
+
+    /// Called when the innermost call frame returns.
+    fn exit_call(&mut self) {}
+
+    /// Consume the capture. `None` records nothing for this tx.
+    fn finish(self: Box<Self>) -> Option<serde_json::Value>;
+}
+
+/// One inspector plugin's result for one transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginOutput {
+    pub plugin: String,
+    pub tx_hash: B256,
+    pub data: serde_json::Value,
+}
+
+/// Everything a block-level analysis can see.
+pub struct BlockAnalysisInput<'a> {
+    pub block_number: u64,
+    pub chain: &'static ChainConfig,
+    pub transactions: &'a [Transaction],
+    pub access_lists: &'a [AccessList],
+    pub graph: &'a ConflictGraph,
+    pub plugin_outputs: &'a [PluginOutput],
+    /// Mined receipts, when they were fetched; empty otherwise.
+    pub receipts: &'a [TxReceipt],
+}
+
+/// Custom analysis over a finished block.
+pub trait BlockAnalysisPlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection>;
+}
+
+/// Streams results out of a block while it is still simulating.
+///
+/// `on_tx` is called from simulation worker threads in completion order,
+/// not block order; `index` is the transaction's position in the block.
+pub trait SimulationObserver: Send + Sync {
+    /// Called once before any transaction of `block_number` is simulated.
+    fn begin_block(&self, _chain: &'static ChainConfig, _block_number: u64) {}
+
+    fn on_tx(&self, index: usize, access_list: &AccessList);
+
+    /// Called once after every transaction has been reported.
+    fn end_block(&self) {}
+}
+
+/// A titled block of report output contributed by a plugin.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSection {
+    pub title: String,
+    /// Human-readable lines appended to the rendered report.
+    pub lines: Vec<String>,
+    /// Machine-readable payload for JSON consumers.
+    pub data: serde_json::Value,
+}
+
+impl ReportSection {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            lines: Vec::new(),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    pub fn line(mut self, line: impl Into<String>) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = data;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TxCount;
+
+    impl BlockAnalysisPlugin for TxCount {
+        fn name(&self) -> &str {
+            "tx_count"
+        }
+
+        fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection> {
+            Ok(ReportSection::new("Tx count")
+                .line(format!("{} txs", input.transactions.len()))
+                .with_data(serde_json::json!(input.transactions.len())))
+        }
+    }
+
+    #[test]
+    fn block_plugin_builds_section() {
+        let graph = ConflictGraph::default();
+        let input = BlockAnalysisInput {
+            block_number: 1,
+            chain: &argus_core::chain::ETHEREUM,
+            transactions: &[],
+            access_lists: &[],
+            graph: &graph,
+            plugin_outputs: &[],
+            receipts: &[],
+        };
+        let section = TxCount.analyze(&input).unwrap();
+        assert_eq!(section.title, "Tx count");
+        assert_eq!(section.lines, vec!["0 txs"]);
+        assert_eq!(section.data, serde_json::json!(0));
+    }
+}
@@ -0,0 +1,382 @@
+//! What-if search for intra-block orderings with more parallelism.
+//!
+//! An ordering turns the undirected conflict graph into a DAG: of two
+//! conflicting transactions, the earlier one must commit first. Same-sender
+//! transactions are additionally chained in nonce (block) order. The DAG's
+//! depth is the number of parallel rounds, and its gas-weighted longest path
+//! bounds execution time. Gas weights are the gas each transaction used in
+//! simulation, falling back to its gas limit where it was not simulated.
+//!
+//! Finding the ordering with the fewest rounds is graph colouring, so the
+//! optimizer runs a few greedy colouring strategies and keeps the best.
+//!
+//! ```ignore
+//! let report = ReorderReport::build(block_number, &transactions, &access_lists, &graph);
+//! println!("{:.2}x -> {:.2}x", report.original.speedup, report.best.speedup);
+//! ```
+
+use crate::plugin::{BlockAnalysisInput, BlockAnalysisPlugin, ReportSection};
+use alloy_primitives::{Address, B256};
+use argus_core::error::ArgusResult;
+use argus_core::{AccessList, ConflictGraph, Transaction};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Dependency structure of a block, indexed by block position.
+pub(crate) struct BlockDeps {
+    pub gas: Vec<u64>,
+    /// Previous transaction from the same sender.
+    pub sender_prev: Vec<Option<usize>>,
+    /// Conflicting transactions (deduplicated).
+    pub neighbors: Vec<Vec<usize>>,
+}
+
+impl BlockDeps {
+    /// Gas weights come from `access_lists` where simulation recorded gas
+    /// used, otherwise from the transaction's gas limit.
+    pub fn new(
+        transactions: &[Transaction],
+        access_lists: &[AccessList],
+        graph: &ConflictGraph,
+    ) -> Self {
+        let index: HashMap<B256, usize> = transactions
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| (tx.hash, i))
+            .collect();
+
+        let simulated: HashMap<B256, u64> = access_lists
+            .iter()
+            .filter(|list| list.gas_used > 0)
+            .map(|list| (list.tx_hash, list.gas_used))
+            .collect();
+
+        let mut last_by_sender: HashMap<Address, usize> = HashMap::new();
+        let sender_prev = transactions
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| last_by_sender.insert(tx.from, i))
+            .collect();
+
+        let mut neighbors = vec![Vec::new(); transactions.len()];
+        for (hash, adj) in &graph.adjacency {
+            let Some(&i) = index.get(hash) else { continue };
+            neighbors[i] = adj.iter().filter_map(|h| index.get(h).copied()).collect();
+            neighbors[i].sort_unstable();
+            neighbors[i].dedup();
+        }
+
+        Self {
+            gas: transactions
+                .iter()
+                .map(|tx| simulated.get(&tx.hash).copied().unwrap_or(tx.gas))
+                .collect(),
+            sender_prev,
+            neighbors,
+        }
+    }
+
+    pub fn tx_count(&self) -> usize {
+        self.gas.len()
+    }
+
+    pub fn total_gas(&self) -> u64 {
+        self.gas.iter().sum()
+    }
+
+    /// Rounds and critical-path gas of executing in `order`.
+    pub fn evaluate(&self, order: &[usize]) -> OrderingStats {
+        let mut pos = vec![0; self.tx_count()];
+        for (p, &i) in order.iter().enumerate() {
+            pos[i] = p;
+        }
+
+        let mut level = vec![0usize; self.tx_count()];
+        let mut finish = vec![0u64; self.tx_count()];
+        for &i in order {
+            let preds = self.neighbors[i]
+                .iter()
+                .copied()
+                .filter(|&j| pos[j] < pos[i])
+                .chain(self.sender_prev[i]);
+            let (mut lvl, mut start) = (0, 0);
+            for j in preds {
+                lvl = lvl.max(level[j]);
+                start = start.max(finish[j]);
+            }
+            level[i] = lvl + 1;
+            finish[i] = start + self.gas[i];
+        }
+
+        let critical_path_gas = finish.iter().copied().max().unwrap_or(0);
+        OrderingStats {
+            rounds: level.iter().copied().max().unwrap_or(0),
+            critical_path_gas,
+            speedup: speedup(self.total_gas(), critical_path_gas),
+        }
+    }
+}
+
+fn speedup(total_gas: u64, critical_path_gas: u64) -> f64 {
+    if critical_path_gas == 0 {
+        1.0
+    } else {
+        total_gas as f64 / critical_path_gas as f64
+    }
+}
+
+/// Parallelism of one ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OrderingStats {
+    /// Depth of the dependency DAG.
+    pub rounds: usize,
+    /// Gas along the heaviest dependency chain.
+    pub critical_path_gas: u64,
+    /// Total gas over critical-path gas, i.e. ideal speedup with unlimited cores.
+    pub speedup: f64,
+}
+
+/// Original block order versus the best ordering found.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReorderReport {
+    pub block_number: u64,
+    pub total_txs: usize,
+    pub total_gas: u64,
+    pub original: OrderingStats,
+    pub best: OrderingStats,
+    /// Strategy that produced `best`, or `"block order"` if none beat it.
+    pub strategy: String,
+    /// Best ordering as transaction hashes.
+    pub order: Vec<B256>,
+}
+
+/// Greedy colouring priorities, applied among transactions whose sender
+/// predecessor is already placed.
+const STRATEGIES: &[(&str, fn(&BlockDeps, usize) -> u64)] = &[
+    ("greedy (block order)", |_, _| 0),
+    ("greedy (most conflicts first)", |d, i| {
+        d.neighbors[i].len() as u64
+    }),
+    ("greedy (heaviest first)", |d, i| d.gas[i]),
+];
+
+impl ReorderReport {
+    pub fn build(
+        block_number: u64,
+        transactions: &[Transaction],
+        access_lists: &[AccessList],
+        graph: &ConflictGraph,
+    ) -> Self {
+        let deps = BlockDeps::new(transactions, access_lists, graph);
+        let identity: Vec<usize> = (0..deps.tx_count()).collect();
+        let original = deps.evaluate(&identity);
+
+        let mut best = (original, "block order", identity);
+        for &(name, priority) in STRATEGIES {
+            let order = greedy_order(&deps, priority);
+            let stats = deps.evaluate(&order);
+            let key = |s: &OrderingStats| (s.critical_path_gas, s.rounds);
+            if key(&stats) < key(&best.0) {
+                best = (stats, name, order);
+            }
+        }
+
+        Self {
+            block_number,
+            total_txs: deps.tx_count(),
+            total_gas: deps.total_gas(),
+            original,
+            best: best.0,
+            strategy: best.1.to_string(),
+            order: best.2.iter().map(|&i| transactions[i].hash).collect(),
+        }
+    }
+
+    pub fn rounds_saved(&self) -> usize {
+        self.original.rounds.saturating_sub(self.best.rounds)
+    }
+
+    /// Relative speedup gained from reordering alone.
+    pub fn improvement(&self) -> f64 {
+        self.best.speedup / self.original.speedup - 1.0
+    }
+}
+
+/// The orderings [`ReorderReport::build`] tries, by strategy name, as block
+/// positions. Those identical to block order are left out.
+pub fn candidate_orderings(
+    transactions: &[Transaction],
+    access_lists: &[AccessList],
+    graph: &ConflictGraph,
+) -> Vec<(String, Vec<usize>)> {
+    let deps = BlockDeps::new(transactions, access_lists, graph);
+    let mut orders: Vec<(String, Vec<usize>)> = Vec::new();
+    for &(name, priority) in STRATEGIES {
+        let order = greedy_order(&deps, priority);
+        let is_identity = order.iter().enumerate().all(|(p, &i)| p == i);
+        if !is_identity && !orders.iter().any(|(_, o)| *o == order) {
+            orders.push((name.to_string(), order));
+        }
+    }
+    orders
+}
+
+/// Colour transactions greedily in `priority` order (ties by block index),
+/// then order by colour. Same-sender transactions get strictly increasing
+/// colours, so nonce order is preserved.
+fn greedy_order(deps: &BlockDeps, priority: fn(&BlockDeps, usize) -> u64) -> Vec<usize> {
+    let n = deps.tx_count();
+    let mut sender_next: Vec<Option<usize>> = vec![None; n];
+    for (i, prev) in deps.sender_prev.iter().enumerate() {
+        if let Some(p) = *prev {
+            sender_next[p] = Some(i);
+        }
+    }
+
+    let mut ready: BinaryHeap<(u64, Reverse<usize>)> = (0..n)
+        .filter(|&i| deps.sender_prev[i].is_none())
+        .map(|i| (priority(deps, i), Reverse(i)))
+        .collect();
+
+    let mut color: Vec<Option<usize>> = vec![None; n];
+    while let Some((_, Reverse(i))) = ready.pop() {
+        let min = deps.sender_prev[i].map_or(0, |p| color[p].unwrap_or(0) + 1);
+        let mut taken: Vec<usize> = deps.neighbors[i].iter().filter_map(|&j| color[j]).collect();
+        taken.sort_unstable();
+        let mut c = min;
+        for t in taken {
+            if t == c {
+                c += 1;
+            } else if t > c {
+                break;
+            }
+        }
+        color[i] = Some(c);
+        if let Some(next) = sender_next[i] {
+            ready.push((priority(deps, next), Reverse(next)));
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| (color[i], i));
+    order
+}
+
+/// Reports how much reordering alone could improve parallelism.
+pub struct ReorderPlugin;
+
+impl BlockAnalysisPlugin for ReorderPlugin {
+    fn name(&self) -> &str {
+        "reorder"
+    }
+
+    fn analyze(&self, input: &BlockAnalysisInput<'_>) -> ArgusResult<ReportSection> {
+        let r = ReorderReport::build(
+            input.block_number,
+            input.transactions,
+            input.access_lists,
+            input.graph,
+        );
+        let line = |label: &str, s: &OrderingStats| {
+            format!(
+                "{label:<12} {} rounds, critical path {} gas, speedup {:.2}x",
+                s.rounds, s.critical_path_gas, s.speedup
+            )
+        };
+        let data = serde_json::to_value(&r).unwrap_or_default();
+        Ok(ReportSection::new("Reordering what-if")
+            .line(line("Block order:", &r.original))
+            .line(line("Best found:", &r.best))
+            .line(format!(
+                "Strategy: {} | {} rounds saved | {:+.1}% speedup",
+                r.strategy,
+                r.rounds_saved(),
+                r.improvement() * 100.0
+            ))
+            .with_data(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argus_core::{Conflict, ConflictKind, StorageLocation};
+
+    fn tx(n: u8, from: u8) -> Transaction {
+        Transaction {
+            hash: B256::with_last_byte(n),
+            from: Address::with_last_byte(from),
+            gas: 100,
+            ..Default::default()
+        }
+    }
+
+    fn conflict(a: u8, b: u8) -> Conflict {
+        Conflict {
+            tx_a: B256::with_last_byte(a),
+            tx_b: B256::with_last_byte(b),
+            location: StorageLocation {
+                address: Address::ZERO,
+                slot: B256::with_last_byte(a.max(b)),
+            },
+            kind: ConflictKind::WriteWrite,
+        }
+    }
+
+    fn graph(edges: &[(u8, u8)]) -> ConflictGraph {
+        let mut g = ConflictGraph::new();
+        for &(a, b) in edges {
+            g.add_conflict(conflict(a, b));
+        }
+        g
+    }
+
+    #[test]
+    fn reordering_breaks_a_chain() {
+        // Path 1-2-3-4 in block order is 4 rounds deep; it is 2-colourable.
+        let txs: Vec<_> = (1..=4).map(|n| tx(n, n)).collect();
+        let r = ReorderReport::build(1, &txs, &[], &graph(&[(1, 2), (2, 3), (3, 4)]));
+        assert_eq!(r.original.rounds, 4);
+        assert_eq!(r.best.rounds, 2);
+        assert_eq!(r.rounds_saved(), 2);
+        assert!((r.best.speedup - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sender_nonce_order_is_kept() {
+        // Same sender: 1 must precede 2 even though they don't conflict.
+        let txs = vec![tx(1, 9), tx(2, 9), tx(3, 3)];
+        let r = ReorderReport::build(1, &txs, &[], &graph(&[(2, 3)]));
+        let pos = |n: u8| r.order.iter().position(|h| *h == B256::with_last_byte(n));
+        assert!(pos(1) < pos(2));
+        assert_eq!(r.best.rounds, 2);
+    }
+
+    #[test]
+    fn conflict_free_block_is_one_round() {
+        let txs: Vec<_> = (1..=3).map(|n| tx(n, n)).collect();
+        let r = ReorderReport::build(1, &txs, &[], &ConflictGraph::new());
+        assert_eq!(r.original.rounds, 1);
+        assert_eq!(r.strategy, "block order");
+        assert!((r.original.speedup - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn simulated_gas_replaces_gas_limits() {
+        let txs: Vec<_> = (1..=3).map(|n| tx(n, n)).collect();
+        let heavy = AccessList {
+            tx_hash: B256::with_last_byte(3),
+            entries: Default::default(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 700,
+            writes: Vec::new(),
+        };
+        let r = ReorderReport::build(1, &txs, &[heavy], &graph(&[(1, 2)]));
+        assert_eq!(r.total_gas, 900);
+        // The lone heavy tx outweighs the 1-2 chain.
+        assert_eq!(r.original.critical_path_gas, 700);
+    }
+}
@@ -3,21 +3,97 @@
 //! Takes a `ConflictGraph` and produces a human-readable report with
 //! protocol labels, conflict grouping, and summary statistics.
 
-use alloy_primitives::Address;
-use argus_core::{AccessList, ConflictGraph, ConflictKind};
-use std::collections::HashMap;
+use crate::plugin::ReportSection;
+use crate::severity::{DensityThresholds, SeverityScorer};
+use alloy_primitives::{Address, B256};
+use argus_core::chain::{self, ChainConfig};
+use argus_core::{
+    AccessList, ConflictGraph, ConflictKind, ExecutionOutcome, FastHashMap, FastHashSet,
+    PrefetchStats, StorageLocation, TxReceipt,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Contracts listed in [`StateCoverage::top_missed`].
+pub const TOP_MISSED: usize = 5;
 
 /// Enriched report produced from a ConflictGraph.
 #[derive(Debug)]
 pub struct Report {
     pub block_number: u64,
+    /// Chain the block belongs to; drives labels and explorer links.
+    pub chain: &'static ChainConfig,
     pub total_txs: usize,
     pub txs_with_storage: usize,
     pub total_entries: usize,
     pub total_conflicts: usize,
     pub groups: Vec<ConflictGroup>,
+    /// Sections contributed by block analysis plugins.
+    pub sections: Vec<ReportSection>,
     pub fetch_time: std::time::Duration,
     pub total_time: std::time::Duration,
+    /// Header timestamp of the block, when known.
+    pub block_timestamp: Option<u64>,
+    /// Set by [`make_deterministic`](Self::make_deterministic), which also
+    /// zeroes the prefetch's time and retry count.
+    pub deterministic: bool,
+    /// Labels contention events; see [`with_severity`](Self::with_severity).
+    pub severity: Arc<dyn SeverityScorer>,
+    /// Gas used per tx: simulated gas from the access lists, replaced by
+    /// mined gas with [`with_receipts`](Self::with_receipts).
+    pub gas_used: FastHashMap<B256, u64>,
+    /// Touches of a location by one tx, where the tx hit it more than once.
+    pub touches: FastHashMap<(B256, StorageLocation), u32>,
+    /// Set by [`with_receipts`](Self::with_receipts).
+    pub has_receipts: bool,
+    /// Reverted txs among those with receipts.
+    pub failed_txs: usize,
+    /// Txs whose simulation reverted; their access lists stop at the revert.
+    pub sim_reverted: usize,
+    /// Txs whose simulation halted, timed out or never ran.
+    pub sim_failed: usize,
+    /// How much of the state simulation read was prefetched; see
+    /// [`with_coverage`](Self::with_coverage).
+    pub coverage: Option<StateCoverage>,
+    /// What the state prefetch took; `None` when none ran.
+    pub prefetch: Option<PrefetchStats>,
+}
+
+/// How much of the state simulation read came from the warm cache.
+///
+/// Reads the cache lacked fell through to empty state (no code, zero
+/// balance, zero slots), so access lists past them may be incomplete.
+/// Distinct reads across the block; precompile and system addresses below
+/// `0x10000` are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StateCoverage {
+    pub accounts: usize,
+    pub missed_accounts: usize,
+    pub slots: usize,
+    pub missed_slots: usize,
+    /// Contracts with the most missed reads, most first; at most
+    /// [`TOP_MISSED`].
+    pub top_missed: Vec<(Address, usize)>,
+}
+
+impl StateCoverage {
+    /// Accounts and slots read.
+    pub fn reads(&self) -> usize {
+        self.accounts + self.slots
+    }
+
+    /// Accounts and slots read from empty state.
+    pub fn misses(&self) -> usize {
+        self.missed_accounts + self.missed_slots
+    }
+
+    /// Share of reads served by the warm cache; 1.0 when nothing was read.
+    pub fn hit_rate(&self) -> f64 {
+        match self.reads() {
+            0 => 1.0,
+            reads => 1.0 - self.misses() as f64 / reads as f64,
+        }
+    }
 }
 
 /// A group of conflicts on the same contract.
@@ -33,7 +109,8 @@ pub struct ConflictGroup {
 }
 
 impl Report {
-    /// Build a report from conflict graph + access lists.
+    /// Build a report from conflict graph + access lists (Ethereum labels;
+    /// see [`with_chain`](Self::with_chain)).
     pub fn build(
         block_number: u64,
         access_lists: &[AccessList],
@@ -47,29 +124,64 @@ impl Report {
             .filter(|al| !al.entries.is_empty())
             .count();
         let total_entries: usize = access_lists.iter().map(|al| al.entries.len()).sum();
+        let sim_reverted = access_lists
+            .iter()
+            .filter(|al| matches!(al.outcome, ExecutionOutcome::Revert { .. }))
+            .count();
+        let sim_failed = access_lists
+            .iter()
+            .filter(|al| {
+                matches!(
+                    al.outcome,
+                    ExecutionOutcome::Halt { .. }
+                        | ExecutionOutcome::Error { .. }
+                        | ExecutionOutcome::TimedOut
+                )
+            })
+            .count();
+        let gas_used = access_lists
+            .iter()
+            .filter(|al| al.gas_used > 0)
+            .map(|al| (al.tx_hash, al.gas_used))
+            .collect();
+        let touches = access_lists
+            .iter()
+            .flat_map(|al| {
+                al.entries
+                    .iter()
+                    .filter(|e| e.count > 1)
+                    .map(|e| ((al.tx_hash, e.location.clone()), e.count))
+            })
+            .collect();
 
-        // Group conflicts by contract address.
-        let mut by_address: HashMap<Address, ContractConflicts> = HashMap::new();
-
-        for c in &graph.conflicts {
-            let entry = by_address.entry(c.location.address).or_default();
-            entry.slots.insert(c.location.slot);
-            entry.tx_hashes.insert(c.tx_a);
-            entry.tx_hashes.insert(c.tx_b);
-            entry.conflict_count += 1;
-            match c.kind {
-                ConflictKind::WriteWrite => entry.ww_count += 1,
-                ConflictKind::ReadWrite => entry.rw_count += 1,
-            }
-        }
+        // Group conflicts by contract address (sharded across threads for
+        // large graphs).
+        let by_address = crate::par::group_by(
+            &graph.conflicts,
+            |c| c.location.address,
+            |entry: &mut ContractConflicts, c| {
+                entry.slots.insert(c.location.slot);
+                entry.tx_hashes.insert(c.tx_a);
+                entry.tx_hashes.insert(c.tx_b);
+                entry.conflict_count += 1;
+                match c.kind {
+                    ConflictKind::WriteWrite => entry.ww_count += 1,
+                    ConflictKind::ReadWrite => entry.rw_count += 1,
+                }
+            },
+            |a, b| {
+                a.slots.extend(b.slots);
+                a.tx_hashes.extend(b.tx_hashes);
+                a.conflict_count += b.conflict_count;
+                a.ww_count += b.ww_count;
+                a.rw_count += b.rw_count;
+            },
+        );
 
         let mut groups: Vec<ConflictGroup> = by_address
             .into_iter()
             .map(|(addr, cc)| {
-                let (protocol, label) = match argus_provider::labels::lookup(&addr) {
-                    Some(l) => (l.protocol.to_string(), l.name.to_string()),
-                    None => ("Unknown".to_string(), format!("{}", addr)),
-                };
+                let (protocol, label) = group_label(&chain::ETHEREUM, &addr);
 
                 let kind_summary = if cc.rw_count > 0 && cc.ww_count > 0 {
                     format!("{} W-W, {} R-W", cc.ww_count, cc.rw_count)
@@ -91,21 +203,124 @@ impl Report {
             })
             .collect();
 
-        // Sort by conflict count descending.
-        groups.sort_by(|a, b| b.conflict_count.cmp(&a.conflict_count));
+        // Sort by conflict count descending, then address for stable output.
+        groups.sort_by(|a, b| {
+            b.conflict_count
+                .cmp(&a.conflict_count)
+                .then(a.address.cmp(&b.address))
+        });
 
         Report {
             block_number,
+            chain: &chain::ETHEREUM,
             total_txs,
             txs_with_storage,
             total_entries,
             total_conflicts: graph.len(),
             groups,
+            sections: Vec::new(),
             fetch_time,
             total_time,
+            block_timestamp: None,
+            deterministic: false,
+            severity: Arc::new(DensityThresholds::default()),
+            gas_used,
+            touches,
+            has_receipts: false,
+            failed_txs: 0,
+            sim_reverted,
+            sim_failed,
+            coverage: None,
+            prefetch: None,
+        }
+    }
+
+    /// Weight contention by mined gas instead of simulated gas, and record
+    /// each tx's status.
+    pub fn with_receipts(mut self, receipts: &[TxReceipt]) -> Self {
+        self.gas_used = receipts.iter().map(|r| (r.tx_hash, r.gas_used)).collect();
+        self.has_receipts = true;
+        self.failed_txs = receipts.iter().filter(|r| !r.success).count();
+        self
+    }
+
+    /// Times `txs` touched `location`, counting each at least once.
+    pub fn touches_of<'a>(
+        &self,
+        location: &StorageLocation,
+        txs: impl IntoIterator<Item = &'a B256>,
+    ) -> u32 {
+        txs.into_iter()
+            .map(|tx| {
+                self.touches
+                    .get(&(*tx, location.clone()))
+                    .copied()
+                    .unwrap_or(1)
+            })
+            .sum()
+    }
+
+    /// Total gas of `txs`; txs without a gas figure count as zero.
+    pub fn gas_of<'a>(&self, txs: impl IntoIterator<Item = &'a B256>) -> u64 {
+        txs.into_iter().filter_map(|tx| self.gas_used.get(tx)).sum()
+    }
+
+    /// Use `scorer` for contention-event severity instead of the default
+    /// density thresholds.
+    pub fn with_severity(mut self, scorer: Arc<dyn SeverityScorer>) -> Self {
+        self.severity = scorer;
+        self
+    }
+
+    /// Attach simulation's state coverage, so readers can tell how far to
+    /// trust the access lists.
+    pub fn with_coverage(mut self, coverage: StateCoverage) -> Self {
+        self.coverage = Some(coverage);
+        self
+    }
+
+    /// Attach the prefetch's fetch, failure and retry counts and timing.
+    pub fn with_prefetch_stats(mut self, stats: PrefetchStats) -> Self {
+        self.prefetch = Some(stats);
+        self
+    }
+
+    /// Attach the block's header timestamp.
+    pub fn with_timestamp(mut self, timestamp: Option<u64>) -> Self {
+        self.block_timestamp = timestamp;
+        self
+    }
+
+    /// Make output a pure function of the block: wall-clock timings are
+    /// zeroed and rows are stamped with the block time instead of now.
+    pub fn make_deterministic(&mut self) {
+        self.deterministic = true;
+        self.fetch_time = Default::default();
+        self.total_time = Default::default();
+        if let Some(stats) = &mut self.prefetch {
+            stats.elapsed = Default::default();
+            stats.retried = 0;
+        }
+    }
+
+    /// `created_at` for rows built from this report.
+    pub(crate) fn row_timestamp(&self) -> String {
+        if self.deterministic {
+            crate::sink::iso8601(self.block_timestamp.unwrap_or(0))
+        } else {
+            crate::sink::chrono_now()
         }
     }
 
+    /// Re-scope the report to `chain`, relabeling contract groups.
+    pub fn with_chain(mut self, chain: &'static ChainConfig) -> Self {
+        self.chain = chain;
+        for g in &mut self.groups {
+            (g.protocol, g.label) = group_label(chain, &g.address);
+        }
+        self
+    }
+
     /// Render the report as a formatted string with contention density.
     pub fn render(&self, graph: &ConflictGraph) -> String {
         let mut out = String::new();
@@ -117,6 +332,10 @@ impl Report {
         out.push_str("╔══════════════════════════════════════════════════════════════╗\n");
         out.push_str("║                    ARGUS ANALYSIS REPORT                    ║\n");
         out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+        out.push_str(&format!(
+            "║  Chain:              {:>38} ║\n",
+            self.chain.name
+        ));
         out.push_str(&format!(
             "║  Block:              {:>38} ║\n",
             self.block_number
@@ -137,10 +356,54 @@ impl Report {
             "║  Conflicts:          {:>38} ║\n",
             self.total_conflicts
         ));
+        if !self.gas_used.is_empty() {
+            out.push_str(&format!(
+                "║  Gas used:           {:>38} ║\n",
+                self.gas_used.values().sum::<u64>()
+            ));
+        }
+        if self.has_receipts {
+            out.push_str(&format!(
+                "║  Reverted:           {:>38} ║\n",
+                self.failed_txs
+            ));
+        }
+        if self.sim_reverted + self.sim_failed > 0 {
+            out.push_str(&format!(
+                "║  Sim reverted:       {:>38} ║\n",
+                self.sim_reverted
+            ));
+            out.push_str(&format!(
+                "║  Sim failed:         {:>38} ║\n",
+                self.sim_failed
+            ));
+        }
         out.push_str(&format!(
             "║  Fetch time:         {:>35?} ║\n",
             self.fetch_time
         ));
+        if let Some(stats) = &self.prefetch {
+            out.push_str(&format!(
+                "║  Prefetch:           {:>38} ║\n",
+                format!(
+                    "{} fetched, {} failed, {} retried",
+                    stats.fetched, stats.failed, stats.retried
+                )
+            ));
+            out.push_str(&format!(
+                "║  Slots warmed:       {:>38} ║\n",
+                format!(
+                    "{}/{} ({:.1}%)",
+                    stats.slots,
+                    stats.slots_requested,
+                    stats.slot_coverage() * 100.0
+                )
+            ));
+            out.push_str(&format!(
+                "║  Prefetch time:      {:>35?} ║\n",
+                stats.elapsed
+            ));
+        }
         out.push_str(&format!(
             "║  Total time:         {:>35?} ║\n",
             self.total_time
@@ -171,6 +434,40 @@ impl Report {
                     "║     Hazard: {}  |  Txs: {}  |  Conflicts: {}  |  Density: {:.2}\n",
                     ev.hazard_type, ev.affected_tx_count, ev.conflict_count, ev.conflict_density
                 ));
+                if ev.access_count > ev.affected_tx_count {
+                    out.push_str(&format!("║     Touches: {}\n", ev.access_count));
+                }
+                if !self.gas_used.is_empty() {
+                    out.push_str(&format!("║     Gas: {}\n", ev.affected_gas_used));
+                }
+                if let Some(base) = self.chain.explorer {
+                    out.push_str(&format!("║     {base}/address/{}\n", ev.contract_address));
+                }
+            }
+        }
+
+        if let Some(coverage) = self.coverage.as_ref().filter(|c| c.reads() > 0) {
+            out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+            out.push_str("║  STATE COVERAGE                                            ║\n");
+            out.push_str(&format!(
+                "║     Accounts: {}/{} prefetched  |  Slots: {}/{} prefetched  ({:.1}%)\n",
+                coverage.accounts - coverage.missed_accounts,
+                coverage.accounts,
+                coverage.slots - coverage.missed_slots,
+                coverage.slots,
+                coverage.hit_rate() * 100.0
+            ));
+            for (addr, misses) in &coverage.top_missed {
+                let (protocol, name) = group_label(self.chain, addr);
+                out.push_str(&format!("║     {misses} missed: {protocol} / {name}\n"));
+            }
+        }
+
+        for section in &self.sections {
+            out.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+            out.push_str(&format!("║  {}\n", section.title.to_uppercase()));
+            for line in &section.lines {
+                out.push_str(&format!("║     {line}\n"));
             }
         }
 
@@ -179,10 +476,18 @@ impl Report {
     }
 }
 
+/// `(protocol, name)` for a contract, falling back to the raw address.
+pub(crate) fn group_label(chain: &ChainConfig, addr: &Address) -> (String, String) {
+    match chain.label(addr) {
+        Some(l) => (l.protocol.to_string(), l.name.to_string()),
+        None => ("Unknown".to_string(), format!("{}", addr)),
+    }
+}
+
 #[derive(Default)]
 struct ContractConflicts {
-    slots: std::collections::HashSet<alloy_primitives::B256>,
-    tx_hashes: std::collections::HashSet<alloy_primitives::B256>,
+    slots: FastHashSet<alloy_primitives::B256>,
+    tx_hashes: FastHashSet<alloy_primitives::B256>,
     conflict_count: usize,
     ww_count: usize,
     rw_count: usize,
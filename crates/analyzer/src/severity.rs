@@ -0,0 +1,75 @@
+//! Severity assignment for contention events.
+//!
+//! Severity drives alerting, so it is pluggable: implement [`SeverityScorer`]
+//! and attach it with [`Report::with_severity`](crate::reporter::Report::with_severity)
+//! (or `ArgusBuilder::severity_scorer`). The default, [`DensityThresholds`],
+//! buckets conflict density.
+//!
+//! ```ignore
+//! #[derive(Debug)]
+//! struct AmmFirst;
+//!
+//! impl SeverityScorer for AmmFirst {
+//!     fn score(&self, event: &ContentionEvent) -> String {
+//!         let bump = if event.contract_protocol == "Uniswap V2" { 2.0 } else { 1.0 };
+//!         DensityThresholds::default().label(event.raw_density() * bump).into()
+//!     }
+//! }
+//! ```
+
+use crate::sink::ContentionEvent;
+use std::fmt::Debug;
+
+/// Maps a contention event to a severity label (e.g. `"HIGH"`).
+pub trait SeverityScorer: Debug + Send + Sync {
+    fn score(&self, event: &ContentionEvent) -> String;
+}
+
+/// Density cut-offs: below `medium` is LOW, then MEDIUM, HIGH, CRITICAL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityThresholds {
+    pub medium: f64,
+    pub high: f64,
+    pub critical: f64,
+}
+
+impl Default for DensityThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 1.0,
+            high: 3.0,
+            critical: 5.0,
+        }
+    }
+}
+
+impl DensityThresholds {
+    pub fn label(&self, density: f64) -> &'static str {
+        match density {
+            d if d >= self.critical => "CRITICAL",
+            d if d >= self.high => "HIGH",
+            d if d >= self.medium => "MEDIUM",
+            _ => "LOW",
+        }
+    }
+}
+
+impl SeverityScorer for DensityThresholds {
+    fn score(&self, event: &ContentionEvent) -> String {
+        self.label(event.raw_density()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_thresholds() {
+        let t = DensityThresholds::default();
+        assert_eq!(t.label(0.5), "LOW");
+        assert_eq!(t.label(1.0), "MEDIUM");
+        assert_eq!(t.label(4.99), "HIGH");
+        assert_eq!(t.label(5.0), "CRITICAL");
+    }
+}
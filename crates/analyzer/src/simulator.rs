@@ -1,24 +1,63 @@
 //! EVM transaction simulator using `revm`.
 //!
 //! Replays transactions against an EVM database and captures every
-//! `SLOAD`/`SSTORE` to produce an [`AccessList`] per transaction.
+//! `SLOAD`/`SSTORE`, plus balance, nonce and code accesses, to produce an
+//! [`AccessList`] per transaction, along with the events it emitted.
 
-use alloy_primitives::{Address, B256};
-use argus_core::error::{ArgusError, ArgusResult};
-use argus_core::types::{AccessEntry, AccessMode, StorageLocation};
-use argus_core::{AccessList, Transaction};
-use revm::context::Context;
+use crate::plugin::{PluginOutput, SimulationObserver, TxCapture, TxInspectorPlugin};
+use alloy_primitives::{Address, B256, U256};
+use argus_core::chain::Hardfork;
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::types::{AccessEntry, AccessMode, AccountField, SlotWrite, StorageLocation};
+use argus_core::{AccessList, BlockContext, ExecutionOutcome, FastHashSet, Transaction, TxLog};
+use revm::context::result::ExecutionResult;
+use revm::context::{BlockEnv, Context};
 use revm::database::EmptyDB;
+use revm::database_interface::DatabaseRef;
 use revm::inspector::Inspector;
 use revm::interpreter::interpreter::EthInterpreter;
-use revm::interpreter::{interpreter_types::*, Interpreter};
+use revm::interpreter::{interpreter_types::*, InstructionResult, Interpreter};
+use revm::primitives::hardfork::SpecId;
+use revm::state::EvmState;
 use smallvec::SmallVec;
+use std::cell::RefCell;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 /// Re-export from provider for backward compatibility.
 pub use argus_provider::WarmCacheDB;
 
+use crate::reporter::{StateCoverage, TOP_MISSED};
+use argus_provider::{ProgressHandler, TouchedState};
+use revm::database::{AccountState, CacheDB};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
 const OPCODE_SLOAD: u8 = 0x54;
 const OPCODE_SSTORE: u8 = 0x55;
+const OPCODE_TLOAD: u8 = 0x5c;
+const OPCODE_TSTORE: u8 = 0x5d;
+const OPCODE_BALANCE: u8 = 0x31;
+const OPCODE_SELFBALANCE: u8 = 0x47;
+const OPCODE_EXTCODESIZE: u8 = 0x3b;
+const OPCODE_EXTCODECOPY: u8 = 0x3c;
+const OPCODE_EXTCODEHASH: u8 = 0x3f;
+
+/// Opcodes between deadline checks, keeping `Instant::now` off the hot path.
+const DEADLINE_CHECK_INTERVAL: u32 = 1024;
+
+/// Entries a spare inspector may hold capacity for, in storage and in
+/// transient storage each; one outsized transaction shouldn't pin its
+/// buffers to the worker for good.
+const SPARE_INSPECTOR_CAPACITY: usize = 4096;
+
+thread_local! {
+    /// Each worker's inspector from its last transaction, reused so its
+    /// buffers don't have to grow again for the next one.
+    static SPARE_INSPECTOR: RefCell<Option<AccessListInspector>> = const { RefCell::new(None) };
+}
 
 // ---------------------------------------------------------------------------
 // Inspector
@@ -27,10 +66,25 @@ const OPCODE_SSTORE: u8 = 0x55;
 /// Records `SLOAD`/`SSTORE` accesses during EVM execution.
 ///
 /// Tracks the current contract address via `call()`/`call_end()` hooks
-/// so storage accesses are attributed to the correct account.
+/// so storage accesses are attributed to the correct account. Account
+/// fields are recorded as pseudo-slots ([`StorageLocation::account`]):
+/// balances moved by value transfers and self-destructs, nonces bumped by
+/// `CREATE`, code deployed, and `BALANCE`/`EXTCODE*` reads. Gas payment is
+/// left out, since every transaction would write the coinbase; batch runs
+/// can add it back with [`FeeAccounting::Strict`]. Destroyed
+/// accounts are added from the final state, not here.
+/// `TLOAD`/`TSTORE` land in [`transient`](Self::transient) instead.
+///
+/// With a deadline ([`with_deadline`](Self::with_deadline)), every frame
+/// still running past it is halted and [`timed_out`](Self::timed_out) set.
 pub struct AccessListInspector {
     pub entries: SmallVec<[AccessEntry; 32]>,
+    pub transient: SmallVec<[AccessEntry; 4]>,
+    pub timed_out: bool,
     address_stack: SmallVec<[Address; 8]>,
+    captures: Vec<Box<dyn TxCapture>>,
+    deadline: Option<Instant>,
+    steps: u32,
 }
 
 impl AccessListInspector {
@@ -41,29 +95,108 @@ impl AccessListInspector {
         }
         Self {
             entries: SmallVec::new(),
+            transient: SmallVec::new(),
+            timed_out: false,
             address_stack,
+            captures: Vec::new(),
+            deadline: None,
+            steps: 0,
         }
     }
 
+    /// Abort execution once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Forward execution hooks to plugin captures as well.
+    pub fn with_captures(mut self, captures: Vec<Box<dyn TxCapture>>) -> Self {
+        self.captures = captures;
+        self
+    }
+
     pub fn into_entries(self) -> SmallVec<[AccessEntry; 32]> {
         self.entries
     }
 
+    /// Forget the last transaction, keeping the buffers' capacity, and
+    /// start the next at `initial_address`.
+    pub fn reset(&mut self, initial_address: Option<Address>) {
+        self.entries.clear();
+        self.transient.clear();
+        self.timed_out = false;
+        self.address_stack.clear();
+        self.address_stack.extend(initial_address);
+        self.captures.clear();
+        self.deadline = None;
+        self.steps = 0;
+    }
+
     #[inline]
     fn current_address(&self) -> Option<&Address> {
         self.address_stack.last()
     }
+
+    /// Record an access to `field` of `address`.
+    pub fn record_account(&mut self, address: Address, field: AccountField, mode: AccessMode) {
+        self.entries.push(AccessEntry {
+            location: StorageLocation::account(address, field),
+            mode,
+            count: 1,
+        });
+    }
 }
 
 impl<CTX> Inspector<CTX, EthInterpreter> for AccessListInspector {
     #[inline]
     fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if let Some(deadline) = self.deadline {
+            self.steps = self.steps.wrapping_add(1);
+            if self.timed_out
+                || (self.steps % DEADLINE_CHECK_INTERVAL == 0 && Instant::now() >= deadline)
+            {
+                // Halting only ends this frame; callers hit this again on
+                // their next opcode.
+                self.timed_out = true;
+                interp.halt(InstructionResult::OutOfGas);
+                return;
+            }
+        }
         let opcode = interp.bytecode.opcode();
-        if opcode != OPCODE_SLOAD && opcode != OPCODE_SSTORE {
-            return;
+        if !self.captures.is_empty() {
+            if let Some(&address) = self.current_address() {
+                let stack = interp.stack.data();
+                for c in &mut self.captures {
+                    c.step(address, opcode, stack);
+                }
+            }
+        }
+        match opcode {
+            OPCODE_SLOAD | OPCODE_SSTORE | OPCODE_TLOAD | OPCODE_TSTORE => {}
+            OPCODE_SELFBALANCE => {
+                if let Some(&address) = self.current_address() {
+                    self.record_account(address, AccountField::Balance, AccessMode::Read);
+                }
+                return;
+            }
+            OPCODE_BALANCE | OPCODE_EXTCODESIZE | OPCODE_EXTCODECOPY | OPCODE_EXTCODEHASH => {
+                let Some(top) = interp.stack.data().last() else {
+                    return;
+                };
+                let address = Address::from_word(B256::from(top.to_be_bytes()));
+                let field = if opcode == OPCODE_BALANCE {
+                    AccountField::Balance
+                } else {
+                    AccountField::Code
+                };
+                self.record_account(address, field, AccessMode::Read);
+                return;
+            }
+            _ => return,
         }
 
-        let mode = if opcode == OPCODE_SLOAD {
+        let mode = if opcode == OPCODE_SLOAD || opcode == OPCODE_TLOAD {
             AccessMode::Read
         } else {
             AccessMode::Write
@@ -81,10 +214,16 @@ impl<CTX> Inspector<CTX, EthInterpreter> for AccessListInspector {
             None => return,
         };
 
-        self.entries.push(AccessEntry {
+        let entry = AccessEntry {
             location: StorageLocation { address, slot },
             mode,
-        });
+            count: 1,
+        };
+        if opcode == OPCODE_TLOAD || opcode == OPCODE_TSTORE {
+            self.transient.push(entry);
+        } else {
+            self.entries.push(entry);
+        }
     }
 
     fn call(
@@ -92,7 +231,18 @@ impl<CTX> Inspector<CTX, EthInterpreter> for AccessListInspector {
         _context: &mut CTX,
         inputs: &mut revm::interpreter::CallInputs,
     ) -> Option<revm::interpreter::CallOutcome> {
+        if inputs.transfer_value().is_some_and(|v| !v.is_zero()) {
+            self.record_account(inputs.caller, AccountField::Balance, AccessMode::Write);
+            self.record_account(
+                inputs.target_address,
+                AccountField::Balance,
+                AccessMode::Write,
+            );
+        }
         self.address_stack.push(inputs.target_address);
+        for c in &mut self.captures {
+            c.enter_call(inputs.target_address);
+        }
         None
     }
 
@@ -103,15 +253,46 @@ impl<CTX> Inspector<CTX, EthInterpreter> for AccessListInspector {
         _outcome: &mut revm::interpreter::CallOutcome,
     ) {
         self.address_stack.pop();
+        for c in &mut self.captures {
+            c.exit_call();
+        }
     }
 
     fn create(
         &mut self,
         _context: &mut CTX,
-        _inputs: &mut revm::interpreter::CreateInputs,
+        inputs: &mut revm::interpreter::CreateInputs,
     ) -> Option<revm::interpreter::CreateOutcome> {
+        // A top-level create bumps the sender's nonce, recorded per tx.
+        if let Some(&creator) = self.current_address() {
+            self.record_account(creator, AccountField::Nonce, AccessMode::Write);
+        }
+        if !inputs.value().is_zero() {
+            self.record_account(inputs.caller(), AccountField::Balance, AccessMode::Write);
+        }
         None
     }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &revm::interpreter::CreateInputs,
+        outcome: &mut revm::interpreter::CreateOutcome,
+    ) {
+        if let Some(address) = outcome.address.filter(|_| outcome.result.is_ok()) {
+            self.record_account(address, AccountField::Code, AccessMode::Write);
+            if !inputs.value().is_zero() {
+                self.record_account(address, AccountField::Balance, AccessMode::Write);
+            }
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if !value.is_zero() {
+            self.record_account(contract, AccountField::Balance, AccessMode::Write);
+            self.record_account(target, AccountField::Balance, AccessMode::Write);
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -119,19 +300,64 @@ impl<CTX> Inspector<CTX, EthInterpreter> for AccessListInspector {
 // ---------------------------------------------------------------------------
 
 /// Simulates a batch against `EmptyDB`. Offloaded to `spawn_blocking`.
-pub async fn simulate_batch(transactions: Vec<Transaction>) -> ArgusResult<Vec<AccessList>> {
-    tokio::task::spawn_blocking(move || simulate_batch_sync(&transactions))
+///
+/// Accepts a `Vec` or a shared `Arc<[Transaction]>`; the latter is moved
+/// into the blocking task without copying the transactions.
+pub async fn simulate_batch(
+    transactions: impl Into<Arc<[Transaction]>>,
+) -> ArgusResult<Vec<AccessList>> {
+    simulate_batch_cancellable(transactions, CancellationToken::new()).await
+}
+
+/// Like [`simulate_batch`], stopping with [`ArgusError::Cancelled`] before
+/// the next transaction once `cancel` fires.
+pub async fn simulate_batch_cancellable(
+    transactions: impl Into<Arc<[Transaction]>>,
+    cancel: CancellationToken,
+) -> ArgusResult<Vec<AccessList>> {
+    simulate_batch_in_block(transactions, BatchOptions::default(), cancel).await
+}
+
+/// Like [`simulate_batch_cancellable`], executing as `options` describe: in
+/// their block's environment (see [`block_env`]) under their hardfork, so
+/// TIMESTAMP, NUMBER, BASEFEE and friends read the real header rather than
+/// revm's defaults, with the same fee accounting and exclusions as a warm
+/// run. Transactions running longer than the timeout are aborted as
+/// [`ExecutionOutcome::TimedOut`]. The thread settings have nothing to do
+/// here.
+pub async fn simulate_batch_in_block(
+    transactions: impl Into<Arc<[Transaction]>>,
+    options: BatchOptions,
+    cancel: CancellationToken,
+) -> ArgusResult<Vec<AccessList>> {
+    let transactions = transactions.into();
+    tokio::task::spawn_blocking(move || simulate_batch_sync(&transactions, &options, &cancel))
         .await
         .map_err(|e| ArgusError::Internal(format!("spawn_blocking panicked: {e}")))?
 }
 
-fn simulate_batch_sync(transactions: &[Transaction]) -> ArgusResult<Vec<AccessList>> {
+fn simulate_batch_sync(
+    transactions: &[Transaction],
+    options: &BatchOptions,
+    cancel: &CancellationToken,
+) -> ArgusResult<Vec<AccessList>> {
+    let block = options.block_env();
     let mut access_lists = Vec::with_capacity(transactions.len());
     for tx in transactions {
-        access_lists.push(simulate_one_tx(
+        if cancel.is_cancelled() {
+            return Err(ArgusError::Cancelled);
+        }
+        let (mut list, _) = simulate_one_tx(
             tx,
             revm::database::CacheDB::new(EmptyDB::new()),
-        )?);
+            options.spec,
+            block.as_ref(),
+            &[],
+            options.tx_timeout,
+        )
+        .for_tx(tx.hash)?;
+        options.finish(&mut list, tx);
+        access_lists.push(list);
     }
     Ok(access_lists)
 }
@@ -140,19 +366,119 @@ fn simulate_batch_sync(transactions: &[Transaction]) -> ArgusResult<Vec<AccessLi
 // Per-tx simulation (generic over DB backend)
 // ---------------------------------------------------------------------------
 
+/// revm spec for a chain-registry hardfork.
+pub fn spec_id(fork: Hardfork) -> SpecId {
+    match fork {
+        Hardfork::Frontier => SpecId::FRONTIER,
+        Hardfork::Homestead => SpecId::HOMESTEAD,
+        Hardfork::TangerineWhistle => SpecId::TANGERINE,
+        Hardfork::SpuriousDragon => SpecId::SPURIOUS_DRAGON,
+        Hardfork::Byzantium => SpecId::BYZANTIUM,
+        Hardfork::Constantinople => SpecId::CONSTANTINOPLE,
+        Hardfork::Petersburg => SpecId::PETERSBURG,
+        Hardfork::Istanbul => SpecId::ISTANBUL,
+        Hardfork::Berlin => SpecId::BERLIN,
+        Hardfork::London => SpecId::LONDON,
+        Hardfork::Paris => SpecId::MERGE,
+        Hardfork::Shanghai => SpecId::SHANGHAI,
+        Hardfork::Cancun => SpecId::CANCUN,
+        Hardfork::Prague => SpecId::PRAGUE,
+    }
+}
+
+/// revm block environment for `block` under `spec` (latest when `None`).
+/// Before the merge PREVRANDAO reads the difficulty, so that stands in when
+/// the header has no `mixHash` randomness.
+pub fn block_env(block: &BlockContext, spec: Option<SpecId>) -> BlockEnv {
+    use revm::primitives::eip4844::{
+        BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
+    };
+
+    let mut env = BlockEnv {
+        number: U256::from(block.number),
+        beneficiary: block.coinbase,
+        timestamp: U256::from(block.timestamp),
+        gas_limit: block.gas_limit,
+        basefee: block.base_fee.unwrap_or_default(),
+        difficulty: block.difficulty,
+        prevrandao: Some(
+            block
+                .prevrandao
+                .unwrap_or_else(|| B256::from(block.difficulty)),
+        ),
+        ..Default::default()
+    };
+    if let Some(excess) = block.excess_blob_gas {
+        let fraction = match spec {
+            Some(spec) if !spec.is_enabled_in(SpecId::PRAGUE) => {
+                BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN
+            }
+            _ => BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
+        };
+        env.set_blob_excess_gas_and_price(excess, fraction);
+    }
+    env
+}
+
 /// Simulates a single transaction and returns its deduplicated access list.
 ///
 /// Entries are sorted `(location asc, mode desc)` and deduped by location,
-/// keeping the worst-case mode (Write over Read).
-fn simulate_one_tx<DB>(tx: &Transaction, db: DB) -> ArgusResult<AccessList>
+/// keeping the worst-case mode (Write over Read). `spec: None` keeps revm's
+/// default (latest) spec, `block: None` revm's default block environment.
+/// A transaction still running after `timeout` is aborted and marked
+/// [`ExecutionOutcome::TimedOut`]. Plugin outputs are returned alongside.
+fn simulate_one_tx<DB>(
+    tx: &Transaction,
+    db: DB,
+    spec: Option<SpecId>,
+    block: Option<&BlockEnv>,
+    plugins: &[Arc<dyn TxInspectorPlugin>],
+    timeout: Option<Duration>,
+) -> ArgusResult<(AccessList, Vec<PluginOutput>)>
+where
+    DB: DatabaseRef,
+    DB::Error: core::fmt::Debug,
+{
+    let (list, outputs, _) = execute_tx(tx, db, spec, block, plugins, timeout)?;
+    Ok((list, outputs))
+}
+
+/// [`simulate_one_tx`], also returning the state the transaction left
+/// behind over `db`, or `None` if it failed to execute.
+pub(crate) fn execute_tx<DB>(
+    tx: &Transaction,
+    db: DB,
+    spec: Option<SpecId>,
+    block: Option<&BlockEnv>,
+    plugins: &[Arc<dyn TxInspectorPlugin>],
+    timeout: Option<Duration>,
+) -> ArgusResult<(AccessList, Vec<PluginOutput>, Option<EvmState>)>
 where
-    DB: revm::database_interface::DatabaseRef,
+    DB: DatabaseRef,
     DB::Error: core::fmt::Debug,
 {
     use revm::context::TxEnv;
-    use revm::handler::{MainBuilder, MainContext};
+    use revm::context_interface::transaction::{Authorization, SignedAuthorization};
+    use revm::handler::{ExecuteEvm, MainBuilder, MainContext};
     use revm::inspector::InspectEvm;
 
+    let authorizations: Vec<SignedAuthorization> = tx
+        .authorization_list
+        .iter()
+        .map(|auth| {
+            SignedAuthorization::new_unchecked(
+                Authorization {
+                    chain_id: auth.chain_id,
+                    address: auth.address,
+                    nonce: auth.nonce,
+                },
+                auth.y_parity,
+                auth.r,
+                auth.s,
+            )
+        })
+        .collect();
+
     let tx_env = TxEnv::builder()
         .caller(tx.from)
         .kind(match tx.to {
@@ -162,10 +488,39 @@ where
         .data(tx.input.clone())
         .value(tx.value)
         .gas_limit(tx.gas)
+        // GASPRICE reads the effective price: the fee cap plus tip capped
+        // at the block's base fee for EIP-1559, else the legacy price.
+        .gas_price(tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default())
+        .gas_priority_fee(tx.max_priority_fee_per_gas)
+        .blob_hashes(tx.blob_hashes.clone())
+        .max_fee_per_blob_gas(tx.max_fee_per_blob_gas.unwrap_or_default())
+        .authorization_list_signed(authorizations.clone())
         .build()
         .map_err(|e| ArgusError::Simulation(format!("Failed to build TxEnv: {e:?}")))?;
 
-    let inspector = AccessListInspector::new(tx.to);
+    let captures = plugins.iter().map(|p| p.begin_tx(tx)).collect();
+    let mut inspector = match SPARE_INSPECTOR.with_borrow_mut(Option::take) {
+        Some(mut spare) => {
+            spare.reset(tx.to);
+            spare
+        }
+        None => AccessListInspector::new(tx.to),
+    }
+    .with_captures(captures);
+    if let Some(timeout) = timeout {
+        inspector = inspector.with_deadline(Instant::now() + timeout);
+    }
+    inspector.record_account(tx.from, AccountField::Nonce, AccessMode::Write);
+    // EIP-7702: each authority gets its delegation code and a nonce bump
+    // before the call runs. Recorded for every recoverable signature, even
+    // ones the chain id or nonce would reject, so this over-approximates.
+    for authority in authorizations
+        .iter()
+        .filter_map(|a| a.recover_authority().ok())
+    {
+        inspector.record_account(authority, AccountField::Code, AccessMode::Write);
+        inspector.record_account(authority, AccountField::Nonce, AccessMode::Write);
+    }
 
     // Disable all validation so txs execute through to SLOAD/SSTORE
     // even without exact balances, nonces, or gas pricing.
@@ -178,46 +533,326 @@ where
     ctx.cfg.disable_block_gas_limit = true;
     ctx.cfg.disable_base_fee = true;
     ctx.cfg.disable_eip3607 = true;
+    if let Some(spec) = spec {
+        ctx.cfg.spec = spec;
+    }
+    if let Some(block) = block {
+        ctx.block = block.clone();
+    }
 
     let mut evm = ctx.build_mainnet_with_inspector(inspector);
 
     // Pass actual tx_env — inspect_one_tx calls set_tx() internally.
     let result = evm.inspect_one_tx(tx_env);
 
-    match &result {
+    let outcome = match &result {
+        _ if evm.inspector.timed_out => ExecutionOutcome::TimedOut,
+        Ok(ExecutionResult::Success { .. }) => ExecutionOutcome::Success,
+        Ok(ExecutionResult::Revert { output, .. }) => ExecutionOutcome::Revert {
+            reason: revert_reason(output),
+        },
+        Ok(ExecutionResult::Halt { reason, .. }) => ExecutionOutcome::Halt {
+            reason: format!("{reason:?}"),
+        },
+        Err(e) => ExecutionOutcome::Error {
+            reason: format!("{e:?}"),
+        },
+    };
+    let gas_used = result.as_ref().map_or(0, |res| res.gas_used());
+    let logs = match &result {
+        Ok(res) => res
+            .logs()
+            .iter()
+            .map(|log| TxLog {
+                address: log.address,
+                topics: log.data.topics().to_vec(),
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let state = match &result {
         Ok(res) => {
             tracing::debug!(
                 tx_hash = %tx.hash,
                 gas_used = res.gas_used(),
                 "evm execution ok"
             );
+            Some(evm.finalize())
         }
         Err(e) => {
             tracing::debug!(tx_hash = %tx.hash, error = ?e, "evm execution error");
+            None
         }
+    };
+
+    let mut inspector = std::mem::replace(&mut evm.inspector, AccessListInspector::new(None));
+    let outputs = plugins
+        .iter()
+        .zip(std::mem::take(&mut inspector.captures))
+        .filter_map(|(plugin, capture)| {
+            capture.finish().map(|data| PluginOutput {
+                plugin: plugin.name().to_string(),
+                tx_hash: tx.hash,
+                data,
+            })
+        })
+        .collect();
+
+    // Only what actually went away: since EIP-6780 most self-destructs just
+    // move the balance.
+    for (&address, account) in state.iter().flatten() {
+        if account.is_selfdestructed() {
+            inspector.entries.push(AccessEntry {
+                location: StorageLocation::account(address, AccountField::Existence),
+                mode: AccessMode::Write,
+                count: 1,
+            });
+        }
+    }
+    sort_dedup(&mut inspector.entries);
+    sort_dedup(&mut inspector.transient);
+    // The lists move out whole; the spare reserves what they grew to, so
+    // the next transaction allocates once instead of growing again.
+    let entries = std::mem::take(&mut inspector.entries);
+    let transient = std::mem::take(&mut inspector.transient);
+    if entries.capacity() <= SPARE_INSPECTOR_CAPACITY
+        && transient.capacity() <= SPARE_INSPECTOR_CAPACITY
+    {
+        inspector.entries.reserve(entries.capacity());
+        inspector.transient.reserve(transient.capacity());
+        SPARE_INSPECTOR.set(Some(inspector));
     }
+    let writes = entries
+        .iter()
+        .filter(|e| e.mode == AccessMode::Write && e.location.account_field().is_none())
+        .filter_map(|e| {
+            let index = U256::from_be_bytes(e.location.slot.0);
+            let slot = state
+                .as_ref()?
+                .get(&e.location.address)?
+                .storage
+                .get(&index)?;
+            Some(SlotWrite {
+                location: e.location.clone(),
+                original: slot.original_value.into(),
+                value: slot.present_value.into(),
+            })
+        })
+        .collect();
+
+    tracing::debug!(tx_hash = %tx.hash, entries = entries.len(), "simulated");
+
+    Ok((
+        AccessList {
+            tx_hash: tx.hash,
+            entries,
+            transient,
+            logs,
+            outcome,
+            gas_used,
+            writes,
+        },
+        outputs,
+        state,
+    ))
+}
 
-    let mut entries = std::mem::take(&mut evm.inspector.entries);
+/// Human-readable reason from `REVERT` output: the message of a Solidity
+/// `Error(string)`, the code of a `Panic(uint256)`, else the raw bytes.
+fn revert_reason(output: &alloy_primitives::Bytes) -> Option<String> {
+    const ERROR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    const PANIC: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    if output.is_empty() {
+        return None;
+    }
+    let (selector, body) = output.split_at(output.len().min(4));
+    if selector == ERROR {
+        // offset, length, bytes
+        let word = |at: usize| -> Option<usize> {
+            let bytes = body.get(at..at.checked_add(32)?)?;
+            usize::try_from(U256::from_be_slice(bytes)).ok()
+        };
+        let message = word(0).and_then(|offset| {
+            let len = word(offset)?;
+            let start = offset.checked_add(32)?;
+            body.get(start..start.checked_add(len)?)
+        });
+        if let Some(message) = message {
+            return Some(String::from_utf8_lossy(message).into_owned());
+        }
+    } else if selector == PANIC && body.len() == 32 {
+        return Some(format!("Panic({:#x})", U256::from_be_slice(body)));
+    }
+    Some(format!("{output}"))
+}
 
+/// Sort `(location asc, mode desc)` and keep one entry per location, the
+/// worst-case mode, carrying the total number of touches.
+fn sort_dedup<A: smallvec::Array<Item = AccessEntry>>(entries: &mut SmallVec<A>) {
     entries.sort_unstable_by(|a, b| {
         a.location
             .cmp(&b.location)
             .then(a.mode.cmp(&b.mode).reverse())
     });
-    entries.dedup_by(|a, b| a.location == b.location);
-
-    tracing::debug!(tx_hash = %tx.hash, entries = entries.len(), "simulated");
-
-    Ok(AccessList {
-        tx_hash: tx.hash,
-        entries,
-    })
+    entries.dedup_by(|a, b| {
+        if a.location != b.location {
+            return false;
+        }
+        b.count = b.count.saturating_add(a.count);
+        true
+    });
 }
 
 // ---------------------------------------------------------------------------
 // Parallel simulation with pre-fetched state
 // ---------------------------------------------------------------------------
 
+/// Whether gas payment shows up in access lists.
+///
+/// Every transaction debits its sender for gas and credits the block's
+/// coinbase with the priority fee, so recording those writes makes every
+/// pair of transactions conflict on the coinbase balance. Builders and
+/// parallel EVMs settle fees outside the conflict check, which is what
+/// [`Relaxed`](Self::Relaxed) assumes. The burned base fee moves no
+/// account's balance and is never recorded; neither are chain-specific fee
+/// vaults such as the OP Stack's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeAccounting {
+    /// Leave gas payment out; only value transfers touch balances.
+    #[default]
+    Relaxed,
+    /// Record a balance write to the sender and to the coinbase for every
+    /// transaction that executed.
+    Strict,
+}
+
+/// How a batch is simulated: the environment transactions execute in, the
+/// threads they run on, and what their access lists record.
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    /// Hardfork to execute under; `None` uses revm's latest.
+    pub spec: Option<SpecId>,
+    /// Header the transactions see (see [`block_env`]); its coinbase
+    /// receives fees under [`FeeAccounting::Strict`].
+    pub block: Option<BlockContext>,
+    /// Abort a transaction still running after this long, recording it as
+    /// [`ExecutionOutcome::TimedOut`].
+    pub tx_timeout: Option<Duration>,
+    /// Worker threads; 0 runs on rayon's global pool (one per core).
+    pub threads: usize,
+    /// Minimum transactions a worker takes at once; 0 or 1 lets rayon
+    /// split down to single transactions.
+    pub chunk_size: usize,
+    /// Whether gas payment is recorded; relaxed by default.
+    pub fees: FeeAccounting,
+    /// Accounts whose storage and fields are never recorded, e.g. system
+    /// contracts every block touches.
+    pub excluded_addresses: FastHashSet<Address>,
+    /// Individual slots never recorded.
+    pub excluded_slots: FastHashSet<StorageLocation>,
+    /// The dedicated pool, built on first use and shared by clones.
+    pool: Arc<OnceLock<rayon::ThreadPool>>,
+}
+
+impl BatchOptions {
+    pub fn with_spec(mut self, spec: SpecId) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    pub fn with_block(mut self, block: BlockContext) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn with_tx_timeout(mut self, timeout: Duration) -> Self {
+        self.tx_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self.pool = Arc::default();
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn with_fees(mut self, fees: FeeAccounting) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Leave everything at `address` out of access lists.
+    pub fn with_excluded_address(mut self, address: Address) -> Self {
+        self.excluded_addresses.insert(address);
+        self
+    }
+
+    /// Leave `slot` of `address` out of access lists.
+    pub fn with_excluded_slot(mut self, address: Address, slot: B256) -> Self {
+        self.excluded_slots
+            .insert(StorageLocation { address, slot });
+        self
+    }
+
+    /// Whether `location` is left out of access lists.
+    pub fn excludes(&self, location: &StorageLocation) -> bool {
+        self.excluded_addresses.contains(&location.address)
+            || self.excluded_slots.contains(location)
+    }
+
+    /// The block environment transactions execute in.
+    fn block_env(&self) -> Option<BlockEnv> {
+        self.block.as_ref().map(|block| block_env(block, self.spec))
+    }
+
+    /// Adjust a simulated access list to these options. Exclusions apply
+    /// last, so they can drop fee writes too.
+    fn finish(&self, list: &mut AccessList, tx: &Transaction) {
+        let executed = !matches!(list.outcome, ExecutionOutcome::Error { .. });
+        if self.fees == FeeAccounting::Strict && executed {
+            let coinbase = self.block.as_ref().map(|b| b.coinbase).unwrap_or_default();
+            for address in [tx.from, coinbase] {
+                list.entries.push(AccessEntry {
+                    location: StorageLocation::account(address, AccountField::Balance),
+                    mode: AccessMode::Write,
+                    count: 1,
+                });
+            }
+            sort_dedup(&mut list.entries);
+        }
+        if !self.excluded_addresses.is_empty() || !self.excluded_slots.is_empty() {
+            list.entries.retain(|e| !self.excludes(&e.location));
+            list.transient.retain(|e| !self.excludes(&e.location));
+            list.writes.retain(|w| !self.excludes(&w.location));
+        }
+    }
+
+    /// Run `op` on a pool of [`threads`](Self::threads) workers, or on the
+    /// global pool if no thread count is set. The pool is built by the
+    /// first call and reused after.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> ArgusResult<R> {
+        if self.threads == 0 {
+            return Ok(op());
+        }
+        if self.pool.get().is_none() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .thread_name(|i| format!("argus-sim-{i}"))
+                .build()
+                .map_err(|e| ArgusError::Internal(format!("Failed to build thread pool: {e}")))?;
+            // A concurrent first call may win; its pool is as good as ours.
+            let _ = self.pool.set(pool);
+        }
+        Ok(self.pool.get().expect("pool initialized above").install(op))
+    }
+}
+
 /// Simulates all transactions in parallel against pre-fetched state.
 ///
 /// Uses a reference overlay: `CacheDB::new(&warm_db)` creates a per-tx
@@ -226,29 +861,488 @@ pub fn simulate_batch_with_state(
     warm_db: &WarmCacheDB,
     transactions: &[Transaction],
 ) -> ArgusResult<Vec<AccessList>> {
+    Ok(simulate_batch_with_plugins(warm_db, transactions, None, &[])?.0)
+}
+
+/// Like [`simulate_batch_with_state`], executing under a specific hardfork.
+pub fn simulate_batch_with_spec(
+    warm_db: &WarmCacheDB,
+    transactions: &[Transaction],
+    spec: SpecId,
+) -> ArgusResult<Vec<AccessList>> {
+    Ok(simulate_batch_with_plugins(warm_db, transactions, Some(spec), &[])?.0)
+}
+
+/// Parallel warm simulation that also runs inspector plugins on every tx.
+pub fn simulate_batch_with_plugins(
+    warm_db: &WarmCacheDB,
+    transactions: &[Transaction],
+    spec: Option<SpecId>,
+    plugins: &[Arc<dyn TxInspectorPlugin>],
+) -> ArgusResult<(Vec<AccessList>, Vec<PluginOutput>)> {
+    let options = BatchOptions {
+        spec,
+        ..Default::default()
+    };
+    let (lists, outputs, _) =
+        simulate_batch_observed(warm_db, transactions, plugins, None, None, &options)?;
+    Ok((lists, outputs))
+}
+
+/// Like [`simulate_batch_with_state`], as `options` describe.
+pub fn simulate_batch_with_options(
+    warm_db: &WarmCacheDB,
+    transactions: &[Transaction],
+    options: &BatchOptions,
+) -> ArgusResult<Vec<AccessList>> {
+    let (lists, _, _) = simulate_batch_observed(warm_db, transactions, &[], None, None, options)?;
+    Ok(lists)
+}
+
+/// [`simulate_batch_with_options`] for async callers: the batch runs on
+/// tokio's blocking pool, so awaiting it parks the task instead of tying up
+/// a runtime worker for the seconds a large block takes.
+///
+/// Stops early with [`ArgusError::Cancelled`] once `cancel` fires, or when
+/// the returned future is dropped.
+pub async fn simulate_batch_with_state_async(
+    warm_db: impl Into<Arc<WarmCacheDB>>,
+    transactions: impl Into<Arc<[Transaction]>>,
+    options: BatchOptions,
+    cancel: CancellationToken,
+) -> ArgusResult<Vec<AccessList>> {
+    let warm_db = warm_db.into();
+    let transactions = transactions.into();
+    // Child token: dropping this future stops the batch without cancelling
+    // the caller's token.
+    let cancel = cancel.child_token();
+    let guard = cancel.clone().drop_guard();
+    let result = tokio::task::spawn_blocking(move || {
+        simulate_batch_observed(&*warm_db, &transactions, &[], None, Some(&cancel), &options)
+    })
+    .await
+    .map_err(|e| ArgusError::Internal(format!("spawn_blocking panicked: {e}")))?;
+    guard.disarm();
+    Ok(result?.0)
+}
+
+/// Like [`simulate_batch_with_state_async`], sending each access list on
+/// `lists` with its transaction's index as soon as it is simulated, rather
+/// than returning them all at the end. Lists arrive in completion order,
+/// not block order. A full channel holds the workers back; a dropped
+/// receiver stops the batch, which still returns `Ok`.
+pub async fn simulate_batch_streaming(
+    warm_db: impl Into<Arc<WarmCacheDB>>,
+    transactions: impl Into<Arc<[Transaction]>>,
+    options: BatchOptions,
+    cancel: CancellationToken,
+    lists: tokio::sync::mpsc::Sender<(usize, AccessList)>,
+) -> ArgusResult<()> {
+    let warm_db = warm_db.into();
+    let transactions = transactions.into();
+    let cancel = cancel.child_token();
+    let guard = cancel.clone().drop_guard();
+    let receiver_gone = lists.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let observer = StreamObserver {
+            lists,
+            cancel: cancel.clone(),
+        };
+        simulate_batch_observed(
+            &*warm_db,
+            &transactions,
+            &[],
+            Some(&observer),
+            Some(&cancel),
+            &options,
+        )
+    })
+    .await
+    .map_err(|e| ArgusError::Internal(format!("spawn_blocking panicked: {e}")))?;
+    guard.disarm();
+    match result {
+        Ok(_) => Ok(()),
+        Err(ArgusError::Cancelled) if receiver_gone.is_closed() => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Sends each finished list down a channel for
+/// [`simulate_batch_streaming`], cancelling the batch once nobody is
+/// listening.
+struct StreamObserver {
+    lists: tokio::sync::mpsc::Sender<(usize, AccessList)>,
+    cancel: CancellationToken,
+}
+
+impl SimulationObserver for StreamObserver {
+    fn on_tx(&self, index: usize, access_list: &AccessList) {
+        // Workers are plain threads, so blocking here is fine.
+        if self
+            .lists
+            .blocking_send((index, access_list.clone()))
+            .is_err()
+        {
+            self.cancel.cancel();
+        }
+    }
+}
+
+/// Like [`simulate_batch_with_plugins`], executing as `options` describe
+/// and reporting each transaction's access list to `observer` as soon as it
+/// finishes. Once `cancel` fires, remaining transactions are skipped and the
+/// batch fails with [`ArgusError::Cancelled`]. Also reports how much of the
+/// state the batch read was in `warm_db`. A
+/// [`tx_timeout`](BatchOptions::tx_timeout) keeps one pathological
+/// transaction from stalling a worker.
+///
+/// `warm_db` may sit over any backend, such as an
+/// [`argus_provider::FallbackDB`] that fetches what the prefetch missed; its
+/// misses are then the reads that went to the node.
+pub fn simulate_batch_observed<ExtDB>(
+    warm_db: &CacheDB<ExtDB>,
+    transactions: &[Transaction],
+    plugins: &[Arc<dyn TxInspectorPlugin>],
+    observer: Option<&dyn SimulationObserver>,
+    cancel: Option<&CancellationToken>,
+    options: &BatchOptions,
+) -> ArgusResult<(Vec<AccessList>, Vec<PluginOutput>, StateCoverage)>
+where
+    ExtDB: DatabaseRef + Sync,
+    ExtDB::Error: core::fmt::Debug,
+{
     use rayon::prelude::*;
 
-    tracing::info!(txs = transactions.len(), "parallel simulation");
+    tracing::info!(
+        txs = transactions.len(),
+        spec = ?options.spec,
+        plugins = plugins.len(),
+        threads = options.threads,
+        "parallel simulation"
+    );
 
-    let results: Vec<ArgusResult<AccessList>> = transactions
-        .par_iter()
-        .map(|tx| simulate_one_tx(tx, warm_db))
-        .collect();
+    let (spec, tx_timeout) = (options.spec, options.tx_timeout);
+    let block = options.block_env();
+    let results: Vec<ArgusResult<(AccessList, Vec<PluginOutput>, Reads)>> =
+        options.install(|| {
+            transactions
+                .par_iter()
+                .with_min_len(options.chunk_size.max(1))
+                .enumerate()
+                .map(|(i, tx)| {
+                    if cancel.is_some_and(|c| c.is_cancelled()) {
+                        return Err(ArgusError::Cancelled);
+                    }
+                    let recording = RecordingDb::new(warm_db);
+                    let (mut list, out) =
+                        simulate_one_tx(tx, &recording, spec, block.as_ref(), plugins, tx_timeout)
+                            .for_tx(tx.hash)?;
+                    options.finish(&mut list, tx);
+                    if let Some(observer) = observer {
+                        observer.on_tx(i, &list);
+                    }
+                    Ok((list, out, recording.into_reads()))
+                })
+                .collect()
+        })?;
 
     let mut access_lists = Vec::with_capacity(results.len());
+    let mut outputs = Vec::new();
+    let mut reads = Reads::default();
     for r in results {
-        access_lists.push(r?);
+        let (list, out, tx_reads) = r?;
+        access_lists.push(list);
+        outputs.extend(out);
+        reads.merge(tx_reads);
     }
+    let coverage = reads.coverage();
 
-    tracing::info!(lists = access_lists.len(), "simulation complete");
-    Ok(access_lists)
+    tracing::info!(
+        lists = access_lists.len(),
+        missed_accounts = coverage.missed_accounts,
+        missed_slots = coverage.missed_slots,
+        "simulation complete"
+    );
+    Ok((access_lists, outputs, coverage))
+}
+
+/// Like [`simulate_batch_observed`], but executes the block as a node
+/// would: one transaction at a time, each against the state every earlier
+/// transaction committed. A read of a slot an earlier transaction wrote sees
+/// the written value, so the access lists follow the paths the block
+/// actually took. Slower, as nothing runs in parallel.
+///
+/// Coverage only counts reads that reached `warm_db`, not those answered by
+/// earlier transactions' writes. The thread settings of `options` have
+/// nothing to do here.
+pub fn simulate_batch_sequential<ExtDB>(
+    warm_db: &CacheDB<ExtDB>,
+    transactions: &[Transaction],
+    plugins: &[Arc<dyn TxInspectorPlugin>],
+    observer: Option<&dyn SimulationObserver>,
+    cancel: Option<&CancellationToken>,
+    options: &BatchOptions,
+) -> ArgusResult<(Vec<AccessList>, Vec<PluginOutput>, StateCoverage)>
+where
+    ExtDB: DatabaseRef,
+    ExtDB::Error: core::fmt::Debug,
+{
+    use revm::database_interface::DatabaseCommit;
+
+    tracing::info!(
+        txs = transactions.len(),
+        spec = ?options.spec,
+        plugins = plugins.len(),
+        "sequential simulation"
+    );
+
+    let (spec, tx_timeout) = (options.spec, options.tx_timeout);
+    let block = options.block_env();
+    let recording = RecordingDb::new(warm_db);
+    // Writes of the transactions so far, over the warm state.
+    let mut committed = CacheDB::new(&recording);
+    let mut access_lists = Vec::with_capacity(transactions.len());
+    let mut outputs = Vec::new();
+    for (i, tx) in transactions.iter().enumerate() {
+        if cancel.is_some_and(|c| c.is_cancelled()) {
+            return Err(ArgusError::Cancelled);
+        }
+        let (mut list, out, state) =
+            execute_tx(tx, &committed, spec, block.as_ref(), plugins, tx_timeout)
+                .for_tx(tx.hash)?;
+        if let Some(state) = state {
+            committed.commit(state);
+        }
+        options.finish(&mut list, tx);
+        if let Some(observer) = observer {
+            observer.on_tx(i, &list);
+        }
+        access_lists.push(list);
+        outputs.extend(out);
+    }
+    drop(committed);
+    let coverage = recording.into_reads().coverage();
+
+    tracing::info!(
+        lists = access_lists.len(),
+        missed_accounts = coverage.missed_accounts,
+        missed_slots = coverage.missed_slots,
+        "simulation complete"
+    );
+    Ok((access_lists, outputs, coverage))
+}
+
+/// [`SimulationObserver`] that reports each finished transaction to a
+/// [`ProgressHandler`] as well as to an inner observer, if any. Pass it as
+/// the `observer` of [`simulate_batch_observed`].
+pub struct ProgressObserver<'a> {
+    handler: &'a dyn ProgressHandler,
+    inner: Option<&'a dyn SimulationObserver>,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl<'a> ProgressObserver<'a> {
+    /// Progress over a batch of `total` transactions.
+    pub fn new(
+        handler: &'a dyn ProgressHandler,
+        inner: Option<&'a dyn SimulationObserver>,
+        total: usize,
+    ) -> Self {
+        Self {
+            handler,
+            inner,
+            total,
+            done: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl SimulationObserver for ProgressObserver<'_> {
+    fn begin_block(&self, chain: &'static argus_core::ChainConfig, block_number: u64) {
+        if let Some(inner) = self.inner {
+            inner.begin_block(chain, block_number);
+        }
+    }
+
+    fn on_tx(&self, index: usize, access_list: &AccessList) {
+        if let Some(inner) = self.inner {
+            inner.on_tx(index, access_list);
+        }
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.handler.on_tx_simulated(index, done, self.total);
+    }
+
+    fn end_block(&self) {
+        if let Some(inner) = self.inner {
+            inner.end_block();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// State discovery
+// ---------------------------------------------------------------------------
+
+/// Read-through view of a warm cache that notes every account and slot
+/// execution asks for, and which of them the cache lacked.
+struct RecordingDb<'a, ExtDB> {
+    db: &'a CacheDB<ExtDB>,
+    reads: Mutex<Reads>,
+}
+
+#[derive(Default)]
+struct Reads {
+    touched: TouchedState,
+    missed_accounts: BTreeSet<Address>,
+    missed_slots: TouchedState,
+}
+
+impl<'a, ExtDB> RecordingDb<'a, ExtDB> {
+    fn new(db: &'a CacheDB<ExtDB>) -> Self {
+        Self {
+            db,
+            reads: Mutex::default(),
+        }
+    }
+
+    fn reads(&self) -> std::sync::MutexGuard<'_, Reads> {
+        self.reads.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn into_reads(self) -> Reads {
+        self.reads.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `CacheDB` answers this slot itself rather than falling
+    /// through to its backend.
+    fn has_slot(&self, address: Address, index: U256) -> bool {
+        self.db.cache.accounts.get(&address).is_some_and(|account| {
+            account.storage.contains_key(&index)
+                || matches!(
+                    account.account_state,
+                    AccountState::StorageCleared | AccountState::NotExisting
+                )
+        })
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for RecordingDb<'_, ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<revm::state::AccountInfo>, Self::Error> {
+        let mut reads = self.reads();
+        reads.touched.entry(address).or_default();
+        if !self.db.cache.accounts.contains_key(&address) {
+            reads.missed_accounts.insert(address);
+        }
+        self.db.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<revm::state::Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let mut reads = self.reads();
+        reads.touched.entry(address).or_default().insert(index);
+        if !self.has_slot(address, index) {
+            reads.missed_slots.entry(address).or_default().insert(index);
+        }
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl Reads {
+    fn merge(&mut self, other: Reads) {
+        for (addr, slots) in other.touched {
+            self.touched.entry(addr).or_default().extend(slots);
+        }
+        self.missed_accounts.extend(other.missed_accounts);
+        for (addr, slots) in other.missed_slots {
+            self.missed_slots.entry(addr).or_default().extend(slots);
+        }
+    }
+
+    /// Coverage over the reads, leaving out precompiles and system
+    /// addresses (the first 2^16), which are never prefetched.
+    fn coverage(self) -> StateCoverage {
+        let counted = |addr: &Address| addr.0[..18].iter().any(|&b| b != 0);
+        let touched = self.touched.iter().filter(|(addr, _)| counted(addr));
+        let mut coverage = StateCoverage {
+            accounts: touched.clone().count(),
+            slots: touched.map(|(_, slots)| slots.len()).sum(),
+            ..Default::default()
+        };
+        let mut misses: BTreeMap<Address, usize> = BTreeMap::new();
+        for addr in self.missed_accounts.iter().filter(|a| counted(a)) {
+            coverage.missed_accounts += 1;
+            *misses.entry(*addr).or_default() += 1;
+        }
+        for (addr, slots) in self.missed_slots.iter().filter(|(a, _)| counted(a)) {
+            coverage.missed_slots += slots.len();
+            *misses.entry(*addr).or_default() += slots.len();
+        }
+        let mut top: Vec<_> = misses.into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top.truncate(TOP_MISSED);
+        coverage.top_missed = top;
+        coverage
+    }
+}
+
+/// Every account and slot `transactions` read when simulated against
+/// `warm_db`, for [`argus_provider::Prefetcher::prefetch_discovered`].
+/// Against missing state execution takes other paths, so each round of
+/// fetching what was read can turn up more.
+pub fn discover_state(
+    warm_db: &WarmCacheDB,
+    transactions: &[Transaction],
+    spec: Option<SpecId>,
+    block: Option<&BlockContext>,
+) -> ArgusResult<TouchedState> {
+    use rayon::prelude::*;
+
+    let recording = RecordingDb::new(warm_db);
+    let block = block.map(|block| block_env(block, spec));
+    transactions.par_iter().try_for_each(|tx| {
+        simulate_one_tx(tx, &recording, spec, block.as_ref(), &[], None)
+            .for_tx(tx.hash)
+            .map(drop)
+    })?;
+    Ok(recording.into_reads().touched)
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use alloy_primitives::{Bytes, U256};
 
+    /// A cache holding `code` deployed at `0x1111…11`, which is returned
+    /// with it.
+    pub(crate) fn deploy(code: impl Into<Bytes>) -> (Address, WarmCacheDB) {
+        let bytecode = revm::state::Bytecode::new_raw(code.into());
+        let target = Address::repeat_byte(0x11);
+        let mut warm = WarmCacheDB::new(EmptyDB::new());
+        warm.insert_account_info(
+            target,
+            revm::state::AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode),
+        );
+        (target, warm)
+    }
+
+    /// Storage entries of `list`, without the account-field pseudo-slots.
+    fn storage(list: &AccessList) -> Vec<&AccessEntry> {
+        list.entries
+            .iter()
+            .filter(|e| e.location.account_field().is_none())
+            .collect()
+    }
+
     #[test]
     fn inspector_records_nothing_without_execution() {
         let inspector = AccessListInspector::new(Some(Address::ZERO));
@@ -269,6 +1363,822 @@ mod tests {
         assert_eq!(inspector.current_address(), None);
     }
 
+    #[test]
+    fn spec_follows_chain_schedule() {
+        let fork = argus_core::chain::ETHEREUM.hardfork_at(19_500_000, 1_711_000_000);
+        assert_eq!(spec_id(fork), SpecId::CANCUN);
+        assert_eq!(spec_id(Hardfork::Paris), SpecId::MERGE);
+    }
+
+    #[test]
+    fn simulate_with_spec_empty_state() {
+        let warm = WarmCacheDB::new(EmptyDB::new());
+        let lists = simulate_batch_with_spec(&warm, &[], SpecId::LONDON).unwrap();
+        assert!(lists.is_empty());
+    }
+
+    #[derive(Default)]
+    struct Progress(Mutex<Vec<(usize, usize, usize)>>);
+
+    impl ProgressHandler for Progress {
+        fn on_tx_simulated(&self, index: usize, done: usize, total: usize) {
+            self.0.lock().unwrap().push((index, done, total));
+        }
+    }
+
+    #[derive(Default)]
+    struct Seen(AtomicUsize);
+
+    impl SimulationObserver for Seen {
+        fn on_tx(&self, _: usize, _: &AccessList) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn progress_counts_every_transaction() {
+        let tx = |n: u8| Transaction {
+            hash: B256::repeat_byte(n),
+            from: Address::repeat_byte(0x22),
+            to: Some(Address::repeat_byte(0x11)),
+            gas: 100_000,
+            ..Default::default()
+        };
+        let (progress, seen) = (Progress::default(), Seen::default());
+        let observer = ProgressObserver::new(&progress, Some(&seen), 3);
+
+        let warm = WarmCacheDB::new(EmptyDB::new());
+        let txs = [tx(1), tx(2), tx(3)];
+        simulate_batch_observed(&warm, &txs, &[], Some(&observer), None, &Default::default())
+            .unwrap();
+
+        let mut events = progress.0.into_inner().unwrap();
+        let mut done: Vec<_> = events.iter().map(|e| e.1).collect();
+        done.sort_unstable();
+        assert_eq!(done, [1, 2, 3]);
+        events.sort_unstable();
+        assert_eq!(
+            events.iter().map(|e| (e.0, e.2)).collect::<Vec<_>>(),
+            [(0, 3), (1, 3), (2, 3)]
+        );
+        assert_eq!(seen.0.into_inner(), 3);
+    }
+
+    struct OpcodeCounter;
+
+    struct Count(u64);
+
+    impl TxCapture for Count {
+        fn step(&mut self, _: Address, _: u8, _: &[U256]) {
+            self.0 += 1;
+        }
+
+        fn finish(self: Box<Self>) -> Option<serde_json::Value> {
+            Some(serde_json::json!(self.0))
+        }
+    }
+
+    impl TxInspectorPlugin for OpcodeCounter {
+        fn name(&self) -> &str {
+            "opcodes"
+        }
+
+        fn begin_tx(&self, _tx: &Transaction) -> Box<dyn TxCapture> {
+            Box::new(Count(0))
+        }
+    }
+
+    #[test]
+    fn inspector_plugin_sees_execution() {
+        // PUSH1 0 SLOAD STOP
+        let code = Bytes::from_static(&[0x60, 0x00, 0x54, 0x00]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let plugins: Vec<Arc<dyn TxInspectorPlugin>> = vec![Arc::new(OpcodeCounter)];
+        let (lists, outputs) = simulate_batch_with_plugins(&warm, &[tx], None, &plugins).unwrap();
+
+        assert_eq!(storage(&lists[0]).len(), 1);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].plugin, "opcodes");
+        assert_eq!(outputs[0].data, serde_json::json!(3));
+    }
+
+    #[test]
+    fn discovery_reaches_deeper_as_state_fills_in() {
+        let target = Address::repeat_byte(0x11);
+        let sender = Address::repeat_byte(0x22);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: sender,
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        // Without the target's code, only the accounts themselves are read.
+        let mut warm = WarmCacheDB::new(EmptyDB::new());
+        let touched = discover_state(&warm, std::slice::from_ref(&tx), None, None).unwrap();
+        assert!(touched[&sender].is_empty());
+        assert!(touched[&target].is_empty());
+
+        // PUSH1 7 SLOAD STOP
+        let bytecode =
+            revm::state::Bytecode::new_raw(Bytes::from_static(&[0x60, 0x07, 0x54, 0x00]));
+        warm.insert_account_info(
+            target,
+            revm::state::AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode),
+        );
+        let touched = discover_state(&warm, &[tx], None, None).unwrap();
+        assert_eq!(
+            touched[&target].iter().collect::<Vec<_>>(),
+            [&U256::from(7)]
+        );
+    }
+
+    #[test]
+    fn coverage_counts_reads_the_cache_lacked() {
+        // PUSH1 7 SLOAD PUSH1 8 SLOAD STOP
+        let code = Bytes::from_static(&[0x60, 0x07, 0x54, 0x60, 0x08, 0x54, 0x00]);
+        let (target, mut warm) = deploy(code);
+        let sender = Address::repeat_byte(0x22);
+        warm.insert_account_storage(target, U256::from(7), U256::from(1))
+            .unwrap();
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: sender,
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let (_, _, coverage) =
+            simulate_batch_observed(&warm, &[tx], &[], None, None, &Default::default()).unwrap();
+        // The sender and slot 8 read as empty; the zero-address coinbase
+        // isn't counted.
+        assert_eq!((coverage.accounts, coverage.missed_accounts), (2, 1));
+        assert_eq!((coverage.slots, coverage.missed_slots), (2, 1));
+        assert_eq!(coverage.top_missed, [(target, 1), (sender, 1)]);
+        assert_eq!(coverage.hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn sequential_runs_see_earlier_writes() {
+        // PUSH1 0 SLOAD PUSH1 12 JUMPI PUSH1 1 PUSH1 0 SSTORE STOP
+        // JUMPDEST PUSH1 9 SLOAD STOP: the first call sets slot 0, later
+        // calls find it set and read slot 9 instead.
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x54, 0x60, 0x0c, 0x57, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00, 0x5b, 0x60,
+            0x09, 0x54, 0x00,
+        ]);
+        let (target, warm) = deploy(code);
+        let tx = |n: u8| Transaction {
+            hash: B256::repeat_byte(n),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+        let txs = [tx(1), tx(2)];
+
+        let (isolated, _, _) =
+            simulate_batch_observed(&warm, &txs, &[], None, None, &Default::default()).unwrap();
+        assert_eq!(storage(&isolated[1]).len(), 1);
+
+        let (lists, _, coverage) =
+            simulate_batch_sequential(&warm, &txs, &[], None, None, &Default::default()).unwrap();
+        assert_eq!(storage(&lists[0]).len(), 1);
+        assert_eq!(storage(&lists[0])[0].mode, AccessMode::Write);
+        assert_eq!(storage(&lists[1]).len(), 2);
+        assert!(storage(&lists[1])
+            .iter()
+            .all(|e| e.mode == AccessMode::Read));
+        // Slot 0 came from the first transaction's write the second time.
+        assert_eq!(coverage.slots, 2);
+    }
+
+    #[test]
+    fn bounded_pool_matches_global_pool() {
+        // PUSH1 0 SLOAD STOP
+        let (target, warm) = deploy(Bytes::from_static(&[0x60, 0x00, 0x54, 0x00]));
+        let txs: Vec<Transaction> = (1..=8)
+            .map(|n| Transaction {
+                hash: B256::repeat_byte(n),
+                from: Address::repeat_byte(n),
+                to: Some(target),
+                gas: 100_000,
+                ..Default::default()
+            })
+            .collect();
+
+        let options = BatchOptions::default().with_threads(2).with_chunk_size(3);
+        let bounded = simulate_batch_with_options(&warm, &txs, &options).unwrap();
+        let global = simulate_batch_with_state(&warm, &txs).unwrap();
+        assert_eq!(bounded.len(), 8);
+        for (a, b) in bounded.iter().zip(&global) {
+            assert_eq!(a.tx_hash, b.tx_hash);
+            assert_eq!(a.entries, b.entries);
+        }
+    }
+
+    #[test]
+    fn strict_fees_write_sender_and_coinbase() {
+        let warm = WarmCacheDB::new(EmptyDB::new());
+        let txs: Vec<Transaction> = (1..=2)
+            .map(|n| Transaction {
+                hash: B256::repeat_byte(n),
+                from: Address::repeat_byte(n),
+                to: Some(Address::repeat_byte(0x10 + n)),
+                gas: 100_000,
+                ..Default::default()
+            })
+            .collect();
+        let coinbase = StorageLocation::account(Address::ZERO, AccountField::Balance);
+        let writes = |list: &AccessList, location: &StorageLocation| {
+            list.entries
+                .iter()
+                .any(|e| &e.location == location && e.mode == AccessMode::Write)
+        };
+
+        let relaxed = simulate_batch_with_state(&warm, &txs).unwrap();
+        assert!(relaxed.iter().all(|list| !writes(list, &coinbase)));
+
+        let options = BatchOptions::default().with_fees(FeeAccounting::Strict);
+        let strict = simulate_batch_with_options(&warm, &txs, &options).unwrap();
+        for (list, tx) in strict.iter().zip(&txs) {
+            assert!(writes(list, &coinbase));
+            let sender = StorageLocation::account(tx.from, AccountField::Balance);
+            assert!(writes(list, &sender));
+        }
+        // Unrelated calls now conflict through the coinbase.
+        let graph = crate::graph::build_conflict_graph(&strict);
+        assert!(graph.conflicts.iter().any(|c| c.location == coinbase));
+    }
+
+    #[test]
+    fn exclusions_drop_locations() {
+        // PUSH1 1 PUSH1 0 SSTORE PUSH1 1 PUSH1 1 SSTORE STOP
+        let (target, warm) = deploy(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x01, 0x60, 0x01, 0x55, 0x00,
+        ]));
+        let txs = [Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(1),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        }];
+        let slot = |n: u8| StorageLocation {
+            address: target,
+            slot: B256::with_last_byte(n),
+        };
+
+        let options = BatchOptions::default().with_excluded_slot(target, B256::ZERO);
+        let list = &simulate_batch_with_options(&warm, &txs, &options).unwrap()[0];
+        let slots: Vec<_> = storage(list).iter().map(|e| e.location.clone()).collect();
+        assert_eq!(slots, [slot(1)]);
+
+        let options = BatchOptions::default().with_excluded_address(target);
+        let list = &simulate_batch_with_options(&warm, &txs, &options).unwrap()[0];
+        assert!(list.entries.iter().all(|e| e.location.address != target));
+        assert!(list.writes.iter().all(|w| w.location.address != target));
+    }
+
+    /// Backend where every slot holds 1, standing in for a node.
+    struct Node;
+
+    impl DatabaseRef for Node {
+        type Error = core::convert::Infallible;
+
+        fn basic_ref(&self, _: Address) -> Result<Option<revm::state::AccountInfo>, Self::Error> {
+            Ok(None)
+        }
+
+        fn code_by_hash_ref(&self, _: B256) -> Result<revm::state::Bytecode, Self::Error> {
+            Ok(revm::state::Bytecode::default())
+        }
+
+        fn storage_ref(&self, _: Address, _: U256) -> Result<U256, Self::Error> {
+            Ok(U256::from(1))
+        }
+
+        fn block_hash_ref(&self, _: u64) -> Result<B256, Self::Error> {
+            Ok(B256::ZERO)
+        }
+    }
+
+    #[test]
+    fn missing_state_falls_through_to_backend() {
+        // PUSH1 8 SLOAD PUSH1 7 JUMPI STOP JUMPDEST PUSH1 9 SLOAD STOP:
+        // slot 9 is only read when slot 8 is non-zero.
+        let code = Bytes::from_static(&[
+            0x60, 0x08, 0x54, 0x60, 0x07, 0x57, 0x00, 0x5b, 0x60, 0x09, 0x54, 0x00,
+        ]);
+        let bytecode = revm::state::Bytecode::new_raw(code);
+        let target = Address::repeat_byte(0x11);
+        let info = revm::state::AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let mut warm = WarmCacheDB::new(EmptyDB::new());
+        warm.insert_account_info(target, info.clone());
+        let (lists, _, _) =
+            simulate_batch_observed(&warm, &[tx.clone()], &[], None, None, &Default::default())
+                .unwrap();
+        assert_eq!(storage(&lists[0]).len(), 1);
+
+        let mut over_node = CacheDB::new(Node);
+        over_node.insert_account_info(target, info);
+        let (lists, _, coverage) =
+            simulate_batch_observed(&over_node, &[tx], &[], None, None, &Default::default())
+                .unwrap();
+        assert_eq!(storage(&lists[0]).len(), 2);
+        assert_eq!(coverage.missed_slots, 2);
+    }
+
+    #[test]
+    fn writes_record_their_values() {
+        // SSTORE(0, 1) SSTORE(0, 0) SSTORE(1, 9) STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x09, 0x60, 0x01,
+            0x55, 0x00,
+        ]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        let writes = &lists[0].writes;
+        assert_eq!(writes.len(), 2);
+        assert!(writes[0].is_restore());
+        assert_eq!(writes[1].value, B256::with_last_byte(9));
+        assert!(!writes[1].is_restore());
+    }
+
+    #[test]
+    fn repeated_touches_are_counted() {
+        // SLOAD(0) SLOAD(0) SSTORE(0, 1) STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x54, 0x60, 0x00, 0x54, 0x60, 0x01, 0x60, 0x00, 0x55, 0x00,
+        ]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        let slots = storage(&lists[0]);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].mode, AccessMode::Write);
+        assert_eq!(slots[0].count, 3);
+    }
+
+    #[test]
+    fn reused_inspector_starts_clean() {
+        // PUSH1 i SLOAD POP for 40 slots: spills past the inline buffer.
+        let code: Vec<u8> = (0..40u8).flat_map(|i| [0x60, i, 0x54, 0x50]).collect();
+        let (target, warm) = deploy(code);
+        let tx = |n: u8, to: Address| Transaction {
+            hash: B256::repeat_byte(n),
+            from: Address::repeat_byte(0x22),
+            to: Some(to),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        // Same thread, so the second transaction gets the first's inspector.
+        let (heavy, _) = simulate_one_tx(&tx(1, target), &warm, None, None, &[], None).unwrap();
+        assert_eq!(storage(&heavy).len(), 40);
+        // The spare holds room for as many again, not the list itself.
+        SPARE_INSPECTOR.with_borrow(|spare| {
+            let spare = spare.as_ref().unwrap();
+            assert!(spare.entries.is_empty() && spare.entries.capacity() >= heavy.entries.len());
+        });
+        let plain = tx(2, Address::repeat_byte(0x33));
+        let (light, _) = simulate_one_tx(&plain, &warm, None, None, &[], None).unwrap();
+        assert!(storage(&light).is_empty());
+        assert!(light.entries.iter().all(|e| e.location.address != target));
+    }
+
+    #[test]
+    fn value_transfer_writes_both_balances() {
+        let (from, to) = (Address::repeat_byte(0x22), Address::repeat_byte(0x33));
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from,
+            to: Some(to),
+            value: U256::from(1),
+            gas: 21_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&WarmCacheDB::new(EmptyDB::new()), &[tx]).unwrap();
+        let fields: Vec<_> = lists[0]
+            .entries
+            .iter()
+            .map(|e| (e.location.address, e.location.account_field(), e.mode))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (from, Some(AccountField::Balance), AccessMode::Write),
+                (from, Some(AccountField::Nonce), AccessMode::Write),
+                (to, Some(AccountField::Balance), AccessMode::Write),
+            ]
+        );
+        assert_eq!(lists[0].gas_used, 21_000);
+    }
+
+    #[test]
+    fn endowed_create_writes_both_balances() {
+        let from = Address::repeat_byte(0x22);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from,
+            value: U256::from(1),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&WarmCacheDB::new(EmptyDB::new()), &[tx]).unwrap();
+        let created = from.create(0);
+        let writes: Vec<_> = lists[0]
+            .entries
+            .iter()
+            .filter(|e| e.mode == AccessMode::Write)
+            .map(|e| (e.location.address, e.location.account_field()))
+            .collect();
+        assert!(writes.contains(&(from, Some(AccountField::Balance))));
+        assert!(writes.contains(&(created, Some(AccountField::Balance))));
+        assert!(writes.contains(&(created, Some(AccountField::Code))));
+    }
+
+    #[test]
+    fn balance_reads_conflict_with_transfers() {
+        // PUSH20 payee BALANCE PUSH20 other EXTCODEHASH STOP
+        let (payee, other) = (Address::repeat_byte(0x33), Address::repeat_byte(0x55));
+        let mut code = vec![0x73];
+        code.extend_from_slice(payee.as_slice());
+        code.extend_from_slice(&[0x31, 0x73]);
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[0x3f, 0x00]);
+        let (reader, warm) = deploy(code);
+        let tx = |n: u8, to: Address, value: u64| Transaction {
+            hash: B256::repeat_byte(n),
+            from: Address::repeat_byte(0x20 + n),
+            to: Some(to),
+            value: U256::from(value),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx(1, payee, 1), tx(2, reader, 0)]).unwrap();
+        let reads: Vec<_> = lists[1]
+            .entries
+            .iter()
+            .filter(|e| e.mode == AccessMode::Read)
+            .map(|e| (e.location.address, e.location.account_field()))
+            .collect();
+        assert!(reads.contains(&(payee, Some(AccountField::Balance))));
+        assert!(reads.contains(&(other, Some(AccountField::Code))));
+
+        let graph = crate::graph::build_conflict_graph(&lists);
+        assert_eq!(graph.len(), 1);
+        assert_eq!(
+            graph.conflicts[0].location,
+            StorageLocation::account(payee, AccountField::Balance)
+        );
+        assert_eq!(graph.conflicts[0].kind, argus_core::ConflictKind::ReadWrite);
+    }
+
+    #[test]
+    fn self_destruct_writes_the_account_before_cancun() {
+        // PUSH20 beneficiary SELFDESTRUCT
+        let beneficiary = Address::repeat_byte(0x44);
+        let mut code = vec![0x73];
+        code.extend_from_slice(beneficiary.as_slice());
+        code.push(0xff);
+        let bytecode = revm::state::Bytecode::new_raw(Bytes::from(code));
+        let target = Address::repeat_byte(0x11);
+        let mut warm = WarmCacheDB::new(EmptyDB::new());
+        warm.insert_account_info(
+            target,
+            revm::state::AccountInfo::new(U256::from(1), 0, bytecode.hash_slow(), bytecode),
+        );
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+        let fields = |spec| {
+            simulate_batch_with_spec(&warm, &[tx.clone()], spec).unwrap()[0]
+                .entries
+                .iter()
+                .map(|e| (e.location.address, e.location.account_field()))
+                .collect::<Vec<_>>()
+        };
+
+        let shanghai = fields(SpecId::SHANGHAI);
+        assert!(shanghai.contains(&(target, Some(AccountField::Existence))));
+        assert!(shanghai.contains(&(beneficiary, Some(AccountField::Balance))));
+        // EIP-6780: only the balance moves.
+        let cancun = fields(SpecId::CANCUN);
+        assert!(!cancun.contains(&(target, Some(AccountField::Existence))));
+        assert!(cancun.contains(&(beneficiary, Some(AccountField::Balance))));
+    }
+
+    #[test]
+    fn revert_reasons_are_decoded() {
+        // Error("nope")
+        let mut error = vec![0x08, 0xc3, 0x79, 0xa0];
+        error.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        error.extend_from_slice(&U256::from(4).to_be_bytes::<32>());
+        error.extend_from_slice(&[b'n', b'o', b'p', b'e']);
+        error.resize(4 + 96, 0);
+        assert_eq!(revert_reason(&Bytes::from(error)).as_deref(), Some("nope"));
+
+        let mut panic = vec![0x4e, 0x48, 0x7b, 0x71];
+        panic.extend_from_slice(&U256::from(0x11).to_be_bytes::<32>());
+        assert_eq!(
+            revert_reason(&Bytes::from(panic)).as_deref(),
+            Some("Panic(0x11)")
+        );
+
+        assert_eq!(revert_reason(&Bytes::new()), None);
+        assert_eq!(
+            revert_reason(&Bytes::from_static(&[0xde, 0xad])).as_deref(),
+            Some("0xdead")
+        );
+    }
+
+    #[test]
+    fn reverted_runs_record_the_outcome() {
+        // PUSH1 0 SLOAD PUSH1 0 PUSH1 0 REVERT
+        let code = Bytes::from_static(&[0x60, 0x00, 0x54, 0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        assert_eq!(lists[0].outcome, ExecutionOutcome::Revert { reason: None });
+        // The read before the revert still counts.
+        assert_eq!(storage(&lists[0]).len(), 1);
+    }
+
+    #[test]
+    fn runaway_loops_time_out() {
+        // JUMPDEST PUSH1 0 JUMP: spins until the gas runs out.
+        let code = Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 30_000_000,
+            ..Default::default()
+        };
+
+        let (lists, _, _) = simulate_batch_observed(
+            &warm,
+            &[tx.clone()],
+            &[],
+            None,
+            None,
+            &BatchOptions::default().with_tx_timeout(Duration::ZERO),
+        )
+        .unwrap();
+        assert_eq!(lists[0].outcome, ExecutionOutcome::TimedOut);
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        assert!(matches!(lists[0].outcome, ExecutionOutcome::Halt { .. }));
+    }
+
+    #[test]
+    fn emitted_logs_are_attached() {
+        // PUSH1 7 PUSH1 0 PUSH1 0 LOG1 STOP: an empty event with topic 7.
+        let code = Bytes::from_static(&[0x60, 0x07, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        assert_eq!(
+            lists[0].logs,
+            [TxLog {
+                address: target,
+                topics: vec![B256::from(U256::from(7))],
+            }]
+        );
+    }
+
+    #[test]
+    fn transient_storage_is_kept_apart() {
+        // PUSH1 1 PUSH1 5 TSTORE PUSH1 5 TLOAD PUSH1 5 SLOAD STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x05, 0x5d, 0x60, 0x05, 0x5c, 0x60, 0x05, 0x54, 0x00,
+        ]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        let slot = B256::from(U256::from(5));
+        assert_eq!(storage(&lists[0]).len(), 1);
+        assert_eq!(storage(&lists[0])[0].mode, AccessMode::Read);
+        assert_eq!(lists[0].transient.len(), 1);
+        assert_eq!(lists[0].transient[0].location.slot, slot);
+        assert_eq!(lists[0].transient[0].mode, AccessMode::Write);
+    }
+
+    #[test]
+    fn blob_hashes_reach_blobhash() {
+        // PUSH1 0 BLOBHASH SLOAD STOP: reads the slot named by the first blob hash.
+        let code = Bytes::from_static(&[0x60, 0x00, 0x49, 0x54, 0x00]);
+        let (target, warm) = deploy(code);
+        let mut blob_hash = B256::repeat_byte(0xaa);
+        blob_hash[0] = 0x01; // versioned hash: KZG version byte
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            blob_hashes: vec![blob_hash],
+            max_fee_per_blob_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[tx]).unwrap();
+        assert_eq!(storage(&lists[0]).len(), 1);
+        assert_eq!(storage(&lists[0])[0].location.slot, blob_hash);
+    }
+
+    #[test]
+    fn authorizations_delegate_the_authority() {
+        use k256::ecdsa::SigningKey;
+        use revm::context_interface::transaction::Authorization;
+
+        // PUSH1 1 PUSH1 0 SSTORE STOP
+        let (delegate, warm) = deploy(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55, 0x00]));
+        let key = SigningKey::from_bytes(&[0x42; 32].into()).unwrap();
+        let point = key.verifying_key().to_encoded_point(false);
+        let authority = Address::from_raw_public_key(&point.as_bytes()[1..]);
+        let auth = Authorization {
+            chain_id: U256::from(1),
+            address: delegate,
+            nonce: 0,
+        };
+        let (signature, recovery) = key
+            .sign_prehash_recoverable(auth.signature_hash().as_slice())
+            .unwrap();
+        let signature = signature.to_bytes();
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(authority),
+            gas: 100_000,
+            authorization_list: vec![argus_core::Authorization {
+                chain_id: auth.chain_id,
+                address: delegate,
+                nonce: auth.nonce,
+                y_parity: recovery.to_byte(),
+                r: U256::from_be_slice(&signature[..32]),
+                s: U256::from_be_slice(&signature[32..]),
+            }],
+            ..Default::default()
+        };
+
+        let list = &simulate_batch_with_state(&warm, &[tx]).unwrap()[0];
+        assert_eq!(list.outcome, ExecutionOutcome::Success);
+        for field in [AccountField::Code, AccountField::Nonce] {
+            let location = StorageLocation::account(authority, field);
+            assert!(list
+                .entries
+                .iter()
+                .any(|e| e.location == location && e.mode == AccessMode::Write));
+        }
+        // The call ran the delegate's code in the authority's storage.
+        let slots: Vec<_> = storage(list).iter().map(|e| e.location.clone()).collect();
+        assert_eq!(
+            slots,
+            [StorageLocation {
+                address: authority,
+                slot: B256::ZERO,
+            }]
+        );
+    }
+
+    #[test]
+    fn gasprice_reads_the_effective_price() {
+        // GASPRICE SLOAD STOP: reads the slot named by the gas price.
+        let code = Bytes::from_static(&[0x3a, 0x54, 0x00]);
+        let (target, warm) = deploy(code);
+        let legacy = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            gas_price: Some(7),
+            ..Default::default()
+        };
+        // Base fee 0 without a block: the tip is the whole price.
+        let dynamic = Transaction {
+            hash: B256::repeat_byte(2),
+            gas_price: None,
+            max_fee_per_gas: Some(100),
+            max_priority_fee_per_gas: Some(2),
+            ..legacy.clone()
+        };
+
+        let lists = simulate_batch_with_state(&warm, &[legacy, dynamic]).unwrap();
+        let slot = |list: &AccessList| storage(list)[0].location.slot;
+        assert_eq!(slot(&lists[0]), B256::with_last_byte(7));
+        assert_eq!(slot(&lists[1]), B256::with_last_byte(2));
+    }
+
+    #[test]
+    fn block_context_reaches_number_and_prevrandao() {
+        // NUMBER SLOAD PREVRANDAO SLOAD STOP
+        let code = Bytes::from_static(&[0x43, 0x54, 0x44, 0x54, 0x00]);
+        let (target, warm) = deploy(code);
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 100_000,
+            ..Default::default()
+        };
+        let block = BlockContext {
+            number: 7,
+            timestamp: 84,
+            gas_limit: 30_000_000,
+            prevrandao: Some(B256::repeat_byte(0xcc)),
+            ..Default::default()
+        };
+
+        let (lists, _, _) = simulate_batch_observed(
+            &warm,
+            &[tx.clone()],
+            &[],
+            None,
+            None,
+            &BatchOptions::default().with_block(block.clone()),
+        )
+        .unwrap();
+        let slots: Vec<B256> = storage(&lists[0]).iter().map(|e| e.location.slot).collect();
+        assert_eq!(slots.len(), 2);
+        assert!(slots.contains(&B256::from(U256::from(7))));
+        assert!(slots.contains(&B256::repeat_byte(0xcc)));
+    }
+
+    #[test]
+    fn block_env_falls_back_to_difficulty() {
+        let block = BlockContext {
+            difficulty: U256::from(1),
+            excess_blob_gas: Some(0),
+            ..Default::default()
+        };
+        let env = block_env(&block, Some(SpecId::CANCUN));
+        assert_eq!(env.prevrandao, Some(B256::from(U256::from(1))));
+        assert!(env.blob_excess_gas_and_price.is_some());
+    }
+
     #[tokio::test]
     async fn empty_batch_returns_empty() {
         assert!(simulate_batch(vec![]).await.unwrap().is_empty());
@@ -277,15 +2187,142 @@ mod tests {
     #[tokio::test]
     async fn single_tx_does_not_panic() {
         let tx = Transaction {
-            hash: B256::ZERO,
-            from: Address::ZERO,
             to: Some(Address::ZERO),
-            input: Bytes::new(),
-            value: U256::ZERO,
             gas: 21000,
+            ..Default::default()
         };
         let result = simulate_batch(vec![tx]).await.unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].tx_hash, B256::ZERO);
     }
+
+    #[tokio::test]
+    async fn dry_run_honours_exclusions() {
+        let target = Address::repeat_byte(0x11);
+        let txs: Arc<[Transaction]> = Arc::from([Transaction {
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 21000,
+            ..Default::default()
+        }]);
+        let touches = |list: &AccessList| list.entries.iter().any(|e| e.location.address == target);
+
+        let cancel = CancellationToken::new();
+        let lists = simulate_batch_in_block(txs.clone(), BatchOptions::default(), cancel.clone())
+            .await
+            .unwrap();
+        assert!(touches(&lists[0]));
+
+        let options = BatchOptions::default().with_excluded_address(target);
+        let lists = simulate_batch_in_block(txs, options, cancel).await.unwrap();
+        assert!(!touches(&lists[0]));
+    }
+
+    #[tokio::test]
+    async fn dry_run_honours_strict_fees() {
+        let tx = Transaction {
+            from: Address::repeat_byte(0x22),
+            to: Some(Address::repeat_byte(0x11)),
+            gas: 21000,
+            ..Default::default()
+        };
+        let block = BlockContext {
+            coinbase: Address::repeat_byte(0xcb),
+            ..Default::default()
+        };
+        let options = BatchOptions::default()
+            .with_block(block)
+            .with_fees(FeeAccounting::Strict);
+        let lists = simulate_batch_in_block(vec![tx.clone()], options, CancellationToken::new())
+            .await
+            .unwrap();
+        for address in [tx.from, Address::repeat_byte(0xcb)] {
+            let balance = StorageLocation::account(address, AccountField::Balance);
+            assert!(lists[0]
+                .entries
+                .iter()
+                .any(|e| e.location == balance && e.mode == AccessMode::Write));
+        }
+    }
+
+    #[tokio::test]
+    async fn async_warm_batch_honours_cancel() {
+        let tx = Transaction {
+            to: Some(Address::ZERO),
+            gas: 21000,
+            ..Default::default()
+        };
+        let warm = Arc::new(WarmCacheDB::new(EmptyDB::new()));
+        let lists = simulate_batch_with_state_async(
+            Arc::clone(&warm),
+            vec![tx.clone()],
+            BatchOptions::default(),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(lists.len(), 1);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = simulate_batch_with_state_async(warm, vec![tx], Default::default(), cancel)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ArgusError::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn streaming_sends_every_list() {
+        let txs: Vec<Transaction> = (1..=5)
+            .map(|n| Transaction {
+                hash: B256::repeat_byte(n),
+                from: Address::repeat_byte(n),
+                to: Some(Address::ZERO),
+                gas: 21000,
+                ..Default::default()
+            })
+            .collect();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(2);
+        let run = tokio::spawn(simulate_batch_streaming(
+            WarmCacheDB::new(EmptyDB::new()),
+            txs,
+            BatchOptions::default(),
+            CancellationToken::new(),
+            sender,
+        ));
+
+        let mut seen = Vec::new();
+        while let Some((i, list)) = receiver.recv().await {
+            assert_eq!(list.tx_hash, B256::repeat_byte(i as u8 + 1));
+            seen.push(i);
+        }
+        run.await.unwrap().unwrap();
+        seen.sort_unstable();
+        assert_eq!(seen, [0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn streaming_honours_batch_options() {
+        // JUMPDEST PUSH1 0 JUMP: spins until the timeout stops it.
+        let (target, warm) = deploy(Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]));
+        let tx = Transaction {
+            hash: B256::repeat_byte(1),
+            from: Address::repeat_byte(0x22),
+            to: Some(target),
+            gas: 30_000_000,
+            ..Default::default()
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+        simulate_batch_streaming(
+            warm,
+            vec![tx],
+            BatchOptions::default().with_tx_timeout(Duration::ZERO),
+            CancellationToken::new(),
+            sender,
+        )
+        .await
+        .unwrap();
+        let (_, list) = receiver.recv().await.unwrap();
+        assert_eq!(list.outcome, ExecutionOutcome::TimedOut);
+    }
 }
@@ -0,0 +1,322 @@
+//! Arrow IPC file sink.
+//!
+//! Builds Arrow `RecordBatch`es for the block summary, conflict,
+//! contention-event and slot-value row schemas and writes them as Arrow IPC files that
+//! Polars, pandas (pyarrow) and DuckDB read zero-copy. Requires the `arrow`
+//! feature flag.
+//!
+//! ```ignore
+//! let mut sink = ArrowIpcSink::create(Path::new("out/"))?;
+//! sink.write_summary(&summary)?;
+//! sink.write_conflicts(&conflicts)?;
+//! sink.write_contention_events(&events)?;
+//! sink.finish()?;
+//! ```
+//!
+//! ```python
+//! import polars as pl
+//! pl.read_ipc("out/contention_events.arrow")
+//! ```
+
+use super::{BlockSummaryRow, ConflictRow, ContentionEvent, SlotValueRow};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt32Array, UInt64Array};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// File names, matching the StarRocks table names.
+pub const SUMMARY_FILE: &str = "block_summary.arrow";
+pub const CONFLICTS_FILE: &str = "conflicts.arrow";
+pub const CONTENTION_FILE: &str = "contention_events.arrow";
+pub const SLOT_VALUES_FILE: &str = "slot_values.arrow";
+
+fn schema(fields: &[(&str, DataType)]) -> SchemaRef {
+    Arc::new(Schema::new(
+        fields
+            .iter()
+            .map(|(name, ty)| Field::new(*name, ty.clone(), false))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+pub fn summary_schema() -> SchemaRef {
+    schema(&[
+        ("block_number", DataType::UInt64),
+        ("total_txs", DataType::UInt32),
+        ("txs_with_storage", DataType::UInt32),
+        ("total_entries", DataType::UInt32),
+        ("total_conflicts", DataType::UInt32),
+        ("hotspot_count", DataType::UInt32),
+        ("state_reads", DataType::UInt32),
+        ("state_misses", DataType::UInt32),
+        ("prefetch_fetched", DataType::UInt32),
+        ("prefetch_failed", DataType::UInt32),
+        ("prefetch_retried", DataType::UInt32),
+        ("prefetch_time_ms", DataType::UInt64),
+        ("fetch_time_ms", DataType::UInt64),
+        ("total_time_ms", DataType::UInt64),
+        ("created_at", DataType::Utf8),
+    ])
+}
+
+pub fn conflicts_schema() -> SchemaRef {
+    schema(&[
+        ("block_number", DataType::UInt64),
+        ("tx_a", DataType::Utf8),
+        ("tx_b", DataType::Utf8),
+        ("contract_address", DataType::Utf8),
+        ("contract_protocol", DataType::Utf8),
+        ("contract_name", DataType::Utf8),
+        ("slot", DataType::Utf8),
+        ("conflict_kind", DataType::Utf8),
+        ("created_at", DataType::Utf8),
+    ])
+}
+
+pub fn contention_schema() -> SchemaRef {
+    schema(&[
+        ("block_number", DataType::UInt64),
+        ("contract_address", DataType::Utf8),
+        ("contract_protocol", DataType::Utf8),
+        ("contract_name", DataType::Utf8),
+        ("slot_id", DataType::Utf8),
+        ("hazard_type", DataType::Utf8),
+        ("affected_tx_count", DataType::UInt32),
+        ("conflict_count", DataType::UInt32),
+        ("conflict_density", DataType::Float64),
+        ("access_count", DataType::UInt32),
+        ("affected_gas_used", DataType::UInt64),
+        ("gas_weighted_density", DataType::Float64),
+        ("severity", DataType::Utf8),
+        ("created_at", DataType::Utf8),
+    ])
+}
+
+pub fn slot_values_schema() -> SchemaRef {
+    schema(&[
+        ("block_number", DataType::UInt64),
+        ("contract_address", DataType::Utf8),
+        ("slot_id", DataType::Utf8),
+        ("value", DataType::Utf8),
+        ("created_at", DataType::Utf8),
+    ])
+}
+
+fn u64s<T>(rows: &[T], f: impl Fn(&T) -> u64) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(rows.iter().map(f)))
+}
+
+fn u32s<T>(rows: &[T], f: impl Fn(&T) -> u32) -> ArrayRef {
+    Arc::new(UInt32Array::from_iter_values(rows.iter().map(f)))
+}
+
+fn f64s<T>(rows: &[T], f: impl Fn(&T) -> f64) -> ArrayRef {
+    Arc::new(Float64Array::from_iter_values(rows.iter().map(f)))
+}
+
+fn strs<T>(rows: &[T], f: impl Fn(&T) -> &str) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(rows.iter().map(f)))
+}
+
+pub fn summary_batch(rows: &[BlockSummaryRow]) -> Result<RecordBatch, ArrowError> {
+    RecordBatch::try_new(
+        summary_schema(),
+        vec![
+            u64s(rows, |r| r.block_number),
+            u32s(rows, |r| r.total_txs),
+            u32s(rows, |r| r.txs_with_storage),
+            u32s(rows, |r| r.total_entries),
+            u32s(rows, |r| r.total_conflicts),
+            u32s(rows, |r| r.hotspot_count),
+            u32s(rows, |r| r.state_reads),
+            u32s(rows, |r| r.state_misses),
+            u32s(rows, |r| r.prefetch_fetched),
+            u32s(rows, |r| r.prefetch_failed),
+            u32s(rows, |r| r.prefetch_retried),
+            u64s(rows, |r| r.prefetch_time_ms),
+            u64s(rows, |r| r.fetch_time_ms),
+            u64s(rows, |r| r.total_time_ms),
+            strs(rows, |r| r.created_at.as_str()),
+        ],
+    )
+}
+
+pub fn conflicts_batch(rows: &[ConflictRow]) -> Result<RecordBatch, ArrowError> {
+    RecordBatch::try_new(
+        conflicts_schema(),
+        vec![
+            u64s(rows, |r| r.block_number),
+            strs(rows, |r| r.tx_a.as_str()),
+            strs(rows, |r| r.tx_b.as_str()),
+            strs(rows, |r| r.contract_address.as_str()),
+            strs(rows, |r| r.contract_protocol.as_str()),
+            strs(rows, |r| r.contract_name.as_str()),
+            strs(rows, |r| r.slot.as_str()),
+            strs(rows, |r| r.conflict_kind.as_str()),
+            strs(rows, |r| r.created_at.as_str()),
+        ],
+    )
+}
+
+pub fn contention_batch(rows: &[ContentionEvent]) -> Result<RecordBatch, ArrowError> {
+    RecordBatch::try_new(
+        contention_schema(),
+        vec![
+            u64s(rows, |r| r.block_number),
+            strs(rows, |r| r.contract_address.as_str()),
+            strs(rows, |r| r.contract_protocol.as_str()),
+            strs(rows, |r| r.contract_name.as_str()),
+            strs(rows, |r| r.slot_id.as_str()),
+            strs(rows, |r| r.hazard_type.as_str()),
+            u32s(rows, |r| r.affected_tx_count),
+            u32s(rows, |r| r.conflict_count),
+            f64s(rows, |r| r.conflict_density),
+            u32s(rows, |r| r.access_count),
+            u64s(rows, |r| r.affected_gas_used),
+            f64s(rows, |r| r.gas_weighted_density),
+            strs(rows, |r| r.severity.as_str()),
+            strs(rows, |r| r.created_at.as_str()),
+        ],
+    )
+}
+
+pub fn slot_values_batch(rows: &[SlotValueRow]) -> Result<RecordBatch, ArrowError> {
+    RecordBatch::try_new(
+        slot_values_schema(),
+        vec![
+            u64s(rows, |r| r.block_number),
+            strs(rows, |r| r.contract_address.as_str()),
+            strs(rows, |r| r.slot_id.as_str()),
+            strs(rows, |r| r.value.as_str()),
+            strs(rows, |r| r.created_at.as_str()),
+        ],
+    )
+}
+
+/// Writes one Arrow IPC file per row schema into a directory.
+///
+/// Each `write_*` call appends one record batch; files are only valid
+/// after [`finish`](Self::finish) writes their footers.
+pub struct ArrowIpcSink {
+    summary: FileWriter<File>,
+    conflicts: FileWriter<File>,
+    contention: FileWriter<File>,
+    slot_values: FileWriter<File>,
+    rows_written: usize,
+}
+
+fn to_io(e: ArrowError) -> io::Error {
+    io::Error::other(e)
+}
+
+impl ArrowIpcSink {
+    /// Create (or truncate) the four IPC files under `dir`.
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let open = |name: &str, schema: SchemaRef| -> io::Result<FileWriter<File>> {
+            FileWriter::try_new(File::create(dir.join(name))?, &schema).map_err(to_io)
+        };
+        Ok(Self {
+            summary: open(SUMMARY_FILE, summary_schema())?,
+            conflicts: open(CONFLICTS_FILE, conflicts_schema())?,
+            contention: open(CONTENTION_FILE, contention_schema())?,
+            slot_values: open(SLOT_VALUES_FILE, slot_values_schema())?,
+            rows_written: 0,
+        })
+    }
+
+    pub fn write_summary(&mut self, row: &BlockSummaryRow) -> io::Result<()> {
+        let batch = summary_batch(std::slice::from_ref(row)).map_err(to_io)?;
+        self.summary.write(&batch).map_err(to_io)?;
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    pub fn write_conflicts(&mut self, rows: &[ConflictRow]) -> io::Result<()> {
+        let batch = conflicts_batch(rows).map_err(to_io)?;
+        self.conflicts.write(&batch).map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    pub fn write_contention_events(&mut self, rows: &[ContentionEvent]) -> io::Result<()> {
+        let batch = contention_batch(rows).map_err(to_io)?;
+        self.contention.write(&batch).map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    pub fn write_slot_values(&mut self, rows: &[SlotValueRow]) -> io::Result<()> {
+        let batch = slot_values_batch(rows).map_err(to_io)?;
+        self.slot_values.write(&batch).map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    /// Write file footers. Returns total rows written.
+    pub fn finish(mut self) -> io::Result<usize> {
+        self.summary.finish().map_err(to_io)?;
+        self.conflicts.finish().map_err(to_io)?;
+        self.contention.finish().map_err(to_io)?;
+        self.slot_values.finish().map_err(to_io)?;
+        Ok(self.rows_written)
+    }
+
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_ipc::reader::FileReader;
+
+    fn event(density: f64) -> ContentionEvent {
+        ContentionEvent {
+            block_number: 7,
+            contract_address: "0x01".into(),
+            contract_protocol: "Uniswap V2".into(),
+            contract_name: "WETH/USDC".into(),
+            slot_id: "0x08".into(),
+            hazard_type: "WAW".into(),
+            affected_tx_count: 4,
+            conflict_count: 6,
+            conflict_density: density,
+            access_count: 4,
+            affected_gas_used: 0,
+            gas_weighted_density: density,
+            severity: "MEDIUM".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    #[test]
+    fn batches_match_schemas() {
+        let batch = contention_batch(&[event(1.5), event(2.5)]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), contention_schema());
+        assert_eq!(conflicts_batch(&[]).unwrap().num_rows(), 0);
+    }
+
+    #[test]
+    fn ipc_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("argus-arrow-{}", std::process::id()));
+        let mut sink = ArrowIpcSink::create(&dir).unwrap();
+        sink.write_contention_events(&[event(1.5)]).unwrap();
+        sink.write_contention_events(&[event(2.5), event(3.5)])
+            .unwrap();
+        assert_eq!(sink.finish().unwrap(), 3);
+
+        let file = File::open(dir.join(CONTENTION_FILE)).unwrap();
+        let rows: usize = FileReader::try_new(file, None)
+            .unwrap()
+            .map(|b| b.unwrap().num_rows())
+            .sum();
+        assert_eq!(rows, 3);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,254 @@
+//! DuckDB sink: append rows into a local `.duckdb` file.
+//!
+//! The tables (same names and columns as the StarRocks sink) are
+//! created on open if missing, so the file is immediately queryable with the
+//! `duckdb` CLI or any DuckDB client. Requires the `duckdb` feature flag.
+//!
+//! ```ignore
+//! let mut sink = DuckDbSink::open(Path::new("argus.duckdb"))?;
+//! sink.write_summary(&summary)?;
+//! sink.write_conflicts(&conflicts)?;
+//! sink.write_contention_events(&events)?;
+//! ```
+//!
+//! ```sql
+//! SELECT contract_protocol, sum(conflict_count) AS conflicts
+//! FROM contention_events GROUP BY 1 ORDER BY 2 DESC;
+//! ```
+
+use super::{BlockSummaryRow, ConflictRow, ContentionEvent, SlotValueRow};
+use duckdb::{params, Connection};
+use std::io;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS block_summary (
+    block_number      UBIGINT  NOT NULL,
+    total_txs         UINTEGER NOT NULL,
+    txs_with_storage  UINTEGER NOT NULL,
+    total_entries     UINTEGER NOT NULL,
+    total_conflicts   UINTEGER NOT NULL,
+    hotspot_count     UINTEGER NOT NULL,
+    state_reads       UINTEGER NOT NULL,
+    state_misses      UINTEGER NOT NULL,
+    prefetch_fetched  UINTEGER NOT NULL,
+    prefetch_failed   UINTEGER NOT NULL,
+    prefetch_retried  UINTEGER NOT NULL,
+    prefetch_time_ms  UBIGINT  NOT NULL,
+    fetch_time_ms     UBIGINT  NOT NULL,
+    total_time_ms     UBIGINT  NOT NULL,
+    created_at        VARCHAR  NOT NULL
+);
+CREATE TABLE IF NOT EXISTS conflicts (
+    block_number       UBIGINT NOT NULL,
+    tx_a               VARCHAR NOT NULL,
+    tx_b               VARCHAR NOT NULL,
+    contract_address   VARCHAR NOT NULL,
+    contract_protocol  VARCHAR NOT NULL,
+    contract_name      VARCHAR NOT NULL,
+    slot               VARCHAR NOT NULL,
+    conflict_kind      VARCHAR NOT NULL,
+    created_at         VARCHAR NOT NULL
+);
+CREATE TABLE IF NOT EXISTS contention_events (
+    block_number       UBIGINT  NOT NULL,
+    contract_address   VARCHAR  NOT NULL,
+    contract_protocol  VARCHAR  NOT NULL,
+    contract_name      VARCHAR  NOT NULL,
+    slot_id            VARCHAR  NOT NULL,
+    hazard_type        VARCHAR  NOT NULL,
+    affected_tx_count  UINTEGER NOT NULL,
+    conflict_count     UINTEGER NOT NULL,
+    conflict_density   DOUBLE   NOT NULL,
+    access_count       UINTEGER NOT NULL,
+    affected_gas_used  UBIGINT  NOT NULL,
+    gas_weighted_density DOUBLE NOT NULL,
+    severity           VARCHAR  NOT NULL,
+    created_at         VARCHAR  NOT NULL
+);
+CREATE TABLE IF NOT EXISTS slot_values (
+    block_number       UBIGINT NOT NULL,
+    contract_address   VARCHAR NOT NULL,
+    slot_id            VARCHAR NOT NULL,
+    value              VARCHAR NOT NULL,
+    created_at         VARCHAR NOT NULL
+);
+";
+
+fn to_io(e: duckdb::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Appends sink rows to a DuckDB database.
+pub struct DuckDbSink {
+    conn: Connection,
+    rows_written: usize,
+}
+
+impl DuckDbSink {
+    /// Open (or create) the database at `path` and ensure the tables exist.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::with_connection(Connection::open(path).map_err(to_io)?)
+    }
+
+    /// Use an existing connection, e.g. `Connection::open_in_memory()`.
+    pub fn with_connection(conn: Connection) -> io::Result<Self> {
+        conn.execute_batch(SCHEMA).map_err(to_io)?;
+        Ok(Self {
+            conn,
+            rows_written: 0,
+        })
+    }
+
+    pub fn write_summary(&mut self, row: &BlockSummaryRow) -> io::Result<()> {
+        let mut app = self.conn.appender("block_summary").map_err(to_io)?;
+        app.append_row(params![
+            row.block_number,
+            row.total_txs,
+            row.txs_with_storage,
+            row.total_entries,
+            row.total_conflicts,
+            row.hotspot_count,
+            row.state_reads,
+            row.state_misses,
+            row.prefetch_fetched,
+            row.prefetch_failed,
+            row.prefetch_retried,
+            row.prefetch_time_ms,
+            row.fetch_time_ms,
+            row.total_time_ms,
+            row.created_at,
+        ])
+        .map_err(to_io)?;
+        app.flush().map_err(to_io)?;
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    pub fn write_conflicts(&mut self, rows: &[ConflictRow]) -> io::Result<()> {
+        let mut app = self.conn.appender("conflicts").map_err(to_io)?;
+        for r in rows {
+            app.append_row(params![
+                r.block_number,
+                r.tx_a,
+                r.tx_b,
+                r.contract_address,
+                r.contract_protocol,
+                r.contract_name,
+                r.slot,
+                r.conflict_kind,
+                r.created_at,
+            ])
+            .map_err(to_io)?;
+        }
+        app.flush().map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    pub fn write_contention_events(&mut self, rows: &[ContentionEvent]) -> io::Result<()> {
+        let mut app = self.conn.appender("contention_events").map_err(to_io)?;
+        for r in rows {
+            app.append_row(params![
+                r.block_number,
+                r.contract_address,
+                r.contract_protocol,
+                r.contract_name,
+                r.slot_id,
+                r.hazard_type,
+                r.affected_tx_count,
+                r.conflict_count,
+                r.conflict_density,
+                r.access_count,
+                r.affected_gas_used,
+                r.gas_weighted_density,
+                r.severity,
+                r.created_at,
+            ])
+            .map_err(to_io)?;
+        }
+        app.flush().map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    pub fn write_slot_values(&mut self, rows: &[SlotValueRow]) -> io::Result<()> {
+        let mut app = self.conn.appender("slot_values").map_err(to_io)?;
+        for r in rows {
+            app.append_row(params![
+                r.block_number,
+                r.contract_address,
+                r.slot_id,
+                r.value,
+                r.created_at,
+            ])
+            .map_err(to_io)?;
+        }
+        app.flush().map_err(to_io)?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    /// The underlying connection, for ad-hoc queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(block_number: u64) -> ConflictRow {
+        ConflictRow {
+            block_number,
+            tx_a: "0xa".into(),
+            tx_b: "0xb".into(),
+            contract_address: "0x01".into(),
+            contract_protocol: "Uniswap V2".into(),
+            contract_name: "WETH/USDC".into(),
+            slot: "0x08".into(),
+            conflict_kind: "W-W".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    #[test]
+    fn appends_are_queryable() {
+        let mut sink = DuckDbSink::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        sink.write_conflicts(&[conflict(1), conflict(1), conflict(2)])
+            .unwrap();
+        assert_eq!(sink.rows_written(), 3);
+
+        let n: i64 = sink
+            .connection()
+            .query_row(
+                "SELECT count(*) FROM conflicts WHERE block_number = 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(n, 2);
+    }
+
+    #[test]
+    fn reopening_keeps_tables() {
+        let path = std::env::temp_dir().join(format!("argus-{}.duckdb", std::process::id()));
+        DuckDbSink::open(&path)
+            .unwrap()
+            .write_conflicts(&[conflict(1)])
+            .unwrap();
+        let sink = DuckDbSink::open(&path).unwrap();
+        let n: i64 = sink
+            .connection()
+            .query_row("SELECT count(*) FROM conflicts", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(n, 1);
+        drop(sink);
+        std::fs::remove_file(&path).ok();
+    }
+}
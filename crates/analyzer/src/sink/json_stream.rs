@@ -71,6 +71,66 @@ impl<W: Write> JsonStreamSink<W> {
         Ok(())
     }
 
+    /// Write scored pending transactions.
+    pub fn write_pending_scores(&mut self, rows: &[super::PendingScoreRow]) -> io::Result<()> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.writer.write_all(b"\n")?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Write rolling-window aggregate snapshots.
+    pub fn write_aggregates(&mut self, rows: &[super::AggregateRow]) -> io::Result<()> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.writer.write_all(b"\n")?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Write per-transaction storage access rows.
+    pub fn write_access_rows(&mut self, rows: &[super::AccessRow]) -> io::Result<()> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.writer.write_all(b"\n")?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Write per-transaction event rows.
+    pub fn write_log_rows(&mut self, rows: &[super::LogRow]) -> io::Result<()> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.writer.write_all(b"\n")?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Write contested-slot pre-state values.
+    pub fn write_slot_values(&mut self, rows: &[super::SlotValueRow]) -> io::Result<()> {
+        for row in rows {
+            serde_json::to_writer(&mut self.writer, row)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.writer.write_all(b"\n")?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered rows without closing the sink (long-running streams).
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
     /// Flush and return how many rows were written.
     pub fn finish(mut self) -> io::Result<usize> {
         self.writer.flush()?;
@@ -99,6 +159,12 @@ mod tests {
             total_entries: 304,
             total_conflicts: 70,
             hotspot_count: 3,
+            state_reads: 812,
+            state_misses: 4,
+            prefetch_fetched: 640,
+            prefetch_failed: 2,
+            prefetch_retried: 5,
+            prefetch_time_ms: 2100,
             fetch_time_ms: 340,
             total_time_ms: 42000,
             created_at: "2026-02-28T00:00:00Z".into(),
@@ -0,0 +1,148 @@
+//! NDJSON rows emitted while a block is still simulating.
+//!
+//! [`LiveNdjsonSink`] is a [`SimulationObserver`]: as each transaction
+//! finishes it writes that transaction's [`AccessRow`]s and [`LogRow`]s plus
+//! any [`ConflictRow`]s it forms with transactions already seen, then
+//! flushes.
+//! Only the location index is retained, so memory stays bounded by the
+//! block's distinct storage locations rather than its rows.
+//!
+//! ```ignore
+//! let live = Arc::new(LiveNdjsonSink::new(std::io::stdout()));
+//! let argus = ArgusBuilder::new().rpc_url(url).simulation_observer(live.clone()).build()?;
+//! ```
+
+use super::json_stream::JsonStreamSink;
+use super::{chrono_now, AccessRow, ConflictRow, ContractLabels, LogRow};
+use crate::graph::IncrementalGraph;
+use crate::plugin::SimulationObserver;
+use argus_core::chain::{self, ChainConfig};
+use argus_core::AccessList;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+struct State<W: Write> {
+    sink: JsonStreamSink<W>,
+    graph: IncrementalGraph,
+    labels: ContractLabels<'static>,
+    block_number: u64,
+    /// First write error; later rows are dropped.
+    error: Option<io::Error>,
+}
+
+/// Streams access, event and conflict rows as NDJSON during simulation.
+pub struct LiveNdjsonSink<W: Write + Send> {
+    state: Mutex<State<W>>,
+}
+
+impl<W: Write + Send> LiveNdjsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: Mutex::new(State {
+                sink: JsonStreamSink::new(writer),
+                graph: IncrementalGraph::new(),
+                labels: ContractLabels::new(&chain::ETHEREUM),
+                block_number: 0,
+                error: None,
+            }),
+        }
+    }
+
+    /// Rows written so far, or the first write error.
+    pub fn rows_written(&self) -> io::Result<usize> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match &state.error {
+            Some(e) => Err(io::Error::new(e.kind(), e.to_string())),
+            None => Ok(state.sink.rows_written()),
+        }
+    }
+}
+
+impl<W: Write + Send> SimulationObserver for LiveNdjsonSink<W> {
+    fn begin_block(&self, chain: &'static ChainConfig, block_number: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.graph.clear();
+        state.labels = ContractLabels::new(chain);
+        state.block_number = block_number;
+    }
+
+    fn on_tx(&self, index: usize, access_list: &AccessList) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.error.is_some() {
+            return;
+        }
+        let now = chrono_now();
+        let block_number = state.block_number;
+        let accesses = AccessRow::from_access_list(block_number, access_list, &now);
+        let logs = LogRow::from_access_list(block_number, access_list, &now);
+        let state = &mut *state;
+        let conflicts: Vec<ConflictRow> = state
+            .graph
+            .add(index, access_list)
+            .iter()
+            .map(|c| ConflictRow::with_labels(block_number, &mut state.labels, c, now.clone()))
+            .collect();
+
+        let result = state
+            .sink
+            .write_access_rows(&accesses)
+            .and_then(|_| state.sink.write_log_rows(&logs))
+            .and_then(|_| state.sink.write_conflicts(&conflicts))
+            .and_then(|_| state.sink.flush());
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "live sink write failed; dropping further rows");
+            state.error = Some(e);
+        }
+    }
+
+    fn end_block(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.graph.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use argus_core::{AccessEntry, AccessMode, StorageLocation};
+
+    fn write(n: u8) -> AccessList {
+        AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: [AccessEntry {
+                location: StorageLocation {
+                    address: Address::ZERO,
+                    slot: B256::ZERO,
+                },
+                mode: AccessMode::Write,
+                count: 1,
+            }]
+            .into_iter()
+            .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn streams_accesses_and_new_conflicts() {
+        let mut buf = Vec::new();
+        let live = LiveNdjsonSink::new(&mut buf);
+        live.begin_block(&chain::ETHEREUM, 9);
+        live.on_tx(1, &write(2));
+        live.on_tx(0, &write(1));
+        live.end_block();
+        assert_eq!(live.rows_written().unwrap(), 3);
+        drop(live);
+
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].contains("\"conflict_kind\":\"W-W\""));
+        assert!(lines[2].contains(&format!("\"tx_a\":\"{}\"", B256::with_last_byte(1))));
+    }
+}
@@ -1,15 +1,31 @@
 //! High-performance data sink for enriched conflict reports.
 //!
-//! Three row schemas:
+//! Row schemas:
 //! - [`BlockSummaryRow`] — one per analyzed block
 //! - [`ConflictRow`] — one per conflict edge (denormalized)
 //! - [`ContentionEvent`] — one per contract×slot×hazard (aggregated, with density)
+//! - [`PendingScoreRow`] — one per scored mempool transaction
+//! - [`AggregateRow`] — one per rolling-window snapshot
+//! - [`AccessRow`] — one per storage access, streamed during simulation
+//! - [`LogRow`] — one per emitted event, for joining protocol activity
+//!   (Swap, Transfer, ...) to contention
+//! - [`SlotValueRow`] — pre-state value of each contested slot (opt-in)
 //!
-//! Two backends:
+//! Backends:
 //! - **NDJSON stream** — write newline-delimited JSON rows to any `Write` impl
 //! - **StarRocks Stream Load** — HTTP PUT directly to StarRocks FE (feature-gated)
+//! - **Arrow IPC** — columnar files for dataframe consumers (feature-gated)
+//! - **DuckDB** — append into a local `.duckdb` file (feature-gated)
+//! - **Server-Sent Events** — push rows to live HTTP clients (feature-gated)
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "duckdb")]
+pub mod duckdb;
 pub mod json_stream;
+pub mod live;
+#[cfg(feature = "sse")]
+pub mod sse;
 #[cfg(feature = "starrocks")]
 pub mod starrocks;
 
@@ -33,6 +49,160 @@ pub struct ConflictRow {
     pub created_at: String,
 }
 
+impl ConflictRow {
+    pub fn from_conflict(
+        block_number: u64,
+        chain: &argus_core::chain::ChainConfig,
+        c: &argus_core::Conflict,
+        created_at: String,
+    ) -> Self {
+        Self::with_labels(block_number, &mut ContractLabels::new(chain), c, created_at)
+    }
+
+    /// Like [`from_conflict`](Self::from_conflict), reusing cached contract strings.
+    pub(crate) fn with_labels(
+        block_number: u64,
+        labels: &mut ContractLabels<'_>,
+        c: &argus_core::Conflict,
+        created_at: String,
+    ) -> Self {
+        let contract = labels.get(&c.location.address);
+        ConflictRow {
+            block_number,
+            tx_a: format!("{}", c.tx_a),
+            tx_b: format!("{}", c.tx_b),
+            contract_address: contract.address.clone(),
+            contract_protocol: contract.protocol.clone(),
+            contract_name: contract.name.clone(),
+            slot: format!("{}", c.location.slot),
+            conflict_kind: match c.kind {
+                argus_core::ConflictKind::WriteWrite => "W-W".into(),
+                argus_core::ConflictKind::ReadWrite => "R-W".into(),
+            },
+            created_at,
+        }
+    }
+}
+
+/// Formatted address and label strings for one contract.
+pub(crate) struct ContractStrings {
+    pub address: String,
+    pub protocol: String,
+    pub name: String,
+}
+
+/// Per-contract string cache for row building. A block's edges repeat a
+/// small set of contracts, so each is formatted and labeled once.
+pub(crate) struct ContractLabels<'a> {
+    chain: &'a argus_core::chain::ChainConfig,
+    cache: FastHashMap<alloy_primitives::Address, ContractStrings>,
+}
+
+impl<'a> ContractLabels<'a> {
+    pub fn new(chain: &'a argus_core::chain::ChainConfig) -> Self {
+        Self {
+            chain,
+            cache: FastHashMap::default(),
+        }
+    }
+
+    pub fn get(&mut self, addr: &alloy_primitives::Address) -> &ContractStrings {
+        let chain = self.chain;
+        self.cache.entry(*addr).or_insert_with(|| {
+            let (protocol, name) = crate::reporter::group_label(chain, addr);
+            ContractStrings {
+                address: format!("{}", addr),
+                protocol,
+                name,
+            }
+        })
+    }
+}
+
+/// One row per storage access of one transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessRow {
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub contract_address: String,
+    pub slot: String,
+    /// "R" or "W".
+    pub mode: String,
+    pub created_at: String,
+}
+
+impl AccessRow {
+    /// All rows for one transaction's access list.
+    pub fn from_access_list(
+        block_number: u64,
+        access_list: &argus_core::AccessList,
+        created_at: &str,
+    ) -> Vec<Self> {
+        let tx_hash = format!("{}", access_list.tx_hash);
+        access_list
+            .entries
+            .iter()
+            .map(|e| AccessRow {
+                block_number,
+                tx_hash: tx_hash.clone(),
+                contract_address: format!("{}", e.location.address),
+                slot: format!("{}", e.location.slot),
+                mode: match e.mode {
+                    argus_core::AccessMode::Read => "R".into(),
+                    argus_core::AccessMode::Write => "W".into(),
+                },
+                created_at: created_at.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// One row per event a transaction emitted. `topic0` is the event
+/// signature hash, so e.g. every ERC-20 `Transfer` shares one value.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRow {
+    pub block_number: u64,
+    pub tx_hash: String,
+    /// Position among the transaction's events.
+    pub log_index: u32,
+    pub contract_address: String,
+    pub topic0: Option<String>,
+    pub topic1: Option<String>,
+    pub topic2: Option<String>,
+    pub topic3: Option<String>,
+    pub created_at: String,
+}
+
+impl LogRow {
+    /// All rows for the events in one transaction's access list.
+    pub fn from_access_list(
+        block_number: u64,
+        access_list: &argus_core::AccessList,
+        created_at: &str,
+    ) -> Vec<Self> {
+        let tx_hash = format!("{}", access_list.tx_hash);
+        access_list
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(i, log)| {
+                let topic = |n: usize| log.topics.get(n).map(|t| format!("{t}"));
+                LogRow {
+                    block_number,
+                    tx_hash: tx_hash.clone(),
+                    log_index: i as u32,
+                    contract_address: format!("{}", log.address),
+                    topic0: topic(0),
+                    topic1: topic(1),
+                    topic2: topic(2),
+                    topic3: topic(3),
+                    created_at: created_at.to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
 /// One row per analyzed block — summary statistics.
 #[derive(Debug, Clone, Serialize)]
 pub struct BlockSummaryRow {
@@ -42,6 +212,17 @@ pub struct BlockSummaryRow {
     pub total_entries: u32,
     pub total_conflicts: u32,
     pub hotspot_count: u32,
+    /// Distinct accounts and slots simulation read; 0 without warm state.
+    pub state_reads: u32,
+    /// Of `state_reads`, those missing from the warm state (read as empty).
+    pub state_misses: u32,
+    /// Accounts and slots the prefetch read from the node; 0 without one.
+    pub prefetch_fetched: u32,
+    /// Prefetch reads that failed after retries.
+    pub prefetch_failed: u32,
+    /// Requests the prefetch retried.
+    pub prefetch_retried: u32,
+    pub prefetch_time_ms: u64,
     pub fetch_time_ms: u64,
     pub total_time_ms: u64,
     pub created_at: String,
@@ -67,19 +248,85 @@ pub struct ContentionEvent {
     pub conflict_count: u32,
     /// conflict_count / affected_tx_count — the "enemy score".
     pub conflict_density: f64,
+    /// SLOAD/SSTOREs of the slot by the affected transactions; above
+    /// `affected_tx_count` when they hit it repeatedly, e.g. reentrant pool
+    /// updates. Breaks density ties in the ranking.
+    pub access_count: u32,
+    /// Total gas used by the affected transactions: simulated, or mined
+    /// with [`Report::with_receipts`]; 0 when neither is known.
+    pub affected_gas_used: u64,
+    /// `conflict_density` scaled by the affected txs' mean gas over the
+    /// block's mean gas, so a hot slot shared by heavy swaps outranks one
+    /// shared by transfers. Equals `conflict_density` without gas figures.
+    pub gas_weighted_density: f64,
     /// Severity: LOW (<1.0), MEDIUM (1.0–3.0), HIGH (3.0–5.0), CRITICAL (>5.0)
     pub severity: String,
     pub created_at: String,
 }
 
+/// Pre-state value of one contested slot, read from the warm cache: one row
+/// per (contract, slot) per block. Joins to [`ContentionEvent`] on
+/// `(block_number, contract_address, slot_id)`, so a warehouse can track
+/// e.g. pool reserves next to the contention they cause.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotValueRow {
+    pub block_number: u64,
+    pub contract_address: String,
+    pub slot_id: String,
+    /// 32-byte hex word.
+    pub value: String,
+    pub created_at: String,
+}
+
+/// One row per scored pending transaction (mempool predictor output).
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingScoreRow {
+    pub tx_hash: String,
+    /// Distinct pending txs this tx is predicted to conflict with.
+    pub pending_conflicts: u32,
+    /// Contract of the location contributing the most pending conflicts.
+    pub top_contract_address: Option<String>,
+    pub top_contract_protocol: Option<String>,
+    pub top_contract_name: Option<String>,
+    pub top_slot: Option<String>,
+    /// Pending conflicts on the top location alone.
+    pub top_slot_conflicts: u32,
+    /// Touched locations that were hotspots in the latest analyzed block.
+    pub hotspot_hits: u32,
+    pub max_hotspot_density: f64,
+    pub created_at: String,
+}
+
+/// Rolling-window rollup over the most recent analyzed blocks.
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregateRow {
+    pub window_start: u64,
+    pub window_end: u64,
+    pub blocks: u32,
+    pub total_txs: u64,
+    pub total_conflicts: u64,
+    pub density_p50: f64,
+    pub density_p90: f64,
+    pub density_p99: f64,
+    /// Mean fraction of conflict-free txs per block.
+    pub mean_parallelism_score: f64,
+    /// Per-block slope of the parallelism score; negative = degrading.
+    pub parallelism_trend: f64,
+    pub protocol_shares: Vec<ProtocolShare>,
+    pub created_at: String,
+}
+
+/// Fraction of windowed conflict edges attributed to one protocol.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolShare {
+    pub protocol: String,
+    pub share: f64,
+}
+
 impl ContentionEvent {
-    fn severity_label(density: f64) -> &'static str {
-        match density {
-            d if d >= 5.0 => "CRITICAL",
-            d if d >= 3.0 => "HIGH",
-            d if d >= 1.0 => "MEDIUM",
-            _ => "LOW",
-        }
+    /// `conflict_count / affected_tx_count` before rounding.
+    pub fn raw_density(&self) -> f64 {
+        self.conflict_count as f64 / self.affected_tx_count.max(1) as f64
     }
 }
 
@@ -88,12 +335,12 @@ impl ContentionEvent {
 // ---------------------------------------------------------------------------
 
 use crate::reporter::Report;
-use std::collections::{HashMap, HashSet};
+use argus_core::{FastHashMap, FastHashSet};
 
 impl Report {
     /// Flatten the report into sink-ready rows.
     pub fn to_rows(&self) -> (BlockSummaryRow, Vec<ConflictRow>) {
-        let now = chrono_now();
+        let now = self.row_timestamp();
 
         let summary = BlockSummaryRow {
             block_number: self.block_number,
@@ -102,6 +349,12 @@ impl Report {
             total_entries: self.total_entries as u32,
             total_conflicts: self.total_conflicts as u32,
             hotspot_count: self.groups.len() as u32,
+            state_reads: self.coverage.as_ref().map_or(0, |c| c.reads() as u32),
+            state_misses: self.coverage.as_ref().map_or(0, |c| c.misses() as u32),
+            prefetch_fetched: self.prefetch.map_or(0, |p| p.fetched as u32),
+            prefetch_failed: self.prefetch.map_or(0, |p| p.failed as u32),
+            prefetch_retried: self.prefetch.map_or(0, |p| p.retried as u32),
+            prefetch_time_ms: self.prefetch.map_or(0, |p| p.elapsed.as_millis() as u64),
             fetch_time_ms: self.fetch_time.as_millis() as u64,
             total_time_ms: self.total_time.as_millis() as u64,
             created_at: now.clone(),
@@ -117,7 +370,7 @@ impl Report {
         &self,
         graph: &argus_core::ConflictGraph,
     ) -> (BlockSummaryRow, Vec<ConflictRow>) {
-        let now = chrono_now();
+        let now = self.row_timestamp();
 
         let summary = BlockSummaryRow {
             block_number: self.block_number,
@@ -126,35 +379,22 @@ impl Report {
             total_entries: self.total_entries as u32,
             total_conflicts: self.total_conflicts as u32,
             hotspot_count: self.groups.len() as u32,
+            state_reads: self.coverage.as_ref().map_or(0, |c| c.reads() as u32),
+            state_misses: self.coverage.as_ref().map_or(0, |c| c.misses() as u32),
+            prefetch_fetched: self.prefetch.map_or(0, |p| p.fetched as u32),
+            prefetch_failed: self.prefetch.map_or(0, |p| p.failed as u32),
+            prefetch_retried: self.prefetch.map_or(0, |p| p.retried as u32),
+            prefetch_time_ms: self.prefetch.map_or(0, |p| p.elapsed.as_millis() as u64),
             fetch_time_ms: self.fetch_time.as_millis() as u64,
             total_time_ms: self.total_time.as_millis() as u64,
             created_at: now.clone(),
         };
 
+        let mut labels = ContractLabels::new(self.chain);
         let conflicts: Vec<ConflictRow> = graph
             .conflicts
             .iter()
-            .map(|c| {
-                let (protocol, name) = match argus_provider::labels::lookup(&c.location.address) {
-                    Some(l) => (l.protocol.to_string(), l.name.to_string()),
-                    None => ("Unknown".into(), format!("{}", c.location.address)),
-                };
-
-                ConflictRow {
-                    block_number: self.block_number,
-                    tx_a: format!("{}", c.tx_a),
-                    tx_b: format!("{}", c.tx_b),
-                    contract_address: format!("{}", c.location.address),
-                    contract_protocol: protocol,
-                    contract_name: name,
-                    slot: format!("{}", c.location.slot),
-                    conflict_kind: match c.kind {
-                        argus_core::ConflictKind::WriteWrite => "W-W".into(),
-                        argus_core::ConflictKind::ReadWrite => "R-W".into(),
-                    },
-                    created_at: now.clone(),
-                }
-            })
+            .map(|c| ConflictRow::with_labels(self.block_number, &mut labels, c, now.clone()))
             .collect();
 
         (summary, conflicts)
@@ -165,72 +405,130 @@ impl Report {
     /// Key metric: `conflict_density` = conflicts / affected_txs.
     /// Sorted by density descending — worst offenders first.
     pub fn to_contention_events(&self, graph: &argus_core::ConflictGraph) -> Vec<ContentionEvent> {
-        let now = chrono_now();
+        let now = self.row_timestamp();
 
         // Group: (address, slot, kind) → { tx_hashes, conflict_count }
         #[derive(Default)]
         struct Bucket {
-            tx_hashes: HashSet<alloy_primitives::B256>,
+            tx_hashes: FastHashSet<alloy_primitives::B256>,
             count: u32,
         }
 
-        type Key = (alloy_primitives::Address, alloy_primitives::B256, String);
-        let mut buckets: HashMap<Key, Bucket> = HashMap::new();
-
-        for c in &graph.conflicts {
-            let hazard = match c.kind {
-                argus_core::ConflictKind::WriteWrite => "WAW",
-                argus_core::ConflictKind::ReadWrite => "RAW",
-            };
+        // Aggregated in parallel shards for large graphs.
+        let buckets = crate::par::group_by(
+            &graph.conflicts,
+            |c| {
+                let hazard = match c.kind {
+                    argus_core::ConflictKind::WriteWrite => "WAW",
+                    argus_core::ConflictKind::ReadWrite => "RAW",
+                };
+                (c.location.address, c.location.slot, hazard)
+            },
+            |bucket: &mut Bucket, c| {
+                bucket.tx_hashes.insert(c.tx_a);
+                bucket.tx_hashes.insert(c.tx_b);
+                bucket.count += 1;
+            },
+            |a, b| {
+                a.tx_hashes.extend(b.tx_hashes);
+                a.count += b.count;
+            },
+        );
 
-            let key = (c.location.address, c.location.slot, hazard.to_string());
-            let bucket = buckets.entry(key).or_default();
-            bucket.tx_hashes.insert(c.tx_a);
-            bucket.tx_hashes.insert(c.tx_b);
-            bucket.count += 1;
-        }
+        let block_mean_gas = if self.gas_used.is_empty() {
+            0.0
+        } else {
+            self.gas_used.values().sum::<u64>() as f64 / self.gas_used.len() as f64
+        };
 
+        let mut labels = ContractLabels::new(self.chain);
         let mut events: Vec<ContentionEvent> = buckets
             .into_iter()
             .map(|((addr, slot, hazard), bucket)| {
                 let affected = bucket.tx_hashes.len() as u32;
                 let density = bucket.count as f64 / affected as f64;
 
-                let (protocol, name) = match argus_provider::labels::lookup(&addr) {
-                    Some(l) => (l.protocol.to_string(), l.name.to_string()),
-                    None => ("Unknown".into(), format!("{}", addr)),
+                let gas = self.gas_of(&bucket.tx_hashes);
+                let location = argus_core::StorageLocation {
+                    address: addr,
+                    slot,
                 };
+                let access_count = self.touches_of(&location, &bucket.tx_hashes);
+                let weight = if block_mean_gas > 0.0 {
+                    gas as f64 / affected as f64 / block_mean_gas
+                } else {
+                    1.0
+                };
+
+                let contract = labels.get(&addr);
 
-                ContentionEvent {
+                let mut event = ContentionEvent {
                     block_number: self.block_number,
-                    contract_address: format!("{}", addr),
-                    contract_protocol: protocol,
-                    contract_name: name,
+                    contract_address: contract.address.clone(),
+                    contract_protocol: contract.protocol.clone(),
+                    contract_name: contract.name.clone(),
                     slot_id: format!("{}", slot),
-                    hazard_type: hazard,
+                    hazard_type: hazard.to_string(),
                     affected_tx_count: affected,
                     conflict_count: bucket.count,
                     conflict_density: (density * 100.0).round() / 100.0, // 2 decimal
-                    severity: ContentionEvent::severity_label(density).into(),
+                    access_count,
+                    affected_gas_used: gas,
+                    gas_weighted_density: (density * weight * 100.0).round() / 100.0,
+                    severity: String::new(),
                     created_at: now.clone(),
-                }
+                };
+                event.severity = self.severity.score(&event);
+                event
             })
             .collect();
 
-        // Sort by density descending — worst offenders first.
-        events.sort_by(|a, b| b.conflict_density.partial_cmp(&a.conflict_density).unwrap());
+        // Sort by density descending — worst offenders first; then the most
+        // hammered, then key order.
+        events.sort_by(|a, b| {
+            b.conflict_density
+                .total_cmp(&a.conflict_density)
+                .then_with(|| b.access_count.cmp(&a.access_count))
+                .then_with(|| a.contract_address.cmp(&b.contract_address))
+                .then_with(|| a.slot_id.cmp(&b.slot_id))
+                .then_with(|| a.hazard_type.cmp(&b.hazard_type))
+        });
 
         events
     }
+
+    /// Build slot-value rows from `(location, pre-state value)` pairs, e.g.
+    /// the contested slots found in the warm cache.
+    pub fn to_slot_values(
+        &self,
+        values: &[(argus_core::StorageLocation, alloy_primitives::B256)],
+    ) -> Vec<SlotValueRow> {
+        let now = self.row_timestamp();
+        values
+            .iter()
+            .map(|(location, value)| SlotValueRow {
+                block_number: self.block_number,
+                contract_address: format!("{}", location.address),
+                slot_id: format!("{}", location.slot),
+                value: format!("{}", value),
+                created_at: now.clone(),
+            })
+            .collect()
+    }
 }
 
 /// ISO-8601 timestamp without chrono dependency.
-fn chrono_now() -> String {
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn chrono_now() -> String {
     use std::time::SystemTime;
     let d = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap();
-    let secs = d.as_secs();
+    iso8601(d.as_secs())
+}
+
+/// Format Unix seconds the way [`chrono_now`] does.
+pub(crate) fn iso8601(secs: u64) -> String {
     format!(
         "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
         1970 + secs / 31_536_000,
@@ -241,3 +539,312 @@ fn chrono_now() -> String {
         secs % 60,
     )
 }
+
+/// `SystemTime::now()` panics on `wasm32-unknown-unknown`; the host is
+/// expected to stamp rows itself.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn chrono_now() -> String {
+    "1970-01-01T00:00:00Z".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use argus_core::chain;
+
+    #[test]
+    fn deterministic_rows_ignore_build_order() {
+        use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation};
+        // Same accesses, listed in a different order within each tx.
+        let list = |n: u8, rev: bool| AccessList {
+            tx_hash: alloy_primitives::B256::with_last_byte(n),
+            entries: (0..3u8)
+                .map(|i| if rev { 2 - i } else { i })
+                .map(|slot| AccessEntry {
+                    location: StorageLocation {
+                        address: alloy_primitives::Address::with_last_byte(slot),
+                        slot: alloy_primitives::B256::with_last_byte(slot),
+                    },
+                    mode: AccessMode::Write,
+                    count: 1,
+                })
+                .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+        let render = |lists: Vec<AccessList>| {
+            let mut graph = crate::graph::build_conflict_graph(&lists);
+            graph.canonicalize();
+            let mut report = Report::build(
+                7,
+                &lists,
+                &graph,
+                std::time::Duration::from_millis(3),
+                std::time::Duration::from_millis(9),
+            )
+            .with_timestamp(Some(86_400));
+            report.make_deterministic();
+            let (summary, conflicts) = report.to_rows_from_graph(&graph);
+            let events = report.to_contention_events(&graph);
+            assert_eq!(summary.created_at, "1970-01-02T00:00:00Z");
+            assert_eq!(summary.total_time_ms, 0);
+            serde_json::to_string(&(summary, conflicts, events, &graph)).unwrap()
+        };
+
+        assert_eq!(
+            render((1..4).map(|n| list(n, false)).collect()),
+            render((1..4).map(|n| list(n, true)).collect())
+        );
+    }
+
+    #[test]
+    fn simulation_failures_are_counted() {
+        use argus_core::{AccessList, ExecutionOutcome};
+        let list = |n: u8, outcome| AccessList {
+            tx_hash: alloy_primitives::B256::with_last_byte(n),
+            entries: Default::default(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome,
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+        let lists = [
+            list(1, ExecutionOutcome::Success),
+            list(2, ExecutionOutcome::Revert { reason: None }),
+            list(
+                3,
+                ExecutionOutcome::Halt {
+                    reason: "OutOfGas".into(),
+                },
+            ),
+        ];
+        let graph = argus_core::ConflictGraph::new();
+        let report = Report::build(1, &lists, &graph, Default::default(), Default::default());
+        assert_eq!((report.sim_reverted, report.sim_failed), (1, 1));
+        assert!(report.render(&graph).contains("Sim reverted:"));
+    }
+
+    #[test]
+    fn log_rows_spread_topics_over_columns() {
+        use alloy_primitives::{Address, B256};
+        use argus_core::{AccessList, TxLog};
+        let list = AccessList {
+            tx_hash: B256::with_last_byte(1),
+            entries: Default::default(),
+            transient: Default::default(),
+            logs: vec![
+                TxLog {
+                    address: Address::with_last_byte(9),
+                    topics: vec![B256::with_last_byte(0xaa), B256::with_last_byte(0xbb)],
+                },
+                TxLog {
+                    address: Address::with_last_byte(9),
+                    topics: Vec::new(),
+                },
+            ],
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+
+        let rows = LogRow::from_access_list(5, &list, "now");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0].topic0,
+            Some(format!("{}", B256::with_last_byte(0xaa)))
+        );
+        assert_eq!(
+            rows[0].topic1,
+            Some(format!("{}", B256::with_last_byte(0xbb)))
+        );
+        assert_eq!(rows[0].topic2, None);
+        assert_eq!(rows[1].log_index, 1);
+        assert_eq!(rows[1].topic0, None);
+    }
+
+    #[test]
+    fn contract_labels_are_cached_per_address() {
+        let router = address!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D");
+        let mut labels = ContractLabels::new(&chain::ETHEREUM);
+        let first = labels.get(&router).address.as_ptr();
+        assert_eq!(labels.get(&router).address.as_ptr(), first);
+        assert_ne!(labels.get(&router).protocol, "Unknown");
+        assert_eq!(labels.cache.len(), 1);
+
+        let unknown = labels.get(&alloy_primitives::Address::ZERO);
+        assert_eq!(unknown.protocol, "Unknown");
+        assert_eq!(unknown.name, unknown.address);
+    }
+
+    #[test]
+    fn slot_values_cover_each_contested_location_once() {
+        use alloy_primitives::{Address, B256};
+        use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation};
+        let location = StorageLocation {
+            address: Address::with_last_byte(1),
+            slot: B256::with_last_byte(8),
+        };
+        let lists: Vec<AccessList> = (1..4u8)
+            .map(|n| AccessList {
+                tx_hash: B256::with_last_byte(n),
+                entries: [AccessEntry {
+                    location: location.clone(),
+                    mode: AccessMode::Write,
+                    count: 1,
+                }]
+                .into_iter()
+                .collect(),
+                transient: Default::default(),
+                logs: Vec::new(),
+                outcome: Default::default(),
+                gas_used: 0,
+                writes: Vec::new(),
+            })
+            .collect();
+        let graph = crate::graph::build_conflict_graph(&lists);
+        assert!(!graph.is_empty());
+
+        let contested = crate::graph::contested_locations(&graph);
+        assert_eq!(contested, vec![location.clone()]);
+
+        let zero = std::time::Duration::ZERO;
+        let report = Report::build(5, &lists, &graph, zero, zero);
+        let rows = report.to_slot_values(&[(location, B256::with_last_byte(42))]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].block_number, 5);
+        assert_eq!(rows[0].value, B256::with_last_byte(42).to_string());
+    }
+
+    #[test]
+    fn contention_events_sum_receipt_gas() {
+        use alloy_primitives::{Address, B256};
+        use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation, TxReceipt};
+        let location = StorageLocation {
+            address: Address::with_last_byte(1),
+            slot: B256::with_last_byte(8),
+        };
+        let lists: Vec<AccessList> = (1..4u8)
+            .map(|n| AccessList {
+                tx_hash: B256::with_last_byte(n),
+                entries: [AccessEntry {
+                    location: location.clone(),
+                    mode: AccessMode::Write,
+                    count: 1,
+                }]
+                .into_iter()
+                .collect(),
+                transient: Default::default(),
+                logs: Vec::new(),
+                outcome: Default::default(),
+                gas_used: 0,
+                writes: Vec::new(),
+            })
+            .collect();
+        let graph = crate::graph::build_conflict_graph(&lists);
+        let zero = std::time::Duration::ZERO;
+        let report = Report::build(5, &lists, &graph, zero, zero);
+        assert_eq!(report.to_contention_events(&graph)[0].affected_gas_used, 0);
+
+        let receipts: Vec<TxReceipt> = (1..4u8)
+            .map(|n| TxReceipt {
+                tx_hash: B256::with_last_byte(n),
+                gas_used: 1_000 * n as u64,
+                success: n != 2,
+            })
+            .collect();
+        let report = report.with_receipts(&receipts);
+        assert_eq!(report.failed_txs, 1);
+        let events = report.to_contention_events(&graph);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].affected_gas_used, 6_000);
+        assert!(report.render(&graph).contains("Gas: 6000"));
+    }
+
+    #[test]
+    fn repeated_touches_raise_access_count() {
+        use alloy_primitives::{Address, B256};
+        use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation};
+        let location = StorageLocation {
+            address: Address::with_last_byte(1),
+            slot: B256::with_last_byte(8),
+        };
+        let list = |n: u8, count: u32| AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: [AccessEntry {
+                location: location.clone(),
+                mode: AccessMode::Write,
+                count,
+            }]
+            .into_iter()
+            .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        };
+        let lists = [list(1, 1), list(2, 4)];
+        let graph = crate::graph::build_conflict_graph(&lists);
+        let zero = std::time::Duration::ZERO;
+        let report = Report::build(5, &lists, &graph, zero, zero);
+        let events = report.to_contention_events(&graph);
+        assert_eq!(events[0].affected_tx_count, 2);
+        assert_eq!(events[0].access_count, 5);
+        assert!(report.render(&graph).contains("Touches: 5"));
+    }
+
+    #[test]
+    fn simulated_gas_weights_density() {
+        use alloy_primitives::{Address, B256};
+        use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation};
+        // Txs 1-2 (3000 gas each) share slot 1; txs 3-4 (1000 each) slot 2.
+        let list = |n: u8, slot: u8, gas_used: u64| AccessList {
+            tx_hash: B256::with_last_byte(n),
+            entries: [AccessEntry {
+                location: StorageLocation {
+                    address: Address::with_last_byte(1),
+                    slot: B256::with_last_byte(slot),
+                },
+                mode: AccessMode::Write,
+                count: 1,
+            }]
+            .into_iter()
+            .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used,
+            writes: Vec::new(),
+        };
+        let lists = [
+            list(1, 1, 3_000),
+            list(2, 1, 3_000),
+            list(3, 2, 1_000),
+            list(4, 2, 1_000),
+        ];
+        let graph = crate::graph::build_conflict_graph(&lists);
+        let zero = std::time::Duration::ZERO;
+        let report = Report::build(5, &lists, &graph, zero, zero);
+        assert!(!report.has_receipts);
+
+        let events = report.to_contention_events(&graph);
+        assert_eq!(events.len(), 2);
+        let heavy = events
+            .iter()
+            .find(|e| e.affected_gas_used == 6_000)
+            .unwrap();
+        let light = events
+            .iter()
+            .find(|e| e.affected_gas_used == 2_000)
+            .unwrap();
+        assert_eq!(heavy.conflict_density, light.conflict_density);
+        assert_eq!(heavy.gas_weighted_density, 0.75);
+        assert_eq!(light.gas_weighted_density, 0.25);
+        assert!(!report.render(&graph).contains("Reverted:"));
+    }
+}
@@ -0,0 +1,228 @@
+//! Server-Sent Events push of per-block rows.
+//!
+//! A minimal HTTP/1.1 endpoint: every client that connects (any path) gets a
+//! `text/event-stream` response and receives one `summary` event plus one
+//! `contention` event per [`ContentionEvent`] as each block is published,
+//! and an `aggregate` event per rolling-window [`AggregateRow`] published.
+//! Slow clients that fall behind the buffer skip the missed events rather
+//! than stall the publisher. Requires the `sse` feature flag.
+//!
+//! ```ignore
+//! let sse = SseBroadcaster::bind("127.0.0.1:8547".parse()?).await?;
+//! sse.publish_block(&summary, &events);
+//! ```
+//!
+//! ```js
+//! const es = new EventSource("http://127.0.0.1:8547/events");
+//! es.addEventListener("contention", (e) => console.log(JSON.parse(e.data)));
+//! ```
+
+use super::{AggregateRow, BlockSummaryRow, ContentionEvent};
+use serde::Serialize;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Events buffered per client before it starts skipping.
+const CLIENT_BUFFER: usize = 4096;
+
+const RESPONSE_HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+Content-Type: text/event-stream\r\n\
+Cache-Control: no-cache\r\n\
+Connection: keep-alive\r\n\
+Access-Control-Allow-Origin: *\r\n\r\n";
+
+/// Fans pre-rendered SSE frames out to every connected client. Dropping it
+/// closes the port and ends every client's stream.
+pub struct SseBroadcaster {
+    tx: broadcast::Sender<Arc<str>>,
+    local_addr: SocketAddr,
+    accept: JoinHandle<()>,
+}
+
+impl SseBroadcaster {
+    /// Listen on `addr` and accept clients in a background task.
+    pub async fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let (tx, _) = broadcast::channel(CLIENT_BUFFER);
+        let accept_tx = tx.clone();
+        let accept = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        tracing::debug!(%peer, "sse client connected");
+                        tokio::spawn(serve_client(stream, accept_tx.subscribe()));
+                    }
+                    Err(e) => tracing::warn!(error = %e, "sse accept failed"),
+                }
+            }
+        });
+        Ok(Self {
+            tx,
+            local_addr,
+            accept,
+        })
+    }
+
+    /// Address actually bound (useful with port 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Connected clients.
+    pub fn subscribers(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Push one block's summary and contention events to all clients.
+    /// Returns the number of clients reached.
+    pub fn publish_block(&self, summary: &BlockSummaryRow, events: &[ContentionEvent]) -> usize {
+        let reached = self.send("summary", summary);
+        for e in events {
+            self.send("contention", e);
+        }
+        reached
+    }
+
+    /// Push a rolling-window snapshot (see
+    /// [`RollingAggregator`](crate::aggregate::RollingAggregator)) to all
+    /// clients. Returns the number of clients reached.
+    pub fn publish_aggregate(&self, row: &AggregateRow) -> usize {
+        self.send("aggregate", row)
+    }
+
+    fn send<T: Serialize>(&self, event: &str, row: &T) -> usize {
+        match serde_json::to_string(row) {
+            Ok(json) => self
+                .tx
+                .send(format!("event: {event}\ndata: {json}\n\n").into())
+                .unwrap_or(0),
+            Err(e) => {
+                tracing::warn!(error = %e, "sse: failed to serialize {event}");
+                0
+            }
+        }
+    }
+}
+
+impl Drop for SseBroadcaster {
+    fn drop(&mut self) {
+        // Drops the listener; clients see the channel close once `tx` goes.
+        self.accept.abort();
+    }
+}
+
+async fn serve_client(mut stream: TcpStream, mut rx: broadcast::Receiver<Arc<str>>) {
+    // Consume the request head; the path and headers are not inspected.
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => head.extend_from_slice(&buf[..n]),
+        }
+        if head.len() > 16 * 1024 {
+            return;
+        }
+    }
+    if stream.write_all(RESPONSE_HEAD).await.is_err() {
+        return;
+    }
+
+    loop {
+        let frame = match rx.recv().await {
+            Ok(frame) => frame,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::debug!(skipped, "sse client lagged");
+                format!(": skipped {skipped} events\n\n").into()
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if stream.write_all(frame.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> BlockSummaryRow {
+        BlockSummaryRow {
+            block_number: 42,
+            total_txs: 3,
+            txs_with_storage: 3,
+            total_entries: 9,
+            total_conflicts: 1,
+            hotspot_count: 1,
+            state_reads: 0,
+            state_misses: 0,
+            prefetch_fetched: 0,
+            prefetch_failed: 0,
+            prefetch_retried: 0,
+            prefetch_time_ms: 0,
+            fetch_time_ms: 5,
+            total_time_ms: 20,
+            created_at: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_receives_published_block() {
+        let sse = SseBroadcaster::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TcpStream::connect(sse.local_addr()).await.unwrap();
+        client
+            .write_all(b"GET /events HTTP/1.1\r\nHost: x\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut received = String::new();
+        let mut buf = [0u8; 4096];
+        while !received.contains("\r\n\r\n") {
+            let n = client.read(&mut buf).await.unwrap();
+            received.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+        }
+        assert!(received.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(sse.subscribers(), 1);
+
+        assert_eq!(sse.publish_block(&summary(), &[]), 1);
+        while !received.ends_with("\n\n") || !received.contains("event: summary") {
+            let n = client.read(&mut buf).await.unwrap();
+            received.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+        }
+        assert!(received.contains("data: {\"block_number\":42"));
+    }
+
+    #[tokio::test]
+    async fn publish_without_clients_is_a_no_op() {
+        let sse = SseBroadcaster::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(sse.publish_block(&summary(), &[]), 0);
+    }
+
+    #[tokio::test]
+    async fn drop_closes_the_port() {
+        let sse = SseBroadcaster::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = sse.local_addr();
+        drop(sse);
+        // The accept task goes away at its next poll.
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while TcpStream::connect(addr).await.is_ok() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("port still open");
+    }
+}
@@ -21,6 +21,12 @@
 //!     total_entries INT           NOT NULL,
 //!     total_conflicts INT         NOT NULL,
 //!     hotspot_count INT           NOT NULL,
+//!     state_reads   INT           NOT NULL COMMENT '0 without warm state',
+//!     state_misses  INT           NOT NULL,
+//!     prefetch_fetched INT        NOT NULL COMMENT '0 without prefetch',
+//!     prefetch_failed  INT        NOT NULL,
+//!     prefetch_retried INT        NOT NULL,
+//!     prefetch_time_ms BIGINT     NOT NULL,
 //!     fetch_time_ms BIGINT        NOT NULL,
 //!     total_time_ms BIGINT        NOT NULL,
 //!     created_at    VARCHAR(32)   NOT NULL
@@ -54,12 +60,41 @@
 //!     affected_tx_count  INT          NOT NULL,
 //!     conflict_count     INT          NOT NULL,
 //!     conflict_density   FLOAT        NOT NULL COMMENT 'conflicts / txs — enemy score',
+//!     access_count       INT          NOT NULL COMMENT 'touches by the affected txs',
+//!     affected_gas_used  BIGINT       NOT NULL COMMENT 'simulated, or mined with receipts',
+//!     gas_weighted_density FLOAT      NOT NULL COMMENT 'density x affected / block mean gas',
 //!     severity           VARCHAR(10)  NOT NULL COMMENT 'LOW / MEDIUM / HIGH / CRITICAL',
 //!     created_at         VARCHAR(32)  NOT NULL
 //! ) ENGINE = OLAP
 //! DUPLICATE KEY (block_number, contract_address)
 //! DISTRIBUTED BY HASH(contract_address) BUCKETS 4
 //! PROPERTIES ("replication_num" = "1");
+//!
+//! CREATE TABLE argus.slot_values (
+//!     block_number       BIGINT       NOT NULL,
+//!     contract_address   VARCHAR(42)  NOT NULL,
+//!     slot_id            VARCHAR(66)  NOT NULL,
+//!     value              VARCHAR(66)  NOT NULL,
+//!     created_at         VARCHAR(32)  NOT NULL
+//! ) ENGINE = OLAP
+//! DUPLICATE KEY (block_number, contract_address)
+//! DISTRIBUTED BY HASH(contract_address) BUCKETS 4
+//! PROPERTIES ("replication_num" = "1");
+//!
+//! CREATE TABLE argus.tx_logs (
+//!     block_number       BIGINT       NOT NULL,
+//!     tx_hash            VARCHAR(66)  NOT NULL,
+//!     log_index          INT          NOT NULL,
+//!     contract_address   VARCHAR(42)  NOT NULL,
+//!     topic0             VARCHAR(66)  NULL COMMENT 'event signature hash',
+//!     topic1             VARCHAR(66)  NULL,
+//!     topic2             VARCHAR(66)  NULL,
+//!     topic3             VARCHAR(66)  NULL,
+//!     created_at         VARCHAR(32)  NOT NULL
+//! ) ENGINE = OLAP
+//! DUPLICATE KEY (block_number, tx_hash)
+//! DISTRIBUTED BY HASH(contract_address) BUCKETS 4
+//! PROPERTIES ("replication_num" = "1");
 //! ```
 
 use super::{BlockSummaryRow, ConflictRow};
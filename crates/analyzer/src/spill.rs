@@ -0,0 +1,299 @@
+//! Conflict-graph construction under a memory budget.
+//!
+//! The in-memory builder indexes every storage access of the batch at once.
+//! When that index would exceed the budget, accesses are instead partitioned
+//! by location hash into temporary files, and each partition is indexed and
+//! turned into edges on its own. Peak index memory is then roughly one
+//! partition; the resulting edges are the same as the in-memory build's.
+//!
+//! The budget covers the index, not the access lists (the caller's input)
+//! or the edges. [`build_conflict_graph_within`] still collects every edge
+//! into one [`ConflictGraph`]; [`for_each_conflict_within`] hands them off
+//! as each partition produces them, so a caller writing edges out (to a
+//! sink, say) never holds the edge set either.
+//!
+//! ```ignore
+//! let budget = MemoryBudget::new(256 << 20).with_spill_dir("/var/tmp");
+//! let graph = build_conflict_graph_within(&access_lists, &budget)?;
+//! for_each_conflict_within(&access_lists, &budget, |c| sink.write(&c))?;
+//! ```
+
+use crate::graph::{destruction_conflicts, for_each_conflict, location_conflicts, WriteFilter};
+use alloy_primitives::{Address, B256};
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{AccessList, AccessMode, Conflict, ConflictGraph, FastHashMap, StorageLocation};
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Approximate in-memory index cost of one access (key, accessor, table overhead).
+pub const INDEX_BYTES_PER_ENTRY: usize = 96;
+
+/// Most partitions, however small the budget; past this each partition is
+/// already tiny and opening more only costs passes.
+pub const MAX_PARTITIONS: usize = 4096;
+
+/// Partition files open at once. More partitions are written in rounds,
+/// one pass over the access lists each, to stay clear of open-file limits.
+const MAX_OPEN_PARTITIONS: usize = 64;
+
+/// address [20] | slot [32] | tx index u32 | mode u8
+const RECORD_LEN: usize = 20 + 32 + 4 + 1;
+
+/// Memory allowed for intermediate graph-building state.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    pub bytes: usize,
+    /// Where partition files go (default: the system temp dir).
+    pub spill_dir: PathBuf,
+}
+
+impl MemoryBudget {
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            spill_dir: std::env::temp_dir(),
+        }
+    }
+
+    pub fn with_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = dir.into();
+        self
+    }
+
+    /// Partitions needed so each fits the budget (1 = no spilling), at
+    /// most [`MAX_PARTITIONS`].
+    pub fn partitions_for(&self, access_lists: &[AccessList]) -> usize {
+        let entries: usize = access_lists.iter().map(|al| al.entries.len()).sum();
+        entries
+            .saturating_mul(INDEX_BYTES_PER_ENTRY)
+            .div_ceil(self.bytes.max(1))
+            .clamp(1, MAX_PARTITIONS)
+    }
+}
+
+/// Like [`build_conflict_graph`](crate::graph::build_conflict_graph),
+/// spilling to disk if the location index would exceed `budget`.
+pub fn build_conflict_graph_within(
+    access_lists: &[AccessList],
+    budget: &MemoryBudget,
+) -> ArgusResult<ConflictGraph> {
+    let mut graph = ConflictGraph::new();
+    for_each_conflict_within(access_lists, budget, |conflict| {
+        graph.add_conflict(conflict)
+    })?;
+    Ok(graph)
+}
+
+/// Like [`for_each_conflict`], spilling to disk if the location index would
+/// exceed `budget`. Edges go to `emit` as each partition yields them.
+pub fn for_each_conflict_within(
+    access_lists: &[AccessList],
+    budget: &MemoryBudget,
+    mut emit: impl FnMut(Conflict),
+) -> ArgusResult<()> {
+    let partitions = budget.partitions_for(access_lists);
+    if partitions == 1 {
+        for_each_conflict(access_lists, emit);
+        return Ok(());
+    }
+    if access_lists.len() > u32::MAX as usize {
+        return Err(ArgusError::InvalidInput(
+            "too many transactions to spill".into(),
+        ));
+    }
+    tracing::info!(
+        partitions,
+        dir = %budget.spill_dir.display(),
+        "location index exceeds memory budget; spilling"
+    );
+
+    let dir = SpillDir::create(&budget.spill_dir).map_err(spill_err)?;
+    partition(access_lists, &dir.0, partitions).map_err(spill_err)?;
+
+    for p in 0..partitions {
+        let path = partition_path(&dir.0, p);
+        merge_partition(&path, access_lists, &mut emit).map_err(spill_err)?;
+        std::fs::remove_file(&path).ok();
+    }
+    // Only destroyed accounts are indexed here, so this stays in memory.
+    destruction_conflicts(access_lists, &mut emit);
+    Ok(())
+}
+
+fn spill_err(e: std::io::Error) -> ArgusError {
+    ArgusError::Internal(format!("graph spill failed: {e}"))
+}
+
+fn partition_path(dir: &Path, p: usize) -> PathBuf {
+    dir.join(format!("part-{p:04}.bin"))
+}
+
+/// Pass 1: route every access to a written location to that location's
+/// partition file, in block order, so accessor lists come back ordered.
+/// Writes [`MAX_OPEN_PARTITIONS`] files per pass over the access lists.
+fn partition(access_lists: &[AccessList], dir: &Path, partitions: usize) -> std::io::Result<()> {
+    let hasher = rustc_hash::FxBuildHasher;
+    let writes = WriteFilter::new(access_lists);
+
+    let mut record = [0u8; RECORD_LEN];
+    for first in (0..partitions).step_by(MAX_OPEN_PARTITIONS) {
+        let round = first..partitions.min(first + MAX_OPEN_PARTITIONS);
+        let mut files = round
+            .clone()
+            .map(|p| File::create(partition_path(dir, p)).map(BufWriter::new))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        for (i, al) in access_lists.iter().enumerate() {
+            for entry in al
+                .entries
+                .iter()
+                .filter(|e| writes.may_conflict(&e.location))
+            {
+                let p = (hasher.hash_one(&entry.location) % partitions as u64) as usize;
+                if !round.contains(&p) {
+                    continue;
+                }
+                record[..20].copy_from_slice(entry.location.address.as_slice());
+                record[20..52].copy_from_slice(entry.location.slot.as_slice());
+                record[52..56].copy_from_slice(&(i as u32).to_be_bytes());
+                record[56] = entry.mode as u8;
+                files[p - first].write_all(&record)?;
+            }
+        }
+        for f in &mut files {
+            f.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Pass 2: index one partition and emit its edges.
+fn merge_partition(
+    path: &Path,
+    access_lists: &[AccessList],
+    emit: &mut impl FnMut(Conflict),
+) -> std::io::Result<()> {
+    let mut r = BufReader::new(File::open(path)?);
+    let mut index: FastHashMap<StorageLocation, Vec<(B256, AccessMode)>> = FastHashMap::default();
+    let mut record = [0u8; RECORD_LEN];
+    loop {
+        match r.read_exact(&mut record) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let location = StorageLocation {
+            address: Address::from_slice(&record[..20]),
+            slot: B256::from_slice(&record[20..52]),
+        };
+        let tx = u32::from_be_bytes(record[52..56].try_into().expect("4 bytes")) as usize;
+        let mode = if record[56] == AccessMode::Write as u8 {
+            AccessMode::Write
+        } else {
+            AccessMode::Read
+        };
+        index
+            .entry(location)
+            .or_default()
+            .push((access_lists[tx].tx_hash, mode));
+    }
+    for (location, accessors) in &index {
+        location_conflicts(location, accessors, emit);
+    }
+    Ok(())
+}
+
+/// Unique scratch directory, removed on drop.
+struct SpillDir(PathBuf);
+
+impl SpillDir {
+    fn create(parent: &Path) -> std::io::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = parent.join(format!("argus-spill-{}-{nanos}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::build_conflict_graph;
+    use argus_core::AccessEntry;
+
+    fn lists(n: u8) -> Vec<AccessList> {
+        (0..n)
+            .map(|i| AccessList {
+                tx_hash: B256::with_last_byte(i),
+                entries: (0..8u8)
+                    .map(|slot| AccessEntry {
+                        location: StorageLocation {
+                            address: Address::with_last_byte(slot % 3),
+                            slot: B256::with_last_byte(slot),
+                        },
+                        mode: if (i + slot) % 3 == 0 {
+                            AccessMode::Write
+                        } else {
+                            AccessMode::Read
+                        },
+                        count: 1,
+                    })
+                    .collect(),
+                transient: Default::default(),
+                logs: Vec::new(),
+                outcome: Default::default(),
+                gas_used: 0,
+                writes: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn edges(g: &ConflictGraph) -> Vec<(B256, B256, B256, u8)> {
+        let mut e: Vec<_> = g
+            .conflicts
+            .iter()
+            .map(|c| (c.tx_a, c.tx_b, c.location.slot, c.kind as u8))
+            .collect();
+        e.sort();
+        e
+    }
+
+    #[test]
+    fn spilled_build_matches_in_memory() {
+        let lists = lists(20);
+        let budget = MemoryBudget::new(2 * 1024);
+        assert!(budget.partitions_for(&lists) > 1);
+
+        let spilled = build_conflict_graph_within(&lists, &budget).unwrap();
+        let in_memory = build_conflict_graph(&lists);
+        assert!(!in_memory.is_empty());
+        assert_eq!(edges(&spilled), edges(&in_memory));
+    }
+
+    #[test]
+    fn small_batches_stay_in_memory() {
+        assert_eq!(MemoryBudget::new(1 << 20).partitions_for(&lists(4)), 1);
+    }
+
+    #[test]
+    fn partitions_past_the_open_file_cap_are_written_in_rounds() {
+        let lists = lists(20);
+        assert_eq!(MemoryBudget::new(1).partitions_for(&lists), MAX_PARTITIONS);
+        let budget = MemoryBudget::new(80);
+        assert!(budget.partitions_for(&lists) > MAX_OPEN_PARTITIONS);
+
+        let mut streamed = ConflictGraph::new();
+        for_each_conflict_within(&lists, &budget, |c| streamed.add_conflict(c)).unwrap();
+        assert_eq!(edges(&streamed), edges(&build_conflict_graph(&lists)));
+    }
+}
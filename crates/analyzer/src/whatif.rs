@@ -0,0 +1,232 @@
+//! What-if analysis of hypothetical transactions.
+//!
+//! A [`HypotheticalTx`] is an unsigned call (sender, target, value and
+//! calldata) appended to a fetched block. Once the block is simulated with
+//! it, [`WhatIfReport`] lists the block's own transactions each injected
+//! one would conflict with, which is the contention a searcher or protocol
+//! developer would face by submitting it.
+//!
+//! ```ignore
+//! let injected = HypotheticalTx::new(me, Some(router)).with_input(calldata);
+//! let (result, what_if) = argus.analyze_what_if(21_000_000, &[injected]).await?;
+//! for tx in &what_if.injected {
+//!     println!("{} conflicts with {} txs", tx.tx_hash, tx.conflicting_txs.len());
+//! }
+//! ```
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use argus_core::{Conflict, ConflictGraph, FastHashMap, FastHashSet, Transaction};
+use serde::{Deserialize, Serialize};
+
+/// Gas limit of a hypothetical transaction that doesn't set one.
+pub const DEFAULT_WHAT_IF_GAS: u64 = 30_000_000;
+
+/// An unsigned transaction to try against a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HypotheticalTx {
+    pub from: Address,
+    /// `None` deploys `input` as init code.
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub value: U256,
+    #[serde(default, alias = "data")]
+    pub input: Bytes,
+    /// Defaults to [`DEFAULT_WHAT_IF_GAS`].
+    #[serde(default)]
+    pub gas: Option<u64>,
+}
+
+impl HypotheticalTx {
+    pub fn new(from: Address, to: Option<Address>) -> Self {
+        Self {
+            from,
+            to,
+            value: U256::ZERO,
+            input: Bytes::new(),
+            gas: None,
+        }
+    }
+
+    pub fn with_value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn with_input(mut self, input: impl Into<Bytes>) -> Self {
+        self.input = input.into();
+        self
+    }
+
+    pub fn with_gas(mut self, gas: u64) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    /// The transaction to simulate. Its hash is synthetic: derived from the
+    /// fields and `index`, so identical injections stay distinct and never
+    /// collide with a real transaction.
+    pub fn to_transaction(&self, index: usize) -> Transaction {
+        let mut preimage = b"argus-what-if".to_vec();
+        preimage.extend_from_slice(&(index as u64).to_be_bytes());
+        preimage.extend_from_slice(self.from.as_slice());
+        preimage.extend_from_slice(self.to.unwrap_or_default().as_slice());
+        preimage.extend_from_slice(&self.value.to_be_bytes::<32>());
+        preimage.extend_from_slice(&self.input);
+        Transaction {
+            hash: keccak256(&preimage),
+            from: self.from,
+            to: self.to,
+            input: self.input.clone(),
+            value: self.value,
+            gas: self.gas.unwrap_or(DEFAULT_WHAT_IF_GAS),
+            ..Default::default()
+        }
+    }
+}
+
+/// What one injected transaction would contend with.
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectedConflicts {
+    pub tx_hash: B256,
+    /// Block transactions it conflicts with, in block order.
+    pub conflicting_txs: Vec<B256>,
+    /// Its edges to those transactions.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Conflicts between injected transactions and the block they were added to.
+#[derive(Debug, Clone, Serialize)]
+pub struct WhatIfReport {
+    pub block_number: u64,
+    /// One per injected transaction, in injection order.
+    pub injected: Vec<InjectedConflicts>,
+}
+
+impl WhatIfReport {
+    /// Split `graph`'s edges by injected transaction. `transactions` is the
+    /// simulated block including the injected ones, whose hashes are
+    /// `injected`; edges between two injected transactions are left out.
+    pub fn build(
+        block_number: u64,
+        graph: &ConflictGraph,
+        transactions: &[Transaction],
+        injected: &[B256],
+    ) -> Self {
+        let position: FastHashMap<B256, usize> = transactions
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| (tx.hash, i))
+            .collect();
+        let is_injected: FastHashSet<B256> = injected.iter().copied().collect();
+
+        let injected = injected
+            .iter()
+            .map(|&hash| {
+                let conflicts: Vec<Conflict> = graph
+                    .conflicts
+                    .iter()
+                    .filter(|c| {
+                        let other = if c.tx_a == hash {
+                            c.tx_b
+                        } else if c.tx_b == hash {
+                            c.tx_a
+                        } else {
+                            return false;
+                        };
+                        !is_injected.contains(&other)
+                    })
+                    .cloned()
+                    .collect();
+                let mut conflicting_txs: Vec<B256> = conflicts
+                    .iter()
+                    .map(|c| if c.tx_a == hash { c.tx_b } else { c.tx_a })
+                    .collect();
+                conflicting_txs.sort_by_key(|h| position.get(h).copied().unwrap_or(usize::MAX));
+                conflicting_txs.dedup();
+                InjectedConflicts {
+                    tx_hash: hash,
+                    conflicting_txs,
+                    conflicts,
+                }
+            })
+            .collect();
+
+        Self {
+            block_number,
+            injected,
+        }
+    }
+
+    /// Injected transactions that would conflict with nothing in the block.
+    pub fn conflict_free(&self) -> usize {
+        self.injected
+            .iter()
+            .filter(|tx| tx.conflicting_txs.is_empty())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argus_core::{ConflictKind, StorageLocation};
+
+    fn conflict(a: B256, b: B256) -> Conflict {
+        Conflict {
+            tx_a: a,
+            tx_b: b,
+            location: StorageLocation {
+                address: Address::with_last_byte(1),
+                slot: B256::ZERO,
+            },
+            kind: ConflictKind::WriteWrite,
+        }
+    }
+
+    #[test]
+    fn synthetic_hashes_are_distinct() {
+        let tx = HypotheticalTx::new(Address::with_last_byte(1), None);
+        assert_ne!(tx.to_transaction(0).hash, tx.to_transaction(1).hash);
+        assert_eq!(tx.to_transaction(0).gas, DEFAULT_WHAT_IF_GAS);
+
+        let parsed: HypotheticalTx = serde_json::from_str(
+            r#"{"from": "0x0000000000000000000000000000000000000001",
+                "to": "0x0000000000000000000000000000000000000002", "data": "0x1234"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.input, Bytes::from_static(&[0x12, 0x34]));
+        assert_eq!(parsed.value, U256::ZERO);
+    }
+
+    #[test]
+    fn lists_block_txs_each_injection_conflicts_with() {
+        let block: Vec<Transaction> = (1..=3)
+            .map(|n| {
+                HypotheticalTx::new(Address::with_last_byte(n), None).to_transaction(n as usize)
+            })
+            .collect();
+        let (a, b, c) = (block[0].hash, block[1].hash, block[2].hash);
+        let mine = HypotheticalTx::new(Address::with_last_byte(9), None);
+        let (x, y) = (mine.to_transaction(10).hash, mine.to_transaction(11).hash);
+        let mut all = block.clone();
+        all.push(mine.to_transaction(10));
+        all.push(mine.to_transaction(11));
+
+        let graph = ConflictGraph {
+            conflicts: vec![
+                conflict(c, x),
+                conflict(a, x),
+                conflict(a, b),
+                // Between two injected txs: not reported.
+                conflict(x, y),
+            ],
+            adjacency: Default::default(),
+        };
+        let r = WhatIfReport::build(7, &graph, &all, &[x, y]);
+        assert_eq!(r.injected[0].conflicting_txs, [a, c]);
+        assert_eq!(r.injected[0].conflicts.len(), 2);
+        assert!(r.injected[1].conflicting_txs.is_empty());
+        assert_eq!(r.conflict_free(), 1);
+    }
+}
@@ -15,7 +15,7 @@ async fn analyze_real_block() {
         .expect("Failed to connect");
     use argus_provider::DataProvider;
     let txs = provider
-        .get_block_transactions(block_number)
+        .get_block_transactions(block_number.into())
         .await
         .expect("Failed to fetch block");
 
@@ -27,14 +27,17 @@ async fn analyze_real_block() {
     );
 
     let prefetcher = argus_provider::Prefetcher::new(provider.into_provider());
-    let warm_db = prefetcher
+    let (warm_db, stats) = prefetcher
         .prefetch(block_number, &txs)
         .await
         .expect("Prefetch failed");
 
     eprintln!(
-        "[e2e] Prefetched state for {} accounts",
-        warm_db.cache.accounts.len()
+        "[e2e] Prefetched state for {} accounts ({} reads, {} failed) in {:?}",
+        warm_db.cache.accounts.len(),
+        stats.fetched,
+        stats.failed,
+        stats.elapsed
     );
 
     let access_lists = argus_analyzer::simulator::simulate_batch_with_state(&warm_db, &txs)
@@ -67,7 +70,7 @@ async fn fetch_block_smoke() {
     use argus_provider::DataProvider;
 
     let txs = provider
-        .get_block_transactions(18_000_000)
+        .get_block_transactions(argus_provider::BlockId::number(18_000_000))
         .await
         .expect("Failed to fetch block");
 
@@ -0,0 +1,399 @@
+//! End-to-end replay benchmarks over a recorded corpus.
+//!
+//! A corpus is a directory of `<block>.json` [`BlockFixture`]s, each with an
+//! optional `<block>.snap` state snapshot. Fixtures are written by
+//! [`RecordStage`] (`argus analyze --record DIR`) and replayed network-free
+//! by [`run_corpus`], so timings from different builds are comparable.
+//!
+//! ```ignore
+//! let report = argus::bench::run_corpus(Path::new("fixtures"), 3).await?;
+//! print!("{}", report.render());
+//! ```
+
+use crate::pipeline::{
+    GraphStage, LoadStateStage, Pipeline, PipelineContext, PipelineStage, ReportStage,
+    SimulateStage,
+};
+use argus_core::chain;
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{BlockContext, Transaction};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Transactions and block metadata needed to replay one block offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockFixture {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    /// Absent in fixtures recorded before headers were kept.
+    #[serde(default)]
+    pub block_context: Option<BlockContext>,
+    pub transactions: Vec<Transaction>,
+}
+
+impl BlockFixture {
+    /// Capture the fetched block from a pipeline context.
+    pub fn from_context(ctx: &PipelineContext) -> Self {
+        Self {
+            chain_id: ctx.chain().chain_id,
+            block_number: ctx.block_number,
+            timestamp: ctx.timestamp,
+            block_context: ctx.block_context.clone(),
+            transactions: ctx.transactions.to_vec(),
+        }
+    }
+
+    pub fn load(path: &Path) -> ArgusResult<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            ArgusError::InvalidInput(format!("cannot read fixture {}: {e}", path.display()))
+        })?;
+        serde_json::from_str(&text).map_err(|e| {
+            ArgusError::InvalidInput(format!("invalid fixture {}: {e}", path.display()))
+        })
+    }
+
+    /// Write `<dir>/<block>.json`, returning its path.
+    pub fn save(&self, dir: &Path) -> ArgusResult<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| ArgusError::Internal(format!("create {}: {e}", dir.display())))?;
+        let path = fixture_path(dir, self.block_number);
+        let json = serde_json::to_vec(self)
+            .map_err(|e| ArgusError::Internal(format!("encode fixture: {e}")))?;
+        std::fs::write(&path, json)
+            .map_err(|e| ArgusError::Internal(format!("write {}: {e}", path.display())))?;
+        Ok(path)
+    }
+}
+
+fn fixture_path(dir: &Path, block_number: u64) -> PathBuf {
+    dir.join(format!("{block_number}.json"))
+}
+
+fn snapshot_path(dir: &Path, block_number: u64) -> PathBuf {
+    dir.join(format!("{block_number}.snap"))
+}
+
+/// Fixture files in `dir`, sorted by path.
+pub fn fixture_paths(dir: &Path) -> ArgusResult<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| ArgusError::InvalidInput(format!("cannot read {}: {e}", dir.display())))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// ---------------------------------------------------------------------------
+// Stages
+// ---------------------------------------------------------------------------
+
+/// Serves a recorded block in place of [`FetchStage`](crate::pipeline::FetchStage).
+///
+/// Named `"fetch"` so replays time the same stage names as live runs.
+pub struct FixtureStage {
+    chain_id: u64,
+    timestamp: Option<u64>,
+    block_context: Option<BlockContext>,
+    transactions: Arc<[Transaction]>,
+}
+
+impl FixtureStage {
+    pub fn new(fixture: BlockFixture) -> Self {
+        Self {
+            chain_id: fixture.chain_id,
+            timestamp: fixture.timestamp,
+            block_context: fixture.block_context,
+            transactions: fixture.transactions.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for FixtureStage {
+    fn name(&self) -> &str {
+        "fetch"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        if ctx.chain.is_none() {
+            ctx.chain = Some(chain::resolve(self.chain_id));
+        }
+        ctx.timestamp = self.timestamp;
+        ctx.block_context = self.block_context.clone();
+        ctx.transactions = Arc::clone(&self.transactions);
+        Ok(())
+    }
+}
+
+/// Records the fetched block, and the warm state if any, into a corpus dir.
+pub struct RecordStage {
+    dir: PathBuf,
+}
+
+impl RecordStage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for RecordStage {
+    fn name(&self) -> &str {
+        "record"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let path = BlockFixture::from_context(ctx).save(&self.dir)?;
+        if let Some(db) = &ctx.warm_db {
+            let snap = snapshot_path(&self.dir, ctx.block_number);
+            argus_provider::snapshot::save_snapshot(db, ctx.block_number, &snap)?;
+        }
+        tracing::info!(path = %path.display(), "recorded block fixture");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Replay
+// ---------------------------------------------------------------------------
+
+/// The replay pipeline for one fixture: fixture -> [snapshot] -> simulate
+/// -> graph -> report.
+pub fn replay_pipeline(dir: &Path, fixture: BlockFixture) -> Pipeline {
+    let snap = snapshot_path(dir, fixture.block_number);
+    let mut pipeline = Pipeline::new().stage(FixtureStage::new(fixture));
+    if snap.is_file() {
+        pipeline = pipeline.stage(LoadStateStage::new(snap));
+    }
+    pipeline
+        .stage(SimulateStage::new())
+        .stage(GraphStage::new())
+        .stage(ReportStage::new())
+}
+
+/// Replay every fixture in `dir` `iterations` times.
+pub async fn run_corpus(dir: &Path, iterations: usize) -> ArgusResult<BenchReport> {
+    let paths = fixture_paths(dir)?;
+    if paths.is_empty() {
+        return Err(ArgusError::InvalidInput(format!(
+            "no fixtures in {}",
+            dir.display()
+        )));
+    }
+
+    let mut samples = Samples::default();
+    let mut txs = 0;
+    for path in &paths {
+        let fixture = BlockFixture::load(path)?;
+        txs += fixture.transactions.len();
+        let pipeline = replay_pipeline(dir, fixture.clone());
+        for _ in 0..iterations.max(1) {
+            let ctx = pipeline.run(fixture.block_number).await?;
+            for t in &ctx.timings {
+                samples.push(&t.stage, t.elapsed);
+            }
+            samples.push("total", ctx.elapsed());
+        }
+    }
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        blocks: paths.len(),
+        iterations: iterations.max(1),
+        transactions: txs,
+        stages: samples.into_stats(),
+        peak_rss_bytes: peak_rss_bytes(),
+    })
+}
+
+/// Per-stage samples, in first-seen order.
+#[derive(Default)]
+struct Samples(Vec<(String, Vec<Duration>)>);
+
+impl Samples {
+    fn push(&mut self, stage: &str, elapsed: Duration) {
+        match self.0.iter_mut().find(|(s, _)| s == stage) {
+            Some((_, v)) => v.push(elapsed),
+            None => self.0.push((stage.to_string(), vec![elapsed])),
+        }
+    }
+
+    fn into_stats(self) -> Vec<StageStats> {
+        self.0
+            .into_iter()
+            .map(|(stage, v)| StageStats::from_samples(stage, v))
+            .collect()
+    }
+}
+
+/// Timing distribution of one stage across all replays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageStats {
+    pub stage: String,
+    pub runs: usize,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub max_ms: f64,
+}
+
+impl StageStats {
+    fn from_samples(stage: String, mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let total: Duration = samples.iter().sum();
+        let runs = samples.len();
+        Self {
+            stage,
+            runs,
+            total_ms: ms(total),
+            mean_ms: ms(total) / runs.max(1) as f64,
+            p50_ms: samples.get(runs / 2).copied().map_or(0.0, ms),
+            max_ms: samples.last().copied().map_or(0.0, ms),
+        }
+    }
+}
+
+/// Result of [`run_corpus`]. Serialize it to keep a baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// `argus` crate version that produced the numbers.
+    pub version: String,
+    pub blocks: usize,
+    pub iterations: usize,
+    pub transactions: usize,
+    /// Per-stage timings; the last entry is `"total"` wall time per block.
+    pub stages: Vec<StageStats>,
+    /// Process peak resident set size, where the OS reports it.
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl BenchReport {
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "argus {} — {} blocks × {} iterations, {} txs",
+            self.version, self.blocks, self.iterations, self.transactions
+        );
+        let _ = writeln!(
+            out,
+            "{:<12} {:>6} {:>10} {:>10} {:>10}",
+            "stage", "runs", "mean ms", "p50 ms", "max ms"
+        );
+        for s in &self.stages {
+            let _ = writeln!(
+                out,
+                "{:<12} {:>6} {:>10.2} {:>10.2} {:>10.2}",
+                s.stage, s.runs, s.mean_ms, s.p50_ms, s.max_ms
+            );
+        }
+        match self.peak_rss_bytes {
+            Some(b) => {
+                let _ = writeln!(out, "peak RSS: {:.1} MiB", b as f64 / (1024.0 * 1024.0));
+            }
+            None => out.push_str("peak RSS: unavailable\n"),
+        }
+        out
+    }
+
+    /// Mean-time change per stage relative to `baseline`, in percent.
+    /// Stages missing from either side are skipped.
+    pub fn diff(&self, baseline: &BenchReport) -> Vec<(String, f64)> {
+        self.stages
+            .iter()
+            .filter_map(|s| {
+                let base = baseline.stages.iter().find(|b| b.stage == s.stage)?;
+                (base.mean_ms > 0.0)
+                    .then(|| (s.stage.clone(), (s.mean_ms / base.mean_ms - 1.0) * 100.0))
+            })
+            .collect()
+    }
+}
+
+/// Peak RSS from `/proc/self/status` (`VmHWM`); `None` off Linux.
+fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(block_number: u64) -> BlockFixture {
+        BlockFixture {
+            chain_id: 1,
+            block_number,
+            timestamp: Some(1_700_000_000),
+            block_context: None,
+            transactions: vec![Transaction {
+                gas: 21_000,
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn fixture_round_trips() {
+        let dir = std::env::temp_dir().join(format!("argus-bench-rt-{}", std::process::id()));
+        let path = fixture(7).save(&dir).unwrap();
+        let back = BlockFixture::load(&path).unwrap();
+        assert_eq!(back.block_number, 7);
+        assert_eq!(back.transactions.len(), 1);
+        assert_eq!(fixture_paths(&dir).unwrap(), vec![path]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn replays_corpus_with_stage_timings() {
+        let dir = std::env::temp_dir().join(format!("argus-bench-run-{}", std::process::id()));
+        fixture(1).save(&dir).unwrap();
+        fixture(2).save(&dir).unwrap();
+
+        let report = run_corpus(&dir, 2).await.unwrap();
+        assert_eq!((report.blocks, report.iterations), (2, 2));
+        let names: Vec<_> = report.stages.iter().map(|s| s.stage.as_str()).collect();
+        assert_eq!(names, vec!["fetch", "simulate", "graph", "report", "total"]);
+        assert!(report.stages.iter().all(|s| s.runs == 4));
+        assert!(report.diff(&report).iter().all(|(_, pct)| pct.abs() < 1e-9));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn recorded_fixture_serves_offline_pipeline() {
+        use crate::pipeline::FetchStage;
+        use argus_provider::{DataProvider, FileProvider};
+
+        let dir = std::env::temp_dir().join(format!("argus-bench-file-{}", std::process::id()));
+        let path = fixture(5).save(&dir).unwrap();
+        let provider = Arc::new(FileProvider::load(&path).unwrap());
+        assert_eq!(provider.state_path(5), None);
+        let ctx = Pipeline::new()
+            .stage(FetchStage::new(provider.clone() as Arc<dyn DataProvider>))
+            .stage(SimulateStage::new())
+            .run(5)
+            .await
+            .unwrap();
+        assert_eq!(ctx.transactions.len(), 1);
+        assert_eq!(ctx.timestamp, Some(1_700_000_000));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn empty_corpus_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("argus-bench-empty-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(run_corpus(&dir, 1).await.is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,1345 @@
+//! One-call pipeline API for Argus.
+//!
+//! Wraps fetch -> prefetch -> simulate -> conflict graph -> report behind a
+//! single entry point so embedders depend on one crate:
+//!
+//! ```ignore
+//! let argus = argus::Argus::builder()
+//!     .rpc_url("https://eth.llamarpc.com")
+//!     .build()?;
+//! let result = argus.analyze_block(21_000_000).await?;
+//! print!("{}", result.report.render(&result.graph));
+//! ```
+//!
+//! [`Argus::analyze`] also accepts a block hash or a tag such as `finalized`.
+//!
+//! The default sequence is a [`pipeline::Pipeline`]; use [`Argus::pipeline`]
+//! to insert or replace stages and [`Argus::analyze_block_with`] to run it.
+//!
+//! With [`ArgusBuilder::cache_dir`] set, [`Argus::analyze_block`] reuses
+//! results from an on-disk [`ResultCache`] keyed by chain id and block hash.
+//! [`ArgusBuilder::rpc_cache_dir`] caches the raw RPC responses underneath
+//! instead, so any re-analysis of a block skips the node.
+
+pub mod bench;
+pub mod pipeline;
+
+use alloy_primitives::{hex, keccak256, Address, B256};
+use argus_analyzer::bundle::{BundleAnalysis, DEFAULT_HOT_POOL_THRESHOLD};
+use argus_analyzer::cache::{CacheKey, CachedAnalysis, ResultCache};
+use argus_analyzer::hotspots::{HotSlotLeaderboard, DEFAULT_HALF_LIFE_BLOCKS};
+use argus_analyzer::plugin::{
+    BlockAnalysisPlugin, PluginOutput, SimulationObserver, TxInspectorPlugin,
+};
+use argus_analyzer::severity::SeverityScorer;
+use argus_analyzer::simulator::{BatchOptions, FeeAccounting};
+use argus_analyzer::spill::MemoryBudget;
+use argus_analyzer::whatif::{HypotheticalTx, WhatIfReport};
+use argus_core::chain::{self, ChainConfig, Hardfork};
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::FastHashSet;
+use argus_provider::{
+    BudgetLimits, Bundle, BundleProvider, DataProvider, FailoverProvider, MetricsSnapshot,
+    PrefetchStrategy, ProgressHandler, RetryPolicy, RpcBudget, RpcCache, TxFilter, WarmCacheDB,
+};
+use bench::RecordStage;
+use pipeline::{
+    AnalysisPluginStage, BlockStmStage, DiscoverStage, FetchStage, GraphStage, InjectStage,
+    LoadStateStage, OrderingStage, Pipeline, PipelineContext, PipelineStream, PrefetchStage,
+    ReceiptsStage, ReportStage, SaveStateStage, SimulateStage, SlotValuesStage, StageTiming,
+    TraceStage,
+};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub use argus_analyzer as analyzer;
+pub use argus_config as config;
+pub use argus_core as core;
+pub use argus_provider as provider;
+
+pub use argus_analyzer::reporter::Report;
+pub use argus_core::{AccessList, ConflictGraph, StorageLocation, Transaction};
+pub use argus_provider::{BlockId, BlockNumberOrTag, BlockRef};
+pub use tokio_util::sync::CancellationToken;
+
+/// Everything produced by analyzing one block.
+#[derive(Debug)]
+pub struct AnalysisResult {
+    pub block_number: u64,
+    /// Empty when the result was served from the cache.
+    pub transactions: Arc<[Transaction]>,
+    pub access_lists: Vec<AccessList>,
+    pub plugin_outputs: Vec<PluginOutput>,
+    pub graph: ConflictGraph,
+    pub report: Report,
+    /// Pre-state values of contested slots, with
+    /// [`ArgusBuilder::slot_values`]; see [`Report::to_slot_values`].
+    pub slot_values: Vec<(StorageLocation, B256)>,
+    pub timings: Vec<StageTiming>,
+    /// Whether the result was served from the on-disk cache.
+    pub cached: bool,
+}
+
+impl AnalysisResult {
+    /// Extract a result from a context that ran through graph and report stages.
+    pub fn from_context(ctx: PipelineContext) -> ArgusResult<Self> {
+        let missing = |what: &str| ArgusError::Internal(format!("pipeline produced no {what}"));
+        Ok(Self {
+            block_number: ctx.block_number,
+            graph: ctx.graph.ok_or_else(|| missing("graph"))?,
+            report: ctx.report.ok_or_else(|| missing("report"))?,
+            transactions: ctx.transactions,
+            access_lists: ctx.access_lists,
+            plugin_outputs: ctx.plugin_outputs,
+            slot_values: ctx.slot_values,
+            timings: ctx.timings,
+            cached: false,
+        })
+    }
+
+    fn from_cache(cached: CachedAnalysis, chain: &'static ChainConfig, started: Instant) -> Self {
+        let report = Report::build(
+            cached.block_number,
+            &cached.access_lists,
+            &cached.graph,
+            Default::default(),
+            started.elapsed(),
+        )
+        .with_chain(chain)
+        .with_timestamp(cached.timestamp);
+        Self {
+            block_number: cached.block_number,
+            transactions: Vec::new().into(),
+            access_lists: cached.access_lists,
+            plugin_outputs: Vec::new(),
+            graph: cached.graph,
+            report,
+            slot_values: Vec::new(),
+            timings: Vec::new(),
+            cached: true,
+        }
+    }
+}
+
+/// Configured analysis pipeline. Cheap to reuse across blocks.
+pub struct Argus {
+    provider: Arc<FailoverProvider>,
+    chain: Option<&'static ChainConfig>,
+    dry_run: bool,
+    concurrency: Option<usize>,
+    retry: Option<RetryPolicy>,
+    prefetch: PrefetchStrategy,
+    batch_size: Option<usize>,
+    access_list_hints: bool,
+    multicall: bool,
+    discover: bool,
+    rpc_fallback: bool,
+    sequential: bool,
+    hardfork: Option<Hardfork>,
+    batch: BatchOptions,
+    from_trace: bool,
+    cache: Option<ResultCache>,
+    load_state: Option<PathBuf>,
+    save_state: Option<PathBuf>,
+    record: Option<PathBuf>,
+    hotspots_db: Option<PathBuf>,
+    hotspot_half_life: f64,
+    inspector_plugins: Vec<Arc<dyn TxInspectorPlugin>>,
+    analysis_plugins: Vec<Arc<dyn BlockAnalysisPlugin>>,
+    observer: Option<Arc<dyn SimulationObserver>>,
+    progress: Option<Arc<dyn ProgressHandler>>,
+    memory_budget: Option<MemoryBudget>,
+    cancel: CancellationToken,
+    tx_range: Option<Range<usize>>,
+    tx_filter: Option<TxFilter>,
+    severity: Option<Arc<dyn SeverityScorer>>,
+    slot_values: bool,
+    receipts: bool,
+    prune_same_value_writes: bool,
+    block_stm: Option<usize>,
+    ordering_sensitivity: bool,
+}
+
+impl Argus {
+    pub fn builder() -> ArgusBuilder {
+        ArgusBuilder::default()
+    }
+
+    /// The default stage sequence: fetch -> prefetch -> simulate -> graph -> report
+    /// (prefetch is omitted in dry-run and trace mode, and reads from a
+    /// snapshot when [`ArgusBuilder::load_state`] is set). Customize before
+    /// running.
+    pub fn pipeline(&self) -> Pipeline {
+        let mut pipeline = Pipeline::new()
+            .stage(FetchStage::new(self.provider.clone()).with_tx_range(self.tx_range.clone()));
+        if self.receipts {
+            pipeline = pipeline.stage(ReceiptsStage::new(self.provider.clone()));
+        }
+
+        if !self.dry_run && !self.from_trace {
+            if let Some(path) = &self.load_state {
+                pipeline = pipeline.stage(LoadStateStage::new(path));
+            } else {
+                let mut prefetcher = self
+                    .provider
+                    .prefetcher()
+                    .with_cancellation(self.cancel.clone())
+                    .with_strategy(self.prefetch)
+                    .with_access_list_hints(self.access_list_hints)
+                    .with_multicall(self.multicall);
+                if let Some(n) = self.concurrency {
+                    prefetcher = prefetcher.with_concurrency(n);
+                }
+                if let Some(n) = self.batch_size {
+                    prefetcher = prefetcher.with_batch_size(n);
+                }
+                if let Some(policy) = &self.retry {
+                    prefetcher = prefetcher.with_retry_policy(policy.clone());
+                }
+                if let Some(handler) = &self.progress {
+                    prefetcher = prefetcher.with_progress(handler.clone());
+                }
+                pipeline = if self.discover {
+                    pipeline.stage(DiscoverStage::new(prefetcher))
+                } else {
+                    pipeline.stage(PrefetchStage::new(prefetcher))
+                };
+            }
+            if let Some(path) = &self.save_state {
+                pipeline = pipeline.stage(SaveStateStage::new(path));
+            }
+        }
+        if let Some(dir) = &self.record {
+            pipeline = pipeline.stage(RecordStage::new(dir));
+        }
+
+        pipeline = if self.from_trace {
+            pipeline.stage(TraceStage::new(self.provider.clone()))
+        } else {
+            let fallback = (self.rpc_fallback && !self.dry_run).then(|| self.provider.prefetcher());
+            pipeline.stage(
+                SimulateStage::new()
+                    .with_plugins(self.inspector_plugins.clone())
+                    .with_observer(self.observer.clone())
+                    .with_progress(self.progress.clone())
+                    .with_rpc_fallback(fallback)
+                    .with_sequential(self.sequential)
+                    .with_options(self.batch.clone()),
+            )
+        };
+        pipeline = pipeline
+            .stage(
+                GraphStage::new()
+                    .with_budget(self.memory_budget.clone())
+                    .with_same_value_pruning(self.prune_same_value_writes),
+            )
+            .stage(ReportStage::new().with_severity(self.severity.clone()));
+        if self.slot_values {
+            pipeline = pipeline.stage(SlotValuesStage);
+        }
+        if let Some(threads) = self.block_stm {
+            pipeline = pipeline.stage(BlockStmStage::new(threads));
+        }
+        if self.ordering_sensitivity {
+            pipeline = pipeline.stage(OrderingStage::new(self.batch.clone()));
+        }
+
+        if !self.analysis_plugins.is_empty() {
+            pipeline = pipeline.stage(AnalysisPluginStage::new(self.analysis_plugins.clone()));
+        }
+        pipeline
+    }
+
+    /// Run the default pipeline for `block_number`, consulting the result
+    /// cache first when one is configured. With a hotspot store configured,
+    /// the block's conflicts are added to the leaderboard.
+    pub async fn analyze_block(&self, block_number: u64) -> ArgusResult<AnalysisResult> {
+        let result = self.analyze_block_cached(block_number, None).await?;
+        self.after_block(&result);
+        Ok(result)
+    }
+
+    /// Like [`analyze_block`](Self::analyze_block), for a block given by
+    /// number, hash or tag. Hashes and tags are resolved once up front and
+    /// every stage then fetches by hash, so a tag that advances mid-run, or
+    /// a block reorged out of the canonical chain, is still analyzed whole.
+    pub async fn analyze(&self, block: BlockId) -> ArgusResult<AnalysisResult> {
+        if let BlockId::Number(BlockNumberOrTag::Number(n)) = block {
+            return self.analyze_block(n).await;
+        }
+        let block = self.resolve_block(block).await?;
+        let result = self
+            .analyze_block_cached(block.number, Some(block.hash))
+            .await?;
+        self.after_block(&result);
+        Ok(result)
+    }
+
+    /// Analyze a Flashbots-style bundle on top of its target block: the
+    /// bundle runs first, followed by the target block's transactions once
+    /// it is mined, else the pending block (with state as of the head); see
+    /// [`BundleProvider`]. Conflicts are also split into intra-bundle and
+    /// competing edges. Not available with [`ArgusBuilder::from_trace`].
+    pub async fn analyze_bundle(
+        &self,
+        bundle: &Bundle,
+    ) -> ArgusResult<(AnalysisResult, BundleAnalysis)> {
+        if self.from_trace {
+            return Err(ArgusError::InvalidInput(
+                "bundles cannot be analyzed from node traces".into(),
+            ));
+        }
+        let provider = Arc::new(BundleProvider::new(self.provider.clone(), bundle)?);
+        let block_number = provider.state_block().await?;
+        let bundled: FastHashSet<B256> = provider.bundle().iter().map(|tx| tx.hash).collect();
+        let pipeline = self.pipeline().replace("fetch", FetchStage::new(provider));
+        let result = self.analyze_block_with(&pipeline, block_number).await?;
+
+        let (bundle_lists, competing): (Vec<_>, Vec<_>) = result
+            .access_lists
+            .iter()
+            .cloned()
+            .partition(|list| bundled.contains(&list.tx_hash));
+        let analysis =
+            argus_analyzer::bundle::classify(bundle_lists, &competing, DEFAULT_HOT_POOL_THRESHOLD);
+        Ok((result, analysis))
+    }
+
+    /// Append `injected` to block `block_number` and analyze the result,
+    /// reporting which of the block's transactions each injected one would
+    /// conflict with. The block's own transactions are simulated as usual;
+    /// the injected ones see the same pre-block state. The result cache is
+    /// not consulted.
+    pub async fn analyze_what_if(
+        &self,
+        block_number: u64,
+        injected: &[HypotheticalTx],
+    ) -> ArgusResult<(AnalysisResult, WhatIfReport)> {
+        if injected.is_empty() {
+            return Err(ArgusError::InvalidInput(
+                "what-if needs at least one transaction".into(),
+            ));
+        }
+        if self.from_trace {
+            return Err(ArgusError::InvalidInput(
+                "injected transactions cannot be analyzed from node traces".into(),
+            ));
+        }
+        let txs: Vec<Transaction> = injected
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| tx.to_transaction(i))
+            .collect();
+        let hashes: Vec<B256> = txs.iter().map(|tx| tx.hash).collect();
+        let pipeline = self.pipeline().insert_after("fetch", InjectStage::new(txs));
+        let result = self.analyze_block_with(&pipeline, block_number).await?;
+        let report =
+            WhatIfReport::build(block_number, &result.graph, &result.transactions, &hashes);
+        Ok((result, report))
+    }
+
+    /// Number and hash of the block `block` currently refers to.
+    pub async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        self.provider.resolve_block(block).await
+    }
+
+    /// Run the default pipeline up to simulation and return the block number
+    /// and the warm state it would simulate against (read from the snapshot
+    /// when [`ArgusBuilder::load_state`] is set). Fails in dry-run mode.
+    pub async fn warm_state(&self, block: BlockId) -> ArgusResult<(u64, WarmCacheDB)> {
+        let mut ctx = match block {
+            BlockId::Number(BlockNumberOrTag::Number(n)) => self.context(n),
+            _ => {
+                let block = self.resolve_block(block).await?;
+                self.context(block.number).with_block_hash(block.hash)
+            }
+        };
+        let (prepare, _) = self.pipeline().split_before("simulate");
+        prepare.run_with(&mut ctx).await?;
+        let db = ctx.warm_db.ok_or_else(|| {
+            ArgusError::InvalidInput("no warm state: dry-run skips prefetch".into())
+        })?;
+        Ok((ctx.block_number, db))
+    }
+
+    /// Run the default pipeline over a stream of blocks: fetch and prefetch
+    /// of one block overlap simulation of the previous one, and at most
+    /// `capacity` blocks wait between segments. Submit contexts from
+    /// [`context`](Self::context) and pass each finished one to
+    /// [`complete`](Self::complete). The result cache is not consulted.
+    pub fn stream(&self, capacity: usize) -> PipelineStream {
+        self.pipeline().spawn_stream(&["simulate"], capacity)
+    }
+
+    /// A fresh context for `block_number`, with the pinned chain and
+    /// hardfork applied.
+    pub fn context(&self, block_number: u64) -> PipelineContext {
+        let mut ctx = PipelineContext::new(block_number).with_cancellation(self.cancel.clone());
+        if let Some(chain) = self.chain {
+            ctx = ctx.with_chain(chain);
+        }
+        if let Some(fork) = self.hardfork {
+            ctx = ctx.with_hardfork(fork);
+        }
+        ctx
+    }
+
+    /// Finish a streamed block the way [`analyze_block`](Self::analyze_block) does.
+    pub fn complete(&self, ctx: PipelineContext) -> ArgusResult<AnalysisResult> {
+        let result = AnalysisResult::from_context(ctx)?;
+        self.after_block(&result);
+        Ok(result)
+    }
+
+    /// RPC requests, retries, 429s, bytes and latency per method since the
+    /// endpoints were connected, across all of them.
+    pub fn rpc_metrics(&self) -> MetricsSnapshot {
+        self.provider.metrics()
+    }
+
+    /// Whether the node has produced `block_number` yet.
+    pub async fn is_block_available(&self, block_number: u64) -> bool {
+        self.provider
+            .get_block_hash(BlockId::number(block_number))
+            .await
+            .is_ok()
+    }
+
+    fn after_block(&self, result: &AnalysisResult) {
+        if let Some(path) = &self.hotspots_db {
+            if let Err(e) = self.record_hotspots(path, result) {
+                tracing::warn!(error = %e, "failed to update hotspot leaderboard");
+            }
+        }
+    }
+
+    fn record_hotspots(&self, path: &std::path::Path, result: &AnalysisResult) -> ArgusResult<()> {
+        let mut board = HotSlotLeaderboard::load_or_new(path, self.hotspot_half_life)?;
+        if board.record(result.block_number, &result.graph) {
+            board.save(path)?;
+        }
+        Ok(())
+    }
+
+    async fn analyze_block_cached(
+        &self,
+        block_number: u64,
+        block_hash: Option<B256>,
+    ) -> ArgusResult<AnalysisResult> {
+        // Snapshots, recordings, plugins, observers, slot values, receipts,
+        // Block-STM and ordering runs, and partial or filtered blocks want
+        // more than the cached lists and graph, so they always run the
+        // pipeline. Options that only change the result are in the key.
+        let bypass = self.save_state.is_some()
+            || self.record.is_some()
+            || !self.inspector_plugins.is_empty()
+            || !self.analysis_plugins.is_empty()
+            || self.observer.is_some()
+            || self.tx_range.is_some()
+            || self.tx_filter.is_some()
+            || self.slot_values
+            || self.receipts
+            || self.block_stm.is_some()
+            || self.ordering_sensitivity;
+        let Some(cache) = self.cache.as_ref().filter(|_| !bypass) else {
+            return self.run_pipeline(block_number, block_hash).await;
+        };
+
+        let started = Instant::now();
+        let key = self
+            .cache_key(block_number, block_hash)
+            .await
+            .in_block(block_number)?;
+        if let Some(cached) = cache.get(&key)? {
+            tracing::info!(block = block_number, "served from result cache");
+            let chain = chain::resolve(key.chain_id);
+            let mut result = AnalysisResult::from_cache(cached, chain, started);
+            if let Some(scorer) = &self.severity {
+                result.report.severity = Arc::clone(scorer);
+            }
+            return Ok(result);
+        }
+
+        let result = self.run_pipeline(block_number, block_hash).await?;
+        let entry = CachedAnalysis {
+            block_number,
+            timestamp: result.report.block_timestamp,
+            access_lists: result.access_lists.clone(),
+            graph: result.graph.clone(),
+        };
+        if let Err(e) = cache.put(&key, &entry) {
+            tracing::warn!(error = %e, "failed to store analysis in result cache");
+        }
+        Ok(result)
+    }
+
+    /// The default pipeline for `block_number`, pinned to `block_hash` if given.
+    async fn run_pipeline(
+        &self,
+        block_number: u64,
+        block_hash: Option<B256>,
+    ) -> ArgusResult<AnalysisResult> {
+        let ctx = self.context(block_number);
+        let ctx = match block_hash {
+            Some(hash) => ctx.with_block_hash(hash),
+            None => ctx,
+        };
+        self.run_context(&self.pipeline(), ctx).await
+    }
+
+    /// Run a custom pipeline for `block_number`.
+    pub async fn analyze_block_with(
+        &self,
+        pipeline: &Pipeline,
+        block_number: u64,
+    ) -> ArgusResult<AnalysisResult> {
+        self.run_context(pipeline, self.context(block_number)).await
+    }
+
+    async fn run_context(
+        &self,
+        pipeline: &Pipeline,
+        mut ctx: PipelineContext,
+    ) -> ArgusResult<AnalysisResult> {
+        pipeline.run_with(&mut ctx).await?;
+        tracing::info!(
+            conflicts = ctx.graph.as_ref().map_or(0, |g| g.len()),
+            elapsed_ms = ctx.elapsed().as_millis(),
+            "analysis complete"
+        );
+        AnalysisResult::from_context(ctx)
+    }
+
+    async fn cache_key(
+        &self,
+        block_number: u64,
+        block_hash: Option<B256>,
+    ) -> ArgusResult<CacheKey> {
+        let chain_id = match self.chain {
+            Some(chain) => chain.chain_id,
+            None => self.provider.chain_id().await?,
+        };
+        let block_hash = match block_hash {
+            Some(hash) => hash,
+            None => {
+                self.provider
+                    .get_block_hash(BlockId::number(block_number))
+                    .await?
+            }
+        };
+        Ok(CacheKey::new(chain_id, block_hash, self.cache_mode()))
+    }
+
+    /// `dry` or `warm`, plus a digest of every option that changes the
+    /// access lists or graph, so results under different ones never mix.
+    fn cache_mode(&self) -> String {
+        let mut excluded_addresses: Vec<_> = self.batch.excluded_addresses.iter().collect();
+        excluded_addresses.sort();
+        let mut excluded_slots: Vec<_> = self.batch.excluded_slots.iter().collect();
+        excluded_slots.sort();
+        let options = format!(
+            "{:?}",
+            (
+                self.prefetch,
+                self.access_list_hints,
+                self.discover,
+                self.rpc_fallback,
+                self.sequential,
+                self.prune_same_value_writes,
+                self.hardfork,
+                self.from_trace,
+                self.batch.fees,
+                self.batch.tx_timeout,
+                excluded_addresses,
+                excluded_slots,
+            )
+        );
+        let base = if self.dry_run { "dry" } else { "warm" };
+        format!("{base}-{}", hex::encode(&keccak256(options)[..8]))
+    }
+}
+
+/// Builder for [`Argus`].
+#[derive(Default, Clone)]
+pub struct ArgusBuilder {
+    rpc_url: Option<String>,
+    fallback_rpc_urls: Vec<String>,
+    chain_id: Option<u64>,
+    dry_run: bool,
+    concurrency: Option<usize>,
+    retry: Option<RetryPolicy>,
+    prefetch: PrefetchStrategy,
+    batch_size: Option<usize>,
+    access_list_hints: bool,
+    multicall: bool,
+    discover: bool,
+    rpc_fallback: bool,
+    sequential: bool,
+    hardfork: Option<Hardfork>,
+    batch: BatchOptions,
+    from_trace: bool,
+    rpc_budget: Option<Arc<RpcBudget>>,
+    rpc_limits: Option<BudgetLimits>,
+    cache_dir: Option<PathBuf>,
+    rpc_cache_dir: Option<PathBuf>,
+    load_state: Option<PathBuf>,
+    save_state: Option<PathBuf>,
+    record: Option<PathBuf>,
+    hotspots_db: Option<PathBuf>,
+    hotspot_half_life: Option<f64>,
+    inspector_plugins: Vec<Arc<dyn TxInspectorPlugin>>,
+    analysis_plugins: Vec<Arc<dyn BlockAnalysisPlugin>>,
+    observer: Option<Arc<dyn SimulationObserver>>,
+    progress: Option<Arc<dyn ProgressHandler>>,
+    memory_budget: Option<MemoryBudget>,
+    cancel: Option<CancellationToken>,
+    tx_range: Option<Range<usize>>,
+    tx_filter: Option<TxFilter>,
+    severity: Option<Arc<dyn SeverityScorer>>,
+    slot_values: bool,
+    receipts: bool,
+    prune_same_value_writes: bool,
+    block_stm: Option<usize>,
+    ordering_sensitivity: bool,
+}
+
+impl ArgusBuilder {
+    /// Seed a builder from layered configuration. Later calls override it.
+    pub fn from_config(config: &argus_config::Config) -> Self {
+        let mut builder = Self::default()
+            .dry_run(config.simulator.dry_run)
+            .prefetch_batch_size(config.prefetcher.batch_size)
+            .access_list_hints(config.prefetcher.access_list_hints)
+            .multicall(config.prefetcher.multicall)
+            .rpc_fallback(config.prefetcher.rpc_fallback)
+            .sequential(config.simulator.sequential);
+        match config.prefetcher.mode {
+            argus_config::PrefetchMode::Full => {}
+            argus_config::PrefetchMode::Lite => {
+                builder = builder.prefetch_strategy(PrefetchStrategy::Lite {
+                    top_k: config.prefetcher.top_k,
+                });
+            }
+            argus_config::PrefetchMode::Prestate => {
+                builder = builder.prefetch_strategy(PrefetchStrategy::Prestate);
+            }
+            argus_config::PrefetchMode::Proof => {
+                builder = builder.prefetch_strategy(PrefetchStrategy::Proof);
+            }
+            argus_config::PrefetchMode::Discovered => {
+                builder = builder.discover_state(true);
+            }
+        }
+        if let Some(url) = &config.provider.rpc_url {
+            builder = builder.rpc_url(url);
+        }
+        for url in &config.provider.fallback_rpc_urls {
+            builder = builder.fallback_rpc_url(url);
+        }
+        if let Some(id) = config.provider.chain_id {
+            builder = builder.chain_id(id);
+        }
+        if let Some(fork) = config.simulator.spec {
+            builder = builder.hardfork(fork);
+        }
+        if let Some(ms) = config.simulator.tx_timeout_ms {
+            builder = builder.tx_timeout(Duration::from_millis(ms));
+        }
+        if let Some(n) = config.simulator.threads {
+            builder = builder.threads(n);
+        }
+        if let Some(n) = config.simulator.chunk_size {
+            builder = builder.chunk_size(n);
+        }
+        if config.simulator.strict_fees {
+            builder = builder.fee_accounting(FeeAccounting::Strict);
+        }
+        for &address in &config.simulator.excluded_addresses {
+            builder = builder.exclude_address(address);
+        }
+        for location in &config.simulator.excluded_slots {
+            builder = builder.exclude_slot(location.address, location.slot);
+        }
+        if let Some(n) = config.prefetcher.concurrency {
+            builder = builder.concurrency(n);
+        }
+        let mut retry = RetryPolicy::default()
+            .with_max_attempts(config.prefetcher.retry_max_attempts)
+            .with_base_delay(Duration::from_millis(config.prefetcher.retry_base_delay_ms))
+            .with_jitter(config.prefetcher.retry_jitter);
+        if let Some(ms) = config.prefetcher.retry_max_elapsed_ms {
+            retry = retry.with_max_elapsed(Duration::from_millis(ms));
+        }
+        builder = builder.retry_policy(retry);
+        if let Some(dir) = &config.simulator.cache_dir {
+            builder = builder.cache_dir(dir);
+        }
+        if let Some(dir) = &config.provider.rpc_cache_dir {
+            builder = builder.rpc_cache_dir(dir);
+        }
+        if let Some(mb) = config.simulator.memory_budget_mb {
+            let mut budget = MemoryBudget::new((mb as usize).saturating_mul(1024 * 1024));
+            if let Some(dir) = &config.simulator.spill_dir {
+                budget = budget.with_spill_dir(dir);
+            }
+            builder = builder.memory_budget(budget);
+        }
+        if let Some(path) = &config.hotspots.path {
+            builder = builder
+                .hotspots_db(path)
+                .hotspot_half_life(config.hotspots.half_life_blocks);
+        }
+        let limits = BudgetLimits {
+            requests_per_second: config.provider.max_rps,
+            burst: config.provider.burst,
+            requests_per_day: config.provider.max_requests_per_day,
+            max_in_flight: config.provider.max_in_flight,
+        };
+        if limits != BudgetLimits::default() {
+            builder = builder.rpc_limits(limits);
+        }
+        builder
+    }
+
+    /// JSON-RPC endpoint (required). IPC paths and WebSocket URLs need
+    /// [`connect`](Self::connect) instead of [`build`](Self::build).
+    pub fn rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.rpc_url = Some(url.into());
+        self
+    }
+
+    /// Endpoint to fail over to when earlier ones are unreachable, time out
+    /// or keep rate limiting. Tried in the order added, after
+    /// [`rpc_url`](Self::rpc_url).
+    pub fn fallback_rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.fallback_rpc_urls.push(url.into());
+        self
+    }
+
+    /// Pin the chain instead of querying `eth_chainId` per block. Must be
+    /// registered in [`argus_core::chain`].
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Skip state prefetch and simulate against `EmptyDB`.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Max concurrent prefetch RPC tasks. See [`Prefetcher::with_concurrency`](argus_provider::Prefetcher::with_concurrency).
+    pub fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = Some(n);
+        self
+    }
+
+    /// How prefetch fetches are retried. See [`RetryPolicy`].
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Record the pre-state value of each contested slot found in the warm
+    /// cache into [`AnalysisResult::slot_values`].
+    pub fn slot_values(mut self, enabled: bool) -> Self {
+        self.slot_values = enabled;
+        self
+    }
+
+    /// Fetch block receipts so the report and contention events weigh each
+    /// transaction by the gas it used. One extra call per block.
+    pub fn receipts(mut self, enabled: bool) -> Self {
+        self.receipts = enabled;
+        self
+    }
+
+    /// Calls packed into each JSON-RPC batch during prefetch (default 1:
+    /// unbatched). Larger batches mean far fewer round-trips; some vendors
+    /// cap batch size, typically somewhere between 10 and 1000.
+    pub fn prefetch_batch_size(mut self, n: usize) -> Self {
+        self.batch_size = Some(n);
+        self
+    }
+
+    /// Take access lists from the node's `prestateTracer` instead of
+    /// simulating locally; no state is prefetched. Needs the debug API.
+    /// Useful to cross-check simulation, or where warm state is hard to
+    /// assemble. Inspector plugins and observers don't run in this mode.
+    pub fn from_trace(mut self, enabled: bool) -> Self {
+        self.from_trace = enabled;
+        self
+    }
+
+    /// Also prefetch the slots `eth_createAccessList` reports for each
+    /// transaction, beyond the static slot table. One extra call per
+    /// transaction.
+    pub fn access_list_hints(mut self, enabled: bool) -> Self {
+        self.access_list_hints = enabled;
+        self
+    }
+
+    /// Prefetch storage through Multicall3, hundreds of slots per
+    /// `eth_call`, falling back to `eth_getStorageAt` where the node
+    /// doesn't support it. See [`Prefetcher::with_multicall`](argus_provider::Prefetcher::with_multicall).
+    pub fn multicall(mut self, enabled: bool) -> Self {
+        self.multicall = enabled;
+        self
+    }
+
+    /// Prefetch exactly the state the transactions read, found by
+    /// simulating them against what has been fetched so far until nothing
+    /// new turns up, instead of the prefetch strategy's static guess. See
+    /// [`Prefetcher::prefetch_discovered`](argus_provider::Prefetcher::prefetch_discovered).
+    pub fn discover_state(mut self, enabled: bool) -> Self {
+        self.discover = enabled;
+        self
+    }
+
+    /// Fetch state the prefetch missed from the node while simulating,
+    /// instead of reading it as empty. Slower per miss, but no read is
+    /// silently zero. See [`Prefetcher::fallback_db`](argus_provider::Prefetcher::fallback_db).
+    pub fn rpc_fallback(mut self, enabled: bool) -> Self {
+        self.rpc_fallback = enabled;
+        self
+    }
+
+    /// Execute the block's transactions in order, each over the state the
+    /// earlier ones wrote, instead of each in isolation over the
+    /// pre-block state. Access lists then follow the block's real
+    /// execution; slower, since transactions no longer run in parallel.
+    pub fn sequential(mut self, enabled: bool) -> Self {
+        self.sequential = enabled;
+        self
+    }
+
+    /// Leave out write-write conflicts where both transactions store the
+    /// same value, or one writes a slot back to what it found. Such pairs
+    /// commute, so counting them overstates contention.
+    pub fn prune_same_value_writes(mut self, enabled: bool) -> Self {
+        self.prune_same_value_writes = enabled;
+        self
+    }
+
+    /// Also execute the block under Block-STM on `threads` workers and
+    /// report the re-executions and speedup it achieved. Needs warm state.
+    pub fn block_stm(mut self, threads: usize) -> Self {
+        self.block_stm = Some(threads);
+        self
+    }
+
+    /// Re-simulate the block sequentially in the orderings the reorder
+    /// optimizer proposes and report how the access lists and conflict
+    /// counts change against block order. Needs warm state.
+    pub fn ordering_sensitivity(mut self, enabled: bool) -> Self {
+        self.ordering_sensitivity = enabled;
+        self
+    }
+
+    /// Simulate under `fork` instead of the hardfork the chain's schedule
+    /// gives for each block, for testnets whose schedule isn't registered
+    /// or what-if runs under another fork's rules.
+    pub fn hardfork(mut self, fork: Hardfork) -> Self {
+        self.hardfork = Some(fork);
+        self
+    }
+
+    /// Abort a transaction whose simulation runs longer than `timeout`
+    /// (marked [`ExecutionOutcome::TimedOut`](argus_core::ExecutionOutcome::TimedOut)),
+    /// so one runaway loop can't stall the batch. Unlimited by default.
+    pub fn tx_timeout(mut self, timeout: Duration) -> Self {
+        self.batch.tx_timeout = Some(timeout);
+        self
+    }
+
+    /// Simulate on a dedicated pool of `threads` workers instead of rayon's
+    /// global one, to bound the CPU an embedding application gives Argus.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.batch.threads = threads;
+        self
+    }
+
+    /// Hand simulation workers at least `chunk_size` transactions at a time.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.batch.chunk_size = chunk_size;
+        self
+    }
+
+    /// Whether gas payment counts as a balance write. Under
+    /// [`FeeAccounting::Strict`] every transaction writes its sender's and
+    /// the coinbase's balance, so all of a block's transactions conflict
+    /// with each other; the default leaves fees out.
+    pub fn fee_accounting(mut self, fees: FeeAccounting) -> Self {
+        self.batch.fees = fees;
+        self
+    }
+
+    /// Leave every access to `address` out of access lists and the conflict
+    /// graph, e.g. the EIP-4788 beacon-roots contract. Repeatable.
+    pub fn exclude_address(mut self, address: Address) -> Self {
+        self.batch = self.batch.with_excluded_address(address);
+        self
+    }
+
+    /// Leave one storage slot out of access lists and the conflict graph.
+    /// Repeatable.
+    pub fn exclude_slot(mut self, address: Address, slot: B256) -> Self {
+        self.batch = self.batch.with_excluded_slot(address, slot);
+        self
+    }
+
+    /// Which accounts to prefetch; [`PrefetchStrategy::Lite`] trades accuracy
+    /// for far fewer RPC calls.
+    pub fn prefetch_strategy(mut self, strategy: PrefetchStrategy) -> Self {
+        self.prefetch = strategy;
+        self
+    }
+
+    /// Draw all block, header and prefetch requests from `budget`. Pass the
+    /// same `Arc` to other components to share one quota process-wide.
+    pub fn rpc_budget(mut self, budget: Arc<RpcBudget>) -> Self {
+        self.rpc_budget = Some(budget);
+        self
+    }
+
+    /// Like [`rpc_budget`](Self::rpc_budget) with a budget of its own,
+    /// created at build time; limits that would stall every request (a
+    /// non-positive rate, zero in flight) fail the build. An explicit
+    /// `rpc_budget` takes precedence.
+    pub fn rpc_limits(mut self, limits: BudgetLimits) -> Self {
+        self.rpc_limits = Some(limits);
+        self
+    }
+
+    /// Cache per-block results under `dir`. See [`ResultCache`].
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Keep block data and prefetched state fetched from the node under
+    /// `dir`, so blocks are only fetched once. See [`RpcCache`].
+    pub fn rpc_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.rpc_cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Restore warm state from a snapshot instead of prefetching.
+    pub fn load_state(mut self, path: impl Into<PathBuf>) -> Self {
+        self.load_state = Some(path.into());
+        self
+    }
+
+    /// Save the warm state to a snapshot after prefetch.
+    pub fn save_state(mut self, path: impl Into<PathBuf>) -> Self {
+        self.save_state = Some(path.into());
+        self
+    }
+
+    /// Record each analyzed block (and its warm state) as a replay fixture
+    /// under `dir`. See [`bench`].
+    pub fn record(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.record = Some(dir.into());
+        self
+    }
+
+    /// Accumulate each analyzed block's conflicts into a persistent
+    /// [`HotSlotLeaderboard`] at `path`.
+    pub fn hotspots_db(mut self, path: impl Into<PathBuf>) -> Self {
+        self.hotspots_db = Some(path.into());
+        self
+    }
+
+    /// Leaderboard decay half-life in blocks (default: 7200). Only applies
+    /// when the store is first created.
+    pub fn hotspot_half_life(mut self, blocks: f64) -> Self {
+        self.hotspot_half_life = Some(blocks);
+        self
+    }
+
+    /// Run a custom inspector on every simulated transaction (warm mode).
+    pub fn inspector_plugin(mut self, plugin: Arc<dyn TxInspectorPlugin>) -> Self {
+        self.inspector_plugins.push(plugin);
+        self
+    }
+
+    /// Add a report section computed after the conflict graph is built.
+    pub fn analysis_plugin(mut self, plugin: Arc<dyn BlockAnalysisPlugin>) -> Self {
+        self.analysis_plugins.push(plugin);
+        self
+    }
+
+    /// Receive each transaction's access list as soon as it is simulated,
+    /// e.g. a [`LiveNdjsonSink`](argus_analyzer::sink::live::LiveNdjsonSink).
+    pub fn simulation_observer(mut self, observer: Arc<dyn SimulationObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Receive prefetch and simulation progress (reads started and done,
+    /// transactions simulated), e.g. for a progress bar. Cached results
+    /// produce no events.
+    pub fn progress_handler(mut self, handler: Arc<dyn ProgressHandler>) -> Self {
+        self.progress = Some(handler);
+        self
+    }
+
+    /// Cap conflict-graph indexing memory; larger blocks are partitioned
+    /// through temporary files. See [`argus_analyzer::spill`].
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Analyze only the transactions at block positions `range`; the rest
+    /// are neither prefetched nor simulated.
+    pub fn tx_range(mut self, range: Range<usize>) -> Self {
+        self.tx_range = Some(range);
+        self
+    }
+
+    /// Analyze only the block transactions `filter` matches, dropped by the
+    /// provider before prefetch. Can't be combined with
+    /// [`tx_range`](Self::tx_range), whose positions refer to the full block.
+    pub fn tx_filter(mut self, filter: TxFilter) -> Self {
+        self.tx_filter = Some(filter).filter(|f| !f.is_empty());
+        self
+    }
+
+    /// Assign contention-event severity with `scorer` instead of the default
+    /// density thresholds.
+    pub fn severity_scorer(mut self, scorer: Arc<dyn SeverityScorer>) -> Self {
+        self.severity = Some(scorer);
+        self
+    }
+
+    /// Abort in-flight work when `token` fires: fetches, prefetch tasks and
+    /// simulation stop promptly and the call fails with
+    /// [`ArgusError::Cancelled`]. Once fired, later calls fail immediately.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Build over HTTP(S) endpoints. No request is made until the first
+    /// analysis.
+    pub fn build(self) -> ArgusResult<Argus> {
+        let urls = self.rpc_urls()?;
+        let provider = FailoverProvider::connect_http(&urls)?;
+        self.finish(provider)
+    }
+
+    /// Like [`build`](Self::build), but also accepts WebSocket URLs and, with
+    /// the `ipc` feature, local IPC socket paths such as `/path/geth.ipc`.
+    /// Persistent transports connect up front, and the chain is detected
+    /// once here rather than per block (checked against a pinned
+    /// [`chain_id`](Self::chain_id)).
+    pub async fn connect(self) -> ArgusResult<Argus> {
+        let urls = self.rpc_urls()?;
+        let provider = FailoverProvider::connect(&urls).await?;
+        self.finish(provider)
+    }
+
+    /// The primary endpoint followed by the fallbacks.
+    fn rpc_urls(&self) -> ArgusResult<Vec<String>> {
+        let rpc_url = self
+            .rpc_url
+            .clone()
+            .ok_or_else(|| ArgusError::InvalidInput("rpc_url is required".into()))?;
+        Ok(std::iter::once(rpc_url)
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect())
+    }
+
+    fn finish(self, mut provider: FailoverProvider) -> ArgusResult<Argus> {
+        if self.tx_range.is_some() && self.tx_filter.is_some() {
+            return Err(ArgusError::InvalidInput(
+                "tx_range and tx_filter can't be combined".into(),
+            ));
+        }
+        let pinned = self
+            .chain_id
+            .map(|id| {
+                chain::lookup(id)
+                    .ok_or_else(|| ArgusError::InvalidInput(format!("unknown chain id {id}")))
+            })
+            .transpose()?;
+        let chain = match (pinned, provider.chain_config()) {
+            (Some(pinned), Some(detected)) if pinned.chain_id != detected.chain_id => {
+                return Err(ArgusError::InvalidInput(format!(
+                    "chain id {} was pinned but the node serves chain {}",
+                    pinned.chain_id, detected.chain_id
+                )));
+            }
+            (pinned, detected) => pinned.or(detected),
+        };
+        if let Some(chain) = chain {
+            provider = provider.with_chain(chain);
+        }
+
+        let budget = match (self.rpc_budget, self.rpc_limits) {
+            (Some(budget), _) => Some(budget),
+            (None, Some(limits)) => Some(RpcBudget::new(limits)?),
+            (None, None) => None,
+        };
+        if let Some(budget) = budget {
+            provider = provider.with_budget(budget);
+        }
+        if let Some(filter) = self.tx_filter.clone() {
+            provider = provider.with_tx_filter(filter);
+        }
+        if let Some(dir) = self.rpc_cache_dir {
+            let mut cache = RpcCache::new(dir);
+            if let Some(chain) = chain {
+                cache = cache.with_chain_id(chain.chain_id);
+            }
+            provider = provider.with_rpc_cache(Arc::new(cache));
+        }
+
+        Ok(Argus {
+            provider: Arc::new(provider),
+            chain,
+            dry_run: self.dry_run,
+            concurrency: self.concurrency,
+            retry: self.retry,
+            prefetch: self.prefetch,
+            batch_size: self.batch_size,
+            access_list_hints: self.access_list_hints,
+            multicall: self.multicall,
+            discover: self.discover,
+            rpc_fallback: self.rpc_fallback,
+            sequential: self.sequential,
+            hardfork: self.hardfork,
+            batch: self.batch,
+            from_trace: self.from_trace,
+            cache: self.cache_dir.map(ResultCache::new),
+            load_state: self.load_state,
+            save_state: self.save_state,
+            record: self.record,
+            hotspots_db: self.hotspots_db,
+            hotspot_half_life: self.hotspot_half_life.unwrap_or(DEFAULT_HALF_LIFE_BLOCKS),
+            inspector_plugins: self.inspector_plugins,
+            analysis_plugins: self.analysis_plugins,
+            observer: self.observer,
+            progress: self.progress,
+            memory_budget: self.memory_budget,
+            cancel: self.cancel.unwrap_or_default(),
+            tx_range: self.tx_range,
+            tx_filter: self.tx_filter,
+            severity: self.severity,
+            slot_values: self.slot_values,
+            receipts: self.receipts,
+            prune_same_value_writes: self.prune_same_value_writes,
+            block_stm: self.block_stm,
+            ordering_sensitivity: self.ordering_sensitivity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_requires_rpc_url() {
+        assert!(matches!(
+            Argus::builder().build(),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn build_rejects_malformed_url() {
+        assert!(Argus::builder().rpc_url("not a url").build().is_err());
+    }
+
+    #[test]
+    fn dry_run_skips_prefetch_stage() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .dry_run(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            argus.pipeline().stage_names(),
+            vec!["fetch", "simulate", "graph", "report"]
+        );
+    }
+
+    #[test]
+    fn build_does_not_connect() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .dry_run(true)
+            .build();
+        assert!(argus.is_ok());
+    }
+
+    #[test]
+    fn cache_dir_is_optional() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .cache_dir(std::env::temp_dir().join("argus-facade-cache"))
+            .build()
+            .unwrap();
+        assert!(argus.cache.is_some());
+    }
+
+    #[test]
+    fn result_options_key_the_cache() {
+        let builder = Argus::builder().rpc_url("http://127.0.0.1:1");
+        let mode = |builder: ArgusBuilder| builder.build().unwrap().cache_mode();
+        let default = mode(builder.clone());
+        assert!(default.starts_with("warm-"));
+        assert_eq!(default, mode(builder.clone().threads(4)));
+        assert_ne!(default, mode(builder.clone().sequential(true)));
+        assert_ne!(
+            default,
+            mode(builder.clone().exclude_address(Address::ZERO))
+        );
+        assert_ne!(
+            default,
+            mode(builder.clone().fee_accounting(FeeAccounting::Strict))
+        );
+        assert!(mode(builder.dry_run(true)).starts_with("dry-"));
+    }
+
+    #[test]
+    fn load_state_replaces_prefetch() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .load_state("state.bin")
+            .save_state("copy.bin")
+            .build()
+            .unwrap();
+        assert_eq!(
+            argus.pipeline().stage_names(),
+            vec![
+                "fetch",
+                "prefetch",
+                "save_state",
+                "simulate",
+                "graph",
+                "report"
+            ]
+        );
+    }
+
+    #[test]
+    fn chain_id_must_be_registered() {
+        let builder = Argus::builder().rpc_url("http://127.0.0.1:1");
+        assert!(builder.clone().chain_id(8453).build().is_ok());
+        assert!(matches!(
+            builder.chain_id(999_999).build(),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn pinned_chain_needs_no_request() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .chain_id(8453)
+            .build()
+            .unwrap();
+        assert_eq!(argus.provider.chain().await.unwrap().name, "Base");
+    }
+
+    #[test]
+    fn tx_filter_excludes_tx_range() {
+        let builder = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .tx_filter(TxFilter::new().with_min_gas(100_000));
+        assert!(builder.clone().build().unwrap().tx_filter.is_some());
+        assert!(matches!(
+            builder.tx_range(0..10).build(),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    struct Noop;
+
+    impl BlockAnalysisPlugin for Noop {
+        fn name(&self) -> &str {
+            "noop"
+        }
+
+        fn analyze(
+            &self,
+            _input: &argus_analyzer::plugin::BlockAnalysisInput<'_>,
+        ) -> ArgusResult<argus_analyzer::plugin::ReportSection> {
+            Ok(argus_analyzer::plugin::ReportSection::new("noop"))
+        }
+    }
+
+    #[test]
+    fn analysis_plugin_appends_stage() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .dry_run(true)
+            .analysis_plugin(Arc::new(Noop))
+            .build()
+            .unwrap();
+        assert_eq!(
+            argus.pipeline().stage_names(),
+            vec!["fetch", "simulate", "graph", "report", "plugins"]
+        );
+    }
+
+    #[test]
+    fn record_runs_before_simulate() {
+        let argus = Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .record("/tmp/argus-fixtures")
+            .build()
+            .unwrap();
+        assert_eq!(
+            argus.pipeline().stage_names(),
+            vec!["fetch", "prefetch", "record", "simulate", "graph", "report"]
+        );
+    }
+
+    #[test]
+    fn builder_from_config() {
+        let config = argus_config::Config::from_toml(
+            "[provider]\nrpc_url = \"http://127.0.0.1:1\"\n[simulator]\ndry_run = true",
+        )
+        .unwrap();
+        let argus = ArgusBuilder::from_config(&config).build().unwrap();
+        assert!(!argus.pipeline().stage_names().contains(&"prefetch"));
+    }
+
+    #[test]
+    fn budget_is_shared_with_provider() {
+        let config = argus_config::Config::from_toml(
+            "[provider]\nrpc_url = \"http://127.0.0.1:1\"\nmax_in_flight = 4",
+        )
+        .unwrap();
+        let argus = ArgusBuilder::from_config(&config).build().unwrap();
+        assert_eq!(argus.provider.budget().limits().max_in_flight, Some(4));
+
+        let config = argus_config::Config::from_toml(
+            "[provider]\nrpc_url = \"http://127.0.0.1:1\"\nmax_rps = 0.0",
+        )
+        .unwrap();
+        assert!(ArgusBuilder::from_config(&config).build().is_err());
+    }
+
+    #[test]
+    fn fallback_urls_extend_the_primary() {
+        let config = argus_config::Config::from_toml(
+            "[provider]\nrpc_url = \"http://127.0.0.1:1\"\nfallback_rpc_urls = [\"http://127.0.0.1:2\"]",
+        )
+        .unwrap();
+        let argus = ArgusBuilder::from_config(&config).build().unwrap();
+        assert_eq!(argus.provider.active_url(), "http://127.0.0.1:1");
+        assert!(Argus::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .fallback_rpc_url("not a url")
+            .build()
+            .is_err());
+    }
+}
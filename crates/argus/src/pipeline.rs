@@ -0,0 +1,1521 @@
+//! Composable pipeline stages.
+//!
+//! Each stage reads and writes a shared [`PipelineContext`]. The runner
+//! times every stage uniformly, so custom stages (e.g. a tx filter between
+//! fetch and prefetch) get the same instrumentation as built-in ones.
+//!
+//! ```ignore
+//! let pipeline = argus
+//!     .pipeline()
+//!     .insert_after("fetch", TxFilterStage::new(|tx| tx.to == Some(router)));
+//! let ctx = pipeline.run(block).await?;
+//! ```
+
+use alloy_primitives::{B256, U256};
+use argus_analyzer::plugin::{
+    BlockAnalysisInput, BlockAnalysisPlugin, PluginOutput, SimulationObserver, TxInspectorPlugin,
+};
+use argus_analyzer::reporter::{Report, StateCoverage};
+use argus_analyzer::severity::SeverityScorer;
+use argus_analyzer::simulator::{BatchOptions, ProgressObserver};
+use argus_analyzer::spill::{build_conflict_graph_within, MemoryBudget};
+use argus_core::chain::{self, ChainConfig, Hardfork};
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::{
+    AccessList, BlockContext, ConflictGraph, FastHashMap, StorageLocation, Transaction, TxReceipt,
+};
+use argus_provider::{
+    BlockId, DataProvider, PrefetchSource, PrefetchStats, Prefetcher, ProgressHandler, WarmCacheDB,
+};
+use async_trait::async_trait;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Mutable state threaded through every stage.
+pub struct PipelineContext {
+    pub block_number: u64,
+    /// When set, fetches target this exact block (even if reorged out)
+    /// rather than whatever is canonical at `block_number`.
+    pub block_hash: Option<B256>,
+    /// Resolved by [`FetchStage`] unless preset with [`with_chain`](Self::with_chain).
+    pub chain: Option<&'static ChainConfig>,
+    /// Block timestamp, when known; selects the hardfork for simulation.
+    pub timestamp: Option<u64>,
+    /// Simulate under this hardfork instead of the chain's schedule; see
+    /// [`with_hardfork`](Self::with_hardfork).
+    pub hardfork: Option<Hardfork>,
+    /// Header fields simulation exposes to transactions (`NUMBER`,
+    /// `BASEFEE`, `COINBASE`, ...); revm defaults when unset.
+    pub block_context: Option<BlockContext>,
+    /// Shared so stages, plugins and results reference one copy.
+    pub transactions: Arc<[Transaction]>,
+    /// Block position of `transactions[0]`: the start of the range
+    /// [`FetchStage::with_tx_range`] kept, else 0.
+    pub first_tx_index: usize,
+    /// Receipts of `transactions`, filled by [`ReceiptsStage`].
+    pub receipts: Vec<TxReceipt>,
+    pub warm_db: Option<WarmCacheDB>,
+    /// What warming `warm_db` took; set by [`PrefetchStage`] and
+    /// [`DiscoverStage`].
+    pub prefetch_stats: Option<PrefetchStats>,
+    pub access_lists: Vec<AccessList>,
+    /// Per-tx results from inspector plugins.
+    pub plugin_outputs: Vec<PluginOutput>,
+    /// How much of the state simulation read was warm; set by warm
+    /// [`SimulateStage`] runs.
+    pub coverage: Option<StateCoverage>,
+    pub graph: Option<ConflictGraph>,
+    pub report: Option<Report>,
+    /// Pre-state values of contested slots, filled by [`SlotValuesStage`].
+    pub slot_values: Vec<(StorageLocation, B256)>,
+    pub timings: Vec<StageTiming>,
+    /// Fired to abandon the block; the runner and built-in stages stop promptly.
+    pub cancel: CancellationToken,
+    started: Instant,
+}
+
+impl PipelineContext {
+    pub fn new(block_number: u64) -> Self {
+        Self {
+            block_number,
+            block_hash: None,
+            chain: None,
+            timestamp: None,
+            hardfork: None,
+            block_context: None,
+            transactions: Vec::new().into(),
+            first_tx_index: 0,
+            receipts: Vec::new(),
+            warm_db: None,
+            prefetch_stats: None,
+            access_lists: Vec::new(),
+            plugin_outputs: Vec::new(),
+            coverage: None,
+            graph: None,
+            report: None,
+            slot_values: Vec::new(),
+            timings: Vec::new(),
+            cancel: CancellationToken::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Share `token` so cancelling it aborts this block's run.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Pin the chain instead of asking the provider.
+    pub fn with_chain(mut self, chain: &'static ChainConfig) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Simulate under `fork` regardless of the block's position in the
+    /// chain's fork schedule, e.g. for testnets or what-if runs.
+    pub fn with_hardfork(mut self, fork: Hardfork) -> Self {
+        self.hardfork = Some(fork);
+        self
+    }
+
+    /// Pin the block by hash; see [`block_hash`](Self::block_hash).
+    pub fn with_block_hash(mut self, hash: B256) -> Self {
+        self.block_hash = Some(hash);
+        self
+    }
+
+    /// The id stages should fetch by: the pinned hash, else the number.
+    pub fn block_id(&self) -> BlockId {
+        self.block_hash
+            .map_or(BlockId::number(self.block_number), BlockId::from)
+    }
+
+    /// The resolved chain, defaulting to Ethereum mainnet.
+    pub fn chain(&self) -> &'static ChainConfig {
+        self.chain.unwrap_or(&chain::ETHEREUM)
+    }
+
+    /// The hardfork to simulate under: the pinned one, else the chain's
+    /// fork at this block once its timestamp is known. `None` leaves revm's
+    /// latest spec.
+    pub fn hardfork(&self) -> Option<Hardfork> {
+        self.hardfork.or_else(|| {
+            self.timestamp
+                .map(|ts| self.chain().hardfork_at(self.block_number, ts))
+        })
+    }
+
+    /// Wall time since the pipeline started.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Recorded wall time of a completed stage.
+    pub fn stage_time(&self, stage: &str) -> Option<Duration> {
+        self.timings
+            .iter()
+            .find(|t| t.stage == stage)
+            .map(|t| t.elapsed)
+    }
+}
+
+/// Wall time spent in one stage.
+#[derive(Debug, Clone)]
+pub struct StageTiming {
+    pub stage: String,
+    pub elapsed: Duration,
+}
+
+/// One step of the analysis pipeline.
+#[async_trait]
+pub trait PipelineStage: Send + Sync {
+    /// Stable identifier used for timing and for positional insertion.
+    fn name(&self) -> &str;
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()>;
+}
+
+/// Ordered list of stages with a uniform, instrumented runner.
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn PipelineStage>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage.
+    pub fn stage(mut self, stage: impl PipelineStage + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Insert a stage right after the stage named `after`, or append if absent.
+    pub fn insert_after(mut self, after: &str, stage: impl PipelineStage + 'static) -> Self {
+        let pos = self.position(after).map_or(self.stages.len(), |i| i + 1);
+        self.stages.insert(pos, Box::new(stage));
+        self
+    }
+
+    /// Insert a stage right before the stage named `before`, or append if absent.
+    pub fn insert_before(mut self, before: &str, stage: impl PipelineStage + 'static) -> Self {
+        let pos = self.position(before).unwrap_or(self.stages.len());
+        self.stages.insert(pos, Box::new(stage));
+        self
+    }
+
+    /// Replace the stage named `name`, or append if absent.
+    pub fn replace(mut self, name: &str, stage: impl PipelineStage + 'static) -> Self {
+        match self.position(name) {
+            Some(i) => self.stages[i] = Box::new(stage),
+            None => self.stages.push(Box::new(stage)),
+        }
+        self
+    }
+
+    /// Drop the stage named `name`, if present.
+    pub fn remove(mut self, name: &str) -> Self {
+        self.stages.retain(|s| s.name() != name);
+        self
+    }
+
+    /// Stage names in execution order.
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Run all stages in order for `block_number`.
+    pub async fn run(&self, block_number: u64) -> ArgusResult<PipelineContext> {
+        let mut ctx = PipelineContext::new(block_number);
+        self.run_with(&mut ctx).await?;
+        Ok(ctx)
+    }
+
+    /// Run all stages against a caller-provided context.
+    ///
+    /// Fails with [`ArgusError::Cancelled`] as soon as `ctx.cancel` fires:
+    /// the running stage's future is dropped at its next await point.
+    pub async fn run_with(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let cancel = ctx.cancel.clone();
+        let block_number = ctx.block_number;
+        for stage in &self.stages {
+            let t = Instant::now();
+            tokio::select! {
+                biased;
+                _ = cancel.cancelled() => {
+                    tracing::info!(stage = stage.name(), "pipeline cancelled");
+                    return Err(ArgusError::Cancelled);
+                }
+                result = stage.run(ctx) => {
+                    result.in_stage(stage.name()).in_block(block_number)?
+                }
+            }
+            let elapsed = t.elapsed();
+
+            tracing::info!(
+                stage = stage.name(),
+                elapsed_ms = elapsed.as_millis(),
+                txs = ctx.transactions.len(),
+                lists = ctx.access_lists.len(),
+                "stage done"
+            );
+            ctx.timings.push(StageTiming {
+                stage: stage.name().to_string(),
+                elapsed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Split into the stages before `name` and the stages from `name` on.
+    /// If `name` is absent, the second half is empty.
+    pub fn split_before(mut self, name: &str) -> (Pipeline, Pipeline) {
+        let at = self.position(name).unwrap_or(self.stages.len());
+        let tail = self.stages.split_off(at);
+        (self, Pipeline { stages: tail })
+    }
+
+    /// Run as concurrent segments, cut before each stage named in
+    /// `boundaries`, so block N+1 can fetch while block N simulates.
+    ///
+    /// Every channel holds at most `capacity` blocks: a slow reader of
+    /// [`PipelineStream::output`] stalls the segments upstream rather than
+    /// letting them buffer without bound. Must be called within a tokio runtime.
+    pub fn spawn_stream(self, boundaries: &[&str], capacity: usize) -> PipelineStream {
+        let capacity = capacity.max(1);
+        let mut segments = Vec::new();
+        let mut rest = self;
+        for name in boundaries {
+            let (head, tail) = rest.split_before(name);
+            if !head.stages.is_empty() {
+                segments.push(head);
+            }
+            rest = tail;
+        }
+        segments.push(rest);
+
+        let (input, mut rx) = mpsc::channel(capacity);
+        for segment in segments {
+            let (tx, next) = mpsc::channel(capacity);
+            tokio::spawn(segment.run_segment(rx, tx));
+            rx = next;
+        }
+        PipelineStream {
+            input: StreamInput(input),
+            output: rx,
+        }
+    }
+
+    async fn run_segment(self, mut rx: mpsc::Receiver<StreamItem>, tx: mpsc::Sender<StreamItem>) {
+        while let Some((block_number, item)) = rx.recv().await {
+            // Failed blocks pass through so results stay in submission order.
+            let item = match item {
+                Ok(mut ctx) => self.run_with(&mut ctx).await.map(|()| ctx),
+                Err(e) => Err(e),
+            };
+            if tx.send((block_number, item)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|s| s.name() == name)
+    }
+}
+
+/// A block's context after the stream, or the error that stopped it.
+pub type StreamItem = (u64, ArgusResult<PipelineContext>);
+
+/// Feeds blocks into a [`PipelineStream`]. Drop every clone to let the
+/// stream drain and close its output.
+#[derive(Clone)]
+pub struct StreamInput(mpsc::Sender<StreamItem>);
+
+impl StreamInput {
+    /// Queue a block, waiting while the first segment's buffer is full.
+    pub async fn submit(&self, ctx: PipelineContext) -> ArgusResult<()> {
+        self.0
+            .send((ctx.block_number, Ok(ctx)))
+            .await
+            .map_err(|_| ArgusError::Internal("pipeline stream closed".into()))
+    }
+}
+
+/// A [`Pipeline`] running as concurrent segments joined by bounded channels.
+pub struct PipelineStream {
+    pub input: StreamInput,
+    /// Results in submission order.
+    pub output: mpsc::Receiver<StreamItem>,
+}
+
+// ---------------------------------------------------------------------------
+// Built-in stages
+// ---------------------------------------------------------------------------
+
+/// Fetches the block's transactions and header from a [`DataProvider`],
+/// resolves the chain, and drops system transactions.
+pub struct FetchStage {
+    provider: Arc<dyn DataProvider>,
+    tx_range: Option<Range<usize>>,
+}
+
+impl FetchStage {
+    pub fn new(provider: Arc<dyn DataProvider>) -> Self {
+        Self {
+            provider,
+            tx_range: None,
+        }
+    }
+
+    /// Keep only transactions at block positions `range` (end clamped to the
+    /// block size), so later stages prefetch and simulate just that slice.
+    pub fn with_tx_range(mut self, range: Option<Range<usize>>) -> Self {
+        self.tx_range = range;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for FetchStage {
+    fn name(&self) -> &str {
+        "fetch"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        if ctx.chain.is_none() {
+            let chain = self.provider.chain().await?;
+            if chain::lookup(chain.chain_id).is_none() {
+                tracing::warn!(
+                    chain_id = chain.chain_id,
+                    "unregistered chain; using generic EVM defaults"
+                );
+            }
+            ctx.chain = Some(chain);
+        }
+        let block = self.provider.get_block_context(ctx.block_id()).await?;
+        ctx.timestamp = Some(block.timestamp);
+        ctx.block_context = Some(block);
+
+        let chain = ctx.chain();
+        let mut transactions = self.provider.get_block_transactions(ctx.block_id()).await?;
+        if let Some(range) = &self.tx_range {
+            // Positions refer to the full block, before system txs are dropped.
+            if range.start > transactions.len() || range.start > range.end {
+                return Err(ArgusError::InvalidInput(format!(
+                    "tx range {range:?} outside block of {} txs",
+                    transactions.len()
+                )));
+            }
+            let end = range.end.min(transactions.len());
+            transactions.truncate(end);
+            transactions.drain(..range.start);
+            ctx.first_tx_index = range.start;
+        }
+        let before = transactions.len();
+        transactions.retain(|tx| !chain.is_system_account(&tx.from));
+        if transactions.len() < before {
+            tracing::debug!(
+                dropped = before - transactions.len(),
+                "skipping system transactions"
+            );
+        }
+        ctx.transactions = transactions.into();
+        Ok(())
+    }
+}
+
+/// Keeps only transactions matching a predicate.
+pub struct TxFilterStage<F> {
+    predicate: F,
+}
+
+impl<F> TxFilterStage<F>
+where
+    F: Fn(&Transaction) -> bool + Send + Sync,
+{
+    pub fn new(predicate: F) -> Self {
+        Self { predicate }
+    }
+}
+
+#[async_trait]
+impl<F> PipelineStage for TxFilterStage<F>
+where
+    F: Fn(&Transaction) -> bool + Send + Sync,
+{
+    fn name(&self) -> &str {
+        "filter"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        // Only rebuild (and copy the survivors) when something is dropped.
+        if !ctx.transactions.iter().all(|tx| (self.predicate)(tx)) {
+            ctx.transactions = ctx
+                .transactions
+                .iter()
+                .filter(|&tx| (self.predicate)(tx))
+                .cloned()
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+/// Appends transactions that are not in the block, e.g.
+/// [`HypotheticalTx`](argus_analyzer::whatif::HypotheticalTx)s for what-if
+/// runs, so they are prefetched and simulated with the rest.
+pub struct InjectStage {
+    transactions: Vec<Transaction>,
+}
+
+impl InjectStage {
+    pub fn new(transactions: Vec<Transaction>) -> Self {
+        Self { transactions }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for InjectStage {
+    fn name(&self) -> &str {
+        "inject"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        if !self.transactions.is_empty() {
+            ctx.transactions = ctx
+                .transactions
+                .iter()
+                .chain(&self.transactions)
+                .cloned()
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+/// Fetches receipts for the fetched transactions so the report can weight
+/// contention by gas used. Requires [`FetchStage`] to have run.
+pub struct ReceiptsStage {
+    provider: Arc<dyn DataProvider>,
+}
+
+impl ReceiptsStage {
+    pub fn new(provider: Arc<dyn DataProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for ReceiptsStage {
+    fn name(&self) -> &str {
+        "receipts"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let mut receipts: FastHashMap<B256, TxReceipt> = self
+            .provider
+            .get_block_receipts(ctx.block_id())
+            .await?
+            .into_iter()
+            .map(|receipt| (receipt.tx_hash, receipt))
+            .collect();
+        ctx.receipts = ctx
+            .transactions
+            .iter()
+            .filter_map(|tx| receipts.remove(&tx.hash))
+            .collect();
+        Ok(())
+    }
+}
+
+/// Warms state for the fetched transactions.
+pub struct PrefetchStage {
+    source: Arc<dyn PrefetchSource>,
+}
+
+impl PrefetchStage {
+    pub fn new(prefetcher: Prefetcher) -> Self {
+        Self::from_source(Arc::new(prefetcher))
+    }
+
+    /// Warm state from any [`PrefetchSource`], such as a `MockProvider`'s
+    /// canned accounts.
+    pub fn from_source(source: Arc<dyn PrefetchSource>) -> Self {
+        Self { source }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for PrefetchStage {
+    fn name(&self) -> &str {
+        "prefetch"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let (warm_db, stats) = self
+            .source
+            .prefetch_on(ctx.chain(), ctx.block_id(), &ctx.transactions)
+            .await?;
+        ctx.warm_db = Some(warm_db);
+        ctx.prefetch_stats = Some(stats);
+        Ok(())
+    }
+}
+
+/// Warms exactly the state the transactions read, found by simulating them
+/// against what has been fetched so far
+/// ([`Prefetcher::prefetch_discovered`]). Named `"prefetch"` so it can be
+/// swapped in with [`Pipeline::replace`].
+pub struct DiscoverStage {
+    prefetcher: Prefetcher,
+}
+
+impl DiscoverStage {
+    pub fn new(prefetcher: Prefetcher) -> Self {
+        Self { prefetcher }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for DiscoverStage {
+    fn name(&self) -> &str {
+        "prefetch"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let spec = ctx.hardfork().map(argus_analyzer::simulator::spec_id);
+        let (transactions, block) = (&ctx.transactions, ctx.block_context.as_ref());
+        let (warm_db, stats) = self
+            .prefetcher
+            .prefetch_discovered(ctx.block_id(), |db| {
+                if ctx.cancel.is_cancelled() {
+                    return Err(ArgusError::Cancelled);
+                }
+                argus_analyzer::simulator::discover_state(db, transactions, spec, block)
+            })
+            .await?;
+        ctx.warm_db = Some(warm_db);
+        ctx.prefetch_stats = Some(stats);
+        Ok(())
+    }
+}
+
+/// Restores warm state from a snapshot instead of prefetching over RPC.
+///
+/// Named `"prefetch"` so it can be swapped in with [`Pipeline::replace`].
+pub struct LoadStateStage {
+    path: PathBuf,
+}
+
+impl LoadStateStage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for LoadStateStage {
+    fn name(&self) -> &str {
+        "prefetch"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let (block_number, db) = argus_provider::snapshot::load_snapshot(&self.path)?;
+        if block_number != ctx.block_number {
+            return Err(ArgusError::InvalidInput(format!(
+                "snapshot {} is for block {block_number}, not {}",
+                self.path.display(),
+                ctx.block_number
+            )));
+        }
+        ctx.warm_db = Some(db);
+        Ok(())
+    }
+}
+
+/// Writes the warm state to a snapshot for later [`LoadStateStage`] runs.
+pub struct SaveStateStage {
+    path: PathBuf,
+}
+
+impl SaveStateStage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for SaveStateStage {
+    fn name(&self) -> &str {
+        "save_state"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let db = ctx
+            .warm_db
+            .as_ref()
+            .ok_or_else(|| ArgusError::Internal("save_state requires warm state".into()))?;
+        argus_provider::snapshot::save_snapshot(db, ctx.block_number, &self.path)
+    }
+}
+
+/// Simulates against the warm state if a prefetch ran, else `EmptyDB`.
+/// Either way execution uses [`PipelineContext::hardfork`]
+/// and the block's header as its environment; warm simulation also runs any registered [`TxInspectorPlugin`]s. An attached
+/// [`SimulationObserver`] sees each access list as it completes. With an
+/// RPC fallback, state the warm cache lacks is fetched from the node as it
+/// is read. Sequential mode runs transactions in block order over the state
+/// earlier ones committed.
+#[derive(Default)]
+pub struct SimulateStage {
+    plugins: Vec<Arc<dyn TxInspectorPlugin>>,
+    observer: Option<Arc<dyn SimulationObserver>>,
+    progress: Option<Arc<dyn ProgressHandler>>,
+    fallback: Option<Prefetcher>,
+    sequential: bool,
+    options: BatchOptions,
+}
+
+impl SimulateStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_plugins(mut self, plugins: Vec<Arc<dyn TxInspectorPlugin>>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    pub fn with_observer(mut self, observer: Option<Arc<dyn SimulationObserver>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Report each finished transaction to `handler`'s
+    /// [`on_tx_simulated`](ProgressHandler::on_tx_simulated).
+    pub fn with_progress(mut self, handler: Option<Arc<dyn ProgressHandler>>) -> Self {
+        self.progress = handler;
+        self
+    }
+
+    /// Execute transactions one after another, each over the state the
+    /// earlier ones committed (see
+    /// [`simulate_batch_sequential`](argus_analyzer::simulator::simulate_batch_sequential)),
+    /// instead of each in isolation over the warm state.
+    pub fn with_sequential(mut self, enabled: bool) -> Self {
+        self.sequential = enabled;
+        self
+    }
+
+    /// Abort any transaction still executing after `timeout`, recording it
+    /// as [`ExecutionOutcome::TimedOut`](argus_core::ExecutionOutcome::TimedOut).
+    pub fn with_tx_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.options.tx_timeout = timeout;
+        self
+    }
+
+    /// Thread pool, chunking, timeout and what gets recorded, replacing
+    /// any earlier [`with_tx_timeout`](Self::with_tx_timeout). Sequential
+    /// runs ignore the threading; the hardfork and block environment always
+    /// come from the block being simulated.
+    pub fn with_options(mut self, options: BatchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Simulate over [`Prefetcher::fallback_db`] instead of the bare warm
+    /// cache. Has no effect without warm state.
+    pub fn with_rpc_fallback(mut self, prefetcher: Option<Prefetcher>) -> Self {
+        self.fallback = prefetcher;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for SimulateStage {
+    fn name(&self) -> &str {
+        "simulate"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let offset = self.observer.as_deref().map(|inner| OffsetObserver {
+            inner,
+            offset: ctx.first_tx_index,
+        });
+        let inner = offset.as_ref().map(|o| o as &dyn SimulationObserver);
+        let progress = self
+            .progress
+            .as_deref()
+            .map(|handler| ProgressObserver::new(handler, inner, ctx.transactions.len()));
+        let observer = match &progress {
+            Some(progress) => Some(progress as &dyn SimulationObserver),
+            None => inner,
+        };
+        if let Some(o) = observer {
+            o.begin_block(ctx.chain(), ctx.block_number);
+        }
+        let options = BatchOptions {
+            spec: ctx.hardfork().map(argus_analyzer::simulator::spec_id),
+            block: ctx.block_context.clone(),
+            ..self.options.clone()
+        };
+        match &ctx.warm_db {
+            Some(warm_db) => {
+                let cancel = Some(&ctx.cancel);
+                use argus_analyzer::simulator::{
+                    simulate_batch_observed, simulate_batch_sequential,
+                };
+                let txs = &ctx.transactions;
+                let (lists, outputs, coverage) = match &self.fallback {
+                    Some(prefetcher) => {
+                        let db = prefetcher.fallback_db(warm_db.clone(), ctx.block_id())?;
+                        // Hand this worker's tasks off so the runtime keeps
+                        // serving the node requests simulation blocks on.
+                        tokio::task::block_in_place(|| {
+                            if self.sequential {
+                                simulate_batch_sequential(
+                                    &db,
+                                    txs,
+                                    &self.plugins,
+                                    observer,
+                                    cancel,
+                                    &options,
+                                )
+                            } else {
+                                simulate_batch_observed(
+                                    &db,
+                                    txs,
+                                    &self.plugins,
+                                    observer,
+                                    cancel,
+                                    &options,
+                                )
+                            }
+                        })?
+                    }
+                    None if self.sequential => simulate_batch_sequential(
+                        warm_db,
+                        txs,
+                        &self.plugins,
+                        observer,
+                        cancel,
+                        &options,
+                    )?,
+                    None => simulate_batch_observed(
+                        warm_db,
+                        txs,
+                        &self.plugins,
+                        observer,
+                        cancel,
+                        &options,
+                    )?,
+                };
+                ctx.access_lists = lists;
+                ctx.plugin_outputs = outputs;
+                ctx.coverage = Some(coverage);
+            }
+            None => {
+                if !self.plugins.is_empty() {
+                    tracing::warn!("inspector plugins only run against warm state");
+                }
+                tracing::info!("no warm state: simulating against EmptyDB");
+                ctx.access_lists = argus_analyzer::simulator::simulate_batch_in_block(
+                    Arc::clone(&ctx.transactions),
+                    options,
+                    ctx.cancel.clone(),
+                )
+                .await?;
+                // EmptyDB runs are sequential and fast; report them at the end.
+                if let Some(o) = observer {
+                    for (i, list) in ctx.access_lists.iter().enumerate() {
+                        o.on_tx(i, list);
+                    }
+                }
+            }
+        }
+        if let Some(o) = observer {
+            o.end_block();
+        }
+        Ok(())
+    }
+}
+
+/// Reports simulated transactions to `inner` by block position when only a
+/// range of the block was fetched.
+struct OffsetObserver<'a> {
+    inner: &'a dyn SimulationObserver,
+    offset: usize,
+}
+
+impl SimulationObserver for OffsetObserver<'_> {
+    fn begin_block(&self, chain: &'static ChainConfig, block_number: u64) {
+        self.inner.begin_block(chain, block_number);
+    }
+
+    fn on_tx(&self, index: usize, access_list: &AccessList) {
+        self.inner.on_tx(self.offset + index, access_list);
+    }
+
+    fn end_block(&self) {
+        self.inner.end_block();
+    }
+}
+
+/// Takes access lists from the node's tracer
+/// ([`DataProvider::trace_access_lists`]) instead of simulating, keeping
+/// those of the fetched transactions. Named `"simulate"` so it can be swapped
+/// in with [`Pipeline::replace`]; no prefetch is needed.
+pub struct TraceStage {
+    provider: Arc<dyn DataProvider>,
+}
+
+impl TraceStage {
+    pub fn new(provider: Arc<dyn DataProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for TraceStage {
+    fn name(&self) -> &str {
+        "simulate"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let mut traced: FastHashMap<B256, AccessList> = self
+            .provider
+            .trace_access_lists(ctx.block_id())
+            .await?
+            .into_iter()
+            .map(|list| (list.tx_hash, list))
+            .collect();
+        ctx.access_lists = ctx
+            .transactions
+            .iter()
+            .filter_map(|tx| traced.remove(&tx.hash))
+            .collect();
+        Ok(())
+    }
+}
+
+/// Builds the conflict graph from the access lists, spilling to disk when
+/// a [`MemoryBudget`] is set and the batch exceeds it.
+#[derive(Default)]
+pub struct GraphStage {
+    budget: Option<MemoryBudget>,
+    prune_same_value: bool,
+}
+
+impl GraphStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_budget(mut self, budget: Option<MemoryBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Drop write-write edges where both sides leave the slot holding the
+    /// same value, or one of them restores what it found.
+    pub fn with_same_value_pruning(mut self, enabled: bool) -> Self {
+        self.prune_same_value = enabled;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for GraphStage {
+    fn name(&self) -> &str {
+        "graph"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let mut graph = match &self.budget {
+            Some(budget) => build_conflict_graph_within(&ctx.access_lists, budget)?,
+            None => argus_analyzer::graph::build_conflict_graph(&ctx.access_lists),
+        };
+        if self.prune_same_value {
+            let pruned =
+                argus_analyzer::graph::prune_same_value_writes(&mut graph, &ctx.access_lists);
+            tracing::debug!(pruned, "pruned same-value write conflicts");
+        }
+        ctx.graph = Some(graph);
+        Ok(())
+    }
+}
+
+/// Builds the enriched report. Requires [`GraphStage`] to have run.
+#[derive(Default)]
+pub struct ReportStage {
+    severity: Option<Arc<dyn SeverityScorer>>,
+}
+
+impl ReportStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score contention severity with `scorer` instead of the default thresholds.
+    pub fn with_severity(mut self, scorer: Option<Arc<dyn SeverityScorer>>) -> Self {
+        self.severity = scorer;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for ReportStage {
+    fn name(&self) -> &str {
+        "report"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let graph = ctx
+            .graph
+            .as_ref()
+            .ok_or_else(|| ArgusError::Internal("report stage requires a graph".into()))?;
+
+        let mut report = Report::build(
+            ctx.block_number,
+            &ctx.access_lists,
+            graph,
+            ctx.stage_time("fetch").unwrap_or_default(),
+            ctx.elapsed(),
+        )
+        .with_chain(ctx.chain())
+        .with_timestamp(ctx.timestamp);
+        if !ctx.receipts.is_empty() {
+            report = report.with_receipts(&ctx.receipts);
+        }
+        if let Some(scorer) = &self.severity {
+            report = report.with_severity(Arc::clone(scorer));
+        }
+        if let Some(stats) = ctx.prefetch_stats {
+            report = report.with_prefetch_stats(stats);
+        }
+        if let Some(coverage) = &ctx.coverage {
+            report = report.with_coverage(coverage.clone());
+        }
+        ctx.report = Some(report);
+        Ok(())
+    }
+}
+
+/// Looks up the pre-state value of every contested slot in the warm cache.
+/// Slots the prefetcher did not warm are skipped; nothing is recorded in
+/// dry-run mode. Requires [`GraphStage`] to have run.
+pub struct SlotValuesStage;
+
+#[async_trait]
+impl PipelineStage for SlotValuesStage {
+    fn name(&self) -> &str {
+        "slot_values"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let graph = ctx
+            .graph
+            .as_ref()
+            .ok_or_else(|| ArgusError::Internal("slot_values stage requires a graph".into()))?;
+        let Some(db) = &ctx.warm_db else {
+            return Ok(());
+        };
+        ctx.slot_values = argus_analyzer::graph::contested_locations(graph)
+            .into_iter()
+            .filter_map(|location| {
+                let value = db
+                    .cache
+                    .accounts
+                    .get(&location.address)?
+                    .storage
+                    .get(&U256::from_be_bytes(location.slot.0))?;
+                Some((location, B256::from(*value)))
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+/// Re-executes the block under Block-STM on the warm state and adds the
+/// run's aborts and speedup to the report (see [`argus_analyzer::blockstm`]).
+/// Skipped in dry-run mode. Requires [`ReportStage`] to have run.
+pub struct BlockStmStage {
+    threads: usize,
+}
+
+impl BlockStmStage {
+    pub fn new(threads: usize) -> Self {
+        Self { threads }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for BlockStmStage {
+    fn name(&self) -> &str {
+        "block_stm"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let Some(db) = &ctx.warm_db else {
+            tracing::warn!("block-stm needs warm state; skipped");
+            return Ok(());
+        };
+        let spec = ctx.hardfork().map(argus_analyzer::simulator::spec_id);
+        let (_, run) = argus_analyzer::blockstm::execute_block_stm(
+            db,
+            &ctx.transactions,
+            spec,
+            ctx.block_context.as_ref(),
+            self.threads,
+            Some(&ctx.cancel),
+        )?;
+        let report = ctx
+            .report
+            .as_mut()
+            .ok_or_else(|| ArgusError::Internal("block_stm stage requires a report".into()))?;
+        report.sections.push(run.section());
+        Ok(())
+    }
+}
+
+/// Re-simulates the block sequentially in block order and in the orderings
+/// [`argus_analyzer::reorder`] proposes, adding how much the access lists
+/// and conflicts move to the report (see [`argus_analyzer::ordering`]).
+/// Skipped in dry-run mode. Requires [`ReportStage`] to have run.
+pub struct OrderingStage {
+    options: BatchOptions,
+}
+
+impl OrderingStage {
+    /// Re-simulate with the fee accounting and exclusions of `options`,
+    /// which should be those the block was simulated with; the hardfork and
+    /// block environment come from the block.
+    pub fn new(options: BatchOptions) -> Self {
+        Self { options }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for OrderingStage {
+    fn name(&self) -> &str {
+        "ordering"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let Some(db) = &ctx.warm_db else {
+            tracing::warn!("ordering sensitivity needs warm state; skipped");
+            return Ok(());
+        };
+        let graph = ctx
+            .graph
+            .as_ref()
+            .ok_or_else(|| ArgusError::Internal("ordering stage requires a graph".into()))?;
+        let orders = argus_analyzer::reorder::candidate_orderings(
+            &ctx.transactions,
+            &ctx.access_lists,
+            graph,
+        );
+        let options = BatchOptions {
+            spec: ctx.hardfork().map(argus_analyzer::simulator::spec_id),
+            block: ctx.block_context.clone(),
+            ..self.options.clone()
+        };
+        let sensitivity = argus_analyzer::ordering::resimulate_orderings(
+            db,
+            ctx.block_number,
+            &ctx.transactions,
+            &options,
+            &orders,
+            Some(&ctx.cancel),
+        )?;
+        let report = ctx
+            .report
+            .as_mut()
+            .ok_or_else(|| ArgusError::Internal("ordering stage requires a report".into()))?;
+        report.sections.push(sensitivity.section());
+        Ok(())
+    }
+}
+
+/// Runs [`BlockAnalysisPlugin`]s and appends their sections to the report.
+/// Requires [`ReportStage`] to have run.
+pub struct AnalysisPluginStage {
+    plugins: Vec<Arc<dyn BlockAnalysisPlugin>>,
+}
+
+impl AnalysisPluginStage {
+    pub fn new(plugins: Vec<Arc<dyn BlockAnalysisPlugin>>) -> Self {
+        Self { plugins }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for AnalysisPluginStage {
+    fn name(&self) -> &str {
+        "plugins"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+        let (Some(graph), Some(report)) = (&ctx.graph, &mut ctx.report) else {
+            return Err(ArgusError::Internal(
+                "plugins stage requires graph and report".into(),
+            ));
+        };
+
+        let input = BlockAnalysisInput {
+            block_number: ctx.block_number,
+            chain: ctx.chain.unwrap_or(&chain::ETHEREUM),
+            transactions: &ctx.transactions,
+            access_lists: &ctx.access_lists,
+            graph,
+            plugin_outputs: &ctx.plugin_outputs,
+            receipts: &ctx.receipts,
+        };
+        for plugin in &self.plugins {
+            match plugin.analyze(&input) {
+                Ok(section) => report.sections.push(section),
+                Err(e) => tracing::warn!(plugin = plugin.name(), error = %e, "plugin failed"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl PipelineStage for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn run(&self, ctx: &mut PipelineContext) -> ArgusResult<()> {
+            ctx.block_number += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn positional_insertion() {
+        let p = Pipeline::new()
+            .stage(Named("a"))
+            .stage(Named("c"))
+            .insert_after("a", Named("b"))
+            .insert_before("a", Named("start"))
+            .replace("c", Named("end"));
+        assert_eq!(p.stage_names(), vec!["start", "a", "b", "end"]);
+        assert_eq!(p.remove("b").stage_names(), vec!["start", "a", "end"]);
+    }
+
+    #[tokio::test]
+    async fn runner_times_every_stage() {
+        let ctx = Pipeline::new()
+            .stage(Named("a"))
+            .stage(Named("b"))
+            .run(0)
+            .await
+            .unwrap();
+        assert_eq!(ctx.block_number, 2);
+        assert_eq!(ctx.timings.len(), 2);
+        assert!(ctx.stage_time("b").is_some());
+    }
+
+    struct Hang;
+
+    #[async_trait]
+    impl PipelineStage for Hang {
+        fn name(&self) -> &str {
+            "hang"
+        }
+
+        async fn run(&self, _ctx: &mut PipelineContext) -> ArgusResult<()> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn cancellation_interrupts_running_stage() {
+        let token = CancellationToken::new();
+        let mut ctx = PipelineContext::new(1).with_cancellation(token.clone());
+        let pipeline = Pipeline::new().stage(Named("a")).stage(Hang);
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            token.cancel();
+        });
+        let err = pipeline.run_with(&mut ctx).await.unwrap_err();
+        canceller.await.unwrap();
+        assert!(err.is_cancelled());
+        assert_eq!(ctx.timings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_runs_segments_in_order() {
+        let PipelineStream { input, mut output } = Pipeline::new()
+            .stage(Named("a"))
+            .stage(Named("b"))
+            .spawn_stream(&["b"], 1);
+
+        let feed = tokio::spawn(async move {
+            for block in 0..5 {
+                input
+                    .submit(PipelineContext::new(block * 10))
+                    .await
+                    .unwrap();
+            }
+        });
+        let mut seen = Vec::new();
+        while let Some((block, ctx)) = output.recv().await {
+            let ctx = ctx.unwrap();
+            assert_eq!(ctx.block_number, block + 2);
+            assert_eq!(ctx.timings.len(), 2);
+            seen.push(block);
+        }
+        feed.await.unwrap();
+        assert_eq!(seen, vec![0, 10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn pinned_hardfork_overrides_the_schedule() {
+        let mut ctx = PipelineContext::new(21_000_000);
+        assert_eq!(ctx.hardfork(), None);
+        ctx.timestamp = Some(1_730_000_000);
+        assert_eq!(ctx.hardfork(), Some(Hardfork::Cancun));
+        let ctx = ctx.with_hardfork(Hardfork::Shanghai);
+        assert_eq!(ctx.hardfork(), Some(Hardfork::Shanghai));
+    }
+
+    #[tokio::test]
+    async fn fetch_resolves_chain_and_drops_system_txs() {
+        use argus_provider::{MockCall, MockProvider};
+        let tx = |from| Transaction {
+            from,
+            gas: 21_000,
+            ..Default::default()
+        };
+        let system = *chain::BASE.system_accounts.first().unwrap();
+        let provider = Arc::new(
+            MockProvider::new()
+                .with_chain_id(8453)
+                .with_block(7, vec![tx(system), tx(Default::default())]),
+        );
+
+        let ctx = Pipeline::new()
+            .stage(FetchStage::new(provider.clone()))
+            .run(7)
+            .await
+            .unwrap();
+        assert_eq!(ctx.chain().chain_id, 8453);
+        assert_eq!(ctx.timestamp, Some(84));
+        assert_eq!(ctx.block_context.as_ref().map(|b| b.number), Some(7));
+        assert_eq!(ctx.transactions.len(), 1);
+        assert_eq!(provider.call_count(&MockCall::ChainId), 1);
+    }
+
+    #[tokio::test]
+    async fn mock_state_drives_simulation_offline() {
+        use alloy_primitives::{Address, Bytes};
+        use argus_provider::{MockCall, MockProvider};
+        // SLOAD(0); SSTORE(0): every call reads and writes slot 0.
+        let counter = Address::repeat_byte(0xcc);
+        let code = Bytes::from_static(&[0x60, 0x00, 0x54, 0x60, 0x00, 0x55, 0x00]);
+        let call = |n: u8| Transaction {
+            hash: B256::with_last_byte(n),
+            from: Address::repeat_byte(n),
+            to: Some(counter),
+            gas: 100_000,
+            ..Default::default()
+        };
+        let provider = Arc::new(
+            MockProvider::new()
+                .with_block(2, vec![call(1), call(2)])
+                .with_account(counter, U256::ZERO, 1, code)
+                .with_storage(counter, U256::ZERO, U256::from(7)),
+        );
+
+        let ctx = Pipeline::new()
+            .stage(FetchStage::new(provider.clone()))
+            .stage(PrefetchStage::from_source(provider.clone()))
+            .stage(SimulateStage::new())
+            .stage(GraphStage::new())
+            .run(2)
+            .await
+            .unwrap();
+        assert_eq!(provider.call_count(&MockCall::Prefetch(2)), 1);
+        assert_eq!(ctx.prefetch_stats, Some(PrefetchStats::default()));
+        assert!(ctx.access_lists.iter().all(|al| !al.entries.is_empty()));
+        assert!(!ctx.graph.unwrap().is_empty());
+        // Only the senders weren't canned.
+        let coverage = ctx.coverage.unwrap();
+        assert_eq!((coverage.missed_accounts, coverage.missed_slots), (2, 0));
+        assert_eq!(coverage.slots, 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_keeps_only_tx_range() {
+        let tx = |n: u8| Transaction {
+            hash: [n; 32].into(),
+            gas: 21_000,
+            ..Default::default()
+        };
+        let provider =
+            Arc::new(argus_provider::MockProvider::new().with_block(3, (0..6).map(tx).collect()));
+        let fetch = |range| {
+            Pipeline::new().stage(FetchStage::new(provider.clone()).with_tx_range(Some(range)))
+        };
+
+        let ctx = fetch(2..4).run(3).await.unwrap();
+        let hashes: Vec<_> = ctx.transactions.iter().map(|t| t.hash[31]).collect();
+        assert_eq!(hashes, vec![2, 3]);
+        assert_eq!(fetch(4..100).run(3).await.unwrap().transactions.len(), 2);
+        assert!(fetch(7..9).run(3).await.is_err());
+
+        // Observers see block positions, not positions within the range.
+        #[derive(Default)]
+        struct Indices(std::sync::Mutex<Vec<usize>>);
+        impl SimulationObserver for Indices {
+            fn on_tx(&self, index: usize, _: &AccessList) {
+                self.0.lock().unwrap().push(index);
+            }
+        }
+        let seen = Arc::new(Indices::default());
+        let observer: Arc<dyn SimulationObserver> = seen.clone();
+        let ctx = fetch(2..4)
+            .stage(SimulateStage::new().with_observer(Some(observer)))
+            .run(3)
+            .await
+            .unwrap();
+        assert_eq!(ctx.first_tx_index, 2);
+        assert_eq!(*seen.0.lock().unwrap(), [2, 3]);
+    }
+
+    #[tokio::test]
+    async fn fetch_follows_pinned_hash() {
+        use argus_provider::{MockCall, MockProvider};
+        let orphan = B256::repeat_byte(0xab);
+        let provider = Arc::new(
+            MockProvider::new()
+                .with_block(5, Vec::new())
+                .with_block(6, Vec::new())
+                .with_block_hash(6, orphan),
+        );
+
+        // The context says block 5, but the hash pins block 6.
+        let mut ctx = PipelineContext::new(5).with_block_hash(orphan);
+        Pipeline::new()
+            .stage(FetchStage::new(provider.clone()))
+            .run_with(&mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(ctx.timestamp, Some(72));
+        assert_eq!(provider.call_count(&MockCall::BlockTransactions(6)), 1);
+    }
+
+    #[tokio::test]
+    async fn receipts_follow_fetched_txs() {
+        let tx = |n: u8| Transaction {
+            hash: [n; 32].into(),
+            gas: 21_000,
+            ..Default::default()
+        };
+        let receipt = |n: u8| TxReceipt {
+            tx_hash: [n; 32].into(),
+            gas_used: 21_000,
+            success: n != 1,
+        };
+        let provider = Arc::new(
+            argus_provider::MockProvider::new()
+                .with_block(4, (0..3).map(tx).collect())
+                .with_block_receipts(4, (0..3).map(receipt).collect()),
+        );
+
+        let ctx = Pipeline::new()
+            .stage(FetchStage::new(provider.clone()).with_tx_range(Some(1..3)))
+            .stage(ReceiptsStage::new(provider))
+            .stage(GraphStage::new())
+            .stage(ReportStage::new())
+            .run(4)
+            .await
+            .unwrap();
+        let hashes: Vec<_> = ctx.receipts.iter().map(|r| r.tx_hash[0]).collect();
+        assert_eq!(hashes, vec![1, 2]);
+        let report = ctx.report.unwrap();
+        assert_eq!(report.gas_used.len(), 2);
+        assert_eq!(report.failed_txs, 1);
+    }
+
+    #[tokio::test]
+    async fn filter_then_graph_and_report() {
+        let mut ctx = PipelineContext::new(1);
+        ctx.transactions = vec![Transaction {
+            gas: 21_000,
+            ..Default::default()
+        }]
+        .into();
+
+        Pipeline::new()
+            .stage(TxFilterStage::new(|tx: &Transaction| tx.to.is_some()))
+            .stage(GraphStage::new())
+            .stage(ReportStage::new())
+            .run_with(&mut ctx)
+            .await
+            .unwrap();
+
+        assert!(ctx.transactions.is_empty());
+        assert_eq!(ctx.report.unwrap().total_txs, 0);
+    }
+
+    struct Conflicts;
+
+    impl BlockAnalysisPlugin for Conflicts {
+        fn name(&self) -> &str {
+            "conflicts"
+        }
+
+        fn analyze(
+            &self,
+            input: &BlockAnalysisInput<'_>,
+        ) -> ArgusResult<argus_analyzer::plugin::ReportSection> {
+            Ok(argus_analyzer::plugin::ReportSection::new("Custom")
+                .line(format!("{} edges", input.graph.len())))
+        }
+    }
+
+    #[tokio::test]
+    async fn analysis_plugins_extend_report() {
+        let ctx = Pipeline::new()
+            .stage(GraphStage::new())
+            .stage(ReportStage::new())
+            .stage(AnalysisPluginStage::new(vec![Arc::new(Conflicts)]))
+            .run(1)
+            .await
+            .unwrap();
+        let report = ctx.report.unwrap();
+        assert_eq!(report.sections.len(), 1);
+        assert!(report.render(&ConflictGraph::default()).contains("0 edges"));
+    }
+
+    #[tokio::test]
+    async fn report_uses_context_chain() {
+        let mut ctx = PipelineContext::new(1).with_chain(&chain::BASE);
+        Pipeline::new()
+            .stage(GraphStage::new())
+            .stage(ReportStage::new())
+            .run_with(&mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(ctx.report.unwrap().chain.chain_id, 8453);
+    }
+}
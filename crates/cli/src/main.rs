@@ -1,13 +1,17 @@
 //! CLI for the Argus parallel EVM conflict analyzer.
 //!
-//! Pipeline: fetch txs -> prefetch state -> parallel simulate -> conflict graph -> report.
+//! Pipeline: fetch txs -> prefetch state -> parallel simulate -> conflict graph -> report
+//! (orchestrated by the `argus` facade crate).
 
 use clap::{Parser, Subcommand};
-use std::time::Instant;
 
 #[derive(Parser, Debug)]
 #[command(name = "argus", version, about = "Parallel EVM conflict analyzer")]
 struct Cli {
+    /// TOML config file (defaults < file < ARGUS_* env < flags).
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -16,11 +20,12 @@ struct Cli {
 enum Commands {
     /// Analyze a block for transaction conflicts.
     Analyze {
-        #[arg(short, long, env = "ARGUS_RPC_URL")]
-        rpc_url: String,
-
         #[arg(short, long)]
-        block: u64,
+        rpc_url: Option<String>,
+
+        /// Block number, 0x-prefixed hash, or latest / safe / finalized.
+        #[arg(short, long, value_parser = parse_block_id)]
+        block: argus::BlockId,
 
         #[arg(long, default_value_t = false)]
         json: bool,
@@ -29,15 +34,417 @@ enum Commands {
         #[arg(long, default_value_t = false)]
         dry_run: bool,
 
+        /// Simulate transactions in block order, each seeing the state
+        /// earlier ones wrote, instead of each against the pre-block state.
+        #[arg(long, default_value_t = false)]
+        sequential: bool,
+
+        /// Simulate under this hardfork (e.g. shanghai, cancun, prague)
+        /// instead of the one the chain's schedule gives for the block.
+        #[arg(long)]
+        spec: Option<argus_core::Hardfork>,
+
+        /// Abort any single transaction's simulation after this many
+        /// milliseconds and mark it timed out.
+        #[arg(long)]
+        tx_timeout_ms: Option<u64>,
+
+        /// Simulate on this many worker threads (default: one per core).
+        #[arg(long)]
+        threads: Option<usize>,
+
+        /// Count gas payment as a balance write to the sender and the
+        /// coinbase, so every pair of transactions conflicts on fees.
+        #[arg(long, default_value_t = false)]
+        strict_fees: bool,
+
+        /// Leave every access to ADDRESS out of the analysis, e.g. a system
+        /// contract every block touches (repeatable).
+        #[arg(long, value_name = "ADDRESS")]
+        exclude_address: Vec<alloy_primitives::Address>,
+
+        /// Leave one storage slot, given as `ADDRESS:SLOT`, out of the
+        /// analysis (repeatable).
+        #[arg(long, value_name = "ADDRESS:SLOT", value_parser = argus_config::parse_slot)]
+        exclude_slot: Vec<argus_core::StorageLocation>,
+
+        /// `full` warms every sender and call target; `lite` only the
+        /// --prefetch-top-k busiest contracts (fewer RPC calls, less accurate);
+        /// `prestate` exactly the touched state via the debug API; `proof`
+        /// the full set, verified against the state root via eth_getProof;
+        /// `discovered` exactly the touched state, found by re-simulating
+        /// until nothing new is read.
+        #[arg(long)]
+        prefetch: Option<argus_config::PrefetchMode>,
+
+        /// Contracts warmed by `--prefetch lite`.
+        #[arg(long)]
+        prefetch_top_k: Option<usize>,
+
+        /// Also warm the slots `eth_createAccessList` reports for each
+        /// transaction (one extra call per transaction).
+        #[arg(long, default_value_t = false)]
+        access_list_hints: bool,
+
+        /// Read storage slots in bulk through Multicall3 (needs state
+        /// override support in eth_call; falls back per slot otherwise).
+        #[arg(long, default_value_t = false)]
+        multicall: bool,
+
+        /// Fetch state the prefetch missed from the node while simulating,
+        /// instead of reading it as empty (one round trip per miss).
+        #[arg(long, default_value_t = false)]
+        rpc_fallback: bool,
+
         /// Sink output: "ndjson" writes NDJSON to stdout,
-        /// "ndjson:/path/to/file" writes to file.
+        /// "ndjson:/path/to/file" writes to file, "arrow:/dir" writes Arrow
+        /// IPC files (requires the `arrow` feature), "duckdb:/file.duckdb"
+        /// appends to a DuckDB database (requires the `duckdb` feature).
         #[arg(long)]
         sink: Option<String>,
+
+        /// Pin the chain instead of detecting it via eth_chainId.
+        #[arg(long)]
+        chain_id: Option<u64>,
+
+        /// Reuse per-block results cached under this directory.
+        #[arg(long)]
+        cache_dir: Option<std::path::PathBuf>,
+
+        /// Keep fetched block data and state under this directory, so
+        /// re-analyzing a block makes no RPC calls.
+        #[arg(long)]
+        rpc_cache_dir: Option<std::path::PathBuf>,
+
+        /// Restore prefetched state from a snapshot instead of fetching it.
+        #[arg(long, conflicts_with = "dry_run")]
+        load_state: Option<std::path::PathBuf>,
+
+        /// Save prefetched state to a snapshot for later `--load-state` runs.
+        #[arg(long, conflicts_with = "dry_run")]
+        save_state: Option<std::path::PathBuf>,
+
+        /// Take access lists from the node's prestateTracer
+        /// (debug_traceBlockByNumber) instead of simulating.
+        #[arg(long, default_value_t = false, conflicts_with_all = ["dry_run", "load_state", "save_state", "stream"])]
+        from_trace: bool,
+
+        /// Record the block and its state as a replay fixture for `argus bench`.
+        #[arg(long)]
+        record: Option<std::path::PathBuf>,
+
+        /// Report how much a better transaction order could improve parallelism.
+        #[arg(long, default_value_t = false)]
+        reorder: bool,
+
+        /// Compare Block-STM, pessimistic and static scheduling on N threads.
+        #[arg(long, value_name = "THREADS")]
+        cc_compare: Option<usize>,
+
+        /// Execute the block under Block-STM on N threads and report
+        /// re-executions and the speedup achieved.
+        #[arg(long, value_name = "THREADS")]
+        block_stm: Option<usize>,
+
+        /// Re-simulate the block in the orderings --reorder would suggest
+        /// and report how access lists and conflicts change.
+        #[arg(long, default_value_t = false)]
+        ordering_sensitivity: bool,
+
+        /// Report how well transactions' declared EIP-2930 access lists
+        /// match the storage they touched in simulation.
+        #[arg(long, default_value_t = false)]
+        declared_access: bool,
+
+        /// Stream per-tx access and conflict rows to stdout as NDJSON while
+        /// the block simulates.
+        #[arg(long, default_value_t = false)]
+        stream: bool,
+
+        /// Analyze only block positions START..END (either end optional),
+        /// e.g. `--tx-range 40..60`.
+        #[arg(long, value_parser = parse_tx_range)]
+        tx_range: Option<std::ops::Range<usize>>,
+
+        /// Analyze only transactions sent to ADDRESS (repeatable).
+        #[arg(long, value_name = "ADDRESS", conflicts_with = "tx_range")]
+        filter_to: Vec<alloy_primitives::Address>,
+
+        /// Analyze only transactions sent from ADDRESS (repeatable).
+        #[arg(long, value_name = "ADDRESS", conflicts_with = "tx_range")]
+        filter_from: Vec<alloy_primitives::Address>,
+
+        /// Analyze only calls to a 4-byte selector such as `0xa9059cbb`
+        /// (repeatable).
+        #[arg(long, value_name = "SELECTOR", conflicts_with = "tx_range")]
+        filter_selector: Vec<alloy_primitives::Selector>,
+
+        /// Analyze only transactions with a gas limit of at least GAS.
+        #[arg(long, value_name = "GAS", conflicts_with = "tx_range")]
+        filter_min_gas: Option<u64>,
+
+        /// Byte-identical output across runs: stamp rows with the block
+        /// time, zero wall-clock timings and sort every collection.
+        #[arg(long, default_value_t = false, conflicts_with = "stream")]
+        deterministic: bool,
+
+        /// Also write a `slot_values` row with the pre-state value of each
+        /// contested slot the prefetcher warmed (sink output only).
+        #[arg(long, default_value_t = false)]
+        slot_values: bool,
+
+        /// Fetch receipts (eth_getBlockReceipts) and weight contention by
+        /// gas used.
+        #[arg(long, default_value_t = false)]
+        receipts: bool,
+
+        /// Compare simulated gas used and status with the mined receipts
+        /// and report how often they diverge (implies --receipts).
+        #[arg(long, default_value_t = false)]
+        validate_gas: bool,
+
+        /// Ignore write-write conflicts where both transactions store the
+        /// same value or one restores the slot's original value.
+        #[arg(long, default_value_t = false)]
+        prune_same_value: bool,
+
+        /// Print RPC requests, errors, 429s, bytes and latency per method
+        /// to stderr when done.
+        #[arg(long, default_value_t = false)]
+        rpc_metrics: bool,
+    },
+
+    /// Compare dry-run (EmptyDB) and warm-state simulation for a block.
+    Compare {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// Block number, 0x-prefixed hash, or latest / safe / finalized.
+        #[arg(short, long, value_parser = parse_block_id)]
+        block: argus::BlockId,
+
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Pin the chain instead of detecting it via eth_chainId.
+        #[arg(long)]
+        chain_id: Option<u64>,
+
+        /// Reuse per-block results cached under this directory.
+        #[arg(long)]
+        cache_dir: Option<std::path::PathBuf>,
+
+        /// Restore prefetched state from a snapshot instead of fetching it.
+        #[arg(long)]
+        load_state: Option<std::path::PathBuf>,
+
+        /// Save prefetched state to a snapshot for later `--load-state` runs.
+        #[arg(long)]
+        save_state: Option<std::path::PathBuf>,
+    },
+
+    /// Write the warm state a block simulates against as anvil / genesis
+    /// alloc JSON, for cross-checking results in other EVM tools.
+    DumpState {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// Block number, 0x-prefixed hash, or latest / safe / finalized.
+        #[arg(short, long, value_parser = parse_block_id)]
+        block: argus::BlockId,
+
+        /// `anvil` (for `anvil --load-state`) or `alloc` (genesis alloc map).
+        #[arg(long, default_value = "anvil")]
+        format: argus_provider::state_json::StateFormat,
+
+        /// Output file (default: stdout).
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+
+        /// Pin the chain instead of detecting it via eth_chainId.
+        #[arg(long)]
+        chain_id: Option<u64>,
+
+        /// Convert a `--save-state` snapshot instead of prefetching.
+        #[arg(long)]
+        load_state: Option<std::path::PathBuf>,
+    },
+
+    /// Replay recorded block fixtures and report per-stage timings.
+    Bench {
+        /// Directory of fixtures written by `argus analyze --record`.
+        dir: std::path::PathBuf,
+
+        /// Replays per fixture.
+        #[arg(long, default_value_t = 3)]
+        iterations: usize,
+
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Previous `--json` output to compare mean stage times against.
+        #[arg(long)]
+        baseline: Option<std::path::PathBuf>,
+    },
+
+    /// Show the chronically hottest storage slots across analyzed blocks.
+    Hotspots {
+        /// Leaderboard store (defaults to `[hotspots] path` / ARGUS_HOTSPOTS_DB).
+        #[arg(long)]
+        db: Option<std::path::PathBuf>,
+
+        /// Number of slots to list.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Chain used to label contracts.
+        #[arg(long, default_value_t = 1)]
+        chain_id: u64,
+    },
+
+    /// Analyze blocks as they are produced and push results over SSE.
+    Watch {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// First block to analyze; later blocks follow in order.
+        #[arg(long)]
+        from: u64,
+
+        /// Retry interval while the next block is not yet available.
+        #[arg(long, default_value_t = 2000)]
+        interval_ms: u64,
+
+        /// Address for the Server-Sent Events endpoint.
+        #[arg(long, default_value = "127.0.0.1:8547")]
+        listen: std::net::SocketAddr,
+
+        /// Also stream per-tx access and conflict rows to stdout as NDJSON
+        /// while each block simulates.
+        #[arg(long, default_value_t = false)]
+        stream: bool,
+
+        /// Blocks allowed to queue between pipeline stages before fetching
+        /// pauses for a slow consumer.
+        #[arg(long, default_value_t = 4)]
+        buffer: usize,
+
+        /// Every N blocks, also push an `aggregate` event with rolling
+        /// statistics over the last --aggregate-window blocks.
+        #[arg(long)]
+        aggregate_every: Option<usize>,
+
+        /// Blocks the `aggregate` events cover.
+        #[arg(long, default_value_t = 100)]
+        aggregate_window: usize,
+
+        /// `full` warms every sender and call target; `lite` only the
+        /// --prefetch-top-k busiest contracts (fewer RPC calls, less accurate);
+        /// `prestate` exactly the touched state via the debug API; `proof`
+        /// the full set, verified against the state root via eth_getProof;
+        /// `discovered` exactly the touched state, found by re-simulating
+        /// until nothing new is read.
+        #[arg(long)]
+        prefetch: Option<argus_config::PrefetchMode>,
+
+        /// Contracts warmed by `--prefetch lite`.
+        #[arg(long)]
+        prefetch_top_k: Option<usize>,
+
+        /// Also warm the slots `eth_createAccessList` reports for each
+        /// transaction (one extra call per transaction).
+        #[arg(long, default_value_t = false)]
+        access_list_hints: bool,
+
+        /// Read storage slots in bulk through Multicall3 (needs state
+        /// override support in eth_call; falls back per slot otherwise).
+        #[arg(long, default_value_t = false)]
+        multicall: bool,
+
+        /// Fetch state the prefetch missed from the node while simulating,
+        /// instead of reading it as empty (one round trip per miss).
+        #[arg(long, default_value_t = false)]
+        rpc_fallback: bool,
+
+        /// Print RPC requests, errors, 429s, bytes and latency per method
+        /// to stderr on exit.
+        #[arg(long, default_value_t = false)]
+        rpc_metrics: bool,
+    },
+
+    /// Analyze a Flashbots-style bundle against its target block, or the
+    /// pending block if the target is not mined yet.
+    Bundle {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// JSON file with `eth_sendBundle` params:
+        /// `{"txs": ["0x..."], "blockNumber": "0x..."}`.
+        #[arg(long)]
+        file: std::path::PathBuf,
+
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Pin the chain instead of detecting it via eth_chainId.
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+
+    /// Append hypothetical transactions to a block and list the block's
+    /// transactions each would conflict with.
+    WhatIf {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// Block number, 0x-prefixed hash, or latest / safe / finalized.
+        #[arg(short, long, value_parser = parse_block_id)]
+        block: argus::BlockId,
+
+        /// JSON array of unsigned transactions:
+        /// `[{"from": "0x...", "to": "0x...", "value": "0x0", "input": "0x..."}]`.
+        #[arg(long)]
+        file: std::path::PathBuf,
+
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Pin the chain instead of detecting it via eth_chainId.
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+
+    /// Continuously score pending transactions for predicted conflicts.
+    Mempool {
+        #[arg(short, long)]
+        rpc_url: Option<String>,
+
+        /// Poll interval for the pending block, in milliseconds.
+        #[arg(long, default_value_t = 2000)]
+        interval_ms: u64,
+
+        /// Seed hotspots from this block's conflict graph (dry-run).
+        #[arg(long)]
+        hotspot_block: Option<u64>,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::process::ExitCode {
+    match run().await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            // Display, not Debug, so argus errors show their block/stage/tx context.
+            eprintln!("error: {e}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
@@ -46,6 +453,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     let cli = Cli::parse();
+    let mut config = argus_config::Config::load(cli.config.as_deref())?;
 
     match cli.command {
         Commands::Analyze {
@@ -53,40 +461,160 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             block,
             json,
             dry_run,
+            sequential,
+            spec,
+            tx_timeout_ms,
+            threads,
+            strict_fees,
+            exclude_address,
+            exclude_slot,
+            prefetch,
+            prefetch_top_k,
+            access_list_hints,
+            multicall,
+            rpc_fallback,
             sink,
+            chain_id,
+            cache_dir,
+            rpc_cache_dir,
+            load_state,
+            save_state,
+            from_trace,
+            record,
+            reorder,
+            cc_compare,
+            block_stm,
+            ordering_sensitivity,
+            declared_access,
+            stream,
+            deterministic,
+            tx_range,
+            filter_to,
+            filter_from,
+            filter_selector,
+            filter_min_gas,
+            slot_values,
+            receipts,
+            validate_gas,
+            prune_same_value,
+            rpc_metrics,
         } => {
-            let t0 = Instant::now();
-
-            tracing::info!(rpc_url = %rpc_url, block, dry_run, "starting analysis");
-
-            // 1. Fetch transactions from RPC.
-            let provider = argus_provider::rpc::RpcProvider::connect(&rpc_url).await?;
-            use argus_provider::DataProvider;
-            let transactions = provider.get_block_transactions(block).await?;
-            let t_fetch = t0.elapsed();
-            tracing::info!(
-                txs = transactions.len(),
-                elapsed_ms = t_fetch.as_millis(),
-                "fetched block"
+            apply_common_flags(&mut config, rpc_url, chain_id, cache_dir);
+            apply_prefetch_flags(
+                &mut config,
+                prefetch,
+                prefetch_top_k,
+                access_list_hints,
+                multicall,
+                rpc_fallback,
             );
-
-            // 2. Simulate.
-            let access_lists = if dry_run {
-                tracing::info!("dry_run mode: simulating against EmptyDB");
-                argus_analyzer::simulator::simulate_batch(transactions.clone()).await?
-            } else {
-                let prefetcher = argus_provider::Prefetcher::new(provider.into_provider());
-                let warm_db = prefetcher.prefetch(block, &transactions).await?;
-                argus_analyzer::simulator::simulate_batch_with_state(&warm_db, &transactions)?
-            };
-
-            let t_sim = t0.elapsed();
+            config.simulator.dry_run |= dry_run;
+            config.simulator.sequential |= sequential;
+            if spec.is_some() {
+                config.simulator.spec = spec;
+            }
+            if tx_timeout_ms.is_some() {
+                config.simulator.tx_timeout_ms = tx_timeout_ms;
+            }
+            if threads.is_some() {
+                config.simulator.threads = threads;
+            }
+            config.simulator.strict_fees |= strict_fees;
+            if !exclude_address.is_empty() {
+                config.simulator.excluded_addresses = exclude_address;
+            }
+            if !exclude_slot.is_empty() {
+                config.simulator.excluded_slots = exclude_slot;
+            }
+            if sink.is_some() {
+                config.sink.spec = sink;
+            }
+            if rpc_cache_dir.is_some() {
+                config.provider.rpc_cache_dir = rpc_cache_dir;
+            }
+            let dry_run = config.simulator.dry_run;
             tracing::info!(
-                lists = access_lists.len(),
-                elapsed_ms = t_sim.as_millis(),
-                "simulation done"
+                rpc_url = config.rpc_url()?,
+                %block,
+                dry_run,
+                "starting analysis"
             );
 
+            let mut builder =
+                argus::ArgusBuilder::from_config(&config).cancellation_token(cancel_on_ctrl_c());
+            if let Some(path) = load_state {
+                builder = builder.load_state(path);
+            }
+            if let Some(path) = save_state {
+                builder = builder.save_state(path);
+            }
+            if let Some(dir) = record {
+                builder = builder.record(dir);
+            }
+            if let Some(range) = tx_range {
+                builder = builder.tx_range(range);
+            }
+            builder = builder.tx_filter(argus::provider::TxFilter {
+                to: filter_to,
+                from: filter_from,
+                selectors: filter_selector.into_iter().map(|s| s.0).collect(),
+                min_gas: filter_min_gas,
+            });
+            if slot_values {
+                builder = builder.slot_values(true);
+            }
+            if receipts || validate_gas {
+                builder = builder.receipts(true);
+            }
+            if validate_gas {
+                builder = builder.analysis_plugin(std::sync::Arc::new(
+                    argus_analyzer::fidelity::GasValidationPlugin::default(),
+                ));
+            }
+            if prune_same_value {
+                builder = builder.prune_same_value_writes(true);
+            }
+            if from_trace {
+                builder = builder.from_trace(true);
+            }
+            if reorder {
+                builder = builder
+                    .analysis_plugin(std::sync::Arc::new(argus_analyzer::reorder::ReorderPlugin));
+            }
+            if let Some(threads) = cc_compare {
+                builder = builder.analysis_plugin(std::sync::Arc::new(
+                    argus_analyzer::concurrency::ConcurrencyPlugin { threads },
+                ));
+            }
+            if let Some(threads) = block_stm {
+                builder = builder.block_stm(threads);
+            }
+            if ordering_sensitivity {
+                builder = builder.ordering_sensitivity(true);
+            }
+            if declared_access {
+                builder = builder.analysis_plugin(std::sync::Arc::new(
+                    argus_analyzer::declared::DeclaredAccessPlugin,
+                ));
+            }
+            if stream {
+                builder = builder.simulation_observer(std::sync::Arc::new(
+                    argus_analyzer::sink::live::LiveNdjsonSink::new(std::io::stdout()),
+                ));
+            }
+            let argus = builder.connect().await?;
+            let argus::AnalysisResult {
+                access_lists,
+                mut graph,
+                mut report,
+                slot_values,
+                ..
+            } = argus.analyze(block).await?;
+            if deterministic {
+                graph.canonicalize();
+                report.make_deterministic();
+            }
+
             // Stats.
             let txs_with_accesses = access_lists
                 .iter()
@@ -95,35 +623,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let total_entries: usize = access_lists.iter().map(|al| al.entries.len()).sum();
             tracing::info!(txs_with_accesses, total_entries, "access list stats");
 
-            // 3. Build conflict graph.
-            let graph = argus_analyzer::graph::build_conflict_graph(&access_lists);
-            let t_total = t0.elapsed();
-
-            tracing::info!(
-                conflicts = graph.len(),
-                elapsed_ms = t_total.as_millis(),
-                "analysis complete"
-            );
-
-            // 4. Build report.
-            let report = argus_analyzer::reporter::Report::build(
-                block,
-                &access_lists,
-                &graph,
-                t_fetch,
-                t_total,
-            );
-
-            // 5. Sink output.
-            if let Some(ref sink_spec) = sink {
+            // Sink output.
+            if let Some(ref sink_spec) = config.sink.spec {
                 let (summary, conflicts) = report.to_rows_from_graph(&graph);
                 let contention = report.to_contention_events(&graph);
+                let slot_values = report.to_slot_values(&slot_values);
+                let log_rows: Vec<_> = access_lists
+                    .iter()
+                    .flat_map(|al| {
+                        argus_analyzer::sink::LogRow::from_access_list(
+                            summary.block_number,
+                            al,
+                            &summary.created_at,
+                        )
+                    })
+                    .collect();
 
                 if sink_spec == "ndjson" {
                     let mut s = argus_analyzer::sink::json_stream::JsonStreamSink::stdout();
                     s.write_summary(&summary)?;
                     s.write_conflicts(&conflicts)?;
                     s.write_contention_events(&contention)?;
+                    s.write_slot_values(&slot_values)?;
+                    s.write_log_rows(&log_rows)?;
                     let n = s.finish()?;
                     tracing::info!(rows = n, "ndjson sink: wrote to stdout");
                 } else if let Some(path) = sink_spec.strip_prefix("ndjson:") {
@@ -132,11 +654,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     s.write_summary(&summary)?;
                     s.write_conflicts(&conflicts)?;
                     s.write_contention_events(&contention)?;
+                    s.write_slot_values(&slot_values)?;
+                    s.write_log_rows(&log_rows)?;
                     let n = s.finish()?;
                     tracing::info!(rows = n, path, "ndjson sink: wrote to file");
+                } else if let Some(dir) = sink_spec.strip_prefix("arrow:") {
+                    write_arrow(
+                        std::path::Path::new(dir),
+                        &summary,
+                        &conflicts,
+                        &contention,
+                        &slot_values,
+                    )?;
+                } else if let Some(path) = sink_spec.strip_prefix("duckdb:") {
+                    write_duckdb(
+                        std::path::Path::new(path),
+                        &summary,
+                        &conflicts,
+                        &contention,
+                        &slot_values,
+                    )?;
                 } else {
                     eprintln!(
-                        "Unknown sink: {}. Use 'ndjson' or 'ndjson:/path'",
+                        "Unknown sink: {}. Use 'ndjson', 'ndjson:/path', 'arrow:/dir' or 'duckdb:/file'",
                         sink_spec
                     );
                 }
@@ -148,8 +688,486 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             } else {
                 print!("{}", report.render(&graph));
             }
+            if rpc_metrics {
+                eprint!("{}", argus.rpc_metrics().render());
+            }
+        }
+        Commands::Compare {
+            rpc_url,
+            block,
+            json,
+            chain_id,
+            cache_dir,
+            load_state,
+            save_state,
+        } => {
+            apply_common_flags(&mut config, rpc_url, chain_id, cache_dir);
+            tracing::info!(
+                rpc_url = config.rpc_url()?,
+                %block,
+                "starting accuracy comparison"
+            );
+
+            // Resolve tags once so both modes see the same block.
+            let block = argus::ArgusBuilder::from_config(&config)
+                .connect()
+                .await?
+                .resolve_block(block)
+                .await?;
+
+            // Both modes go through the facade so each is cached independently.
+            let analyze = |dry_run: bool| {
+                let mut builder = argus::ArgusBuilder::from_config(&config).dry_run(dry_run);
+                if let Some(path) = &load_state {
+                    builder = builder.load_state(path);
+                }
+                if let Some(path) = &save_state {
+                    builder = builder.save_state(path);
+                }
+                async move { builder.connect().await?.analyze(block.hash.into()).await }
+            };
+            let argus::AnalysisResult {
+                access_lists: dry,
+                graph: dry_graph,
+                ..
+            } = analyze(true).await?;
+            let argus::AnalysisResult {
+                access_lists: warm,
+                graph: warm_graph,
+                ..
+            } = analyze(false).await?;
+
+            let report = argus_analyzer::compare::AccuracyReport::build(
+                block.number,
+                &dry,
+                &warm,
+                &dry_graph,
+                &warm_graph,
+            );
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.render());
+            }
+        }
+        Commands::DumpState {
+            rpc_url,
+            block,
+            format,
+            out,
+            chain_id,
+            load_state,
+        } => {
+            apply_common_flags(&mut config, rpc_url, chain_id, None);
+            config.simulator.dry_run = false;
+            let mut builder =
+                argus::ArgusBuilder::from_config(&config).cancellation_token(cancel_on_ctrl_c());
+            if let Some(path) = load_state {
+                builder = builder.load_state(path);
+            }
+            let (block_number, db) = builder.connect().await?.warm_state(block).await?;
+
+            match &out {
+                Some(path) => argus_provider::state_json::write_state_json(
+                    &db,
+                    format,
+                    std::fs::File::create(path)?,
+                )?,
+                None => {
+                    argus_provider::state_json::write_state_json(&db, format, std::io::stdout())?
+                }
+            }
+            tracing::info!(
+                block = block_number,
+                accounts = db.cache.accounts.len(),
+                "state exported"
+            );
+        }
+        Commands::Bench {
+            dir,
+            iterations,
+            json,
+            baseline,
+        } => {
+            let report = argus::bench::run_corpus(&dir, iterations).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.render());
+            }
+
+            if let Some(path) = baseline {
+                let base: argus::bench::BenchReport =
+                    serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+                eprintln!("vs {} (argus {}):", path.display(), base.version);
+                for (stage, pct) in report.diff(&base) {
+                    eprintln!("  {stage:<12} {pct:+.1}%");
+                }
+            }
+        }
+        Commands::Hotspots {
+            db,
+            top,
+            json,
+            chain_id,
+        } => {
+            let path = db
+                .or(config.hotspots.path)
+                .ok_or("no hotspot store (--db, ARGUS_HOTSPOTS_DB, or [hotspots] path)")?;
+            let board = argus_analyzer::hotspots::HotSlotLeaderboard::load_or_new(
+                &path,
+                config.hotspots.half_life_blocks,
+            )?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&board.top(top))?);
+            } else {
+                let chain = argus_core::chain::resolve(chain_id);
+                print!("{}", board.render(chain, top));
+            }
+        }
+        Commands::Watch {
+            rpc_url,
+            from,
+            interval_ms,
+            listen,
+            stream,
+            buffer,
+            aggregate_every,
+            aggregate_window,
+            prefetch,
+            prefetch_top_k,
+            access_list_hints,
+            multicall,
+            rpc_fallback,
+            rpc_metrics,
+        } => {
+            apply_common_flags(&mut config, rpc_url, None, None);
+            apply_prefetch_flags(
+                &mut config,
+                prefetch,
+                prefetch_top_k,
+                access_list_hints,
+                multicall,
+                rpc_fallback,
+            );
+            let cancel = cancel_on_ctrl_c();
+            let mut builder =
+                argus::ArgusBuilder::from_config(&config).cancellation_token(cancel.clone());
+            if stream {
+                builder = builder.simulation_observer(std::sync::Arc::new(
+                    argus_analyzer::sink::live::LiveNdjsonSink::new(std::io::stdout()),
+                ));
+            }
+            let argus = builder.connect().await?;
+            let sse = argus_analyzer::sink::sse::SseBroadcaster::bind(listen).await?;
+            tracing::info!(addr = %sse.local_addr(), from, "watching; SSE endpoint ready");
+
+            // Blocks flow through bounded channels: fetching the next block
+            // overlaps simulating this one, and a slow publisher pauses fetching.
+            let argus::pipeline::PipelineStream { input, mut output } = argus.stream(buffer);
+            let interval = std::time::Duration::from_millis(interval_ms);
+            let feed = async {
+                // Owned here so the stream drains and closes once feeding stops.
+                let input = input;
+                let mut block = from;
+                while !cancel.is_cancelled() {
+                    if !argus.is_block_available(block).await {
+                        tokio::select! {
+                            _ = cancel.cancelled() => break,
+                            _ = tokio::time::sleep(interval) => continue,
+                        }
+                    }
+                    if input.submit(argus.context(block)).await.is_err() {
+                        break;
+                    }
+                    block += 1;
+                }
+            };
+            let publish = async {
+                let mut aggregator = aggregate_every.map(|n| {
+                    argus_analyzer::aggregate::RollingAggregator::new(aggregate_window)
+                        .with_dump_every(n)
+                });
+                while let Some((block, ctx)) = output.recv().await {
+                    match ctx.and_then(|ctx| argus.complete(ctx)) {
+                        Ok(argus::AnalysisResult { graph, report, .. }) => {
+                            let (summary, _) = report.to_rows_from_graph(&graph);
+                            let contention = report.to_contention_events(&graph);
+                            let clients = sse.publish_block(&summary, &contention);
+                            tracing::info!(
+                                block,
+                                conflicts = summary.total_conflicts,
+                                clients,
+                                "published block"
+                            );
+                            if let Some(row) =
+                                aggregator.as_mut().and_then(|a| a.push(&report, &graph))
+                            {
+                                sse.publish_aggregate(&row);
+                            }
+                        }
+                        Err(e) if e.is_cancelled() => {}
+                        Err(e) => tracing::warn!(block, error = %e, "block analysis failed"),
+                    }
+                }
+            };
+            tokio::join!(feed, publish);
+            if rpc_metrics {
+                eprint!("{}", argus.rpc_metrics().render());
+            }
+        }
+        Commands::Bundle {
+            rpc_url,
+            file,
+            json,
+            chain_id,
+        } => {
+            apply_common_flags(&mut config, rpc_url, chain_id, None);
+            let bundle: argus_provider::Bundle =
+                serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+            let argus = argus::ArgusBuilder::from_config(&config)
+                .cancellation_token(cancel_on_ctrl_c())
+                .connect()
+                .await?;
+            let (result, analysis) = argus.analyze_bundle(&bundle).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&analysis)?);
+            } else {
+                print!("{}", result.report.render(&result.graph));
+                println!(
+                    "Bundle: {} txs, {} intra-bundle conflicts, {} conflicts with {} competing txs{}",
+                    bundle.txs.len(),
+                    analysis.intra_conflicts.len(),
+                    analysis.competing_conflicts.len(),
+                    analysis.conflicting_competitors,
+                    if analysis.forces_serialization {
+                        " (writes a hot pool)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        Commands::WhatIf {
+            rpc_url,
+            block,
+            file,
+            json,
+            chain_id,
+        } => {
+            apply_common_flags(&mut config, rpc_url, chain_id, None);
+            let injected: Vec<argus::analyzer::whatif::HypotheticalTx> =
+                serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+            let argus = argus::ArgusBuilder::from_config(&config)
+                .cancellation_token(cancel_on_ctrl_c())
+                .connect()
+                .await?;
+            let block = argus.resolve_block(block).await?;
+            let (result, what_if) = argus.analyze_what_if(block.number, &injected).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&what_if)?);
+            } else {
+                print!("{}", result.report.render(&result.graph));
+                for (i, tx) in what_if.injected.iter().enumerate() {
+                    println!(
+                        "Injected #{i}: {} conflicts with {} block txs",
+                        tx.conflicts.len(),
+                        tx.conflicting_txs.len()
+                    );
+                    for hash in &tx.conflicting_txs {
+                        println!("  {hash}");
+                    }
+                }
+            }
+        }
+        Commands::Mempool {
+            rpc_url,
+            interval_ms,
+            hotspot_block,
+        } => {
+            apply_common_flags(&mut config, rpc_url, None, None);
+            // Pending-block polls draw Low-priority permits from the shared budget.
+            let budget = argus_provider::RpcBudget::new(argus_provider::BudgetLimits {
+                requests_per_second: config.provider.max_rps,
+                burst: config.provider.burst,
+                requests_per_day: config.provider.max_requests_per_day,
+                max_in_flight: config.provider.max_in_flight,
+            })?;
+            let provider = argus_provider::rpc::RpcProvider::connect(config.rpc_url()?)
+                .await?
+                .with_budget(budget);
+            use argus_provider::DataProvider;
+
+            let mut predictor = argus_analyzer::mempool::MempoolPredictor::new();
+            if let Some(block) = hotspot_block {
+                let txs = provider.get_block_transactions(block.into()).await?;
+                let lists = argus_analyzer::simulator::simulate_batch(txs).await?;
+                predictor.set_hotspots(&argus_analyzer::graph::build_conflict_graph(&lists));
+                tracing::info!(block, "seeded mempool hotspots");
+            }
+
+            let (tx, mut rx) =
+                tokio::sync::mpsc::channel::<argus_analyzer::mempool::PendingTxScore>(1024);
+            let writer = tokio::spawn(async move {
+                let mut s = argus_analyzer::sink::json_stream::JsonStreamSink::stdout();
+                while let Some(score) = rx.recv().await {
+                    s.write_pending_scores(&[score.to_row()])?;
+                    s.flush()?;
+                }
+                s.finish()
+            });
+
+            predictor
+                .run(&provider, std::time::Duration::from_millis(interval_ms), tx)
+                .await?;
+            let n = writer.await??;
+            tracing::info!(rows = n, "mempool predictor stopped");
         }
     }
 
     Ok(())
 }
+
+#[cfg(feature = "arrow")]
+fn write_arrow(
+    dir: &std::path::Path,
+    summary: &argus_analyzer::sink::BlockSummaryRow,
+    conflicts: &[argus_analyzer::sink::ConflictRow],
+    contention: &[argus_analyzer::sink::ContentionEvent],
+    slot_values: &[argus_analyzer::sink::SlotValueRow],
+) -> std::io::Result<()> {
+    let mut s = argus_analyzer::sink::arrow::ArrowIpcSink::create(dir)?;
+    s.write_summary(summary)?;
+    s.write_conflicts(conflicts)?;
+    s.write_contention_events(contention)?;
+    s.write_slot_values(slot_values)?;
+    let n = s.finish()?;
+    tracing::info!(rows = n, dir = %dir.display(), "arrow sink: wrote IPC files");
+    Ok(())
+}
+
+#[cfg(not(feature = "arrow"))]
+fn write_arrow(
+    _dir: &std::path::Path,
+    _summary: &argus_analyzer::sink::BlockSummaryRow,
+    _conflicts: &[argus_analyzer::sink::ConflictRow],
+    _contention: &[argus_analyzer::sink::ContentionEvent],
+    _slot_values: &[argus_analyzer::sink::SlotValueRow],
+) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "arrow sink requires building argus-cli with --features arrow",
+    ))
+}
+
+#[cfg(feature = "duckdb")]
+fn write_duckdb(
+    path: &std::path::Path,
+    summary: &argus_analyzer::sink::BlockSummaryRow,
+    conflicts: &[argus_analyzer::sink::ConflictRow],
+    contention: &[argus_analyzer::sink::ContentionEvent],
+    slot_values: &[argus_analyzer::sink::SlotValueRow],
+) -> std::io::Result<()> {
+    let mut s = argus_analyzer::sink::duckdb::DuckDbSink::open(path)?;
+    s.write_summary(summary)?;
+    s.write_conflicts(conflicts)?;
+    s.write_contention_events(contention)?;
+    s.write_slot_values(slot_values)?;
+    let n = s.rows_written();
+    tracing::info!(rows = n, path = %path.display(), "duckdb sink: appended rows");
+    Ok(())
+}
+
+#[cfg(not(feature = "duckdb"))]
+fn write_duckdb(
+    _path: &std::path::Path,
+    _summary: &argus_analyzer::sink::BlockSummaryRow,
+    _conflicts: &[argus_analyzer::sink::ConflictRow],
+    _contention: &[argus_analyzer::sink::ContentionEvent],
+    _slot_values: &[argus_analyzer::sink::SlotValueRow],
+) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "duckdb sink requires building argus-cli with --features duckdb",
+    ))
+}
+
+/// A decimal block number, or anything `BlockId` parses (hash, hex number, tag).
+fn parse_block_id(s: &str) -> Result<argus::BlockId, String> {
+    if let Ok(n) = s.parse::<u64>() {
+        return Ok(argus::BlockId::number(n));
+    }
+    s.parse::<argus::BlockId>()
+        .map_err(|e| format!("{s:?}: {e}"))
+}
+
+/// `START..END`, either end optional.
+fn parse_tx_range(s: &str) -> Result<std::ops::Range<usize>, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, got {s:?}"))?;
+    let bound = |v: &str, default| match v {
+        "" => Ok(default),
+        v => v.parse::<usize>().map_err(|e| format!("{v:?}: {e}")),
+    };
+    let range = bound(start, 0)?..bound(end, usize::MAX)?;
+    if range.start >= range.end {
+        return Err(format!("empty tx range {s:?}"));
+    }
+    Ok(range)
+}
+
+/// A token fired by the first Ctrl-C, so in-flight work unwinds cleanly.
+fn cancel_on_ctrl_c() -> argus::CancellationToken {
+    let token = argus::CancellationToken::new();
+    let fired = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            tracing::info!("interrupt received; cancelling");
+            fired.cancel();
+        }
+    });
+    token
+}
+
+/// Flags shared by several subcommands take precedence over config and env.
+fn apply_common_flags(
+    config: &mut argus_config::Config,
+    rpc_url: Option<String>,
+    chain_id: Option<u64>,
+    cache_dir: Option<std::path::PathBuf>,
+) {
+    if rpc_url.is_some() {
+        config.provider.rpc_url = rpc_url;
+    }
+    if chain_id.is_some() {
+        config.provider.chain_id = chain_id;
+    }
+    if cache_dir.is_some() {
+        config.simulator.cache_dir = cache_dir;
+    }
+}
+
+fn apply_prefetch_flags(
+    config: &mut argus_config::Config,
+    mode: Option<argus_config::PrefetchMode>,
+    top_k: Option<usize>,
+    access_list_hints: bool,
+    multicall: bool,
+    rpc_fallback: bool,
+) {
+    if let Some(mode) = mode {
+        config.prefetcher.mode = mode;
+    }
+    if let Some(k) = top_k {
+        config.prefetcher.top_k = k;
+    }
+    if access_list_hints {
+        config.prefetcher.access_list_hints = true;
+    }
+    if multicall {
+        config.prefetcher.multicall = true;
+    }
+    if rpc_fallback {
+        config.prefetcher.rpc_fallback = true;
+    }
+}
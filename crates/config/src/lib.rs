@@ -0,0 +1,566 @@
+//! Layered configuration for Argus.
+//!
+//! Precedence, lowest to highest:
+//!
+//! 1. Built-in defaults ([`Config::default`])
+//! 2. TOML file: `--config`, else `$ARGUS_CONFIG`, else `./argus.toml` if present
+//! 3. `ARGUS_*` environment variables ([`Config::apply_env`])
+//! 4. Command-line flags, applied by the caller on the returned struct
+//!
+//! ```toml
+//! [provider]
+//! rpc_url = "https://eth.llamarpc.com"
+//! fallback_rpc_urls = ["https://rpc.ankr.com/eth"]
+//! chain_id = 1
+//! max_rps = 25
+//! burst = 50
+//! rpc_cache_dir = "/var/cache/argus-rpc"
+//!
+//! [prefetcher]
+//! concurrency = 8
+//! mode = "lite"   # or "full" (default)
+//! top_k = 20
+//! batch_size = 50  # calls per JSON-RPC batch (default 1: unbatched)
+//!
+//! [simulator]
+//! cache_dir = "/var/cache/argus"
+//! ```
+
+use alloy_primitives::{Address, U256};
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{Hardfork, StorageLocation};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File looked up in the working directory when no path is given.
+pub const DEFAULT_FILE: &str = "argus.toml";
+
+/// Complete configuration shared by the CLI and the library facade.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub provider: ProviderConfig,
+    pub prefetcher: PrefetcherConfig,
+    pub simulator: SimulatorConfig,
+    pub sink: SinkConfig,
+    pub hotspots: HotspotsConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProviderConfig {
+    /// JSON-RPC endpoint: HTTP(S), WebSocket or IPC socket path. `ARGUS_RPC_URL`.
+    pub rpc_url: Option<String>,
+    /// Endpoints to fail over to, in order. `ARGUS_FALLBACK_RPC_URLS`
+    /// (comma-separated).
+    pub fallback_rpc_urls: Vec<String>,
+    /// Pin the chain instead of querying `eth_chainId`. `ARGUS_CHAIN_ID`.
+    pub chain_id: Option<u64>,
+    /// Sustained request rate across all components. `ARGUS_MAX_RPS`.
+    pub max_rps: Option<f64>,
+    /// Requests allowed back to back above `max_rps` (default: one second's
+    /// worth). `ARGUS_BURST`.
+    pub burst: Option<u32>,
+    /// Requests allowed per rolling day. `ARGUS_MAX_REQUESTS_PER_DAY`.
+    pub max_requests_per_day: Option<u64>,
+    /// Concurrent requests in flight. `ARGUS_MAX_IN_FLIGHT`.
+    pub max_in_flight: Option<usize>,
+    /// On-disk cache of block data and prefetched state.
+    /// `ARGUS_RPC_CACHE_DIR`.
+    pub rpc_cache_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrefetcherConfig {
+    /// Max concurrent RPC tasks. Unset: a burst's worth when `max_rps` is
+    /// set, else 1. `ARGUS_CONCURRENCY`.
+    pub concurrency: Option<usize>,
+    /// `"full"`, `"lite"`, `"prestate"`, `"proof"` or `"discovered"`.
+    /// `ARGUS_PREFETCH`.
+    pub mode: PrefetchMode,
+    /// Contracts warmed in lite mode. `ARGUS_PREFETCH_TOP_K`.
+    pub top_k: usize,
+    /// Calls packed per JSON-RPC batch; 1 disables batching.
+    /// `ARGUS_PREFETCH_BATCH_SIZE`.
+    pub batch_size: usize,
+    /// Also warm the slots `eth_createAccessList` reports per transaction.
+    /// `ARGUS_ACCESS_LIST_HINTS`.
+    pub access_list_hints: bool,
+    /// Read storage slots in bulk through Multicall3 with state overrides.
+    /// `ARGUS_MULTICALL`.
+    pub multicall: bool,
+    /// Fetch state the prefetch missed from the node during simulation.
+    /// `ARGUS_RPC_FALLBACK`.
+    pub rpc_fallback: bool,
+    /// Tries per fetch, the first included. `ARGUS_RETRY_MAX_ATTEMPTS`.
+    pub retry_max_attempts: u32,
+    /// Delay before the first retry, doubling per retry.
+    /// `ARGUS_RETRY_BASE_DELAY_MS`.
+    pub retry_base_delay_ms: u64,
+    /// Random spread of each delay, as a fraction (0.0 to 1.0).
+    /// `ARGUS_RETRY_JITTER`.
+    pub retry_jitter: f64,
+    /// Stop retrying a fetch after this long. `ARGUS_RETRY_MAX_ELAPSED_MS`.
+    pub retry_max_elapsed_ms: Option<u64>,
+}
+
+impl Default for PrefetcherConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: None,
+            mode: PrefetchMode::Full,
+            top_k: 20,
+            batch_size: 1,
+            access_list_hints: false,
+            multicall: false,
+            rpc_fallback: false,
+            retry_max_attempts: 4,
+            retry_base_delay_ms: 200,
+            retry_jitter: 0.0,
+            retry_max_elapsed_ms: None,
+        }
+    }
+}
+
+/// How much state to prefetch before simulating.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefetchMode {
+    /// Every sender and call target.
+    #[default]
+    Full,
+    /// Only the most-used contracts; cheaper but less accurate.
+    Lite,
+    /// Exactly the touched state, via `debug_traceBlockByNumber` with the
+    /// `prestateTracer`. Needs a node with the debug API.
+    Prestate,
+    /// Like `full`, with every value verified by `eth_getProof` against the
+    /// block's state root.
+    Proof,
+    /// Exactly the touched state, found by simulating the block against
+    /// what has been fetched so far until nothing new is read. Works on any
+    /// node; costs a few simulation rounds.
+    Discovered,
+}
+
+impl std::str::FromStr for PrefetchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "lite" => Ok(Self::Lite),
+            "prestate" => Ok(Self::Prestate),
+            "proof" => Ok(Self::Proof),
+            "discovered" => Ok(Self::Discovered),
+            other => Err(format!(
+                "unknown prefetch mode {other:?} (expected full, lite, prestate, proof or discovered)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulatorConfig {
+    /// Skip prefetch and simulate against `EmptyDB`. `ARGUS_DRY_RUN`.
+    pub dry_run: bool,
+    /// Run transactions in block order over cumulative state instead of
+    /// each in isolation. `ARGUS_SEQUENTIAL`.
+    pub sequential: bool,
+    /// Hardfork to simulate under instead of the chain's schedule, e.g.
+    /// `"cancun"`. `ARGUS_SPEC`.
+    pub spec: Option<Hardfork>,
+    /// Abort a transaction's simulation after this long, in milliseconds.
+    /// `ARGUS_TX_TIMEOUT_MS`.
+    pub tx_timeout_ms: Option<u64>,
+    /// Worker threads for parallel simulation (default: one per core).
+    /// `ARGUS_THREADS`.
+    pub threads: Option<usize>,
+    /// Minimum transactions each worker takes at once. `ARGUS_CHUNK_SIZE`.
+    pub chunk_size: Option<usize>,
+    /// Count gas payment as a write to the sender's and coinbase's balance.
+    /// `ARGUS_STRICT_FEES`.
+    pub strict_fees: bool,
+    /// Addresses left out of access lists and the conflict graph, e.g. a
+    /// system contract every block touches. `ARGUS_EXCLUDED_ADDRESSES`
+    /// (comma-separated).
+    pub excluded_addresses: Vec<Address>,
+    /// Storage slots left out the same way, as `{ address, slot }` tables.
+    /// `ARGUS_EXCLUDED_SLOTS` (comma-separated `ADDRESS:SLOT`).
+    pub excluded_slots: Vec<StorageLocation>,
+    /// Per-block result cache directory. `ARGUS_CACHE_DIR`.
+    pub cache_dir: Option<PathBuf>,
+    /// Memory for conflict-graph indexing before spilling to disk, in MiB.
+    /// `ARGUS_MEMORY_BUDGET_MB`.
+    pub memory_budget_mb: Option<u64>,
+    /// Directory for spill files (default: system temp dir). `ARGUS_SPILL_DIR`.
+    pub spill_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SinkConfig {
+    /// `"ndjson"` or `"ndjson:/path"`. `ARGUS_SINK`.
+    pub spec: Option<String>,
+}
+
+/// Cross-block hot-slot leaderboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotspotsConfig {
+    /// Leaderboard store; recording is off when unset. `ARGUS_HOTSPOTS_DB`.
+    pub path: Option<PathBuf>,
+    /// Blocks after which a conflict counts half as much.
+    pub half_life_blocks: f64,
+}
+
+impl Default for HotspotsConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            // ~1 day of mainnet blocks.
+            half_life_blocks: 7200.0,
+        }
+    }
+}
+
+impl Config {
+    /// Defaults, then the config file, then environment variables.
+    ///
+    /// `path` must exist when given; the implicit `./argus.toml` is optional.
+    pub fn load(path: Option<&Path>) -> ArgusResult<Self> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("ARGUS_CONFIG").map(PathBuf::from));
+
+        let mut config = match explicit {
+            Some(p) => Self::from_file(&p)?,
+            None if Path::new(DEFAULT_FILE).is_file() => Self::from_file(Path::new(DEFAULT_FILE))?,
+            None => Self::default(),
+        };
+        config.apply_env(|key| std::env::var(key).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> ArgusResult<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            ArgusError::InvalidInput(format!("cannot read config {}: {e}", path.display()))
+        })?;
+        let config = Self::from_toml(&text).map_err(|e| {
+            ArgusError::InvalidInput(format!("invalid config {}: {e}", path.display()))
+        })?;
+        tracing::debug!(path = %path.display(), "loaded config file");
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> ArgusResult<Self> {
+        toml::from_str(text).map_err(|e| ArgusError::InvalidInput(e.to_string()))
+    }
+
+    /// Overlay `ARGUS_*` variables read through `var`.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> ArgusResult<()> {
+        if let Some(v) = var("ARGUS_RPC_URL") {
+            self.provider.rpc_url = Some(v);
+        }
+        if let Some(v) = var("ARGUS_FALLBACK_RPC_URLS") {
+            self.provider.fallback_rpc_urls = split_list(&v).map(String::from).collect();
+        }
+        if let Some(v) = var("ARGUS_CHAIN_ID") {
+            self.provider.chain_id = Some(parse_env("ARGUS_CHAIN_ID", &v)?);
+        }
+        if let Some(v) = var("ARGUS_MAX_RPS") {
+            let rps: f64 = parse_env("ARGUS_MAX_RPS", &v)?;
+            if !(rps.is_finite() && rps > 0.0) {
+                return Err(ArgusError::InvalidInput(format!(
+                    "ARGUS_MAX_RPS={v}: must be positive"
+                )));
+            }
+            self.provider.max_rps = Some(rps);
+        }
+        if let Some(v) = var("ARGUS_BURST") {
+            self.provider.burst = Some(parse_env("ARGUS_BURST", &v)?);
+        }
+        if let Some(v) = var("ARGUS_MAX_REQUESTS_PER_DAY") {
+            self.provider.max_requests_per_day = Some(parse_env("ARGUS_MAX_REQUESTS_PER_DAY", &v)?);
+        }
+        if let Some(v) = var("ARGUS_MAX_IN_FLIGHT") {
+            let max: usize = parse_env("ARGUS_MAX_IN_FLIGHT", &v)?;
+            if max == 0 {
+                return Err(ArgusError::InvalidInput(format!(
+                    "ARGUS_MAX_IN_FLIGHT={v}: must be at least 1"
+                )));
+            }
+            self.provider.max_in_flight = Some(max);
+        }
+        if let Some(v) = var("ARGUS_RPC_CACHE_DIR") {
+            self.provider.rpc_cache_dir = Some(PathBuf::from(v));
+        }
+        if let Some(v) = var("ARGUS_CONCURRENCY") {
+            self.prefetcher.concurrency = Some(parse_env("ARGUS_CONCURRENCY", &v)?);
+        }
+        if let Some(v) = var("ARGUS_PREFETCH") {
+            self.prefetcher.mode = parse_env("ARGUS_PREFETCH", &v)?;
+        }
+        if let Some(v) = var("ARGUS_PREFETCH_TOP_K") {
+            self.prefetcher.top_k = parse_env("ARGUS_PREFETCH_TOP_K", &v)?;
+        }
+        if let Some(v) = var("ARGUS_PREFETCH_BATCH_SIZE") {
+            self.prefetcher.batch_size = parse_env("ARGUS_PREFETCH_BATCH_SIZE", &v)?;
+        }
+        if let Some(v) = var("ARGUS_ACCESS_LIST_HINTS") {
+            self.prefetcher.access_list_hints = parse_env("ARGUS_ACCESS_LIST_HINTS", &v)?;
+        }
+        if let Some(v) = var("ARGUS_MULTICALL") {
+            self.prefetcher.multicall = parse_env("ARGUS_MULTICALL", &v)?;
+        }
+        if let Some(v) = var("ARGUS_RPC_FALLBACK") {
+            self.prefetcher.rpc_fallback = parse_env("ARGUS_RPC_FALLBACK", &v)?;
+        }
+        if let Some(v) = var("ARGUS_RETRY_MAX_ATTEMPTS") {
+            self.prefetcher.retry_max_attempts = parse_env("ARGUS_RETRY_MAX_ATTEMPTS", &v)?;
+        }
+        if let Some(v) = var("ARGUS_RETRY_BASE_DELAY_MS") {
+            self.prefetcher.retry_base_delay_ms = parse_env("ARGUS_RETRY_BASE_DELAY_MS", &v)?;
+        }
+        if let Some(v) = var("ARGUS_RETRY_JITTER") {
+            self.prefetcher.retry_jitter = parse_env("ARGUS_RETRY_JITTER", &v)?;
+        }
+        if let Some(v) = var("ARGUS_RETRY_MAX_ELAPSED_MS") {
+            self.prefetcher.retry_max_elapsed_ms =
+                Some(parse_env("ARGUS_RETRY_MAX_ELAPSED_MS", &v)?);
+        }
+        if let Some(v) = var("ARGUS_DRY_RUN") {
+            self.simulator.dry_run = parse_env("ARGUS_DRY_RUN", &v)?;
+        }
+        if let Some(v) = var("ARGUS_SEQUENTIAL") {
+            self.simulator.sequential = parse_env("ARGUS_SEQUENTIAL", &v)?;
+        }
+        if let Some(v) = var("ARGUS_SPEC") {
+            self.simulator.spec = Some(parse_env("ARGUS_SPEC", &v)?);
+        }
+        if let Some(v) = var("ARGUS_TX_TIMEOUT_MS") {
+            self.simulator.tx_timeout_ms = Some(parse_env("ARGUS_TX_TIMEOUT_MS", &v)?);
+        }
+        if let Some(v) = var("ARGUS_THREADS") {
+            self.simulator.threads = Some(parse_env("ARGUS_THREADS", &v)?);
+        }
+        if let Some(v) = var("ARGUS_CHUNK_SIZE") {
+            self.simulator.chunk_size = Some(parse_env("ARGUS_CHUNK_SIZE", &v)?);
+        }
+        if let Some(v) = var("ARGUS_STRICT_FEES") {
+            self.simulator.strict_fees = parse_env("ARGUS_STRICT_FEES", &v)?;
+        }
+        if let Some(v) = var("ARGUS_EXCLUDED_ADDRESSES") {
+            self.simulator.excluded_addresses = split_list(&v)
+                .map(|address| parse_env("ARGUS_EXCLUDED_ADDRESSES", address))
+                .collect::<ArgusResult<_>>()?;
+        }
+        if let Some(v) = var("ARGUS_EXCLUDED_SLOTS") {
+            self.simulator.excluded_slots = split_list(&v)
+                .map(|slot| {
+                    parse_slot(slot).map_err(|e| {
+                        ArgusError::InvalidInput(format!("ARGUS_EXCLUDED_SLOTS={slot}: {e}"))
+                    })
+                })
+                .collect::<ArgusResult<_>>()?;
+        }
+        if let Some(v) = var("ARGUS_CACHE_DIR") {
+            self.simulator.cache_dir = Some(PathBuf::from(v));
+        }
+        if let Some(v) = var("ARGUS_MEMORY_BUDGET_MB") {
+            self.simulator.memory_budget_mb = Some(parse_env("ARGUS_MEMORY_BUDGET_MB", &v)?);
+        }
+        if let Some(v) = var("ARGUS_SPILL_DIR") {
+            self.simulator.spill_dir = Some(PathBuf::from(v));
+        }
+        if let Some(v) = var("ARGUS_SINK") {
+            self.sink.spec = Some(v);
+        }
+        if let Some(v) = var("ARGUS_HOTSPOTS_DB") {
+            self.hotspots.path = Some(PathBuf::from(v));
+        }
+        Ok(())
+    }
+
+    /// The RPC URL, or an error naming every place it can be set.
+    pub fn rpc_url(&self) -> ArgusResult<&str> {
+        self.provider.rpc_url.as_deref().ok_or_else(|| {
+            ArgusError::InvalidInput(
+                "rpc_url is required (--rpc-url, ARGUS_RPC_URL, or [provider] rpc_url)".into(),
+            )
+        })
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, value: &str) -> ArgusResult<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| ArgusError::InvalidInput(format!("{key}={value}: {e}")))
+}
+
+/// The non-empty items of a comma-separated variable.
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// Parse `ADDRESS:SLOT`, the slot as a 32-byte hex word or a decimal index,
+/// as `ARGUS_EXCLUDED_SLOTS` and the CLI's `--exclude-slot` take it.
+pub fn parse_slot(value: &str) -> Result<StorageLocation, String> {
+    let (address, slot) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected ADDRESS:SLOT, got {value:?}"))?;
+    let address = address.parse().map_err(|e| format!("{address:?}: {e}"))?;
+    let slot: U256 = slot.parse().map_err(|e| format!("{slot:?}: {e}"))?;
+    Ok(StorageLocation {
+        address,
+        slot: slot.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn defaults() {
+        let c = Config::default();
+        assert_eq!(c.prefetcher.concurrency, None);
+        assert!(!c.simulator.dry_run);
+        assert!(c.rpc_url().is_err());
+    }
+
+    #[test]
+    fn prefetch_mode_from_file_and_env() {
+        let mut c = Config::from_toml("[prefetcher]\nmode = \"lite\"\ntop_k = 5").unwrap();
+        assert_eq!(c.prefetcher.mode, PrefetchMode::Lite);
+        assert_eq!(c.prefetcher.top_k, 5);
+        c.apply_env(|k| (k == "ARGUS_PREFETCH").then(|| "full".to_string()))
+            .unwrap();
+        assert_eq!(c.prefetcher.mode, PrefetchMode::Full);
+        c.apply_env(|k| (k == "ARGUS_PREFETCH").then(|| "discovered".to_string()))
+            .unwrap();
+        assert_eq!(c.prefetcher.mode, PrefetchMode::Discovered);
+    }
+
+    #[test]
+    fn file_overrides_defaults_partially() {
+        let c = Config::from_toml(
+            r#"
+            [provider]
+            rpc_url = "http://localhost:8545"
+
+            [prefetcher]
+            concurrency = 8
+            "#,
+        )
+        .unwrap();
+        assert_eq!(c.rpc_url().unwrap(), "http://localhost:8545");
+        assert_eq!(c.prefetcher.concurrency, Some(8));
+        assert_eq!(c.simulator, SimulatorConfig::default());
+    }
+
+    #[test]
+    fn unknown_keys_rejected() {
+        assert!(Config::from_toml("[provider]\nrpc = \"x\"").is_err());
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let mut c = Config::from_toml("[prefetcher]\nconcurrency = 8").unwrap();
+        let env: HashMap<&str, &str> = [
+            ("ARGUS_CONCURRENCY", "2"),
+            ("ARGUS_DRY_RUN", "true"),
+            ("ARGUS_SEQUENTIAL", "true"),
+            ("ARGUS_SPEC", "shanghai"),
+            ("ARGUS_TX_TIMEOUT_MS", "250"),
+            ("ARGUS_THREADS", "4"),
+            ("ARGUS_STRICT_FEES", "true"),
+            (
+                "ARGUS_EXCLUDED_ADDRESSES",
+                "0x000f3df6d732807ef1319fb7b8bb8522d0beac02, ",
+            ),
+            (
+                "ARGUS_EXCLUDED_SLOTS",
+                "0x000f3df6d732807ef1319fb7b8bb8522d0beac02:7",
+            ),
+        ]
+        .into_iter()
+        .collect();
+        c.apply_env(|k| env.get(k).map(|v| v.to_string())).unwrap();
+        assert_eq!(c.prefetcher.concurrency, Some(2));
+        assert!(c.simulator.dry_run);
+        assert!(c.simulator.sequential);
+        assert_eq!(c.simulator.spec, Some(Hardfork::Shanghai));
+        assert_eq!(c.simulator.tx_timeout_ms, Some(250));
+        assert_eq!(c.simulator.threads, Some(4));
+        assert!(c.simulator.strict_fees);
+        let beacon_roots: Address = "0x000f3df6d732807ef1319fb7b8bb8522d0beac02"
+            .parse()
+            .unwrap();
+        assert_eq!(c.simulator.excluded_addresses, [beacon_roots]);
+        assert_eq!(
+            c.simulator.excluded_slots,
+            [StorageLocation {
+                address: beacon_roots,
+                slot: U256::from(7).into(),
+            }]
+        );
+        assert!(c
+            .apply_env(|k| (k == "ARGUS_EXCLUDED_SLOTS").then(|| "7".to_string()))
+            .is_err());
+    }
+
+    #[test]
+    fn env_rejects_stalling_rpc_limits() {
+        for (key, value) in [
+            ("ARGUS_MAX_RPS", "0"),
+            ("ARGUS_MAX_RPS", "-2"),
+            ("ARGUS_MAX_IN_FLIGHT", "0"),
+        ] {
+            let mut c = Config::default();
+            let err = c.apply_env(|k| (k == key).then(|| value.to_string()));
+            assert!(
+                matches!(err, Err(ArgusError::InvalidInput(_))),
+                "{key}={value}"
+            );
+        }
+    }
+
+    #[test]
+    fn retry_settings_from_file_and_env() {
+        let mut c =
+            Config::from_toml("[prefetcher]\nretry_max_attempts = 8\nretry_jitter = 0.2").unwrap();
+        assert_eq!(c.prefetcher.retry_max_attempts, 8);
+        assert_eq!(c.prefetcher.retry_base_delay_ms, 200);
+        c.apply_env(|k| (k == "ARGUS_RETRY_MAX_ELAPSED_MS").then(|| "30000".to_string()))
+            .unwrap();
+        assert_eq!(c.prefetcher.retry_max_elapsed_ms, Some(30_000));
+        assert_eq!(c.prefetcher.retry_jitter, 0.2);
+    }
+
+    #[test]
+    fn fallback_urls_from_env_list() {
+        let mut c = Config::default();
+        c.apply_env(|k| (k == "ARGUS_FALLBACK_RPC_URLS").then(|| "http://a, http://b,".into()))
+            .unwrap();
+        assert_eq!(c.provider.fallback_rpc_urls, ["http://a", "http://b"]);
+    }
+
+    #[test]
+    fn bad_env_value_is_reported() {
+        let mut c = Config::default();
+        let err = c
+            .apply_env(|k| (k == "ARGUS_CHAIN_ID").then(|| "mainnet".to_string()))
+            .unwrap_err();
+        assert!(err.to_string().contains("ARGUS_CHAIN_ID"));
+    }
+}
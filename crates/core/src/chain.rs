@@ -0,0 +1,381 @@
+//! Per-chain configuration registry.
+//!
+//! Everything that differs between networks -- fork schedule, label set,
+//! prefetch slot pack, explorer links, system accounts -- hangs off one
+//! [`ChainConfig`] so callers resolve a chain id once and pass it along.
+//! [`resolve`] also covers chains missing from the registry.
+
+use crate::labels::{self, ContractLabel, LabelSet};
+use alloy_primitives::{address, Address, B256};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// EVM hardforks relevant to simulation, in activation order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hardfork {
+    Frontier,
+    Homestead,
+    #[serde(alias = "tangerine")]
+    TangerineWhistle,
+    #[serde(alias = "spurious")]
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    #[serde(alias = "merge")]
+    Paris,
+    Shanghai,
+    Cancun,
+    Prague,
+}
+
+impl std::str::FromStr for Hardfork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "frontier" => Ok(Self::Frontier),
+            "homestead" => Ok(Self::Homestead),
+            "tangerinewhistle" | "tangerine" => Ok(Self::TangerineWhistle),
+            "spuriousdragon" | "spurious" => Ok(Self::SpuriousDragon),
+            "byzantium" => Ok(Self::Byzantium),
+            "constantinople" => Ok(Self::Constantinople),
+            "petersburg" => Ok(Self::Petersburg),
+            "istanbul" => Ok(Self::Istanbul),
+            "berlin" => Ok(Self::Berlin),
+            "london" => Ok(Self::London),
+            "paris" | "merge" => Ok(Self::Paris),
+            "shanghai" => Ok(Self::Shanghai),
+            "cancun" => Ok(Self::Cancun),
+            "prague" => Ok(Self::Prague),
+            _ => Err(format!(
+                "unknown hardfork {s:?} (expected frontier, homestead, tangerinewhistle, \
+                 spuriousdragon, byzantium, constantinople, petersburg, istanbul, berlin, \
+                 london, paris, shanghai, cancun or prague)"
+            )),
+        }
+    }
+}
+
+/// When a hardfork activates: pre-merge forks by block, later ones by timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkActivation {
+    Block(u64),
+    Timestamp(u64),
+}
+
+/// Hot-slot pack used by the prefetcher for a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotPack {
+    Ethereum,
+    None,
+}
+
+/// Transaction-format family. Non-Ethereum families add transaction types
+/// the Ethereum RPC types don't decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainFamily {
+    Ethereum,
+    /// OP Stack L2s: deposit transactions (type `0x7e`).
+    OpStack,
+    /// Arbitrum Nitro: deposit, retryable and ArbOS internal transactions
+    /// (types `0x64`-`0x6a`).
+    Arbitrum,
+}
+
+/// Static description of one network.
+#[derive(Debug)]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub name: &'static str,
+    /// Hardforks in activation order. Anything before the first is Frontier.
+    pub forks: &'static [(Hardfork, ForkActivation)],
+    pub labels: LabelSet,
+    pub slot_pack: SlotPack,
+    /// Explorer base URL, e.g. `https://etherscan.io`.
+    pub explorer: Option<&'static str>,
+    /// Protocol-level senders and predeploys; their txs are not user activity.
+    pub system_accounts: &'static [Address],
+    pub family: ChainFamily,
+}
+
+impl ChainConfig {
+    /// The hardfork active at `block_number` / `timestamp`.
+    pub fn hardfork_at(&self, block_number: u64, timestamp: u64) -> Hardfork {
+        self.forks
+            .iter()
+            .filter(|(_, at)| match *at {
+                ForkActivation::Block(b) => block_number >= b,
+                ForkActivation::Timestamp(t) => timestamp >= t,
+            })
+            .map(|(fork, _)| *fork)
+            .max()
+            .unwrap_or(Hardfork::Frontier)
+    }
+
+    /// Label for a contract on this chain, if known.
+    pub fn label(&self, address: &Address) -> Option<&'static ContractLabel> {
+        labels::lookup_in(self.labels, address)
+    }
+
+    pub fn is_system_account(&self, address: &Address) -> bool {
+        self.system_accounts.contains(address)
+    }
+
+    pub fn explorer_address_url(&self, address: &Address) -> Option<String> {
+        self.explorer
+            .map(|base| format!("{base}/address/{address}"))
+    }
+
+    pub fn explorer_tx_url(&self, tx_hash: &B256) -> Option<String> {
+        self.explorer.map(|base| format!("{base}/tx/{tx_hash}"))
+    }
+}
+
+/// Returns the config for a known chain id.
+pub fn lookup(chain_id: u64) -> Option<&'static ChainConfig> {
+    CHAINS.iter().find(|c| c.chain_id == chain_id)
+}
+
+/// All registered chains.
+pub fn all() -> &'static [ChainConfig] {
+    CHAINS
+}
+
+/// The config for `chain_id`, or [`unregistered`] for chains missing from
+/// the registry.
+pub fn resolve(chain_id: u64) -> &'static ChainConfig {
+    lookup(chain_id).unwrap_or_else(|| unregistered(chain_id))
+}
+
+/// Config for a chain without a registry entry, e.g. a devnet: Ethereum
+/// transaction rules and system contracts, every fork active from genesis,
+/// no labels, slot pack or explorer. Built once per chain id.
+pub fn unregistered(chain_id: u64) -> &'static ChainConfig {
+    static UNREGISTERED: Mutex<Vec<&'static ChainConfig>> = Mutex::new(Vec::new());
+    let mut configs = UNREGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(config) = configs.iter().copied().find(|c| c.chain_id == chain_id) {
+        return config;
+    }
+    let config: &'static ChainConfig = Box::leak(Box::new(ChainConfig {
+        chain_id,
+        name: "Unregistered",
+        forks: &[(Hardfork::Prague, ForkActivation::Block(0))],
+        labels: LabelSet::None,
+        slot_pack: SlotPack::None,
+        explorer: None,
+        system_accounts: ETHEREUM_SYSTEM_ACCOUNTS,
+        family: ChainFamily::Ethereum,
+    }));
+    configs.push(config);
+    config
+}
+
+/// Beacon roots (EIP-4788), history storage (EIP-2935), and the system caller.
+const ETHEREUM_SYSTEM_ACCOUNTS: &[Address] = &[
+    address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02"),
+    address!("0000F90827F1C53a10cb7A02335B175320002935"),
+    address!("fffffffffffffffffffffffffffffffffffffffe"),
+];
+
+/// L1 attributes depositor, L1Block, and the fee vaults.
+const OP_STACK_SYSTEM_ACCOUNTS: &[Address] = &[
+    address!("DeaDDEaDDeAdDeAdDEAdDEaddeAddEAdDEAd0001"),
+    address!("4200000000000000000000000000000000000015"),
+    address!("4200000000000000000000000000000000000011"),
+    address!("4200000000000000000000000000000000000019"),
+    address!("420000000000000000000000000000000000001A"),
+];
+
+/// ArbOS, the sender of Arbitrum's internal (start-block) transactions.
+const ARBITRUM_SYSTEM_ACCOUNTS: &[Address] =
+    &[address!("00000000000000000000000000000000000A4B05")];
+
+/// Canyon, Ecotone, and Isthmus bring the OP Stack to Shanghai, Cancun,
+/// and Prague EVM semantics; these timestamps are shared by the superchain.
+const OP_STACK_FORKS_AFTER_BEDROCK: [(Hardfork, ForkActivation); 3] = [
+    (Hardfork::Shanghai, ForkActivation::Timestamp(1_704_992_401)),
+    (Hardfork::Cancun, ForkActivation::Timestamp(1_710_374_401)),
+    (Hardfork::Prague, ForkActivation::Timestamp(1_746_806_401)),
+];
+
+pub const ETHEREUM: ChainConfig = ChainConfig {
+    chain_id: 1,
+    name: "Ethereum",
+    forks: &[
+        (Hardfork::Homestead, ForkActivation::Block(1_150_000)),
+        (Hardfork::TangerineWhistle, ForkActivation::Block(2_463_000)),
+        (Hardfork::SpuriousDragon, ForkActivation::Block(2_675_000)),
+        (Hardfork::Byzantium, ForkActivation::Block(4_370_000)),
+        // Petersburg shipped in the same block, undoing EIP-1283.
+        (Hardfork::Constantinople, ForkActivation::Block(7_280_000)),
+        (Hardfork::Petersburg, ForkActivation::Block(7_280_000)),
+        (Hardfork::Istanbul, ForkActivation::Block(9_069_000)),
+        (Hardfork::Berlin, ForkActivation::Block(12_244_000)),
+        (Hardfork::London, ForkActivation::Block(12_965_000)),
+        (Hardfork::Paris, ForkActivation::Block(15_537_394)),
+        (Hardfork::Shanghai, ForkActivation::Timestamp(1_681_338_455)),
+        (Hardfork::Cancun, ForkActivation::Timestamp(1_710_338_135)),
+        (Hardfork::Prague, ForkActivation::Timestamp(1_746_612_311)),
+    ],
+    labels: LabelSet::Ethereum,
+    slot_pack: SlotPack::Ethereum,
+    explorer: Some("https://etherscan.io"),
+    system_accounts: ETHEREUM_SYSTEM_ACCOUNTS,
+    family: ChainFamily::Ethereum,
+};
+
+pub const SEPOLIA: ChainConfig = ChainConfig {
+    chain_id: 11_155_111,
+    name: "Sepolia",
+    forks: &[
+        (Hardfork::London, ForkActivation::Block(0)),
+        (Hardfork::Paris, ForkActivation::Block(1_735_371)),
+        (Hardfork::Shanghai, ForkActivation::Timestamp(1_677_557_088)),
+        (Hardfork::Cancun, ForkActivation::Timestamp(1_706_655_072)),
+        (Hardfork::Prague, ForkActivation::Timestamp(1_741_159_776)),
+    ],
+    labels: LabelSet::None,
+    slot_pack: SlotPack::None,
+    explorer: Some("https://sepolia.etherscan.io"),
+    system_accounts: ETHEREUM_SYSTEM_ACCOUNTS,
+    family: ChainFamily::Ethereum,
+};
+
+pub const OPTIMISM: ChainConfig = ChainConfig {
+    chain_id: 10,
+    name: "OP Mainnet",
+    forks: &[
+        (Hardfork::London, ForkActivation::Block(0)),
+        (Hardfork::Paris, ForkActivation::Block(105_235_063)),
+        OP_STACK_FORKS_AFTER_BEDROCK[0],
+        OP_STACK_FORKS_AFTER_BEDROCK[1],
+        OP_STACK_FORKS_AFTER_BEDROCK[2],
+    ],
+    labels: LabelSet::None,
+    slot_pack: SlotPack::None,
+    explorer: Some("https://optimistic.etherscan.io"),
+    system_accounts: OP_STACK_SYSTEM_ACCOUNTS,
+    family: ChainFamily::OpStack,
+};
+
+pub const BASE: ChainConfig = ChainConfig {
+    chain_id: 8453,
+    name: "Base",
+    forks: &[
+        (Hardfork::Paris, ForkActivation::Block(0)),
+        OP_STACK_FORKS_AFTER_BEDROCK[0],
+        OP_STACK_FORKS_AFTER_BEDROCK[1],
+        OP_STACK_FORKS_AFTER_BEDROCK[2],
+    ],
+    labels: LabelSet::None,
+    slot_pack: SlotPack::None,
+    explorer: Some("https://basescan.org"),
+    system_accounts: OP_STACK_SYSTEM_ACCOUNTS,
+    family: ChainFamily::OpStack,
+};
+
+/// Nitro (block 22,207,817) runs merge-era semantics; ArbOS 20 (Atlas,
+/// 2024-03-18) brought Shanghai and Cancun opcodes together.
+pub const ARBITRUM_ONE: ChainConfig = ChainConfig {
+    chain_id: 42_161,
+    name: "Arbitrum One",
+    forks: &[
+        (Hardfork::London, ForkActivation::Block(0)),
+        (Hardfork::Paris, ForkActivation::Block(22_207_817)),
+        (Hardfork::Cancun, ForkActivation::Timestamp(1_710_720_000)),
+    ],
+    labels: LabelSet::None,
+    slot_pack: SlotPack::None,
+    explorer: Some("https://arbiscan.io"),
+    system_accounts: ARBITRUM_SYSTEM_ACCOUNTS,
+    family: ChainFamily::Arbitrum,
+};
+
+static CHAINS: &[ChainConfig] = &[ETHEREUM, SEPOLIA, OPTIMISM, BASE, ARBITRUM_ONE];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hardfork_names_parse() {
+        assert_eq!("Cancun".parse(), Ok(Hardfork::Cancun));
+        assert_eq!("merge".parse(), Ok(Hardfork::Paris));
+        assert!("osaka".parse::<Hardfork>().is_err());
+    }
+
+    #[test]
+    fn lookup_known_and_unknown() {
+        assert_eq!(lookup(1).unwrap().name, "Ethereum");
+        assert_eq!(lookup(8453).unwrap().name, "Base");
+        assert_eq!(lookup(42_161).unwrap().family, ChainFamily::Arbitrum);
+        assert!(lookup(999_999).is_none());
+    }
+
+    #[test]
+    fn resolve_keeps_unregistered_chain_ids() {
+        assert!(std::ptr::eq(resolve(1), lookup(1).unwrap()));
+        let devnet = resolve(31_337);
+        assert_eq!(devnet.chain_id, 31_337);
+        assert_eq!(devnet.hardfork_at(0, 0), Hardfork::Prague);
+        assert_eq!(devnet.slot_pack, SlotPack::None);
+        assert!(std::ptr::eq(devnet, resolve(31_337)));
+    }
+
+    #[test]
+    fn chain_ids_are_unique() {
+        let mut ids: Vec<u64> = all().iter().map(|c| c.chain_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), all().len());
+    }
+
+    #[test]
+    fn mainnet_fork_schedule() {
+        assert_eq!(ETHEREUM.hardfork_at(1, 0), Hardfork::Frontier);
+        assert_eq!(
+            ETHEREUM.hardfork_at(2_500_000, 0),
+            Hardfork::TangerineWhistle
+        );
+        assert_eq!(ETHEREUM.hardfork_at(3_000_000, 0), Hardfork::SpuriousDragon);
+        assert_eq!(ETHEREUM.hardfork_at(7_280_000, 0), Hardfork::Petersburg);
+        assert_eq!(ETHEREUM.hardfork_at(13_000_000, 0), Hardfork::London);
+        assert_eq!(
+            ETHEREUM.hardfork_at(16_000_000, 1_670_000_000),
+            Hardfork::Paris
+        );
+        assert_eq!(
+            ETHEREUM.hardfork_at(19_500_000, 1_711_000_000),
+            Hardfork::Cancun
+        );
+        assert_eq!(
+            ETHEREUM.hardfork_at(22_500_000, 1_747_000_000),
+            Hardfork::Prague
+        );
+    }
+
+    #[test]
+    fn labels_are_chain_scoped() {
+        let router = address!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D");
+        assert!(ETHEREUM.label(&router).is_some());
+        assert!(BASE.label(&router).is_none());
+    }
+
+    #[test]
+    fn explorer_and_system_accounts() {
+        assert_eq!(
+            ETHEREUM.explorer_address_url(&Address::ZERO).unwrap(),
+            format!("https://etherscan.io/address/{}", Address::ZERO)
+        );
+        assert!(OPTIMISM.is_system_account(&address!("DeaDDEaDDeAdDeAdDEAdDEaddeAddEAdDEAd0001")));
+        assert!(!ETHEREUM.is_system_account(&Address::ZERO));
+        assert!(
+            ARBITRUM_ONE.is_system_account(&address!("00000000000000000000000000000000000A4B05"))
+        );
+    }
+}
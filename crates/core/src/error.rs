@@ -1,5 +1,15 @@
 //! Centralized error types for the Argus workspace.
+//!
+//! Errors can carry an [`ErrorContext`] saying where they happened (block,
+//! transaction, pipeline stage, RPC method). Attach it with [`ResultExt`]
+//! as the error propagates; the innermost value of each field wins.
+//!
+//! ```ignore
+//! provider.get_block_hash(n.into()).await.rpc("eth_getBlockByNumber").in_block(n)?;
+//! ```
 
+use alloy_primitives::{Address, B256};
+use std::fmt;
 use thiserror::Error;
 
 /// Top-level error enum. Variants map to subsystems.
@@ -17,6 +27,147 @@ pub enum ArgusError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// Work was abandoned because its cancellation token fired.
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// Another error plus where it happened.
+    #[error("{source} ({context})")]
+    WithContext {
+        source: Box<ArgusError>,
+        context: ErrorContext,
+    },
+}
+
+/// Where an error happened. Unset fields are omitted from the message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub block: Option<u64>,
+    pub tx: Option<B256>,
+    pub address: Option<Address>,
+    pub stage: Option<String>,
+    pub rpc_method: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        let mut field = |f: &mut fmt::Formatter<'_>, name: &str, value: &dyn fmt::Display| {
+            let r = write!(f, "{sep}{name} {value}");
+            sep = ", ";
+            r
+        };
+        if let Some(block) = self.block {
+            field(f, "block", &block)?;
+        }
+        if let Some(stage) = &self.stage {
+            field(f, "stage", stage)?;
+        }
+        if let Some(tx) = &self.tx {
+            field(f, "tx", tx)?;
+        }
+        if let Some(address) = &self.address {
+            field(f, "address", address)?;
+        }
+        if let Some(method) = &self.rpc_method {
+            field(f, "rpc", method)?;
+        }
+        Ok(())
+    }
+}
+
+impl ArgusError {
+    /// The underlying error, without context.
+    pub fn root(&self) -> &ArgusError {
+        match self {
+            ArgusError::WithContext { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// Context attached so far, if any.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ArgusError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.root(), ArgusError::Cancelled)
+    }
+
+    /// Fill in context fields that are still unset.
+    pub fn with_context(self, update: impl FnOnce(&mut ErrorContext)) -> Self {
+        let (source, mut context) = match self {
+            ArgusError::WithContext { source, context } => (source, context),
+            e => (Box::new(e), ErrorContext::default()),
+        };
+        let mut outer = ErrorContext::default();
+        update(&mut outer);
+        context.block = context.block.or(outer.block);
+        context.tx = context.tx.or(outer.tx);
+        context.address = context.address.or(outer.address);
+        context.stage = context.stage.or(outer.stage);
+        context.rpc_method = context.rpc_method.or(outer.rpc_method);
+        ArgusError::WithContext { source, context }
+    }
+}
+
+/// Attach [`ErrorContext`] to a failing [`ArgusResult`].
+pub trait ResultExt<T> {
+    fn in_block(self, block: u64) -> ArgusResult<T>;
+    fn for_tx(self, tx: B256) -> ArgusResult<T>;
+    fn at_address(self, address: Address) -> ArgusResult<T>;
+    fn in_stage(self, stage: &str) -> ArgusResult<T>;
+    fn rpc(self, method: &str) -> ArgusResult<T>;
+}
+
+impl<T> ResultExt<T> for ArgusResult<T> {
+    fn in_block(self, block: u64) -> ArgusResult<T> {
+        self.map_err(|e| e.with_context(|c| c.block = Some(block)))
+    }
+
+    fn for_tx(self, tx: B256) -> ArgusResult<T> {
+        self.map_err(|e| e.with_context(|c| c.tx = Some(tx)))
+    }
+
+    fn at_address(self, address: Address) -> ArgusResult<T> {
+        self.map_err(|e| e.with_context(|c| c.address = Some(address)))
+    }
+
+    fn in_stage(self, stage: &str) -> ArgusResult<T> {
+        self.map_err(|e| e.with_context(|c| c.stage = Some(stage.to_string())))
+    }
+
+    fn rpc(self, method: &str) -> ArgusResult<T> {
+        self.map_err(|e| e.with_context(|c| c.rpc_method = Some(method.to_string())))
+    }
 }
 
 pub type ArgusResult<T> = Result<T, ArgusError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_accumulates_without_nesting() {
+        let err: ArgusResult<()> = Err(ArgusError::Provider("timeout".into()));
+        let err = err
+            .rpc("eth_getBalance")
+            .in_stage("prefetch")
+            .in_block(7)
+            .in_block(8)
+            .unwrap_err();
+
+        assert!(matches!(err.root(), ArgusError::Provider(_)));
+        assert_eq!(err.context().unwrap().block, Some(7));
+        assert_eq!(
+            err.to_string(),
+            "Provider error: timeout (block 7, stage prefetch, rpc eth_getBalance)"
+        );
+        assert!(!err.is_cancelled());
+    }
+}
@@ -0,0 +1,94 @@
+//! JSON files written atomically, the storage behind the on-disk caches.
+//!
+//! A file is written under a temp name unique to the writing process and
+//! call, then renamed into place, so concurrent writers (threads, or
+//! processes sharing a cache directory) never leave a torn file behind:
+//! readers see one complete version, the last rename's.
+
+use crate::error::{ArgusError, ArgusResult};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Tells apart temp files of concurrent writes within one process.
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// Read the JSON file at `path`, `what` naming it in errors and logs.
+///
+/// Returns `Ok(None)` if it doesn't exist. A file that doesn't parse is
+/// treated as missing too, so a cache entry from an older format is simply
+/// rebuilt.
+pub fn read<T: DeserializeOwned>(path: &Path, what: &str) -> ArgusResult<Option<T>> {
+    let bytes = match std::fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(ArgusError::Internal(format!(
+                "Failed to read {what} {}: {e}",
+                path.display()
+            )))
+        }
+    };
+
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => {
+            tracing::debug!(path = %path.display(), "{what} hit");
+            Ok(Some(value))
+        }
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "ignoring corrupt {what}");
+            Ok(None)
+        }
+    }
+}
+
+/// Write `value` to `path` as JSON atomically, creating its directory.
+pub fn write<T: Serialize>(path: &Path, value: &T, what: &str) -> ArgusResult<()> {
+    let io_err = |e: std::io::Error| {
+        ArgusError::Internal(format!("Failed to write {what} {}: {e}", path.display()))
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_err)?;
+    }
+
+    let bytes = serde_json::to_vec(value)
+        .map_err(|e| ArgusError::Internal(format!("Failed to serialize {what}: {e}")))?;
+    let seq = WRITES.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_extension(format!("json.{}.{seq}.tmp", std::process::id()));
+    std::fs::write(&tmp, bytes).map_err(io_err)?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        std::fs::remove_file(&tmp).ok();
+        return Err(io_err(e));
+    }
+
+    tracing::debug!(path = %path.display(), "{what} stored");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_miss_and_corrupt() {
+        let dir = std::env::temp_dir().join(format!("argus-json-file-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("a/b.json");
+
+        assert_eq!(read::<Vec<u32>>(&path, "entry").unwrap(), None);
+        write(&path, &vec![1u32, 2], "entry").unwrap();
+        write(&path, &vec![3u32], "entry").unwrap();
+        assert_eq!(read::<Vec<u32>>(&path, "entry").unwrap(), Some(vec![3]));
+        // Only the entry itself is left behind.
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        std::fs::write(&path, b"not json").unwrap();
+        assert_eq!(read::<Vec<u32>>(&path, "entry").unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,8 @@
 //! Static label registry for well-known Ethereum contracts.
 //!
 //! Provides instant protocol identification without external API calls.
-//! Used by the reporter module to enrich conflict reports.
+//! Used by the reporter module to enrich conflict reports. Lives in core so
+//! report rendering doesn't pull in the RPC stack.
 
 use alloy_primitives::Address;
 use std::collections::HashMap;
@@ -20,11 +21,26 @@ impl ContractLabel {
     }
 }
 
-/// Returns the label for a known contract, if any.
+/// Which label table a chain uses. See [`crate::chain::ChainConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelSet {
+    Ethereum,
+    None,
+}
+
+/// Returns the label for a known Ethereum mainnet contract, if any.
 pub fn lookup(address: &Address) -> Option<&'static ContractLabel> {
     KNOWN_LABELS.get(address)
 }
 
+/// Returns the label for a known contract in `set`, if any.
+pub fn lookup_in(set: LabelSet, address: &Address) -> Option<&'static ContractLabel> {
+    match set {
+        LabelSet::Ethereum => lookup(address),
+        LabelSet::None => None,
+    }
+}
+
 static KNOWN_LABELS: LazyLock<HashMap<Address, ContractLabel>> = LazyLock::new(|| {
     let mut m = HashMap::new();
 
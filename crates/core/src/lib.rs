@@ -1,12 +1,18 @@
 //! Domain models, shared types, and error definitions.
 //!
-//! Foundation crate -- no async or I/O dependencies.
+//! Foundation crate -- no async or I/O dependencies; the only I/O is
+//! [`json_file`]'s atomic writes for the on-disk caches.
 
+pub mod chain;
 pub mod error;
+pub mod json_file;
+pub mod labels;
 pub mod types;
 
+pub use chain::{ChainConfig, ChainFamily, Hardfork};
 pub use error::ArgusError;
 pub use types::{
-    AccessEntry, AccessList, AccessMode, Conflict, ConflictGraph, ConflictKind, StorageLocation,
-    Transaction,
+    AccessEntry, AccessList, AccessMode, AccountField, Authorization, BlockContext, Conflict,
+    ConflictGraph, ConflictKind, DeclaredAccess, ExecutionOutcome, FastHashMap, FastHashSet,
+    PrefetchStats, SlotWrite, StorageLocation, Transaction, TxLog, TxReceipt,
 };
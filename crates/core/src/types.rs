@@ -3,7 +3,17 @@
 use alloy_primitives::{Address, Bytes, B256, U256};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// `HashMap` keyed with FxHash instead of SipHash.
+///
+/// Graph construction hashes millions of 32- and 52-byte keys per block;
+/// FxHash is several times faster on them. Keys are tx hashes and storage
+/// locations, which are not attacker-chosen to collide.
+pub type FastHashMap<K, V> = HashMap<K, V, rustc_hash::FxBuildHasher>;
+
+/// `HashSet` counterpart of [`FastHashMap`].
+pub type FastHashSet<T> = HashSet<T, rustc_hash::FxBuildHasher>;
 
 // ---------------------------------------------------------------------------
 // Storage
@@ -11,6 +21,10 @@ use std::collections::HashMap;
 
 /// A unique `(contract, slot)` pair in EVM state.
 ///
+/// Account fields (balance, nonce, code) are addressed through reserved
+/// pseudo-slots, see [`StorageLocation::account`], so they conflict like
+/// any other location.
+///
 /// `#[repr(C)]` for stable layout: `Address(20) + B256(32)` = 52 bytes.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(C)]
@@ -19,6 +33,57 @@ pub struct StorageLocation {
     pub slot: B256,
 }
 
+/// Account-level state a transaction can touch outside storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccountField {
+    Balance = 0,
+    Nonce = 1,
+    Code = 2,
+    /// The account as a whole. `SELFDESTRUCT` writes it, and the conflict
+    /// graph treats that write as touching every location of the account.
+    Existence = 3,
+}
+
+/// First 31 bytes of every account pseudo-slot; the last byte is the
+/// [`AccountField`]. Real slots this high only come out of keccak, so a
+/// collision is as unlikely as one between two mapping keys.
+const ACCOUNT_SLOT_PREFIX: [u8; 31] = [0xff; 31];
+
+impl AccountField {
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::Balance),
+            1 => Some(Self::Nonce),
+            2 => Some(Self::Code),
+            3 => Some(Self::Existence),
+            _ => None,
+        }
+    }
+}
+
+impl StorageLocation {
+    /// The pseudo-slot standing for `field` of `address`.
+    pub fn account(address: Address, field: AccountField) -> Self {
+        let mut slot = [0u8; 32];
+        slot[..31].copy_from_slice(&ACCOUNT_SLOT_PREFIX);
+        slot[31] = field as u8;
+        Self {
+            address,
+            slot: B256::from(slot),
+        }
+    }
+
+    /// The account field this location stands for, or `None` for a
+    /// storage slot.
+    pub fn account_field(&self) -> Option<AccountField> {
+        let (prefix, tag) = self.slot.0.split_at(31);
+        if prefix != ACCOUNT_SLOT_PREFIX {
+            return None;
+        }
+        AccountField::from_tag(tag[0])
+    }
+}
+
 /// Read (`SLOAD`) or Write (`SSTORE`).
 ///
 /// Ordered `Read(0) < Write(1)` so reverse-sort puts writes first
@@ -43,12 +108,16 @@ impl Ord for AccessMode {
     }
 }
 
-/// A single storage access: location + read/write mode.
+/// A single storage (or account-field) access: location + read/write mode.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(C)]
 pub struct AccessEntry {
     pub location: StorageLocation,
     pub mode: AccessMode,
+    /// Times the transaction touched the location; deduplication sums the
+    /// touches it folds together.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub count: u32,
 }
 
 /// All storage accesses recorded for one transaction.
@@ -58,6 +127,84 @@ pub struct AccessEntry {
 pub struct AccessList {
     pub tx_hash: B256,
     pub entries: SmallVec<[AccessEntry; 32]>,
+    /// EIP-1153 transient storage (`TLOAD`/`TSTORE`), kept apart from
+    /// `entries`: it is cleared after every transaction, so it never
+    /// conflicts across transactions and the graph ignores it.
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    pub transient: SmallVec<[AccessEntry; 4]>,
+    /// Events the transaction emitted, in order; empty if it reverted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<TxLog>,
+    /// How simulation ended; entries past a revert reflect the path taken
+    /// up to it. Lists not produced by simulation (traces, fixtures) say
+    /// `Success`.
+    #[serde(default, skip_serializing_if = "ExecutionOutcome::is_success")]
+    pub outcome: ExecutionOutcome,
+    /// Gas the simulation used; 0 when not simulated.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gas_used: u64,
+    /// Values behind each storage write in `entries`, for pruning writes
+    /// that don't change anything; empty when not simulated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub writes: Vec<SlotWrite>,
+}
+
+/// A storage slot's value before and after the transaction that wrote it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotWrite {
+    pub location: StorageLocation,
+    pub original: B256,
+    pub value: B256,
+}
+
+impl SlotWrite {
+    /// Written and then set back: no net change.
+    pub fn is_restore(&self) -> bool {
+        self.original == self.value
+    }
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
+}
+
+/// How a simulated transaction ended.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ExecutionOutcome {
+    #[default]
+    Success,
+    /// `REVERT`, with the decoded `Error(string)`/`Panic(uint256)` reason,
+    /// or the raw output in hex; `None` if it returned nothing.
+    Revert { reason: Option<String> },
+    /// Exceptional halt: out of gas, invalid opcode, stack overflow, ...
+    Halt { reason: String },
+    /// The EVM refused to run the transaction at all.
+    Error { reason: String },
+    /// Aborted after running past the per-transaction time limit.
+    TimedOut,
+}
+
+impl ExecutionOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success)
+    }
+}
+
+/// An emitted event: the emitting contract and its topics (`topics[0]` is
+/// the event signature hash for non-anonymous events). Data is dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
 }
 
 // ---------------------------------------------------------------------------
@@ -65,7 +212,7 @@ pub struct AccessList {
 // ---------------------------------------------------------------------------
 
 /// Lightweight EVM transaction -- only the fields the analyzer needs.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Transaction {
     pub hash: B256,
     pub from: Address,
@@ -74,6 +221,106 @@ pub struct Transaction {
     pub input: Bytes,
     pub value: U256,
     pub gas: u64,
+    /// EIP-4844 versioned blob hashes; empty unless a blob transaction.
+    #[serde(default)]
+    pub blob_hashes: Vec<B256>,
+    /// EIP-4844 blob fee cap; `None` unless a blob transaction.
+    #[serde(default)]
+    pub max_fee_per_blob_gas: Option<u128>,
+    /// Legacy and EIP-2930 gas price; `None` for EIP-1559 transactions.
+    #[serde(default)]
+    pub gas_price: Option<u128>,
+    /// EIP-1559 fee cap; `None` for legacy transactions.
+    #[serde(default)]
+    pub max_fee_per_gas: Option<u128>,
+    /// EIP-1559 tip cap; `None` for legacy transactions.
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// EIP-2930 access list the sender declared; empty for legacy txs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub access_list: Vec<DeclaredAccess>,
+    /// EIP-7702 code delegations; empty unless a set-code transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorization_list: Vec<Authorization>,
+}
+
+/// One entry of a transaction's declared EIP-2930 access list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclaredAccess {
+    pub address: Address,
+    #[serde(default)]
+    pub storage_keys: Vec<B256>,
+}
+
+/// One signed EIP-7702 authorization: its signer (the authority) delegates
+/// its code to `address`. The authority is recovered at execution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// Execution outcome of a mined transaction, from its receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxReceipt {
+    pub tx_hash: B256,
+    pub gas_used: u64,
+    /// `false` if the transaction reverted.
+    pub success: bool,
+}
+
+/// Header fields a transaction can observe (`NUMBER`, `TIMESTAMP`,
+/// `BASEFEE`, `COINBASE`, `PREVRANDAO`, ...).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockContext {
+    pub number: u64,
+    pub timestamp: u64,
+    /// EIP-1559 base fee; `None` before London.
+    pub base_fee: Option<u64>,
+    pub gas_limit: u64,
+    pub coinbase: Address,
+    /// Beacon randomness (the header's `mixHash`); `None` before the merge.
+    pub prevrandao: Option<B256>,
+    /// Proof-of-work difficulty; zero since the merge.
+    pub difficulty: U256,
+    /// EIP-4844 excess blob gas; `None` before Cancun.
+    #[serde(default)]
+    pub excess_blob_gas: Option<u64>,
+}
+
+/// What warming a block's state took: reads from the node and the RPC
+/// cache, failures, retries, and wall time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefetchStats {
+    /// Accounts and slots read from the node.
+    pub fetched: usize,
+    /// Reads that still failed after retries; they read as empty.
+    pub failed: usize,
+    /// Requests retried on the prefetcher's endpoints while it ran.
+    pub retried: u64,
+    /// Accounts and slots served from the RPC cache.
+    pub cached: usize,
+    /// Storage slots the prefetch went after.
+    pub slots_requested: usize,
+    /// Storage slots it warmed.
+    pub slots: usize,
+    pub elapsed: std::time::Duration,
+}
+
+impl PrefetchStats {
+    /// Share of requested slots that were warmed; 1.0 when none were.
+    pub fn slot_coverage(&self) -> f64 {
+        match self.slots_requested {
+            0 => 1.0,
+            requested => self.slots.min(requested) as f64 / requested as f64,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -87,7 +334,8 @@ pub enum ConflictKind {
     ReadWrite,
 }
 
-/// An edge connecting two transactions through a shared storage slot.
+/// An edge connecting two transactions through a shared storage slot or
+/// account field.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conflict {
     pub tx_a: B256,
@@ -102,7 +350,7 @@ pub struct Conflict {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConflictGraph {
     pub conflicts: Vec<Conflict>,
-    pub adjacency: HashMap<B256, Vec<B256>>,
+    pub adjacency: FastHashMap<B256, Vec<B256>>,
 }
 
 impl ConflictGraph {
@@ -135,6 +383,43 @@ impl ConflictGraph {
     pub fn is_empty(&self) -> bool {
         self.conflicts.is_empty()
     }
+
+    /// Sort edges by `(tx_a, tx_b, location, kind)` and rebuild the
+    /// adjacency from them, so equal graphs serialize to identical bytes
+    /// regardless of how they were built.
+    pub fn canonicalize(&mut self) {
+        self.conflicts.sort_by(|a, b| {
+            (
+                a.tx_a,
+                a.tx_b,
+                a.location.address,
+                a.location.slot,
+                a.kind as u8,
+            )
+                .cmp(&(
+                    b.tx_a,
+                    b.tx_b,
+                    b.location.address,
+                    b.location.slot,
+                    b.kind as u8,
+                ))
+        });
+        let conflicts = std::mem::take(&mut self.conflicts);
+        self.adjacency = FastHashMap::default();
+        for c in conflicts {
+            self.add_conflict(c);
+        }
+    }
+
+    /// Keep only the edges `keep` accepts, rebuilding the adjacency.
+    pub fn retain(&mut self, keep: impl FnMut(&Conflict) -> bool) {
+        let mut conflicts = std::mem::take(&mut self.conflicts);
+        conflicts.retain(keep);
+        self.adjacency = FastHashMap::default();
+        for c in conflicts {
+            self.add_conflict(c);
+        }
+    }
 }
 
 // Compile-time layout assertions.
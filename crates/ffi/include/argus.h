@@ -0,0 +1,50 @@
+/*
+ * Argus C ABI.
+ *
+ * Link against libargus_ffi (cdylib or staticlib). All results are returned
+ * as UTF-8 JSON in an ArgusBuffer that the caller must release with
+ * argus_buffer_free().
+ */
+#ifndef ARGUS_H
+#define ARGUS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define ARGUS_OK 0
+#define ARGUS_ERR_INVALID_ARGUMENT -1
+#define ARGUS_ERR_ANALYSIS -2
+#define ARGUS_ERR_PANIC -3
+
+/* Owned byte buffer allocated by Argus. */
+typedef struct ArgusBuffer {
+    uint8_t *data;
+    size_t len;
+} ArgusBuffer;
+
+/*
+ * Analyze one block.
+ *
+ * On ARGUS_OK, *out holds {"summary": {...}, "conflicts": [...],
+ * "contention_events": [...]}. On ARGUS_ERR_ANALYSIS or ARGUS_ERR_PANIC,
+ * *out holds {"error": "..."}. On ARGUS_ERR_INVALID_ARGUMENT, *out is
+ * left untouched.
+ */
+int32_t argus_analyze_block(const char *rpc_url, uint64_t block, bool dry_run, ArgusBuffer *out);
+
+/* Release a buffer returned by Argus. Safe to call on a zeroed buffer. */
+void argus_buffer_free(ArgusBuffer buf);
+
+/* Library version as a static NUL-terminated string. */
+const char *argus_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ARGUS_H */
@@ -0,0 +1,147 @@
+//! Stable C ABI for embedding Argus without spawning a subprocess.
+//!
+//! Results cross the boundary as JSON in an [`ArgusBuffer`] using the sink
+//! row schemas, so non-Rust clients see the same columns as the NDJSON
+//! sink. See `include/argus.h` for the C declarations.
+
+use serde::Serialize;
+use std::ffi::{c_char, CStr};
+
+pub const ARGUS_OK: i32 = 0;
+pub const ARGUS_ERR_INVALID_ARGUMENT: i32 = -1;
+pub const ARGUS_ERR_ANALYSIS: i32 = -2;
+pub const ARGUS_ERR_PANIC: i32 = -3;
+
+/// Owned byte buffer handed to C callers. Free with [`argus_buffer_free`].
+#[repr(C)]
+pub struct ArgusBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl ArgusBuffer {
+    fn from_vec(v: Vec<u8>) -> Self {
+        let mut boxed = v.into_boxed_slice();
+        let buf = ArgusBuffer {
+            data: boxed.as_mut_ptr(),
+            len: boxed.len(),
+        };
+        std::mem::forget(boxed);
+        buf
+    }
+
+    fn error(msg: &str) -> Self {
+        Self::from_vec(serde_json::json!({ "error": msg }).to_string().into_bytes())
+    }
+}
+
+#[derive(Serialize)]
+struct FfiResult {
+    summary: argus::analyzer::sink::BlockSummaryRow,
+    conflicts: Vec<argus::analyzer::sink::ConflictRow>,
+    contention_events: Vec<argus::analyzer::sink::ContentionEvent>,
+}
+
+/// Analyze one block and write the JSON result into `out`.
+///
+/// # Safety
+///
+/// `rpc_url` must be a valid NUL-terminated string and `out` a valid,
+/// writable pointer. The returned buffer must be released with
+/// [`argus_buffer_free`].
+#[no_mangle]
+pub unsafe extern "C" fn argus_analyze_block(
+    rpc_url: *const c_char,
+    block: u64,
+    dry_run: bool,
+    out: *mut ArgusBuffer,
+) -> i32 {
+    if rpc_url.is_null() || out.is_null() {
+        return ARGUS_ERR_INVALID_ARGUMENT;
+    }
+    let Ok(rpc_url) = CStr::from_ptr(rpc_url).to_str() else {
+        return ARGUS_ERR_INVALID_ARGUMENT;
+    };
+
+    let result = std::panic::catch_unwind(|| analyze(rpc_url, block, dry_run));
+
+    let (code, buf) = match result {
+        Ok(Ok(json)) => (ARGUS_OK, ArgusBuffer::from_vec(json)),
+        Ok(Err(msg)) => (ARGUS_ERR_ANALYSIS, ArgusBuffer::error(&msg)),
+        Err(_) => (ARGUS_ERR_PANIC, ArgusBuffer::error("analysis panicked")),
+    };
+    out.write(buf);
+    code
+}
+
+/// Release a buffer returned by Argus. Safe to call on a zeroed buffer.
+///
+/// # Safety
+///
+/// `buf` must come from an Argus function and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn argus_buffer_free(buf: ArgusBuffer) {
+    if buf.data.is_null() {
+        return;
+    }
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+        buf.data, buf.len,
+    )));
+}
+
+/// Library version as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn argus_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+fn analyze(rpc_url: &str, block: u64, dry_run: bool) -> Result<Vec<u8>, String> {
+    let rt = tokio::runtime::Runtime::new().map_err(|e| format!("tokio runtime: {e}"))?;
+    let argus = argus::Argus::builder()
+        .rpc_url(rpc_url)
+        .dry_run(dry_run)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let argus::AnalysisResult { report, graph, .. } = rt
+        .block_on(argus.analyze_block(block))
+        .map_err(|e| e.to_string())?;
+
+    let (summary, conflicts) = report.to_rows_from_graph(&graph);
+    let result = FfiResult {
+        summary,
+        conflicts,
+        contention_events: report.to_contention_events(&graph),
+    };
+    serde_json::to_vec(&result).map_err(|e| format!("serialize: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_arguments_rejected() {
+        let mut out = ArgusBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        };
+        let code = unsafe { argus_analyze_block(std::ptr::null(), 1, true, &mut out) };
+        assert_eq!(code, ARGUS_ERR_INVALID_ARGUMENT);
+        assert!(out.data.is_null());
+    }
+
+    #[test]
+    fn error_buffer_roundtrip() {
+        let buf = ArgusBuffer::error("boom");
+        let bytes = unsafe { std::slice::from_raw_parts(buf.data, buf.len) };
+        let v: serde_json::Value = serde_json::from_slice(bytes).unwrap();
+        assert_eq!(v["error"], "boom");
+        unsafe { argus_buffer_free(buf) };
+    }
+
+    #[test]
+    fn version_is_nul_terminated() {
+        let v = unsafe { CStr::from_ptr(argus_version()) };
+        assert_eq!(v.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
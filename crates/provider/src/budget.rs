@@ -0,0 +1,408 @@
+//! Shared RPC budget: rate, daily quota, and in-flight cap with priorities.
+//!
+//! Every component that talks to the node (block fetch, prefetch, mempool
+//! polling) draws a [`BudgetPermit`] from the same [`RpcBudget`] before each
+//! request, so their combined traffic stays within vendor limits. Waiting
+//! callers are served strictly by [`Priority`]; lower priorities only
+//! proceed when no higher-priority caller is queued.
+//!
+//! The rate limit is a token bucket: it refills at `requests_per_second`
+//! and holds up to `burst` tokens, so after idling that many requests go
+//! out back to back before pacing sets in. Match both to the provider plan,
+//! e.g. Alchemy's and Infura's compute-unit budgets per second.
+
+use argus_core::error::{ArgusError, ArgusResult};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Scheduling class of a request. Higher variants go first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Background work (mempool polling).
+    Low = 0,
+    /// State prefetch.
+    Normal = 1,
+    /// Block and header fetches on the critical path.
+    High = 2,
+}
+
+/// Limits for an [`RpcBudget`]. `None` means unlimited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetLimits {
+    pub requests_per_second: Option<f64>,
+    /// Token bucket capacity. Defaults to one second's worth of requests.
+    pub burst: Option<u32>,
+    pub requests_per_day: Option<u64>,
+    pub max_in_flight: Option<usize>,
+}
+
+impl BudgetLimits {
+    /// Requests that may be sent back to back, at least 1.
+    pub fn burst_size(&self) -> f64 {
+        self.burst
+            .map(f64::from)
+            .or(self.requests_per_second)
+            .unwrap_or(0.0)
+            .max(1.0)
+    }
+
+    /// Reject limits that would stall every caller: a rate that isn't
+    /// positive, or no requests in flight at all.
+    pub fn validate(&self) -> ArgusResult<()> {
+        if let Some(rps) = self.requests_per_second {
+            if !(rps.is_finite() && rps > 0.0) {
+                return Err(ArgusError::InvalidInput(format!(
+                    "requests_per_second must be positive, got {rps}"
+                )));
+            }
+        }
+        if self.max_in_flight == Some(0) {
+            return Err(ArgusError::InvalidInput(
+                "max_in_flight must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Process-wide RPC quota shared via `Arc`.
+#[derive(Debug)]
+pub struct RpcBudget {
+    limits: BudgetLimits,
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+#[derive(Debug)]
+struct State {
+    in_flight: usize,
+    waiting: [usize; 3],
+    tokens: f64,
+    refilled_at: Instant,
+    day_count: u64,
+    day_started: Instant,
+}
+
+/// Held for the duration of one RPC request.
+#[derive(Debug)]
+pub struct BudgetPermit {
+    budget: Arc<RpcBudget>,
+}
+
+impl Drop for BudgetPermit {
+    fn drop(&mut self) {
+        self.budget.state.lock().unwrap().in_flight -= 1;
+        self.budget.notify.notify_waiters();
+    }
+}
+
+/// Counts a caller as queued at its priority until dropped, including when
+/// the `acquire` future is cancelled mid-wait.
+struct Queued<'a> {
+    budget: &'a RpcBudget,
+    priority: Priority,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.budget.state.lock().unwrap().waiting[self.priority as usize] -= 1;
+        // A departing waiter may unblock lower priorities.
+        self.budget.notify.notify_waiters();
+    }
+}
+
+impl RpcBudget {
+    /// Fails with [`ArgusError::InvalidInput`] if `limits` don't pass
+    /// [`BudgetLimits::validate`].
+    pub fn new(limits: BudgetLimits) -> ArgusResult<Arc<Self>> {
+        limits.validate()?;
+        Ok(Self::with_limits(limits))
+    }
+
+    fn with_limits(limits: BudgetLimits) -> Arc<Self> {
+        let now = Instant::now();
+        let burst = limits.burst_size();
+        Arc::new(Self {
+            limits,
+            state: Mutex::new(State {
+                in_flight: 0,
+                waiting: [0; 3],
+                tokens: burst,
+                refilled_at: now,
+                day_count: 0,
+                day_started: now,
+            }),
+            notify: Notify::new(),
+        })
+    }
+
+    /// A budget that never throttles.
+    pub fn unlimited() -> Arc<Self> {
+        Self::with_limits(BudgetLimits::default())
+    }
+
+    pub fn limits(&self) -> &BudgetLimits {
+        &self.limits
+    }
+
+    /// Requests issued since the current 24h window began.
+    pub fn used_today(&self) -> u64 {
+        self.state.lock().unwrap().day_count
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight
+    }
+
+    /// Wait for room to issue one request. Fails once the daily quota is spent.
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> ArgusResult<BudgetPermit> {
+        self.acquire_batch(priority, 1).await
+    }
+
+    /// Like [`acquire`](Self::acquire) for `cost` requests sent together.
+    ///
+    /// The batch charges `cost` against the rate and daily limits but holds
+    /// a single in-flight slot, so it can never exceed `max_in_flight`.
+    pub async fn acquire_batch(
+        self: &Arc<Self>,
+        priority: Priority,
+        cost: u32,
+    ) -> ArgusResult<BudgetPermit> {
+        let mut queued: Option<Queued<'_>> = None;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = {
+                let mut st = self.state.lock().unwrap();
+                match self.try_take(&mut st, priority, cost)? {
+                    None => {
+                        return Ok(BudgetPermit {
+                            budget: Arc::clone(self),
+                        });
+                    }
+                    Some(wait) => {
+                        if queued.is_none() {
+                            st.waiting[priority as usize] += 1;
+                            queued = Some(Queued {
+                                budget: self,
+                                priority,
+                            });
+                        }
+                        wait
+                    }
+                }
+            };
+
+            match wait {
+                Some(d) => {
+                    tokio::select! {
+                        _ = &mut notified => {}
+                        _ = tokio::time::sleep(d) => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// `Ok(None)`: permit taken. `Ok(Some(d))`: wait for a release, or
+    /// at most `d` when rate-limited.
+    fn try_take(
+        &self,
+        st: &mut State,
+        priority: Priority,
+        cost: u32,
+    ) -> ArgusResult<Option<Option<Duration>>> {
+        let now = Instant::now();
+
+        if now.duration_since(st.day_started) >= DAY {
+            st.day_started = now;
+            st.day_count = 0;
+        }
+        if let Some(max) = self.limits.requests_per_day {
+            if st.day_count + u64::from(cost) > max {
+                return Err(ArgusError::Provider(format!(
+                    "daily RPC budget of {max} requests exhausted"
+                )));
+            }
+        }
+
+        // Someone more important is already queued.
+        if st.waiting[priority as usize + 1..].iter().any(|&n| n > 0) {
+            return Ok(Some(None));
+        }
+
+        if let Some(max) = self.limits.max_in_flight {
+            if st.in_flight >= max {
+                return Ok(Some(None));
+            }
+        }
+
+        if let Some(rps) = self.limits.requests_per_second {
+            let burst = self.limits.burst_size();
+            let elapsed = now.duration_since(st.refilled_at).as_secs_f64();
+            st.tokens = (st.tokens + elapsed * rps).min(burst);
+            st.refilled_at = now;
+            // A batch larger than the burst goes into debt instead of starving.
+            let needed = f64::from(cost).min(burst);
+            if st.tokens < needed {
+                let wait = Duration::from_secs_f64((needed - st.tokens) / rps);
+                return Ok(Some(Some(wait)));
+            }
+            st.tokens -= f64::from(cost);
+        }
+
+        st.in_flight += 1;
+        st.day_count += u64::from(cost);
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unlimited_never_blocks() {
+        let budget = RpcBudget::unlimited();
+        let permits: Vec<_> = acquire_many(&budget, 100).await;
+        assert_eq!(budget.in_flight(), 100);
+        drop(permits);
+        assert_eq!(budget.in_flight(), 0);
+        assert_eq!(budget.used_today(), 100);
+    }
+
+    #[tokio::test]
+    async fn daily_quota_is_enforced() {
+        let budget = RpcBudget::new(BudgetLimits {
+            requests_per_day: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        budget.acquire(Priority::High).await.unwrap();
+        budget.acquire(Priority::High).await.unwrap();
+        assert!(matches!(
+            budget.acquire(Priority::High).await,
+            Err(ArgusError::Provider(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn burst_then_paced() {
+        let budget = RpcBudget::new(BudgetLimits {
+            requests_per_second: Some(1.0),
+            burst: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+        let _permits = acquire_many(&budget, 3).await;
+        let next = tokio::time::timeout(Duration::from_millis(100), budget.acquire(Priority::High));
+        assert!(next.await.is_err());
+        assert_eq!(budget.used_today(), 3);
+    }
+
+    #[tokio::test]
+    async fn batch_holds_one_slot_and_charges_cost() {
+        let budget = RpcBudget::new(BudgetLimits {
+            max_in_flight: Some(1),
+            requests_per_day: Some(4),
+            ..Default::default()
+        })
+        .unwrap();
+        let batch = budget.acquire_batch(Priority::Normal, 3).await.unwrap();
+        assert_eq!(budget.in_flight(), 1);
+        assert_eq!(budget.used_today(), 3);
+        drop(batch);
+        assert!(budget.acquire_batch(Priority::Normal, 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn in_flight_cap_waits_for_release() {
+        let budget = RpcBudget::new(BudgetLimits {
+            max_in_flight: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let first = budget.acquire(Priority::Normal).await.unwrap();
+
+        let b = Arc::clone(&budget);
+        let waiter = tokio::spawn(async move { b.acquire(Priority::Normal).await.map(|_| ()) });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        drop(first);
+        waiter.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn high_priority_goes_first() {
+        let budget = RpcBudget::new(BudgetLimits {
+            max_in_flight: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let held = budget.acquire(Priority::Normal).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let spawn = |p: Priority| {
+            let (b, order) = (Arc::clone(&budget), Arc::clone(&order));
+            tokio::spawn(async move {
+                let _permit = b.acquire(p).await.unwrap();
+                order.lock().unwrap().push(p);
+            })
+        };
+        let low = spawn(Priority::Low);
+        tokio::task::yield_now().await;
+        let high = spawn(Priority::High);
+        tokio::task::yield_now().await;
+
+        drop(held);
+        high.await.unwrap();
+        low.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), vec![Priority::High, Priority::Low]);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_leaves_the_queue() {
+        let budget = RpcBudget::new(BudgetLimits {
+            max_in_flight: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let held = budget.acquire(Priority::Normal).await.unwrap();
+
+        // A High caller gives up while queued; Normal must not wait on it.
+        let high = tokio::time::timeout(Duration::from_millis(10), budget.acquire(Priority::High));
+        assert!(high.await.is_err());
+        drop(held);
+        let next = tokio::time::timeout(Duration::from_secs(1), budget.acquire(Priority::Normal));
+        assert!(next.await.unwrap().is_ok());
+    }
+
+    #[test]
+    fn rejects_limits_that_stall() {
+        let limits = |rps, in_flight| BudgetLimits {
+            requests_per_second: rps,
+            max_in_flight: in_flight,
+            ..Default::default()
+        };
+        assert!(RpcBudget::new(limits(Some(0.0), None)).is_err());
+        assert!(RpcBudget::new(limits(Some(-1.0), None)).is_err());
+        assert!(RpcBudget::new(limits(Some(f64::NAN), None)).is_err());
+        assert!(RpcBudget::new(limits(None, Some(0))).is_err());
+        assert!(RpcBudget::new(limits(Some(0.5), Some(1))).is_ok());
+    }
+
+    async fn acquire_many(budget: &Arc<RpcBudget>, n: usize) -> Vec<BudgetPermit> {
+        let mut out = Vec::with_capacity(n);
+        for _ in 0..n {
+            out.push(budget.acquire(Priority::Low).await.unwrap());
+        }
+        out
+    }
+}
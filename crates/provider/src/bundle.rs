@@ -0,0 +1,281 @@
+//! Flashbots-style bundles as a transaction source.
+//!
+//! A [`Bundle`] is the `eth_sendBundle` payload: raw signed transactions
+//! plus the block they target. [`BundleProvider`] serves the decoded bundle
+//! at the top of its block, followed by the transactions it competes with:
+//! the target block's own once it is mined (backtesting), otherwise the
+//! node's pending block (before submission). Until the target is mined the
+//! bundle is placed on the current head, so state is read as of the head.
+//!
+//! ```ignore
+//! let bundle: Bundle = serde_json::from_str(&json)?;
+//! let provider = BundleProvider::new(inner, &bundle)?;
+//! let block = provider.state_block().await?;
+//! let txs = provider.get_block_transactions(BlockId::number(block)).await?;
+//! ```
+
+use crate::{BlockId, BlockNumberOrTag, BlockRef, DataProvider};
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Bytes, B256, U64};
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{AccessList, BlockContext, FastHashSet, Transaction, TxReceipt};
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer};
+use std::sync::Arc;
+
+/// `eth_sendBundle` parameters; other fields are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    /// EIP-2718 encoded signed transactions, in execution order.
+    pub txs: Vec<Bytes>,
+    /// Block the bundle targets (a hex quantity in JSON).
+    #[serde(deserialize_with = "quantity")]
+    pub block_number: u64,
+}
+
+fn quantity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    U64::deserialize(deserializer).map(|n| n.to())
+}
+
+impl Bundle {
+    /// Decode every transaction and recover its sender.
+    pub fn transactions(&self) -> ArgusResult<Vec<Transaction>> {
+        self.txs
+            .iter()
+            .enumerate()
+            .map(|(i, raw)| {
+                decode_raw_transaction(raw)
+                    .map_err(|e| ArgusError::InvalidInput(format!("bundle transaction {i}: {e}")))
+            })
+            .collect()
+    }
+}
+
+/// Decode one EIP-2718 encoded signed transaction.
+pub fn decode_raw_transaction(raw: &[u8]) -> ArgusResult<Transaction> {
+    use alloy_consensus::Transaction as _;
+
+    let mut buf = raw;
+    let envelope = TxEnvelope::decode_2718(&mut buf)
+        .map_err(|e| ArgusError::InvalidInput(format!("invalid transaction encoding: {e}")))?;
+    if !buf.is_empty() {
+        return Err(ArgusError::InvalidInput(format!(
+            "{} trailing bytes after transaction",
+            buf.len()
+        )));
+    }
+    let from = envelope
+        .recover_signer()
+        .map_err(|e| ArgusError::InvalidInput(format!("invalid signature: {e}")))?;
+    Ok(Transaction {
+        hash: *envelope.tx_hash(),
+        from,
+        to: envelope.to(),
+        input: envelope.input().clone(),
+        value: envelope.value(),
+        gas: envelope.gas_limit(),
+        blob_hashes: envelope
+            .blob_versioned_hashes()
+            .unwrap_or_default()
+            .to_vec(),
+        max_fee_per_blob_gas: envelope.max_fee_per_blob_gas(),
+        gas_price: envelope.gas_price(),
+        max_fee_per_gas: envelope
+            .is_dynamic_fee()
+            .then(|| envelope.max_fee_per_gas()),
+        max_priority_fee_per_gas: envelope.max_priority_fee_per_gas(),
+        access_list: crate::rpc::declared_access(envelope.access_list()),
+        authorization_list: crate::rpc::authorizations(envelope.authorization_list()),
+    })
+}
+
+/// [`DataProvider`] that puts a bundle at the top of its block; every other
+/// call goes to the wrapped provider.
+pub struct BundleProvider {
+    inner: Arc<dyn DataProvider>,
+    bundle: Vec<Transaction>,
+    target: u64,
+}
+
+impl BundleProvider {
+    /// Decodes `bundle` up front, so malformed transactions fail here.
+    pub fn new(inner: Arc<dyn DataProvider>, bundle: &Bundle) -> ArgusResult<Self> {
+        Ok(Self::from_transactions(
+            inner,
+            bundle.transactions()?,
+            bundle.block_number,
+        ))
+    }
+
+    /// An already decoded bundle targeting block `target`.
+    pub fn from_transactions(
+        inner: Arc<dyn DataProvider>,
+        bundle: Vec<Transaction>,
+        target: u64,
+    ) -> Self {
+        Self {
+            inner,
+            bundle,
+            target,
+        }
+    }
+
+    /// The decoded bundle, in execution order.
+    pub fn bundle(&self) -> &[Transaction] {
+        &self.bundle
+    }
+
+    pub fn target(&self) -> u64 {
+        self.target
+    }
+
+    /// The block the bundle is served at: the target once mined, else the
+    /// current head.
+    pub async fn state_block(&self) -> ArgusResult<u64> {
+        let head = self.inner.resolve_block(BlockId::latest()).await?;
+        Ok(self.target.min(head.number))
+    }
+
+    /// Transactions the bundle competes with when served at `state_block`,
+    /// minus any bundle transaction already among them.
+    async fn competing(&self, state_block: u64) -> ArgusResult<Vec<Transaction>> {
+        let mut competing = if state_block == self.target {
+            self.inner
+                .get_block_transactions(BlockId::number(self.target))
+                .await?
+        } else {
+            self.inner.get_pending_transactions().await?
+        };
+        let bundled: FastHashSet<B256> = self.bundle.iter().map(|tx| tx.hash).collect();
+        competing.retain(|tx| !bundled.contains(&tx.hash));
+        Ok(competing)
+    }
+
+    async fn number_of(&self, block: BlockId) -> ArgusResult<u64> {
+        match block {
+            BlockId::Number(BlockNumberOrTag::Number(n)) => Ok(n),
+            _ => Ok(self.inner.resolve_block(block).await?.number),
+        }
+    }
+}
+
+#[async_trait]
+impl DataProvider for BundleProvider {
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>> {
+        let state_block = self.state_block().await?;
+        if self.number_of(block).await? != state_block {
+            return self.inner.get_block_transactions(block).await;
+        }
+        let mut transactions = self.bundle.clone();
+        transactions.extend(self.competing(state_block).await?);
+        tracing::info!(
+            target_block = self.target,
+            bundle = self.bundle.len(),
+            competing = transactions.len() - self.bundle.len(),
+            "assembled bundle block"
+        );
+        Ok(transactions)
+    }
+
+    async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>> {
+        self.inner.get_pending_transactions().await
+    }
+
+    async fn chain_id(&self) -> ArgusResult<u64> {
+        self.inner.chain_id().await
+    }
+
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256> {
+        self.inner.get_block_hash(block).await
+    }
+
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64> {
+        self.inner.get_block_timestamp(block).await
+    }
+
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext> {
+        self.inner.get_block_context(block).await
+    }
+
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        self.inner.resolve_block(block).await
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        self.inner.get_block_receipts(block).await
+    }
+
+    async fn trace_access_lists(&self, block: BlockId) -> ArgusResult<Vec<AccessList>> {
+        self.inner.trace_access_lists(block).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            hash: B256::repeat_byte(n),
+            gas: 21_000,
+            ..Default::default()
+        }
+    }
+
+    fn hashes(txs: &[Transaction]) -> Vec<u8> {
+        txs.iter().map(|tx| tx.hash[0]).collect()
+    }
+
+    #[test]
+    fn parses_send_bundle_params() {
+        let bundle: Bundle = serde_json::from_str(
+            r#"{"txs": ["0x02f8", "0x01"], "blockNumber": "0x1406f40", "minTimestamp": 0}"#,
+        )
+        .unwrap();
+        assert_eq!(bundle.block_number, 21_000_000);
+        assert_eq!(bundle.txs.len(), 2);
+        let err = bundle.transactions().unwrap_err().to_string();
+        assert!(err.contains("bundle transaction 0"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn mined_target_competes_with_its_block() {
+        let inner = Arc::new(
+            MockProvider::new()
+                .with_block(9, vec![tx(1), tx(2)])
+                .with_block(10, Vec::new()),
+        );
+        let provider = BundleProvider::from_transactions(inner, vec![tx(7), tx(2)], 9);
+        assert_eq!(provider.state_block().await.unwrap(), 9);
+        let block = provider
+            .get_block_transactions(BlockId::number(9))
+            .await
+            .unwrap();
+        assert_eq!(hashes(&block), vec![7, 2, 1]);
+        let other = provider
+            .get_block_transactions(BlockId::number(10))
+            .await
+            .unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[tokio::test]
+    async fn future_target_competes_with_pending() {
+        let inner = Arc::new(
+            MockProvider::new()
+                .with_block(9, vec![tx(1)])
+                .with_pending(vec![tx(3)]),
+        );
+        let provider = BundleProvider::from_transactions(inner, vec![tx(7)], 10);
+        assert_eq!(provider.state_block().await.unwrap(), 9);
+        let block = provider
+            .get_block_transactions(BlockId::latest())
+            .await
+            .unwrap();
+        assert_eq!(hashes(&block), vec![7, 3]);
+    }
+}
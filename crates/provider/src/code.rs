@@ -0,0 +1,207 @@
+//! Bytecode interning for warm caches.
+//!
+//! Proxy-heavy blocks touch many accounts that share one implementation or
+//! minimal-proxy bytecode. Interning by code hash analyzes each distinct
+//! bytecode once, and every `AccountInfo` holding it (plus every per-tx
+//! `CacheDB` overlay that clones it) shares the same reference-counted
+//! buffers and jump table.
+//!
+//! A [`CodeCache`] extends this across blocks: the prefetchers of one
+//! provider share it, so router and token code is stored once per process
+//! and a deployed contract's code is fetched once.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use argus_core::FastHashMap;
+use revm::bytecode::BytecodeDecodeError;
+use revm::state::Bytecode;
+use std::sync::{Mutex, MutexGuard};
+
+/// Distinct bytecodes a [`CodeCache`] holds before it starts over.
+pub const DEFAULT_CODE_CACHE_CAPACITY: usize = 16_384;
+
+/// EIP-7702 delegation designator; an EOA's delegation can change any time.
+const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Distinct bytecodes keyed by code hash.
+#[derive(Debug, Default)]
+pub struct CodeInterner {
+    codes: FastHashMap<B256, Bytecode>,
+}
+
+impl CodeInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash `code` and return the shared bytecode for it.
+    pub fn intern(&mut self, code: Bytes) -> (B256, Bytecode) {
+        let hash = keccak256(&code);
+        (hash, self.intern_with_hash(hash, code))
+    }
+
+    /// Like [`intern`](Self::intern) when the hash is already known.
+    pub fn intern_with_hash(&mut self, hash: B256, code: Bytes) -> Bytecode {
+        self.codes
+            .entry(hash)
+            .or_insert_with(|| Bytecode::new_raw(code))
+            .clone()
+    }
+
+    /// Like [`intern_with_hash`](Self::intern_with_hash) for code from an
+    /// untrusted source: a malformed EIP-7702 designator is an error rather
+    /// than a panic.
+    pub fn try_intern_with_hash(
+        &mut self,
+        hash: B256,
+        code: Bytes,
+    ) -> Result<Bytecode, BytecodeDecodeError> {
+        if let Some(bytecode) = self.codes.get(&hash) {
+            return Ok(bytecode.clone());
+        }
+        let bytecode = Bytecode::new_raw_checked(code)?;
+        self.codes.insert(hash, bytecode.clone());
+        Ok(bytecode)
+    }
+
+    /// The bytecode interned under `hash`, if any.
+    pub fn get(&self, hash: &B256) -> Option<Bytecode> {
+        self.codes.get(hash).cloned()
+    }
+
+    /// Number of distinct bytecodes seen.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+}
+
+/// A [`CodeInterner`] shared across blocks, which also remembers each
+/// contract's code.
+///
+/// Deployed code can't change (SELFDESTRUCT only clears code created in the
+/// same transaction since Cancun), so a contract's code seen at one block
+/// serves every later one. Empty code and EIP-7702 delegations can change
+/// and are not remembered. Before Cancun a contract could self-destruct and
+/// be redeployed with different code at the same address; such
+/// metamorphic contracts are rare, but analyzing pre-Cancun blocks with a
+/// shared cache can give them stale code. Starts over once it holds
+/// `capacity` bytecodes.
+#[derive(Debug)]
+pub struct CodeCache {
+    inner: Mutex<SharedCode>,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct SharedCode {
+    codes: CodeInterner,
+    contracts: FastHashMap<Address, B256>,
+}
+
+impl Default for CodeCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CODE_CACHE_CAPACITY)
+    }
+}
+
+impl CodeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold at most `capacity` distinct bytecodes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SharedCode> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hash `code` and return the shared bytecode for it.
+    pub fn intern(&self, code: Bytes) -> (B256, Bytecode) {
+        let mut inner = self.lock();
+        if inner.codes.len() >= self.capacity {
+            *inner = SharedCode::default();
+        }
+        inner.codes.intern(code)
+    }
+
+    /// Like [`intern`](Self::intern) for `address`'s code, remembering it
+    /// for later blocks when it can't change.
+    pub fn intern_contract(&self, address: Address, code: Bytes) -> (B256, Bytecode) {
+        let lasting = !code.is_empty() && !code.starts_with(&DELEGATION_PREFIX);
+        let mut inner = self.lock();
+        if inner.codes.len() >= self.capacity {
+            *inner = SharedCode::default();
+        }
+        let (hash, bytecode) = inner.codes.intern(code);
+        if lasting {
+            inner.contracts.insert(address, hash);
+        }
+        (hash, bytecode)
+    }
+
+    /// Code remembered for contract `address`.
+    pub fn contract(&self, address: &Address) -> Option<(B256, Bytecode)> {
+        let inner = self.lock();
+        let hash = *inner.contracts.get(address)?;
+        Some((hash, inner.codes.get(&hash)?))
+    }
+
+    /// Number of distinct bytecodes held.
+    pub fn len(&self) -> usize {
+        self.lock().codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_code_shares_one_buffer() {
+        let mut codes = CodeInterner::new();
+        let (h1, a) = codes.intern(Bytes::from_static(&[0x60, 0x00, 0x54]));
+        let (h2, b) = codes.intern(Bytes::from(vec![0x60, 0x00, 0x54]));
+        let (h3, _) = codes.intern(Bytes::from_static(&[0x00]));
+
+        assert_eq!(h1, h2);
+        assert_ne!(h1, h3);
+        assert_eq!(h1, a.hash_slow());
+        assert_eq!(codes.len(), 2);
+        assert_eq!(a.bytecode().as_ptr(), b.bytecode().as_ptr());
+    }
+
+    #[test]
+    fn cache_remembers_lasting_contract_code() {
+        let cache = CodeCache::with_capacity(3);
+        let router = Address::repeat_byte(1);
+        let delegated = Address::repeat_byte(2);
+        let (hash, code) = cache.intern_contract(router, Bytes::from_static(&[0x60, 0x00]));
+        let delegation = [&DELEGATION_PREFIX[..], &[0x11; 20]].concat();
+        cache.intern_contract(delegated, delegation.into());
+        cache.intern_contract(Address::repeat_byte(3), Bytes::new());
+
+        let (known, shared) = cache.contract(&router).unwrap();
+        assert_eq!(known, hash);
+        assert_eq!(shared.bytecode().as_ptr(), code.bytecode().as_ptr());
+        assert!(cache.contract(&delegated).is_none());
+        assert!(cache.contract(&Address::repeat_byte(3)).is_none());
+
+        // Over capacity, it starts over.
+        cache.intern(Bytes::from_static(&[0x00]));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contract(&router).is_none());
+    }
+}
@@ -0,0 +1,665 @@
+//! Multi-endpoint RPC access with automatic failover.
+//!
+//! A [`FailoverProvider`] holds several endpoints and sends every request to
+//! the active one. Connection errors and timeouts move to the next endpoint
+//! immediately; rate limiting (HTTP 429) does so after
+//! [`RATE_LIMIT_STRIKES`] consecutive hits. The rotation state is shared
+//! with the [`Prefetcher`] from [`FailoverProvider::prefetcher`], so block
+//! fetches and state prefetch move together.
+//!
+//! [`FailoverProvider::connect`] asks the node for its chain id and keeps
+//! the matching [`ChainConfig`]; [`FailoverProvider::with_chain`] pins one
+//! without a request.
+//!
+//! [`FailoverProvider::metrics`] reports requests, retries, 429s, bytes and
+//! latency per method across all endpoints.
+//!
+//! With [`FailoverProvider::with_rpc_cache`], historical block data and
+//! prefetched state are served from an [`RpcCache`] once fetched.
+//!
+//! [`FailoverProvider::with_tx_filter`] narrows block transactions to a
+//! [`TxFilter`] before they are returned.
+
+use crate::budget::RpcBudget;
+use crate::code::CodeCache;
+use crate::filter::TxFilter;
+use crate::metrics::{MetricsSnapshot, ProviderMetrics};
+use crate::rpc::RpcProvider;
+use crate::rpc_cache::RpcCache;
+use crate::{BlockId, BlockRef, DataProvider, Prefetcher};
+use alloy_json_rpc::{ErrorPayload, RpcError};
+use alloy_primitives::B256;
+use alloy_provider::DynProvider;
+use alloy_transport::{TransportError, TransportErrorKind};
+use argus_core::chain::ChainConfig;
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{AccessList, BlockContext, Transaction, TxReceipt};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Consecutive 429s on the active endpoint before rotating away from it.
+pub const RATE_LIMIT_STRIKES: u32 = 3;
+
+/// Why a request failed, as far as failover is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Failure {
+    /// HTTP 429: back off, rotate if it keeps happening.
+    RateLimited,
+    /// Connection refused/reset, DNS, timeout or 5xx gateway error.
+    Unreachable,
+    /// Anything else (bad request, missing block): retrying won't help.
+    Other,
+}
+
+impl Failure {
+    /// Classify a transport error by its kind and HTTP status.
+    pub(crate) fn of_transport(error: &TransportError) -> Self {
+        match error {
+            RpcError::Transport(TransportErrorKind::HttpError(http)) => {
+                Self::of_status(http.status)
+            }
+            // Connection-level failures of the underlying client.
+            RpcError::Transport(
+                TransportErrorKind::BackendGone | TransportErrorKind::Custom(_),
+            ) => Self::Unreachable,
+            RpcError::ErrorResp(payload) => Self::of_payload(payload),
+            _ => Self::Other,
+        }
+    }
+
+    /// Classify a JSON-RPC error object. Some gateways answer rate limits
+    /// with code 429, Infura and others with -32005 (EIP-1474's "limit
+    /// exceeded").
+    pub(crate) fn of_payload<E>(payload: &ErrorPayload<E>) -> Self {
+        let message = payload.message.to_ascii_lowercase();
+        if matches!(payload.code, 429 | -32005)
+            || message.contains("too many requests")
+            || message.contains("rate limit")
+        {
+            Self::RateLimited
+        } else {
+            Self::Other
+        }
+    }
+
+    fn of_status(status: u16) -> Self {
+        match status {
+            429 => Self::RateLimited,
+            502..=504 => Self::Unreachable,
+            _ => Self::Other,
+        }
+    }
+
+    /// Classify an error that only survives as its message, by the phrases
+    /// alloy and reqwest render (`HTTP error 429`, `error code -32005`).
+    /// Bare status numbers are not enough: messages carry block numbers
+    /// and hashes.
+    pub(crate) fn classify(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+        if mentions(&[
+            "http error 429",
+            "error code 429",
+            "error code -32005",
+            "too many requests",
+            "rate limit",
+        ]) {
+            Self::RateLimited
+        } else if mentions(&[
+            "http error 502",
+            "http error 503",
+            "http error 504",
+            "error sending request",
+            "dns error",
+            "failed to connect",
+            "connection refused",
+            "connection reset",
+            "connection closed",
+            "backend connection task has stopped",
+            "timed out",
+            "timeout",
+        ]) {
+            Self::Unreachable
+        } else {
+            Self::Other
+        }
+    }
+
+    fn of(error: &ArgusError) -> Self {
+        match error.root() {
+            ArgusError::Provider(message) => Self::classify(message),
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Endpoints plus which one is active, shared across clones.
+pub(crate) struct Endpoints {
+    providers: Vec<DynProvider>,
+    urls: Vec<String>,
+    metrics: Vec<Arc<ProviderMetrics>>,
+    active: AtomicUsize,
+    rate_limited: AtomicU32,
+}
+
+impl Endpoints {
+    /// A single endpoint: failures never rotate. Its request counters are
+    /// not fed by `provider`, so only retries are recorded.
+    pub(crate) fn single(provider: DynProvider) -> Self {
+        Self::new(
+            vec![provider],
+            vec![String::new()],
+            vec![ProviderMetrics::new()],
+        )
+    }
+
+    fn new(
+        providers: Vec<DynProvider>,
+        urls: Vec<String>,
+        metrics: Vec<Arc<ProviderMetrics>>,
+    ) -> Self {
+        Self {
+            providers,
+            urls,
+            metrics,
+            active: AtomicUsize::new(0),
+            rate_limited: AtomicU32::new(0),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Index and handle of the active endpoint.
+    pub(crate) fn current(&self) -> (usize, &DynProvider) {
+        let idx = self.active.load(Ordering::Acquire);
+        (idx, &self.providers[idx])
+    }
+
+    pub(crate) fn record_success(&self, idx: usize) {
+        if self.active.load(Ordering::Acquire) == idx {
+            self.rate_limited.store(0, Ordering::Release);
+        }
+    }
+
+    /// Record a failed request on endpoint `idx`, rotating when warranted.
+    /// Returns whether the request is worth retrying.
+    pub(crate) fn record_failure(&self, idx: usize, failure: Failure) -> bool {
+        match failure {
+            Failure::RateLimited => {
+                let strikes = self.rate_limited.fetch_add(1, Ordering::AcqRel) + 1;
+                if strikes >= RATE_LIMIT_STRIKES {
+                    self.rotate(idx, "rate limited");
+                }
+                true
+            }
+            Failure::Unreachable => {
+                self.rotate(idx, "unreachable");
+                self.len() > 1
+            }
+            Failure::Other => false,
+        }
+    }
+
+    /// Count a retry of a request that failed on endpoint `idx`.
+    pub(crate) fn record_retry(&self, idx: usize) {
+        self.metrics[idx].record_retry();
+    }
+
+    /// Metrics of every endpoint combined.
+    pub(crate) fn metrics(&self) -> MetricsSnapshot {
+        let mut total = MetricsSnapshot::default();
+        for metrics in &self.metrics {
+            total.merge(&metrics.snapshot());
+        }
+        total
+    }
+
+    /// Move past `from`. Concurrent failures on the same endpoint rotate once.
+    fn rotate(&self, from: usize, reason: &str) {
+        if self.len() < 2 {
+            return;
+        }
+        let next = (from + 1) % self.len();
+        if self
+            .active
+            .compare_exchange(from, next, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            self.rate_limited.store(0, Ordering::Release);
+            tracing::warn!(
+                from = %self.urls[from],
+                to = %self.urls[next],
+                reason,
+                "rpc failover"
+            );
+        }
+    }
+}
+
+type Call<'a, T> = Pin<Box<dyn Future<Output = ArgusResult<T>> + Send + 'a>>;
+
+/// [`DataProvider`] over several RPC endpoints, in priority order.
+///
+/// ```ignore
+/// let provider = FailoverProvider::connect_http(&[primary, backup])?;
+/// let prefetcher = provider.prefetcher();
+/// ```
+pub struct FailoverProvider {
+    endpoints: Vec<RpcProvider>,
+    shared: Arc<Endpoints>,
+    budget: Arc<RpcBudget>,
+    rpc_cache: Option<Arc<RpcCache>>,
+    /// Shared by every prefetcher, so code is fetched once across blocks.
+    code_cache: Arc<CodeCache>,
+    chain: Option<&'static ChainConfig>,
+    tx_filter: Option<TxFilter>,
+}
+
+impl FailoverProvider {
+    /// Builds an HTTP(S) provider per URL; the first is used until it fails.
+    pub fn connect_http<S: AsRef<str>>(rpc_urls: &[S]) -> ArgusResult<Self> {
+        let endpoints = rpc_urls
+            .iter()
+            .map(|url| RpcProvider::connect_http(url.as_ref()))
+            .collect::<ArgusResult<Vec<_>>>()?;
+        Self::from_endpoints(endpoints, rpc_urls)
+    }
+
+    /// Like [`connect_http`](Self::connect_http), accepting any endpoint
+    /// [`RpcProvider::connect`] does (WebSocket, IPC). Also detects the
+    /// chain, see [`chain_config`](Self::chain_config).
+    pub async fn connect<S: AsRef<str>>(rpc_urls: &[S]) -> ArgusResult<Self> {
+        let mut endpoints = Vec::with_capacity(rpc_urls.len());
+        for url in rpc_urls {
+            endpoints.push(RpcProvider::connect(url.as_ref()).await?);
+        }
+        Self::from_endpoints(endpoints, rpc_urls)?
+            .detect_chain()
+            .await
+    }
+
+    /// Ask the active endpoint for its chain id and keep the chain's config.
+    pub async fn detect_chain(mut self) -> ArgusResult<Self> {
+        let chain = self.call(|p| p.chain()).await?;
+        tracing::info!(
+            chain_id = chain.chain_id,
+            chain = chain.name,
+            "detected chain"
+        );
+        self.chain = Some(chain);
+        Ok(self)
+    }
+
+    /// Serve `chain` without asking the node.
+    pub fn with_chain(mut self, chain: &'static ChainConfig) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// The chain from [`detect_chain`](Self::detect_chain) or
+    /// [`with_chain`](Self::with_chain), if either ran.
+    pub fn chain_config(&self) -> Option<&'static ChainConfig> {
+        self.chain
+    }
+
+    fn from_endpoints<S: AsRef<str>>(
+        endpoints: Vec<RpcProvider>,
+        rpc_urls: &[S],
+    ) -> ArgusResult<Self> {
+        if endpoints.is_empty() {
+            return Err(ArgusError::InvalidInput(
+                "at least one RPC URL is required".into(),
+            ));
+        }
+        let shared = Endpoints::new(
+            endpoints.iter().map(RpcProvider::provider).collect(),
+            rpc_urls
+                .iter()
+                .map(|url| url.as_ref().to_string())
+                .collect(),
+            endpoints.iter().map(RpcProvider::metrics).collect(),
+        );
+        Ok(Self {
+            endpoints,
+            shared: Arc::new(shared),
+            budget: RpcBudget::unlimited(),
+            rpc_cache: None,
+            code_cache: Arc::new(CodeCache::new()),
+            chain: None,
+            tx_filter: None,
+        })
+    }
+
+    /// Draw every request, on any endpoint, from a shared budget.
+    pub fn with_budget(mut self, budget: Arc<RpcBudget>) -> Self {
+        self.endpoints = self
+            .endpoints
+            .into_iter()
+            .map(|endpoint| endpoint.with_budget(budget.clone()))
+            .collect();
+        self.budget = budget;
+        self
+    }
+
+    /// Serve block transactions, receipts, hashes and timestamps from
+    /// `cache`, fetching each at most once; the prefetcher shares it.
+    pub fn with_rpc_cache(mut self, cache: Arc<RpcCache>) -> Self {
+        self.rpc_cache = Some(cache);
+        self
+    }
+
+    /// Return only the block transactions `filter` matches. The RPC cache
+    /// still holds whole blocks.
+    pub fn with_tx_filter(mut self, filter: TxFilter) -> Self {
+        self.tx_filter = Some(filter).filter(|f| !f.is_empty());
+        self
+    }
+
+    /// The budget shared by all endpoints.
+    pub fn budget(&self) -> Arc<RpcBudget> {
+        self.budget.clone()
+    }
+
+    /// Request metrics per endpoint URL, including the prefetcher's traffic.
+    pub fn endpoint_metrics(&self) -> Vec<(&str, MetricsSnapshot)> {
+        self.shared
+            .urls
+            .iter()
+            .zip(&self.endpoints)
+            .map(|(url, endpoint)| (url.as_str(), endpoint.metrics_snapshot()))
+            .collect()
+    }
+
+    /// Request metrics of all endpoints combined.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.shared.metrics()
+    }
+
+    /// URL of the endpoint requests currently go to.
+    pub fn active_url(&self) -> &str {
+        &self.shared.urls[self.shared.current().0]
+    }
+
+    /// A [`Prefetcher`] that follows this provider's failover, drawing from
+    /// the same budget and sharing one [`CodeCache`] with the others.
+    pub fn prefetcher(&self) -> Prefetcher {
+        let prefetcher = Prefetcher::with_endpoints(self.shared.clone())
+            .with_budget(self.budget())
+            .with_code_cache(self.code_cache.clone());
+        match &self.rpc_cache {
+            Some(cache) => prefetcher.with_rpc_cache(cache.clone()),
+            None => prefetcher,
+        }
+    }
+
+    /// `call`'s result for `block` from the RPC cache, fetching and storing
+    /// it on a miss. Tags always go to the node.
+    async fn cached<T, F>(&self, kind: &str, block: BlockId, call: F) -> ArgusResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: for<'a> Fn(&'a RpcProvider) -> Call<'a, T>,
+    {
+        let (Some(cache), Some(key)) = (&self.rpc_cache, RpcCache::block_key(block)) else {
+            return self.call(call).await;
+        };
+        let chain_id = cache.chain_id(|| self.chain_id()).await?;
+        if let Some(value) = cache.get(chain_id, kind, &key)? {
+            return Ok(value);
+        }
+        let value = self.call(call).await?;
+        cache.put(chain_id, kind, &key, &value)?;
+        Ok(value)
+    }
+
+    /// Run `call` on the active endpoint, failing over until it succeeds,
+    /// fails for a reason another endpoint won't fix, or every endpoint has
+    /// been given [`RATE_LIMIT_STRIKES`] tries.
+    async fn call<T, F>(&self, call: F) -> ArgusResult<T>
+    where
+        F: for<'a> Fn(&'a RpcProvider) -> Call<'a, T>,
+    {
+        let attempts = self.endpoints.len() as u32 * RATE_LIMIT_STRIKES;
+        let mut attempt = 0;
+        loop {
+            let (idx, _) = self.shared.current();
+            let err = match call(&self.endpoints[idx]).await {
+                Ok(value) => {
+                    self.shared.record_success(idx);
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+            let failure = Failure::of(&err);
+            attempt += 1;
+            if !self.shared.record_failure(idx, failure) || attempt >= attempts {
+                return Err(err);
+            }
+            self.shared.record_retry(idx);
+            if failure == Failure::RateLimited {
+                tokio::time::sleep(Duration::from_millis(200 * 2u64.pow(attempt.min(5)))).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DataProvider for FailoverProvider {
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>> {
+        let txs = self
+            .cached("transactions", block, |p| p.get_block_transactions(block))
+            .await?;
+        Ok(match &self.tx_filter {
+            Some(filter) => filter.apply(txs),
+            None => txs,
+        })
+    }
+
+    async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>> {
+        self.call(|p| p.get_pending_transactions()).await
+    }
+
+    async fn chain_id(&self) -> ArgusResult<u64> {
+        match self.chain {
+            Some(chain) => Ok(chain.chain_id),
+            None => self.call(|p| p.chain_id()).await,
+        }
+    }
+
+    async fn chain(&self) -> ArgusResult<&'static ChainConfig> {
+        match self.chain {
+            Some(chain) => Ok(chain),
+            None => self.call(|p| p.chain()).await,
+        }
+    }
+
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256> {
+        self.cached("hash", block, |p| p.get_block_hash(block))
+            .await
+    }
+
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64> {
+        self.cached("timestamp", block, |p| p.get_block_timestamp(block))
+            .await
+    }
+
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext> {
+        self.cached("context", block, |p| p.get_block_context(block))
+            .await
+    }
+
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        self.cached("block_ref", block, |p| p.resolve_block(block))
+            .await
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        self.cached("receipts", block, |p| p.get_block_receipts(block))
+            .await
+    }
+
+    async fn trace_access_lists(&self, block: BlockId) -> ArgusResult<Vec<AccessList>> {
+        self.call(|p| p.trace_access_lists(block)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(n: usize) -> Endpoints {
+        let urls: Vec<String> = (0..n)
+            .map(|i| format!("http://127.0.0.1:{}", 9000 + i))
+            .collect();
+        let rpcs: Vec<_> = urls
+            .iter()
+            .map(|url| RpcProvider::connect_http(url).unwrap())
+            .collect();
+        Endpoints::new(
+            rpcs.iter().map(RpcProvider::provider).collect(),
+            urls,
+            rpcs.iter().map(RpcProvider::metrics).collect(),
+        )
+    }
+
+    #[test]
+    fn classifies_rpc_errors() {
+        assert_eq!(
+            Failure::classify("HTTP error 429 with body: rate limit"),
+            Failure::RateLimited
+        );
+        assert_eq!(
+            Failure::classify("error sending request for url (http://x/)"),
+            Failure::Unreachable
+        );
+        assert_eq!(
+            Failure::classify("operation timed out"),
+            Failure::Unreachable
+        );
+        assert_eq!(Failure::classify("Block 5 not found"), Failure::Other);
+        // Status-like digits inside block numbers and hashes are not statuses.
+        assert_eq!(
+            Failure::classify("Block 21504123 not found"),
+            Failure::Other
+        );
+        assert_eq!(
+            Failure::classify("Failed to fetch block 0x4290…5031: header not found"),
+            Failure::Other
+        );
+        assert_eq!(
+            Failure::classify(
+                "server returned an error response: error code -32005: limit exceeded"
+            ),
+            Failure::RateLimited
+        );
+        let err = ArgusError::Provider("HTTP error 503".into()).with_context(|c| c.block = Some(1));
+        assert_eq!(Failure::of(&err), Failure::Unreachable);
+        assert_eq!(Failure::of(&ArgusError::Cancelled), Failure::Other);
+    }
+
+    #[test]
+    fn classifies_transport_errors_by_kind() {
+        assert_eq!(
+            Failure::of_transport(&TransportErrorKind::http_error(429, String::new())),
+            Failure::RateLimited
+        );
+        assert_eq!(
+            Failure::of_transport(&TransportErrorKind::http_error(
+                503,
+                "Block 21504123".into()
+            )),
+            Failure::Unreachable
+        );
+        assert_eq!(
+            Failure::of_transport(&TransportErrorKind::http_error(400, "429".into())),
+            Failure::Other
+        );
+        assert_eq!(
+            Failure::of_transport(&TransportErrorKind::backend_gone()),
+            Failure::Unreachable
+        );
+        let payload = |code, message: &'static str| ErrorPayload::<()> {
+            code,
+            message: message.into(),
+            data: None,
+        };
+        assert_eq!(
+            Failure::of_payload(&payload(-32005, "limit exceeded")),
+            Failure::RateLimited
+        );
+        assert_eq!(
+            Failure::of_payload(&payload(-32000, "header not found for block 429")),
+            Failure::Other
+        );
+    }
+
+    #[test]
+    fn unreachable_rotates_once_per_endpoint() {
+        let e = endpoints(3);
+        assert!(e.record_failure(0, Failure::Unreachable));
+        assert_eq!(e.current().0, 1);
+        // A late failure from a request still on endpoint 0 doesn't skip 1.
+        e.record_failure(0, Failure::Unreachable);
+        assert_eq!(e.current().0, 1);
+        e.record_failure(1, Failure::Unreachable);
+        e.record_failure(2, Failure::Unreachable);
+        assert_eq!(e.current().0, 0);
+        assert!(!e.record_failure(0, Failure::Other));
+    }
+
+    #[test]
+    fn repeated_rate_limits_rotate() {
+        let e = endpoints(2);
+        for _ in 1..RATE_LIMIT_STRIKES {
+            assert!(e.record_failure(0, Failure::RateLimited));
+        }
+        e.record_success(0);
+        for _ in 1..RATE_LIMIT_STRIKES {
+            e.record_failure(0, Failure::RateLimited);
+        }
+        assert_eq!(e.current().0, 0);
+        e.record_failure(0, Failure::RateLimited);
+        assert_eq!(e.current().0, 1);
+    }
+
+    #[test]
+    fn single_endpoint_never_rotates() {
+        let e = endpoints(1);
+        assert!(!e.record_failure(0, Failure::Unreachable));
+        assert!(e.record_failure(0, Failure::RateLimited));
+        assert_eq!(e.current().0, 0);
+        assert!(FailoverProvider::connect_http::<&str>(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn cached_blocks_skip_the_node() {
+        let dir =
+            std::env::temp_dir().join(format!("argus-rpc-cache-failover-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = Arc::new(RpcCache::new(&dir).with_chain_id(1));
+        cache.put(1, "timestamp", "7", &1_700_000_000u64).unwrap();
+
+        // Nothing listens on the endpoint: only cache hits can succeed.
+        let provider = FailoverProvider::connect_http(&["http://127.0.0.1:9"])
+            .unwrap()
+            .with_rpc_cache(cache);
+        let timestamp = provider.get_block_timestamp(BlockId::number(7)).await;
+        assert_eq!(timestamp.unwrap(), 1_700_000_000);
+        assert!(provider
+            .get_block_timestamp(BlockId::number(8))
+            .await
+            .is_err());
+        assert!(provider
+            .get_block_timestamp(BlockId::latest())
+            .await
+            .is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,148 @@
+//! The node behind a [`FallbackDB`](crate::FallbackDB).
+//!
+//! Reads the warm cache misses during simulation go through the same
+//! [`RpcBudget`], [`RetryPolicy`] and endpoint failover as the prefetch, at
+//! [`Priority::High`]: a simulating thread is blocked on each one.
+
+use crate::budget::{Priority, RpcBudget};
+use crate::code::CodeCache;
+use crate::failover::Endpoints;
+use crate::retry::RetryPolicy;
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, B256, U256};
+use alloy_provider::{DynProvider, Provider};
+use alloy_transport::TransportError;
+use revm::database_interface::async_db::DatabaseAsyncRef;
+use revm::database_interface::DBErrorMarker;
+use revm::state::{AccountInfo, Bytecode};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A node read that failed for good.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct NodeError(String);
+
+impl DBErrorMarker for NodeError {}
+
+/// State at one block, read from the active endpoint on demand.
+pub struct NodeDB {
+    endpoints: Arc<Endpoints>,
+    budget: Arc<RpcBudget>,
+    retry: Arc<RetryPolicy>,
+    code_cache: Arc<CodeCache>,
+    block_id: BlockId,
+}
+
+impl NodeDB {
+    pub(crate) fn new(
+        endpoints: Arc<Endpoints>,
+        budget: Arc<RpcBudget>,
+        retry: Arc<RetryPolicy>,
+        code_cache: Arc<CodeCache>,
+        block_id: BlockId,
+    ) -> Self {
+        Self {
+            endpoints,
+            budget,
+            retry,
+            code_cache,
+            block_id,
+        }
+    }
+
+    /// Run `call` on the active endpoint, charging `cost` requests per
+    /// attempt and retrying per the policy with failover.
+    async fn request<T, F, Fut>(&self, cost: u32, call: F) -> Result<T, NodeError>
+    where
+        F: Fn(DynProvider) -> Fut,
+        Fut: Future<Output = Result<T, TransportError>>,
+    {
+        let started = Instant::now();
+        for attempt in 0..self.retry.max_attempts() {
+            self.retry.backoff(attempt).await;
+
+            let _permit = self
+                .budget
+                .acquire_batch(Priority::High, cost)
+                .await
+                .map_err(|e| NodeError(e.to_string()))?;
+            let (idx, p) = self.endpoints.current();
+            match call(p.clone()).await {
+                Ok(value) => {
+                    self.endpoints.record_success(idx);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    let e = e.to_string();
+                    if self
+                        .retry
+                        .on_failure(&self.endpoints, idx, attempt, started, &e)
+                    {
+                        continue;
+                    }
+                    return Err(NodeError(e));
+                }
+            }
+        }
+        Err(NodeError("max retries exceeded".to_string()))
+    }
+}
+
+impl DatabaseAsyncRef for NodeDB {
+    type Error = NodeError;
+
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, NodeError> {
+        let block_id = self.block_id;
+        // A contract whose code an earlier fetch saw only needs balance and nonce.
+        let known = self.code_cache.contract(&address);
+        let with_code = known.is_none();
+        let (balance, nonce, code) = self
+            .request(if with_code { 3 } else { 2 }, |p| async move {
+                let balance = p.get_balance(address).block_id(block_id);
+                let nonce = p.get_transaction_count(address).block_id(block_id);
+                let code = async {
+                    match with_code {
+                        true => p.get_code_at(address).block_id(block_id).await.map(Some),
+                        false => Ok(None),
+                    }
+                };
+                let (balance, nonce, code) = tokio::join!(balance, nonce, code);
+                Ok::<_, TransportError>((balance?, nonce?, code?))
+            })
+            .await?;
+        let (code_hash, bytecode) = match known {
+            Some(known) => known,
+            None => self
+                .code_cache
+                .intern_contract(address, code.unwrap_or_default()),
+        };
+        Ok(Some(AccountInfo::new(balance, nonce, code_hash, bytecode)))
+    }
+
+    async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, NodeError> {
+        // `basic_async_ref` always returns code, so the cache never asks.
+        Err(NodeError(format!("no code by hash for {code_hash}")))
+    }
+
+    async fn storage_async_ref(&self, address: Address, index: U256) -> Result<U256, NodeError> {
+        let block_id = self.block_id;
+        self.request(1, |p| async move {
+            p.get_storage_at(address, index).block_id(block_id).await
+        })
+        .await
+    }
+
+    async fn block_hash_async_ref(&self, number: u64) -> Result<B256, NodeError> {
+        let block = self
+            .request(
+                1,
+                |p| async move { p.get_block_by_number(number.into()).await },
+            )
+            .await?;
+        block
+            .map(|block| block.header.hash)
+            .ok_or_else(|| NodeError(format!("block {number} not found")))
+    }
+}
@@ -0,0 +1,288 @@
+//! Offline [`DataProvider`] backed by a JSON fixture file.
+//!
+//! A fixture holds one or more [`FixtureBlock`]s, written as a single JSON
+//! object, a JSON array, or NDJSON (one block per line). Block fixtures
+//! recorded with `argus analyze --record DIR` load as-is, so a bug report
+//! can ship the exact block it was seen on and be analyzed without a node.
+//!
+//! ```json
+//! {"chain_id": 1, "block_number": 21000000, "timestamp": 1729000000,
+//!  "transactions": [{"hash": "0x..", "from": "0x..", "to": "0x..",
+//!                    "input": "0x..", "value": "0x0", "gas": 21000}],
+//!  "state": "21000000.snap"}
+//! ```
+//!
+//! Pre-fetched state is a [snapshot](crate::snapshot) named by the block's
+//! `state` field, or `<block>.snap` next to the fixture (the `--record`
+//! layout); [`FileProvider::state_path`] finds it for `LoadStateStage`.
+//!
+//! ```ignore
+//! let provider = Arc::new(FileProvider::load("21000000.json")?);
+//! let block = provider.resolve_block(BlockId::latest()).await?;
+//! ```
+
+use crate::{describe_block, BlockRef, DataProvider};
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{keccak256, B256};
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{BlockContext, Transaction, TxReceipt};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// One block in a fixture file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixtureBlock {
+    /// Defaults to mainnet.
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    pub block_number: u64,
+    /// Defaults to `keccak256(block_number)`.
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    #[serde(default)]
+    pub block_context: Option<BlockContext>,
+    pub transactions: Vec<Transaction>,
+    /// Served by `get_block_receipts` when present.
+    #[serde(default)]
+    pub receipts: Option<Vec<TxReceipt>>,
+    /// Snapshot of the block's pre-state, relative to the fixture file.
+    #[serde(default)]
+    pub state: Option<PathBuf>,
+}
+
+impl FixtureBlock {
+    fn hash(&self) -> B256 {
+        self.block_hash
+            .unwrap_or_else(|| keccak256(self.block_number.to_be_bytes()))
+    }
+
+    fn context(&self) -> BlockContext {
+        let mut context = self.block_context.clone().unwrap_or_default();
+        context.number = self.block_number;
+        if let Some(timestamp) = self.timestamp {
+            context.timestamp = timestamp;
+        }
+        context
+    }
+}
+
+/// Serves blocks from a fixture file. See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    chain_id: u64,
+    blocks: BTreeMap<u64, FixtureBlock>,
+    /// Directory relative `state` paths resolve against.
+    dir: PathBuf,
+}
+
+impl FileProvider {
+    /// Read the fixture at `path`.
+    pub fn load(path: impl AsRef<Path>) -> ArgusResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            ArgusError::InvalidInput(format!("cannot read fixture {}: {e}", path.display()))
+        })?;
+        let blocks = parse_fixture(&text).map_err(|e| {
+            ArgusError::InvalidInput(format!("invalid fixture {}: {e}", path.display()))
+        })?;
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::from_blocks(blocks, dir)
+    }
+
+    /// Serve `blocks` directly; relative `state` paths resolve against `dir`.
+    pub fn from_blocks(blocks: Vec<FixtureBlock>, dir: impl Into<PathBuf>) -> ArgusResult<Self> {
+        if blocks.is_empty() {
+            return Err(ArgusError::InvalidInput("fixture has no blocks".into()));
+        }
+        let chain_id = blocks.iter().find_map(|b| b.chain_id).unwrap_or(1);
+        if let Some(other) = blocks
+            .iter()
+            .filter_map(|b| b.chain_id)
+            .find(|&id| id != chain_id)
+        {
+            return Err(ArgusError::InvalidInput(format!(
+                "fixture mixes chains {chain_id} and {other}"
+            )));
+        }
+        Ok(Self {
+            chain_id,
+            blocks: blocks.into_iter().map(|b| (b.block_number, b)).collect(),
+            dir: dir.into(),
+        })
+    }
+
+    /// Block numbers in the fixture, ascending.
+    pub fn block_numbers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.blocks.keys().copied()
+    }
+
+    /// Pre-state snapshot for block `number`: its `state` field, else an
+    /// existing `<number>.snap` beside the fixture.
+    pub fn state_path(&self, number: u64) -> Option<PathBuf> {
+        let block = self.blocks.get(&number)?;
+        match &block.state {
+            Some(path) => Some(self.dir.join(path)),
+            None => Some(self.dir.join(format!("{number}.snap"))).filter(|p| p.is_file()),
+        }
+    }
+
+    /// The block `id` refers to. Tags other than `earliest` mean the
+    /// highest block in the fixture.
+    fn block(&self, id: BlockId) -> ArgusResult<&FixtureBlock> {
+        let block = match id {
+            BlockId::Hash(h) => self.blocks.values().find(|b| b.hash() == h.block_hash),
+            BlockId::Number(BlockNumberOrTag::Number(n)) => self.blocks.get(&n),
+            BlockId::Number(BlockNumberOrTag::Earliest) => self.blocks.values().next(),
+            BlockId::Number(_) => self.blocks.values().next_back(),
+        };
+        block.ok_or_else(|| {
+            ArgusError::InvalidInput(format!("block {} not in fixture", describe_block(id)))
+        })
+    }
+}
+
+/// A JSON array of blocks, or a stream of block objects (one object, or
+/// NDJSON).
+fn parse_fixture(text: &str) -> serde_json::Result<Vec<FixtureBlock>> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text);
+    }
+    serde_json::Deserializer::from_str(text)
+        .into_iter::<FixtureBlock>()
+        .collect()
+}
+
+#[async_trait]
+impl DataProvider for FileProvider {
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>> {
+        Ok(self.block(block)?.transactions.clone())
+    }
+
+    async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>> {
+        Err(ArgusError::InvalidInput(
+            "fixture has no pending transactions".into(),
+        ))
+    }
+
+    async fn chain_id(&self) -> ArgusResult<u64> {
+        Ok(self.chain_id)
+    }
+
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256> {
+        Ok(self.block(block)?.hash())
+    }
+
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64> {
+        Ok(self.block(block)?.context().timestamp)
+    }
+
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext> {
+        Ok(self.block(block)?.context())
+    }
+
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        let block = self.block(block)?;
+        Ok(BlockRef {
+            number: block.block_number,
+            hash: block.hash(),
+        })
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        let block = self.block(block)?;
+        block.receipts.clone().ok_or_else(|| {
+            ArgusError::InvalidInput(format!(
+                "fixture has no receipts for block {}",
+                block.block_number
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TX: &str = r#"{"hash":"0x0101010101010101010101010101010101010101010101010101010101010101",
+        "from":"0x0202020202020202020202020202020202020202",
+        "to":"0x0303030303030303030303030303030303030303",
+        "input":"0x","value":"0x0","gas":21000}"#;
+
+    #[tokio::test]
+    async fn serves_blocks_from_ndjson_and_arrays() {
+        let ndjson = format!(
+            "{{\"chain_id\":8453,\"block_number\":7,\"timestamp\":84,\"transactions\":[{}]}}\n\
+             {{\"block_number\":9,\"transactions\":[]}}\n",
+            TX.replace('\n', "")
+        );
+        let blocks = parse_fixture(&ndjson).unwrap();
+        let provider = FileProvider::from_blocks(blocks, "fixtures").unwrap();
+        assert_eq!(provider.chain_id().await.unwrap(), 8453);
+        assert_eq!(provider.block_numbers().collect::<Vec<_>>(), [7, 9]);
+
+        let txs = provider
+            .get_block_transactions(BlockId::number(7))
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+        let context = provider
+            .get_block_context(BlockId::number(7))
+            .await
+            .unwrap();
+        assert_eq!((context.number, context.timestamp), (7, 84));
+
+        let latest = provider.resolve_block(BlockId::latest()).await.unwrap();
+        assert_eq!(latest.number, 9);
+        assert_eq!(latest.hash, keccak256(9u64.to_be_bytes()));
+        let by_hash = provider
+            .get_block_transactions(BlockId::hash(latest.hash))
+            .await
+            .unwrap();
+        assert!(by_hash.is_empty());
+        assert!(provider
+            .get_block_transactions(BlockId::number(8))
+            .await
+            .is_err());
+        assert!(provider
+            .get_block_receipts(BlockId::number(7))
+            .await
+            .is_err());
+
+        let array = format!("[{{\"block_number\":7,\"transactions\":[{TX}]}}]");
+        let blocks = parse_fixture(&array).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].transactions.len(), 1);
+    }
+
+    #[test]
+    fn state_paths_and_chains() {
+        let block = |number, chain_id, state: Option<&str>| FixtureBlock {
+            chain_id,
+            block_number: number,
+            state: state.map(PathBuf::from),
+            ..Default::default()
+        };
+        let provider = FileProvider::from_blocks(
+            vec![block(1, None, Some("one.snap")), block(2, None, None)],
+            "fixtures",
+        )
+        .unwrap();
+        assert_eq!(
+            provider.state_path(1),
+            Some(Path::new("fixtures").join("one.snap"))
+        );
+        assert_eq!(provider.state_path(2), None);
+        assert_eq!(provider.state_path(3), None);
+
+        assert!(FileProvider::from_blocks(vec![], "").is_err());
+        assert!(FileProvider::from_blocks(
+            vec![block(1, Some(1), None), block(2, Some(10), None)],
+            ""
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Provider-side transaction filtering.
+//!
+//! A [`TxFilter`] drops block transactions before they reach the pipeline,
+//! so analyzing one protocol doesn't prefetch and simulate every unrelated
+//! transaction in the block. Each criterion matches any of its values;
+//! a transaction must pass every criterion that is set.
+//!
+//! ```ignore
+//! let filter = TxFilter::new()
+//!     .with_to(router)
+//!     .with_selector([0x38, 0xed, 0x17, 0x39])
+//!     .with_min_gas(100_000);
+//! let provider = FailoverProvider::connect_http(&[url])?.with_tx_filter(filter);
+//! ```
+
+use alloy_primitives::Address;
+use argus_core::Transaction;
+use serde::{Deserialize, Serialize};
+
+/// Which block transactions to keep. The default keeps all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxFilter {
+    /// Recipients to keep; contract creations never match.
+    #[serde(default)]
+    pub to: Vec<Address>,
+    #[serde(default)]
+    pub from: Vec<Address>,
+    /// 4-byte function selectors (the first four bytes of the input).
+    #[serde(default)]
+    pub selectors: Vec<[u8; 4]>,
+    /// Minimum gas limit.
+    #[serde(default)]
+    pub min_gas: Option<u64>,
+}
+
+impl TxFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep transactions sent to `address` (in addition to earlier ones).
+    pub fn with_to(mut self, address: Address) -> Self {
+        self.to.push(address);
+        self
+    }
+
+    /// Keep transactions sent from `address` (in addition to earlier ones).
+    pub fn with_from(mut self, address: Address) -> Self {
+        self.from.push(address);
+        self
+    }
+
+    /// Keep calls to `selector` (in addition to earlier ones).
+    pub fn with_selector(mut self, selector: [u8; 4]) -> Self {
+        self.selectors.push(selector);
+        self
+    }
+
+    /// Keep transactions with a gas limit of at least `gas`.
+    pub fn with_min_gas(mut self, gas: u64) -> Self {
+        self.min_gas = Some(gas);
+        self
+    }
+
+    /// Whether no criterion is set, so every transaction passes.
+    pub fn is_empty(&self) -> bool {
+        self.to.is_empty()
+            && self.from.is_empty()
+            && self.selectors.is_empty()
+            && self.min_gas.is_none()
+    }
+
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        (self.to.is_empty() || tx.to.is_some_and(|to| self.to.contains(&to)))
+            && (self.from.is_empty() || self.from.contains(&tx.from))
+            && (self.selectors.is_empty()
+                || tx
+                    .input
+                    .get(..4)
+                    .is_some_and(|selector| self.selectors.iter().any(|s| s == selector)))
+            && self.min_gas.is_none_or(|min| tx.gas >= min)
+    }
+
+    /// Keep the matching transactions, in block order.
+    pub fn apply(&self, mut txs: Vec<Transaction>) -> Vec<Transaction> {
+        if !self.is_empty() {
+            txs.retain(|tx| self.matches(tx));
+        }
+        txs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+
+    fn tx(n: u8, to: Option<Address>, input: &[u8], gas: u64) -> Transaction {
+        Transaction {
+            hash: B256::repeat_byte(n),
+            from: Address::repeat_byte(n),
+            to,
+            input: Bytes::copy_from_slice(input),
+            gas,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn criteria_combine_and_values_alternate() {
+        let router = Address::repeat_byte(0xaa);
+        let other = Address::repeat_byte(0xbb);
+        let swap = [0x38, 0xed, 0x17, 0x39];
+        let txs = vec![
+            tx(1, Some(router), &[0x38, 0xed, 0x17, 0x39, 0], 200_000),
+            tx(2, Some(router), &[0xa9, 0x05, 0x9c, 0xbb], 200_000),
+            tx(3, Some(other), &[0x38, 0xed, 0x17, 0x39], 200_000),
+            tx(4, Some(router), &[0x38, 0xed, 0x17, 0x39], 21_000),
+            tx(5, None, &[0x38, 0xed, 0x17, 0x39], 200_000),
+            tx(6, Some(router), &[0x38], 200_000),
+        ];
+
+        assert_eq!(TxFilter::new().apply(txs.clone()).len(), txs.len());
+
+        let filter = TxFilter::new()
+            .with_to(router)
+            .with_selector(swap)
+            .with_min_gas(100_000);
+        let kept: Vec<u8> = filter
+            .apply(txs.clone())
+            .iter()
+            .map(|t| t.hash[0])
+            .collect();
+        assert_eq!(kept, [1]);
+
+        let filter = TxFilter::new().with_to(router).with_to(other);
+        let kept: Vec<u8> = filter
+            .apply(txs.clone())
+            .iter()
+            .map(|t| t.hash[0])
+            .collect();
+        assert_eq!(kept, [1, 2, 3, 4, 6]);
+
+        let filter = TxFilter::new().with_from(Address::repeat_byte(5));
+        let kept: Vec<u8> = filter.apply(txs).iter().map(|t| t.hash[0]).collect();
+        assert_eq!(kept, [5]);
+    }
+}
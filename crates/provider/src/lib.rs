@@ -1,19 +1,170 @@
 //! Data provider abstraction and state prefetching for Argus.
 
-pub mod labels;
+pub mod budget;
+pub mod bundle;
+pub mod code;
+pub mod failover;
+pub mod fallback;
+pub mod file;
+pub mod filter;
+pub mod metrics;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+mod multicall;
 pub mod prefetcher;
+pub mod progress;
+mod proof;
+pub mod retry;
 pub mod rpc;
+pub mod rpc_cache;
 pub mod slots;
+pub mod snapshot;
+pub mod state_json;
+mod trace;
 
-use argus_core::error::ArgusResult;
-use argus_core::Transaction;
+use alloy_primitives::B256;
+use argus_core::chain::{self, ChainConfig};
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{AccessList, BlockContext, Transaction, TxReceipt};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
 
-pub use prefetcher::{Prefetcher, WarmCacheDB};
+pub use alloy_eips::{BlockId, BlockNumberOrTag};
+pub use argus_core::labels;
+pub use argus_core::PrefetchStats;
+pub use budget::{BudgetLimits, Priority, RpcBudget};
+pub use bundle::{Bundle, BundleProvider};
+pub use failover::FailoverProvider;
+pub use file::{FileProvider, FixtureBlock};
+pub use filter::TxFilter;
+pub use metrics::{MetricsSnapshot, ProviderMetrics};
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{MockCall, MockProvider};
+pub use prefetcher::{FallbackDB, PrefetchStrategy, Prefetcher, TouchedState, WarmCacheDB};
+pub use progress::ProgressHandler;
+pub use retry::RetryPolicy;
+pub use rpc_cache::RpcCache;
+
+/// Blocks from [`DataProvider::get_block_range`], as `(number, transactions)`.
+pub type BlockStream = tokio::sync::mpsc::Receiver<ArgusResult<(u64, Vec<Transaction>)>>;
+
+/// A block pinned by both number and hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockRef {
+    pub number: u64,
+    pub hash: B256,
+}
 
 /// Abstraction for fetching transaction data from any source.
+///
+/// Blocks are addressed by [`BlockId`]: a number, a hash (reaches blocks
+/// that have since been reorged out) or a tag such as `finalized`. Tags move,
+/// so resolve them once with [`resolve_block`](Self::resolve_block) and pass
+/// the hash to later calls.
 #[async_trait]
-pub trait DataProvider: Send + Sync {
-    async fn get_block_transactions(&self, block_number: u64) -> ArgusResult<Vec<Transaction>>;
+pub trait DataProvider: Send + Sync + 'static {
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>>;
     async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>>;
+
+    /// EIP-155 chain id of the data source.
+    async fn chain_id(&self) -> ArgusResult<u64>;
+
+    /// Configuration of the chain behind [`chain_id`](Self::chain_id);
+    /// unregistered chains get [`chain::unregistered`] defaults.
+    async fn chain(&self) -> ArgusResult<&'static ChainConfig> {
+        Ok(chain::resolve(self.chain_id().await?))
+    }
+
+    /// Hash of `block` (identifies the block across reorgs).
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256>;
+
+    /// Header timestamp of `block` (selects the active hardfork).
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64>;
+
+    /// Header fields of `block` that transactions executing in it observe.
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext>;
+
+    /// Number and hash of the block `block` currently refers to.
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef>;
+
+    /// Fetches blocks `from..=to` with up to `concurrency` requests in
+    /// flight, yielding them in block order. The stream ends after the last
+    /// block or the first error; dropping the receiver stops fetching.
+    fn get_block_range(self: Arc<Self>, from: u64, to: u64, concurrency: usize) -> BlockStream {
+        let concurrency = concurrency.max(1);
+        let (tx, rx) = tokio::sync::mpsc::channel(concurrency);
+        tokio::spawn(async move {
+            let mut numbers = from..=to;
+            let mut in_flight = VecDeque::with_capacity(concurrency);
+            loop {
+                while in_flight.len() < concurrency {
+                    let Some(number) = numbers.next() else { break };
+                    let provider = self.clone();
+                    in_flight.push_back(tokio::spawn(async move {
+                        provider
+                            .get_block_transactions(BlockId::number(number))
+                            .await
+                            .map(|txs| (number, txs))
+                    }));
+                }
+                let Some(next) = in_flight.pop_front() else {
+                    break;
+                };
+                let block = next.await.unwrap_or_else(|e| {
+                    Err(ArgusError::Internal(format!(
+                        "block fetch task failed: {e}"
+                    )))
+                });
+                let failed = block.is_err();
+                if tx.send(block).await.is_err() || failed {
+                    in_flight.iter().for_each(|task| task.abort());
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    /// Receipts of every transaction in `block`, in block order.
+    /// Unsupported by default.
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        Err(ArgusError::InvalidInput(format!(
+            "provider cannot fetch receipts for block {}",
+            describe_block(block)
+        )))
+    }
+
+    /// Per-transaction storage access lists from the node's own tracer
+    /// rather than local simulation, in block order. Unsupported by
+    /// default.
+    async fn trace_access_lists(&self, block: BlockId) -> ArgusResult<Vec<AccessList>> {
+        Err(ArgusError::InvalidInput(format!(
+            "provider cannot trace block {}",
+            describe_block(block)
+        )))
+    }
+}
+
+/// Where warm state for a block's transactions comes from: a
+/// [`Prefetcher`] over RPC, or canned state in tests.
+#[async_trait]
+pub trait PrefetchSource: Send + Sync + 'static {
+    /// State `transactions` need as of `block`, using `chain`'s slot pack.
+    async fn prefetch_on(
+        &self,
+        chain: &ChainConfig,
+        block: BlockId,
+        transactions: &[Transaction],
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)>;
+}
+
+/// `block` for messages: numbers in decimal, hashes and tags as-is.
+pub(crate) fn describe_block(block: BlockId) -> String {
+    match block {
+        BlockId::Number(BlockNumberOrTag::Number(n)) => n.to_string(),
+        BlockId::Number(tag) => tag.to_string(),
+        BlockId::Hash(hash) => hash.block_hash.to_string(),
+    }
 }
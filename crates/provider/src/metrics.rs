@@ -0,0 +1,407 @@
+//! Per-endpoint RPC request metrics.
+//!
+//! Every [`RpcProvider`](crate::rpc::RpcProvider) installs a transport
+//! layer that counts requests, errors, 429s and payload bytes per JSON-RPC
+//! method and records their latency, so block fetches, prefetch and batch
+//! calls on that endpoint all land in one [`ProviderMetrics`]. Each call in
+//! a batch is counted on its own, with the batch's round trip as its
+//! latency. Retries are counted by the failover logic that decides them.
+//!
+//! ```ignore
+//! let snapshot = provider.metrics();
+//! eprint!("{}", snapshot.render());
+//! ```
+
+use crate::failover::Failure;
+use alloy_json_rpc::{RequestPacket, ResponsePacket, ResponsePayload};
+use alloy_transport::{TransportError, TransportFut};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+/// Upper bounds of the latency buckets, in milliseconds. Slower requests
+/// land in a final overflow bucket.
+pub const LATENCY_BUCKETS_MS: &[u64] = &[5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Counters for one endpoint, shared via `Arc`.
+#[derive(Debug)]
+pub struct ProviderMetrics {
+    started: Instant,
+    methods: Mutex<BTreeMap<String, MethodMetrics>>,
+    retries: AtomicU64,
+}
+
+/// Counters and latency histogram for one JSON-RPC method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    pub requests: u64,
+    /// Transport failures and JSON-RPC error responses, 429s included.
+    pub errors: u64,
+    pub rate_limited: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub latency: LatencyHistogram,
+}
+
+/// Request counts per [`LATENCY_BUCKETS_MS`] bucket, plus overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    pub counts: Vec<u64>,
+    pub total: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; LATENCY_BUCKETS_MS.len() + 1],
+            total: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= u128::from(bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.total += latency;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        let count = self.count();
+        (count > 0).then(|| self.total / count as u32)
+    }
+
+    /// Upper bound of the bucket holding quantile `q` (0.0..=1.0); `None`
+    /// when empty or in the overflow bucket.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return LATENCY_BUCKETS_MS
+                    .get(bucket)
+                    .map(|&ms| Duration::from_millis(ms));
+            }
+        }
+        None
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (a, b) in self.counts.iter_mut().zip(&other.counts) {
+            *a += b;
+        }
+        self.total += other.total;
+    }
+}
+
+impl MethodMetrics {
+    fn merge(&mut self, other: &Self) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.rate_limited += other.rate_limited;
+        self.request_bytes += other.request_bytes;
+        self.response_bytes += other.response_bytes;
+        self.latency.merge(&other.latency);
+    }
+}
+
+/// Point-in-time copy of [`ProviderMetrics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub elapsed: Duration,
+    pub methods: BTreeMap<String, MethodMetrics>,
+    /// Failed requests retried or moved to another endpoint.
+    pub retries: u64,
+}
+
+impl MetricsSnapshot {
+    /// All methods combined.
+    pub fn total(&self) -> MethodMetrics {
+        let mut total = MethodMetrics::default();
+        for method in self.methods.values() {
+            total.merge(method);
+        }
+        total
+    }
+
+    /// Combine with another endpoint's snapshot.
+    pub fn merge(&mut self, other: &Self) {
+        self.elapsed = self.elapsed.max(other.elapsed);
+        self.retries += other.retries;
+        for (name, method) in &other.methods {
+            self.methods.entry(name.clone()).or_default().merge(method);
+        }
+    }
+
+    /// Requests per second since the metrics were created.
+    pub fn requests_per_second(&self) -> f64 {
+        self.total().requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// One line per method, busiest first, then a total.
+    pub fn render(&self) -> String {
+        let mut methods: Vec<_> = self.methods.iter().collect();
+        methods.sort_by(|a, b| b.1.requests.cmp(&a.1.requests).then(a.0.cmp(b.0)));
+        let mut out = format!(
+            "{:<28} {:>8} {:>6} {:>6} {:>10} {:>9} {:>9}\n",
+            "method", "requests", "errors", "429s", "bytes in", "mean ms", "p99 ms"
+        );
+        let total = self.total();
+        for (name, m) in methods.into_iter().chain([(&"total".to_string(), &total)]) {
+            let p99 = m
+                .latency
+                .quantile(0.99)
+                .map_or_else(|| ">10000".to_string(), |d| d.as_millis().to_string());
+            let _ = writeln!(
+                out,
+                "{:<28} {:>8} {:>6} {:>6} {:>10} {:>9} {:>9}",
+                name,
+                m.requests,
+                m.errors,
+                m.rate_limited,
+                m.response_bytes,
+                m.latency.mean().map_or(0, |d| d.as_millis()),
+                if m.requests == 0 {
+                    "-".to_string()
+                } else {
+                    p99
+                },
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{} retries, {:.1} requests/s over {:.1}s",
+            self.retries,
+            self.requests_per_second(),
+            self.elapsed.as_secs_f64()
+        );
+        out
+    }
+}
+
+impl ProviderMetrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            started: Instant::now(),
+            methods: Mutex::new(BTreeMap::new()),
+            retries: AtomicU64::new(0),
+        })
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            elapsed: self.started.elapsed(),
+            methods: self.methods.lock().unwrap().clone(),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record(&self, call: &Call, latency: Duration, outcome: Outcome) {
+        let mut methods = self.methods.lock().unwrap();
+        let m = methods.entry(call.method.clone()).or_default();
+        m.requests += 1;
+        m.request_bytes += call.bytes as u64;
+        m.latency.record(latency);
+        match outcome {
+            Outcome::Ok { bytes } => m.response_bytes += bytes as u64,
+            Outcome::Failed { bytes, failure } => {
+                m.response_bytes += bytes as u64;
+                m.errors += 1;
+                if failure == Failure::RateLimited {
+                    m.rate_limited += 1;
+                }
+            }
+        }
+    }
+}
+
+/// One call of a request packet.
+struct Call {
+    method: String,
+    id: alloy_json_rpc::Id,
+    bytes: usize,
+}
+
+enum Outcome {
+    Ok { bytes: usize },
+    Failed { bytes: usize, failure: Failure },
+}
+
+/// Transport layer feeding a [`ProviderMetrics`].
+#[derive(Debug, Clone)]
+pub(crate) struct MetricsLayer(pub(crate) Arc<ProviderMetrics>);
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<ProviderMetrics>,
+}
+
+impl<S> Service<RequestPacket> for MetricsService<S>
+where
+    S: Service<
+            RequestPacket,
+            Response = ResponsePacket,
+            Error = TransportError,
+            Future = TransportFut<'static>,
+        > + Send
+        + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let requests = match &request {
+            RequestPacket::Single(r) => std::slice::from_ref(r),
+            RequestPacket::Batch(rs) => rs.as_slice(),
+        };
+        let calls: Vec<Call> = requests
+            .iter()
+            .map(|r| Call {
+                method: r.method().to_string(),
+                id: r.id().clone(),
+                bytes: r.serialized().get().len(),
+            })
+            .collect();
+        let metrics = self.metrics.clone();
+        let started = Instant::now();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let result = response.await;
+            let latency = started.elapsed();
+            match &result {
+                Ok(packet) => {
+                    let responses = match packet {
+                        ResponsePacket::Single(r) => std::slice::from_ref(r),
+                        ResponsePacket::Batch(rs) => rs.as_slice(),
+                    };
+                    for call in &calls {
+                        let outcome = match responses.iter().find(|r| r.id == call.id) {
+                            Some(r) => match &r.payload {
+                                ResponsePayload::Success(raw) => Outcome::Ok {
+                                    bytes: raw.get().len(),
+                                },
+                                ResponsePayload::Failure(e) => Outcome::Failed {
+                                    bytes: e.message.len(),
+                                    failure: Failure::of_payload(e),
+                                },
+                            },
+                            None => Outcome::Failed {
+                                bytes: 0,
+                                failure: Failure::Other,
+                            },
+                        };
+                        metrics.record(call, latency, outcome);
+                    }
+                }
+                Err(e) => {
+                    let failure = Failure::of_transport(e);
+                    for call in &calls {
+                        metrics.record(call, latency, Outcome::Failed { bytes: 0, failure });
+                    }
+                }
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_and_quantiles() {
+        let mut h = LatencyHistogram::default();
+        for ms in [3, 7, 7, 40, 20_000] {
+            h.record(Duration::from_millis(ms));
+        }
+        assert_eq!(h.count(), 5);
+        assert_eq!(h.counts[0], 1);
+        assert_eq!(h.counts[1], 2);
+        assert_eq!(h.quantile(0.5), Some(Duration::from_millis(10)));
+        assert_eq!(h.quantile(0.8), Some(Duration::from_millis(50)));
+        assert_eq!(h.quantile(1.0), None);
+        assert_eq!(h.mean(), Some(Duration::from_micros(4_011_400)));
+    }
+
+    #[test]
+    fn records_by_method_and_merges() {
+        let metrics = ProviderMetrics::new();
+        let call = |method: &str| Call {
+            method: method.to_string(),
+            id: alloy_json_rpc::Id::Number(1),
+            bytes: 10,
+        };
+        let ms = Duration::from_millis(4);
+        metrics.record(&call("eth_getBalance"), ms, Outcome::Ok { bytes: 30 });
+        metrics.record(
+            &call("eth_getBalance"),
+            ms,
+            Outcome::Failed {
+                bytes: 0,
+                failure: Failure::RateLimited,
+            },
+        );
+        metrics.record(&call("eth_getCode"), ms, Outcome::Ok { bytes: 100 });
+        metrics.record_retry();
+
+        let mut snapshot = metrics.snapshot();
+        let balance = &snapshot.methods["eth_getBalance"];
+        assert_eq!(
+            (balance.requests, balance.errors, balance.rate_limited),
+            (2, 1, 1)
+        );
+        assert_eq!(balance.request_bytes, 20);
+        let total = snapshot.total();
+        assert_eq!((total.requests, total.response_bytes), (3, 130));
+
+        snapshot.merge(&metrics.snapshot());
+        assert_eq!(snapshot.total().requests, 6);
+        assert_eq!(snapshot.retries, 2);
+        let rendered = snapshot.render();
+        assert!(rendered
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("eth_getBalance"));
+        assert!(rendered.contains("2 retries"));
+    }
+}
@@ -0,0 +1,521 @@
+//! In-memory [`DataProvider`] for tests. Requires the `test-util` feature.
+//!
+//! Blocks are canned up front; failures can be injected per call, and every
+//! call is recorded for assertions. Block ids are resolved to a canned block
+//! number before recording: `latest` (and `pending`) is the highest block,
+//! and `safe` / `finalized` follow it unless pinned with
+//! [`with_safe`](MockProvider::with_safe) / [`with_finalized`](MockProvider::with_finalized).
+//!
+//! Accounts and storage added with [`with_account`](MockProvider::with_account)
+//! and [`with_storage`](MockProvider::with_storage) are served as a
+//! [`PrefetchSource`], so `PrefetchStage` and simulation run without a node.
+//!
+//! ```ignore
+//! let provider = MockProvider::new()
+//!     .with_chain_id(8453)
+//!     .with_block(100, vec![tx])
+//!     .with_account(sender, U256::from(10).pow(U256::from(18)), 0, Bytes::new())
+//!     .with_transient_failure(MockCall::BlockTransactions(100), 1, "timeout");
+//! assert!(provider.get_block_transactions(BlockId::number(100)).await.is_err());
+//! assert_eq!(provider.get_block_transactions(BlockId::number(100)).await?.len(), 1);
+//! assert_eq!(provider.call_count(&MockCall::BlockTransactions(100)), 2);
+//! ```
+
+use crate::{describe_block, BlockRef, DataProvider, PrefetchSource, WarmCacheDB};
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use argus_core::chain::ChainConfig;
+use argus_core::error::{ArgusError, ArgusResult};
+use argus_core::{BlockContext, FastHashMap, PrefetchStats, Transaction, TxReceipt};
+use async_trait::async_trait;
+use revm::database::{CacheDB, EmptyDB};
+use revm::state::{AccountInfo, Bytecode};
+use std::sync::Mutex;
+
+/// One [`DataProvider`] call, as recorded and as targeted by failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockCall {
+    BlockTransactions(u64),
+    PendingTransactions,
+    ChainId,
+    BlockHash(u64),
+    BlockTimestamp(u64),
+    BlockContext(u64),
+    ResolveBlock(u64),
+    BlockReceipts(u64),
+    Prefetch(u64),
+}
+
+#[derive(Debug, Clone)]
+struct MockBlock {
+    transactions: Vec<Transaction>,
+    receipts: Vec<TxReceipt>,
+    hash: B256,
+    timestamp: u64,
+    context: Option<BlockContext>,
+}
+
+#[derive(Debug)]
+struct Failure {
+    call: MockCall,
+    /// `None` fails every matching call.
+    remaining: Option<usize>,
+    message: String,
+}
+
+/// Programmable [`DataProvider`] with canned blocks and call recording.
+#[derive(Debug)]
+pub struct MockProvider {
+    chain_id: u64,
+    blocks: FastHashMap<u64, MockBlock>,
+    pending: Vec<Transaction>,
+    state: WarmCacheDB,
+    safe: Option<u64>,
+    finalized: Option<u64>,
+    failures: Mutex<Vec<Failure>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self {
+            chain_id: 1,
+            blocks: FastHashMap::default(),
+            pending: Vec::new(),
+            state: CacheDB::new(EmptyDB::new()),
+            safe: None,
+            finalized: None,
+            failures: Mutex::new(Vec::new()),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl MockProvider {
+    /// An empty mainnet (chain id 1) provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Serve `transactions` for `number`. The hash defaults to
+    /// `keccak256(number)` and the timestamp to `12 * number`.
+    pub fn with_block(mut self, number: u64, transactions: Vec<Transaction>) -> Self {
+        self.blocks.insert(
+            number,
+            MockBlock {
+                transactions,
+                receipts: Vec::new(),
+                hash: keccak256(number.to_be_bytes()),
+                timestamp: number * 12,
+                context: None,
+            },
+        );
+        self
+    }
+
+    /// Override the hash of a block added with [`with_block`](Self::with_block).
+    pub fn with_block_hash(mut self, number: u64, hash: B256) -> Self {
+        if let Some(block) = self.blocks.get_mut(&number) {
+            block.hash = hash;
+        }
+        self
+    }
+
+    /// Override the timestamp of a block added with [`with_block`](Self::with_block).
+    pub fn with_block_timestamp(mut self, number: u64, timestamp: u64) -> Self {
+        if let Some(block) = self.blocks.get_mut(&number) {
+            block.timestamp = timestamp;
+        }
+        self
+    }
+
+    /// Serve `context` as the header of a block added with
+    /// [`with_block`](Self::with_block). By default only its number and
+    /// timestamp are set.
+    pub fn with_block_context(mut self, number: u64, context: BlockContext) -> Self {
+        if let Some(block) = self.blocks.get_mut(&number) {
+            block.timestamp = context.timestamp;
+            block.context = Some(context);
+        }
+        self
+    }
+
+    /// Serve `receipts` for a block added with [`with_block`](Self::with_block).
+    pub fn with_block_receipts(mut self, number: u64, receipts: Vec<TxReceipt>) -> Self {
+        if let Some(block) = self.blocks.get_mut(&number) {
+            block.receipts = receipts;
+        }
+        self
+    }
+
+    pub fn with_pending(mut self, transactions: Vec<Transaction>) -> Self {
+        self.pending = transactions;
+        self
+    }
+
+    /// Serve an account with `balance`, `nonce` and `code` as prefetched
+    /// state (for every block).
+    pub fn with_account(
+        mut self,
+        address: Address,
+        balance: U256,
+        nonce: u64,
+        code: Bytes,
+    ) -> Self {
+        let code_hash = keccak256(&code);
+        let info = AccountInfo::new(balance, nonce, code_hash, Bytecode::new_raw(code));
+        self.state.insert_account_info(address, info);
+        self
+    }
+
+    /// Serve storage `slot` of `address` as `value`; the account is created
+    /// empty if not added with [`with_account`](Self::with_account).
+    pub fn with_storage(mut self, address: Address, slot: U256, value: U256) -> Self {
+        self.state.insert_account_storage(address, slot, value).ok();
+        self
+    }
+
+    /// The canned state, as a prefetch would return it.
+    pub fn warm_db(&self) -> WarmCacheDB {
+        self.state.clone()
+    }
+
+    /// Block the `safe` tag resolves to.
+    pub fn with_safe(mut self, number: u64) -> Self {
+        self.safe = Some(number);
+        self
+    }
+
+    /// Block the `finalized` tag resolves to.
+    pub fn with_finalized(mut self, number: u64) -> Self {
+        self.finalized = Some(number);
+        self
+    }
+
+    /// Fail every `call` with `ArgusError::Provider(message)`.
+    pub fn with_failure(self, call: MockCall, message: impl Into<String>) -> Self {
+        self.push_failure(call, None, message.into());
+        self
+    }
+
+    /// Fail the next `times` matching calls, then serve normally.
+    pub fn with_transient_failure(
+        self,
+        call: MockCall,
+        times: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        self.push_failure(call, Some(times), message.into());
+        self
+    }
+
+    /// Every call made so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// How many times `call` was made.
+    pub fn call_count(&self, call: &MockCall) -> usize {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|c| *c == call)
+            .count()
+    }
+
+    fn push_failure(&self, call: MockCall, remaining: Option<usize>, message: String) {
+        self.failures
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Failure {
+                call,
+                remaining,
+                message,
+            });
+    }
+
+    /// Record `call` and return an injected failure for it, if any.
+    fn enter(&self, call: MockCall) -> ArgusResult<()> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(call);
+
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(f) = failures
+            .iter_mut()
+            .find(|f| f.call == call && f.remaining != Some(0))
+        else {
+            return Ok(());
+        };
+        if let Some(n) = &mut f.remaining {
+            *n -= 1;
+        }
+        Err(ArgusError::Provider(f.message.clone()))
+    }
+
+    /// The block number `id` refers to. Only unknown hashes (and tags on an
+    /// empty provider) fail, before anything is recorded.
+    fn resolve(&self, id: BlockId) -> ArgusResult<u64> {
+        let latest = self.blocks.keys().max().copied();
+        let number = match id {
+            BlockId::Hash(h) => self
+                .blocks
+                .iter()
+                .find(|(_, b)| b.hash == h.block_hash)
+                .map(|(&n, _)| n),
+            BlockId::Number(BlockNumberOrTag::Number(n)) => Some(n),
+            BlockId::Number(BlockNumberOrTag::Earliest) => Some(0),
+            BlockId::Number(BlockNumberOrTag::Safe) => self.safe.or(latest),
+            BlockId::Number(BlockNumberOrTag::Finalized) => self.finalized.or(latest),
+            BlockId::Number(BlockNumberOrTag::Latest | BlockNumberOrTag::Pending) => latest,
+        };
+        number
+            .ok_or_else(|| ArgusError::Provider(format!("Block {} not found", describe_block(id))))
+    }
+
+    fn block(&self, number: u64) -> ArgusResult<&MockBlock> {
+        self.blocks
+            .get(&number)
+            .ok_or_else(|| ArgusError::Provider(format!("Block {number} not found")))
+    }
+}
+
+#[async_trait]
+impl DataProvider for MockProvider {
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::BlockTransactions(number))?;
+        Ok(self.block(number)?.transactions.clone())
+    }
+
+    async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>> {
+        self.enter(MockCall::PendingTransactions)?;
+        Ok(self.pending.clone())
+    }
+
+    async fn chain_id(&self) -> ArgusResult<u64> {
+        self.enter(MockCall::ChainId)?;
+        Ok(self.chain_id)
+    }
+
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::BlockHash(number))?;
+        Ok(self.block(number)?.hash)
+    }
+
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::BlockTimestamp(number))?;
+        Ok(self.block(number)?.timestamp)
+    }
+
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::BlockContext(number))?;
+        let block = self.block(number)?;
+        Ok(block.context.clone().unwrap_or(BlockContext {
+            number,
+            timestamp: block.timestamp,
+            ..Default::default()
+        }))
+    }
+
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::ResolveBlock(number))?;
+        Ok(BlockRef {
+            number,
+            hash: self.block(number)?.hash,
+        })
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::BlockReceipts(number))?;
+        Ok(self.block(number)?.receipts.clone())
+    }
+}
+
+#[async_trait]
+impl PrefetchSource for MockProvider {
+    async fn prefetch_on(
+        &self,
+        _chain: &ChainConfig,
+        block: BlockId,
+        _transactions: &[Transaction],
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        let number = self.resolve(block)?;
+        self.enter(MockCall::Prefetch(number))?;
+        // Canned state involves no requests.
+        Ok((self.warm_db(), PrefetchStats::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            hash: B256::with_last_byte(n),
+            gas: 21_000,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_canned_blocks_and_records_calls() {
+        let provider = MockProvider::new()
+            .with_chain_id(10)
+            .with_block(5, vec![tx(1), tx(2)])
+            .with_block_timestamp(5, 1_700_000_000);
+
+        assert_eq!(provider.chain_id().await.unwrap(), 10);
+        assert_eq!(
+            provider
+                .get_block_transactions(BlockId::number(5))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            provider
+                .get_block_timestamp(BlockId::number(5))
+                .await
+                .unwrap(),
+            1_700_000_000
+        );
+        assert!(provider.get_block_hash(BlockId::number(6)).await.is_err());
+        assert_eq!(
+            provider.calls(),
+            vec![
+                MockCall::ChainId,
+                MockCall::BlockTransactions(5),
+                MockCall::BlockTimestamp(5),
+                MockCall::BlockHash(6),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn block_range_streams_in_order_and_stops_at_error() {
+        let provider = Arc::new(
+            MockProvider::new()
+                .with_block(1, vec![tx(1)])
+                .with_block(2, vec![tx(2), tx(3)])
+                .with_block(3, vec![]),
+        );
+        let mut blocks = provider.clone().get_block_range(1, 3, 2);
+        let mut seen = Vec::new();
+        while let Some(block) = blocks.recv().await {
+            let (number, txs) = block.unwrap();
+            seen.push((number, txs.len()));
+        }
+        assert_eq!(seen, vec![(1, 1), (2, 2), (3, 0)]);
+
+        let mut blocks = provider.get_block_range(2, 9, 4);
+        assert_eq!(blocks.recv().await.unwrap().unwrap().0, 2);
+        assert_eq!(blocks.recv().await.unwrap().unwrap().0, 3);
+        assert!(blocks.recv().await.unwrap().is_err());
+        assert!(blocks.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn transient_failures_clear_after_n_calls() {
+        let call = MockCall::BlockTransactions(1);
+        let provider = MockProvider::new()
+            .with_block(1, vec![tx(1)])
+            .with_transient_failure(call, 2, "rate limited")
+            .with_failure(MockCall::PendingTransactions, "unsupported");
+
+        for _ in 0..2 {
+            let err = provider
+                .get_block_transactions(BlockId::number(1))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("rate limited"));
+        }
+        assert!(provider
+            .get_block_transactions(BlockId::number(1))
+            .await
+            .is_ok());
+        assert!(provider.get_pending_transactions().await.is_err());
+        assert!(provider.get_pending_transactions().await.is_err());
+        assert_eq!(provider.call_count(&call), 3);
+    }
+
+    #[tokio::test]
+    async fn resolves_hashes_and_tags() {
+        let provider = MockProvider::new()
+            .with_block(7, vec![tx(1)])
+            .with_block(8, vec![])
+            .with_block(9, vec![])
+            .with_block_hash(8, B256::repeat_byte(0xaa))
+            .with_finalized(7);
+
+        let by_hash = BlockId::from(B256::repeat_byte(0xaa));
+        assert_eq!(provider.resolve_block(by_hash).await.unwrap().number, 8);
+        assert_eq!(
+            provider
+                .resolve_block(BlockId::latest())
+                .await
+                .unwrap()
+                .number,
+            9
+        );
+        let finalized = BlockId::Number(BlockNumberOrTag::Finalized);
+        assert_eq!(
+            provider
+                .get_block_transactions(finalized)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(provider
+            .get_block_hash(BlockId::from(B256::ZERO))
+            .await
+            .is_err());
+        assert_eq!(
+            provider.calls(),
+            vec![
+                MockCall::ResolveBlock(8),
+                MockCall::ResolveBlock(9),
+                MockCall::BlockTransactions(7),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn serves_canned_state_as_prefetch() {
+        let token = Address::repeat_byte(0xaa);
+        let provider = MockProvider::new()
+            .with_block(3, vec![tx(1)])
+            .with_account(token, U256::ZERO, 1, Bytes::from_static(&[0x00]))
+            .with_storage(token, U256::from(1), U256::from(42))
+            .with_transient_failure(MockCall::Prefetch(3), 1, "timeout");
+
+        let chain = &argus_core::chain::ETHEREUM;
+        let block = BlockId::number(3);
+        assert!(provider.prefetch_on(chain, block, &[]).await.is_err());
+        let (db, _) = provider.prefetch_on(chain, block, &[]).await.unwrap();
+        let account = &db.cache.accounts[&token];
+        assert_eq!(account.info.nonce, 1);
+        assert_eq!(account.storage[&U256::from(1)], U256::from(42));
+        assert!(provider
+            .prefetch_on(chain, BlockId::from(B256::ZERO), &[])
+            .await
+            .is_err());
+        assert_eq!(provider.call_count(&MockCall::Prefetch(3)), 2);
+    }
+}
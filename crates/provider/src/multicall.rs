@@ -0,0 +1,259 @@
+//! Storage reads batched through Multicall3.
+//!
+//! A contract's storage can't be read from outside it, so each target's
+//! code is replaced, for the duration of one `eth_call`, by a tiny reader
+//! (via a state override) that returns `SLOAD` of every 32-byte word of its
+//! calldata. Multicall3's `aggregate3` then calls every target at once, so
+//! hundreds of slots across many contracts cost one round trip. Nodes
+//! without state overrides or without Multicall3 reject the call, and the
+//! caller falls back to `eth_getStorageAt`.
+
+use alloy_eips::BlockId;
+use alloy_primitives::{address, Address, Bytes, TxKind, U256};
+use alloy_provider::{DynProvider, Provider};
+use alloy_rpc_types::state::{AccountOverride, StateOverride};
+use alloy_rpc_types::{TransactionInput, TransactionRequest};
+use std::collections::BTreeMap;
+
+/// Multicall3, at the same address on nearly every EVM chain.
+pub(crate) const MULTICALL3: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Slots read per `eth_call`.
+pub(crate) const MAX_SLOTS_PER_CALL: usize = 500;
+
+/// `for (i = 0; i < calldatasize; i += 32) mstore(i, sload(calldataload(i)));
+/// return(0, calldatasize)`.
+const SLOAD_READER: [u8; 26] = [
+    0x60, 0x00, // PUSH1 0
+    0x5b, // JUMPDEST (loop)
+    0x36, 0x81, 0x10, 0x15, // CALLDATASIZE DUP2 LT ISZERO
+    0x60, 0x15, 0x57, // PUSH1 end JUMPI
+    0x80, 0x35, 0x54, // DUP1 CALLDATALOAD SLOAD
+    0x81, 0x52, // DUP2 MSTORE
+    0x60, 0x20, 0x01, // PUSH1 32 ADD
+    0x60, 0x02, 0x56, // PUSH1 loop JUMP
+    0x5b, // JUMPDEST (end)
+    0x36, 0x60, 0x00, 0xf3, // CALLDATASIZE PUSH1 0 RETURN
+];
+
+/// `aggregate3((address,bool,bytes)[])`.
+const AGGREGATE3: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+
+/// Slots to read per contract, in one `eth_call`.
+pub(crate) type StorageCall = Vec<(Address, Vec<U256>)>;
+
+/// Group `reads` by contract into calls of at most [`MAX_SLOTS_PER_CALL`]
+/// slots. Multicall3's own storage can't be read this way and is returned
+/// separately.
+pub(crate) fn plan(reads: Vec<(Address, U256)>) -> (Vec<StorageCall>, Vec<(Address, U256)>) {
+    let mut by_address: BTreeMap<Address, Vec<U256>> = BTreeMap::new();
+    let mut skipped = Vec::new();
+    for (addr, slot) in reads {
+        if addr == MULTICALL3 {
+            skipped.push((addr, slot));
+        } else {
+            by_address.entry(addr).or_default().push(slot);
+        }
+    }
+
+    let mut calls = Vec::new();
+    let mut call: StorageCall = Vec::new();
+    let mut size = 0;
+    for (addr, mut slots) in by_address {
+        while !slots.is_empty() {
+            let take = slots.len().min(MAX_SLOTS_PER_CALL - size);
+            let rest = slots.split_off(take);
+            call.push((addr, slots));
+            slots = rest;
+            size += take;
+            if size == MAX_SLOTS_PER_CALL {
+                calls.push(std::mem::take(&mut call));
+                size = 0;
+            }
+        }
+    }
+    if !call.is_empty() {
+        calls.push(call);
+    }
+    (calls, skipped)
+}
+
+/// Read every slot in `call` as of `block_id`. Per contract, `None` means
+/// its sub-call failed and its slots need another route.
+pub(crate) async fn read_storage(
+    p: &DynProvider,
+    call: &StorageCall,
+    block_id: BlockId,
+) -> Result<Vec<Option<Vec<U256>>>, String> {
+    let request = TransactionRequest {
+        to: Some(TxKind::Call(MULTICALL3)),
+        input: TransactionInput::new(encode_aggregate3(call)),
+        ..Default::default()
+    };
+    let reader = Bytes::from_static(&SLOAD_READER);
+    let overrides: StateOverride = call
+        .iter()
+        .map(|(addr, _)| {
+            let account = AccountOverride {
+                code: Some(reader.clone()),
+                ..Default::default()
+            };
+            (*addr, account)
+        })
+        .collect();
+    let output: Bytes = p
+        .raw_request("eth_call".into(), (request, block_id, overrides))
+        .await
+        .map_err(|e| e.to_string())?;
+    decode_aggregate3(&output, call)
+}
+
+fn encode_aggregate3(call: &StorageCall) -> Bytes {
+    let mut out = AGGREGATE3.to_vec();
+    push_word(&mut out, U256::from(32));
+    push_word(&mut out, U256::from(call.len()));
+    // Tuple offsets, relative to the first offset word.
+    let mut offset = 32 * call.len();
+    for (_, slots) in call {
+        push_word(&mut out, U256::from(offset));
+        offset += 32 * 4 + 32 * slots.len();
+    }
+    for (addr, slots) in call {
+        push_word(&mut out, U256::from_be_slice(addr.as_slice()));
+        push_word(&mut out, U256::from(1)); // allowFailure
+        push_word(&mut out, U256::from(32 * 3));
+        push_word(&mut out, U256::from(32 * slots.len()));
+        for slot in slots {
+            push_word(&mut out, *slot);
+        }
+    }
+    out.into()
+}
+
+/// Decode `(bool success, bytes returnData)[]`, one entry per contract.
+fn decode_aggregate3(data: &[u8], call: &StorageCall) -> Result<Vec<Option<Vec<U256>>>, String> {
+    let base = offset_at(data, 0)?;
+    let len = offset_at(data, base)?;
+    if len != call.len() {
+        return Err(format!(
+            "multicall returned {len} results for {} calls",
+            call.len()
+        ));
+    }
+    let heads = base + 32;
+    let mut results = Vec::with_capacity(len);
+    for (i, (_, slots)) in call.iter().enumerate() {
+        let tuple = heads + offset_at(data, heads + 32 * i)?;
+        let success = !word_at(data, tuple)?.is_zero();
+        let bytes = tuple + offset_at(data, tuple + 32)?;
+        let size = offset_at(data, bytes)?;
+        if !success || size != 32 * slots.len() {
+            results.push(None);
+            continue;
+        }
+        let values = (0..slots.len())
+            .map(|j| word_at(data, bytes + 32 + 32 * j))
+            .collect::<Result<_, _>>()?;
+        results.push(Some(values));
+    }
+    Ok(results)
+}
+
+fn push_word(out: &mut Vec<u8>, word: U256) {
+    out.extend_from_slice(&word.to_be_bytes::<32>());
+}
+
+fn word_at(data: &[u8], pos: usize) -> Result<U256, String> {
+    data.get(pos..pos + 32)
+        .map(U256::from_be_slice)
+        .ok_or_else(|| format!("multicall output truncated at byte {pos}"))
+}
+
+fn offset_at(data: &[u8], pos: usize) -> Result<usize, String> {
+    let word = word_at(data, pos)?;
+    usize::try_from(word)
+        .ok()
+        .filter(|&n| n <= data.len())
+        .ok_or_else(|| format!("multicall output has bad offset {word} at byte {pos}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What Multicall3 returns for `call` with every sub-call reading
+    /// `values` (or failing for `None`).
+    fn response(results: &[Option<Vec<U256>>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_word(&mut out, U256::from(32));
+        push_word(&mut out, U256::from(results.len()));
+        let mut offset = 32 * results.len();
+        for result in results {
+            push_word(&mut out, U256::from(offset));
+            offset += 32 * 3 + 32 * result.as_ref().map_or(0, Vec::len);
+        }
+        for result in results {
+            push_word(&mut out, U256::from(result.is_some() as u8));
+            push_word(&mut out, U256::from(64));
+            let values = result.clone().unwrap_or_default();
+            push_word(&mut out, U256::from(32 * values.len()));
+            values.into_iter().for_each(|v| push_word(&mut out, v));
+        }
+        out
+    }
+
+    #[test]
+    fn plans_calls_by_contract_within_the_slot_cap() {
+        let a = Address::repeat_byte(0xaa);
+        let b = Address::repeat_byte(0xbb);
+        let mut reads: Vec<_> = (0..MAX_SLOTS_PER_CALL + 10)
+            .map(|i| (a, U256::from(i)))
+            .collect();
+        reads.push((b, U256::from(1)));
+        reads.push((MULTICALL3, U256::ZERO));
+
+        let (calls, skipped) = plan(reads);
+        assert_eq!(skipped, vec![(MULTICALL3, U256::ZERO)]);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].len(), 1);
+        assert_eq!(calls[0][0].1.len(), MAX_SLOTS_PER_CALL);
+        assert_eq!(
+            calls[1],
+            vec![
+                (a, (500..510).map(U256::from).collect()),
+                (b, vec![U256::from(1)])
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_aggregate3_and_decodes_results() {
+        let a = Address::repeat_byte(0xaa);
+        let b = Address::repeat_byte(0xbb);
+        let call = vec![
+            (a, vec![U256::from(1), U256::from(2)]),
+            (b, vec![U256::from(3)]),
+        ];
+
+        let input = encode_aggregate3(&call);
+        assert_eq!(input[..4], AGGREGATE3);
+        // Head, length, two offsets, then a's tuple: target, flag, offset,
+        // length, two slots.
+        assert_eq!(word_at(&input[4..], 64).unwrap(), U256::from(64));
+        assert_eq!(word_at(&input[4..], 96).unwrap(), U256::from(64 + 6 * 32));
+        assert_eq!(
+            word_at(&input[4..], 128).unwrap(),
+            U256::from_be_slice(a.as_slice())
+        );
+        assert_eq!(word_at(&input[4..], 192).unwrap(), U256::from(96));
+        assert_eq!(input.len(), 4 + 32 * (4 + 6 + 5));
+
+        let values = vec![Some(vec![U256::from(7), U256::from(8)]), None];
+        assert_eq!(
+            decode_aggregate3(&response(&values), &call).unwrap(),
+            values
+        );
+        assert!(decode_aggregate3(&response(&values[..1]), &call).is_err());
+        assert!(decode_aggregate3(&[0u8; 16], &call).is_err());
+    }
+}
@@ -3,220 +3,1592 @@
 //! Fetches account state + known DeFi storage slots in parallel from an
 //! RPC node, producing a warm `CacheDB<EmptyDB>` for revm simulation.
 
+use crate::budget::{Priority, RpcBudget};
+use crate::code::CodeCache;
+use crate::failover::{Endpoints, Failure};
+use crate::fallback::NodeDB;
+use crate::metrics::MetricsSnapshot;
+use crate::multicall;
+use crate::progress::ProgressHandler;
+use crate::proof::{self, ProofError};
+use crate::retry::RetryPolicy;
+use crate::rpc_cache::{self, CachedAccount, CachedState, RpcCache};
+use crate::trace::{self, Prestate, TxTrace};
+use crate::{describe_block, PrefetchSource};
 use alloy_eips::BlockId;
-use alloy_primitives::Address;
+use alloy_primitives::{Address, Bytes, TxKind, U256, U64};
 use alloy_provider::{DynProvider, Provider};
-use argus_core::error::ArgusResult;
-use argus_core::Transaction;
-use revm::database::{CacheDB, EmptyDB};
-use revm::state::{AccountInfo, Bytecode};
+use alloy_rpc_client::{BatchRequest, Waiter};
+use alloy_rpc_types::{TransactionInput, TransactionRequest};
+use argus_core::chain::{self, ChainConfig};
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::{PrefetchStats, Transaction};
+use async_trait::async_trait;
+use revm::database::{CacheDB, EmptyDB, WrapDatabaseAsync};
+use revm::state::AccountInfo;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 
-/// Default max concurrent RPC tasks (each makes 3 HTTP calls).
-/// Set low for free-tier RPC compatibility; increase with paid RPCs.
+/// Max concurrent RPC tasks (each makes 3 HTTP calls) when neither
+/// [`Prefetcher::with_concurrency`] nor a rate-limited budget says otherwise.
+/// Low for free-tier RPC compatibility.
 const DEFAULT_CONCURRENCY: usize = 1;
 
-/// Max retry attempts for 429 errors.
-const MAX_RETRIES: u32 = 3;
+/// RPC cache entry kind for access list hints.
+const HINTS: &str = "hints";
 
 /// Warm cache ready for simulation. Clone-able, network-free.
 pub type WarmCacheDB = CacheDB<EmptyDB>;
 
+/// A [`WarmCacheDB`]'s state over the node: whatever the cache lacks is
+/// fetched during execution rather than read as empty. See
+/// [`Prefetcher::fallback_db`].
+pub type FallbackDB = CacheDB<WrapDatabaseAsync<NodeDB>>;
+
+/// Accounts a simulation read, each with the storage slots it read.
+pub type TouchedState = BTreeMap<Address, BTreeSet<U256>>;
+
+/// Simulate-and-fetch rounds in [`Prefetcher::prefetch_discovered`]; each
+/// reaches one call deeper.
+pub const DISCOVERY_ROUNDS: usize = 6;
+
+/// Which accounts a [`Prefetcher`] warms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrefetchStrategy {
+    /// Every sender and call target, plus known slots.
+    #[default]
+    Full,
+    /// Only the `top_k` contracts from [`lite_targets`], plus their known
+    /// slots. Senders and other EOAs are skipped.
+    ///
+    /// Costs a fraction of the RPC calls, at some accuracy: skipped accounts
+    /// read as empty, so calls into contracts outside the top K execute no
+    /// code and their storage accesses (and conflicts) are missed, and
+    /// balance- or nonce-dependent paths may diverge. Still far closer to
+    /// warm-state results than a dry run, since the busiest contracts carry
+    /// most of a block's contention.
+    Lite { top_k: usize },
+    /// Exactly the accounts and slots the block touches, with their
+    /// pre-block values, from one `debug_traceBlockBy*` call with geth's
+    /// `prestateTracer`. Needs a node exposing the debug API with archive
+    /// state for the block; no guessing from the slot table.
+    Prestate,
+    /// The accounts and slots of [`Full`](Self::Full), each read with
+    /// `eth_getProof` and verified against the block's state root (code
+    /// against the proven code hash), so the warm cache provably matches
+    /// chain state. Any failed fetch or proof fails the prefetch. One
+    /// `eth_getProof` and one `eth_getCode` per account; never batched or
+    /// served from the RPC cache.
+    Proof,
+}
+
+/// Contract call targets ranked by share of the block's calls plus share
+/// of its gas, best first, at most `k`. Plain transfers (empty calldata)
+/// are ignored, as their targets are usually EOAs.
+pub fn lite_targets(transactions: &[Transaction], k: usize) -> Vec<Address> {
+    let calls: Vec<(Address, u64)> = transactions
+        .iter()
+        .filter(|tx| !tx.input.is_empty())
+        .filter_map(|tx| Some((tx.to?, tx.gas)))
+        .collect();
+    let total_gas = calls.iter().map(|(_, gas)| *gas).sum::<u64>().max(1) as f64;
+    let total_calls = calls.len().max(1) as f64;
+
+    let mut scores: std::collections::HashMap<Address, f64> = std::collections::HashMap::new();
+    for (to, gas) in calls {
+        *scores.entry(to).or_default() += 1.0 / total_calls + gas as f64 / total_gas;
+    }
+    let mut ranked: Vec<(Address, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked.into_iter().take(k).map(|(addr, _)| addr).collect()
+}
+
 /// Concurrent state prefetcher.
 ///
-/// Owns a `DynProvider` and fetches account state + known storage slots
-/// in parallel via `JoinSet`, throttled by a semaphore.
+/// Owns a `DynProvider` (or the endpoints of a
+/// [`FailoverProvider`](crate::FailoverProvider)) and fetches account state +
+/// known storage slots in parallel via `JoinSet`, paced by its
+/// [`RpcBudget`]'s token bucket.
 ///
 /// ```ignore
 /// let prefetcher = Prefetcher::new(provider.into_provider());
-/// let warm_db = prefetcher.prefetch(block_number, &transactions).await?;
+/// let (warm_db, stats) = prefetcher.prefetch(block_number, &transactions).await?;
 /// let results = simulate_batch_with_state(&warm_db, &transactions)?;
 /// ```
 pub struct Prefetcher {
-    provider: DynProvider,
-    max_concurrent: usize,
+    endpoints: Arc<Endpoints>,
+    max_concurrent: Option<usize>,
+    budget: Arc<RpcBudget>,
+    cancel: CancellationToken,
+    strategy: PrefetchStrategy,
+    batch_size: usize,
+    access_list_hints: bool,
+    multicall: bool,
+    rpc_cache: Option<Arc<RpcCache>>,
+    retry: Arc<RetryPolicy>,
+    code_cache: Arc<CodeCache>,
+    progress: Option<Arc<dyn ProgressHandler>>,
 }
 
 impl Prefetcher {
     pub fn new(provider: DynProvider) -> Self {
+        Self::with_endpoints(Arc::new(Endpoints::single(provider)))
+    }
+
+    /// Fetch from `endpoints`, failing over with whoever else shares them.
+    pub(crate) fn with_endpoints(endpoints: Arc<Endpoints>) -> Self {
         Self {
-            provider,
-            max_concurrent: DEFAULT_CONCURRENCY,
+            endpoints,
+            max_concurrent: None,
+            budget: RpcBudget::unlimited(),
+            cancel: CancellationToken::new(),
+            strategy: PrefetchStrategy::Full,
+            batch_size: 1,
+            access_list_hints: false,
+            multicall: false,
+            rpc_cache: None,
+            retry: Arc::new(RetryPolicy::default()),
+            code_cache: Arc::new(CodeCache::new()),
+            progress: None,
         }
     }
 
-    /// Override max concurrent RPC tasks (default: 10).
+    /// Choose which accounts to warm (default: [`PrefetchStrategy::Full`]).
+    pub fn with_strategy(mut self, strategy: PrefetchStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Abort in-flight fetches and return [`ArgusError::Cancelled`] once
+    /// `token` fires.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Draw every request from a shared budget at [`Priority::Normal`].
+    pub fn with_budget(mut self, budget: Arc<RpcBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Retry failed fetches per `policy` (default: [`RetryPolicy::default`]).
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Arc::new(policy);
+        self
+    }
+
+    /// Cap concurrent RPC tasks. By default a budget with a rate limit
+    /// lets a full burst fly at once and paces the rest; without one, tasks
+    /// run one at a time.
     pub fn with_concurrency(mut self, n: usize) -> Self {
-        self.max_concurrent = n;
+        self.max_concurrent = Some(n.max(1));
+        self
+    }
+
+    /// Requests, retries, 429s, bytes and latency per method on the
+    /// endpoints this prefetcher uses. Shared with the
+    /// [`FailoverProvider`](crate::FailoverProvider) it came from; one
+    /// built with [`new`](Self::new) only counts retries, see
+    /// [`RpcProvider::metrics`](crate::rpc::RpcProvider::metrics).
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.endpoints.metrics()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.max_concurrent.unwrap_or_else(|| {
+            let limits = self.budget.limits();
+            match limits.requests_per_second {
+                Some(_) => limits.burst_size() as usize,
+                None => DEFAULT_CONCURRENCY,
+            }
+        })
+    }
+
+    /// Before prefetching, ask the node which slots each transaction touches
+    /// (`eth_createAccessList`, one call per transaction under the same
+    /// concurrency cap) and warm those too, covering contracts the static
+    /// slot table doesn't know. In lite mode only hints for the selected
+    /// contracts are used. Failed hints are skipped.
+    pub fn with_access_list_hints(mut self, enabled: bool) -> Self {
+        self.access_list_hints = enabled;
         self
     }
 
-    /// Concurrently fetches account state and known storage slots.
+    /// Read storage slots through Multicall3, up to 500 per `eth_call`, by
+    /// overriding each contract's code with a storage reader. Needs a node
+    /// that accepts state overrides; slots it can't read that way fall back
+    /// to `eth_getStorageAt`. Accounts are fetched as before.
+    pub fn with_multicall(mut self, enabled: bool) -> Self {
+        self.multicall = enabled;
+        self
+    }
+
+    /// Read accounts, slots and access list hints from `cache` when present
+    /// and store what had to be fetched, so a block is only fetched once.
+    /// Blocks addressed by tag, [`PrefetchStrategy::Prestate`] and
+    /// [`PrefetchStrategy::Proof`] bypass it.
+    pub fn with_rpc_cache(mut self, cache: Arc<RpcCache>) -> Self {
+        self.rpc_cache = Some(cache);
+        self
+    }
+
+    /// Keep bytecode in `cache`, shared with other prefetchers, so code is
+    /// stored once across blocks and a contract's code is only fetched the
+    /// first time (see [`CodeCache`]). Prefetchers from one
+    /// [`FailoverProvider`](crate::FailoverProvider) share its cache.
+    pub fn with_code_cache(mut self, cache: Arc<CodeCache>) -> Self {
+        self.code_cache = cache;
+        self
+    }
+
+    /// Report reads as they start and finish to `handler`.
+    pub fn with_progress(mut self, handler: Arc<dyn ProgressHandler>) -> Self {
+        self.progress = Some(handler);
+        self
+    }
+
+    /// Pack up to `n` calls (an account is three) into one JSON-RPC batch
+    /// request. Default 1 sends every call on its own. Each batch is one
+    /// concurrent task and is charged to the budget per call.
+    pub fn with_batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    /// Concurrently fetches account state and known Ethereum storage slots
+    /// as of `block` (a number, hash or tag), with counts of what was
+    /// fetched, failed and retried.
     pub async fn prefetch(
         &self,
-        block_number: u64,
+        block: impl Into<BlockId>,
         transactions: &[Transaction],
-    ) -> ArgusResult<WarmCacheDB> {
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        self.prefetch_on(&chain::ETHEREUM, block, transactions)
+            .await
+    }
+
+    /// Like [`prefetch`](Self::prefetch), using `chain`'s slot pack.
+    pub async fn prefetch_on(
+        &self,
+        chain: &ChainConfig,
+        block: impl Into<BlockId>,
+        transactions: &[Transaction],
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        let started = Instant::now();
+        let retries = self.metrics().retries;
+        let (warm_db, stats) = self
+            .prefetch_state(chain, block.into(), transactions)
+            .await?;
+        Ok(self.finish_stats(warm_db, stats, started, retries))
+    }
+
+    /// Fill in the counts every strategy shares: slots warmed, retries
+    /// since `retries` was read, and time since `started`.
+    fn finish_stats(
+        &self,
+        warm_db: WarmCacheDB,
+        mut stats: PrefetchStats,
+        started: Instant,
+        retries: u64,
+    ) -> (WarmCacheDB, PrefetchStats) {
+        stats.slots = warm_slots(&warm_db);
+        stats.retried = self.metrics().retries.saturating_sub(retries);
+        stats.elapsed = started.elapsed();
+        (warm_db, stats)
+    }
+
+    async fn prefetch_state(
+        &self,
+        chain: &ChainConfig,
+        block_id: BlockId,
+        transactions: &[Transaction],
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
         let mut addresses = std::collections::HashSet::new();
-        for tx in transactions {
-            addresses.insert(tx.from);
-            if let Some(to) = tx.to {
-                addresses.insert(to);
+        match self.strategy {
+            PrefetchStrategy::Full | PrefetchStrategy::Proof => {
+                for tx in transactions {
+                    addresses.insert(tx.from);
+                    if let Some(to) = tx.to {
+                        addresses.insert(to);
+                    }
+                }
+            }
+            PrefetchStrategy::Lite { top_k } => {
+                addresses.extend(lite_targets(transactions, top_k));
             }
+            PrefetchStrategy::Prestate => {}
         }
 
-        let block_id = BlockId::from(block_number);
+        let block = describe_block(block_id);
+        if self.strategy == PrefetchStrategy::Prestate {
+            return self.prefetch_prestate(block_id, &block).await;
+        }
+        let concurrency = self.concurrency();
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+        let cache = match (&self.rpc_cache, RpcCache::block_key(block_id)) {
+            (Some(cache), Some(key)) if self.strategy != PrefetchStrategy::Proof => {
+                Some((cache, self.cache_chain_id(cache).await?, key))
+            }
+            _ => None,
+        };
+
+        let cached_hints = match &cache {
+            Some((cache, chain_id, key)) if self.access_list_hints => {
+                cache.get(*chain_id, HINTS, key)?
+            }
+            _ => None,
+        };
+        let hints = match cached_hints {
+            Some(hints) => hints,
+            None if self.access_list_hints => {
+                let hints = self
+                    .access_list_hints(block_id, transactions, &semaphore)
+                    .await?;
+                if let Some((cache, chain_id, key)) = &cache {
+                    cache.put(*chain_id, HINTS, key, &hints)?;
+                }
+                hints
+            }
+            None => HashMap::new(),
+        };
+        if matches!(
+            self.strategy,
+            PrefetchStrategy::Full | PrefetchStrategy::Proof
+        ) {
+            addresses.extend(hints.keys().copied());
+        }
         let addr_count = addresses.len();
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(self.max_concurrent));
 
         tracing::info!(
-            block_number,
+            %block,
             addrs = addr_count,
-            concurrency = self.max_concurrent,
+            concurrency,
+            batch_size = self.batch_size,
+            strategy = ?self.strategy,
             "prefetching state"
         );
 
-        let mut tasks = tokio::task::JoinSet::new();
+        // Balance and allowance slots named by ERC-20 calldata.
+        let mut token_slots: HashMap<Address, BTreeSet<U256>> = HashMap::new();
+        for tx in transactions {
+            let Some(to) = tx.to.filter(|to| addresses.contains(to)) else {
+                continue;
+            };
+            let slots = crate::slots::calldata_slots_in(chain.slot_pack, &to, &tx.from, &tx.input);
+            if !slots.is_empty() {
+                token_slots.entry(to).or_default().extend(slots);
+            }
+        }
 
-        // Account info: one task per address.
+        // One job per account, plus one per known, hinted or calldata slot.
+        let mut jobs: Vec<Job> = addresses.iter().map(|&addr| Job::Account(addr)).collect();
         for &addr in &addresses {
-            let p = self.provider.clone();
-            let sem = semaphore.clone();
-            tasks.spawn(async move {
-                let _permit = sem.acquire().await.unwrap();
-                fetch_account_with_retry(&p, addr, block_id).await
-            });
+            let mut slots: BTreeSet<U256> = crate::slots::known_slots_in(chain.slot_pack, &addr)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            slots.extend(hints.get(&addr).into_iter().flatten());
+            slots.extend(token_slots.get(&addr).into_iter().flatten());
+            jobs.extend(slots.into_iter().map(|slot| Job::Storage(addr, slot)));
+        }
+        let slot_count = jobs.len() - addr_count;
+        if slot_count > 0 {
+            tracing::info!(slot_count, "prefetching known and hinted slots");
+        }
+        if self.strategy == PrefetchStrategy::Proof {
+            let warm_db = self
+                .prefetch_proven(block_id, &block, jobs, &semaphore)
+                .await?;
+            let stats = PrefetchStats {
+                fetched: warm_db.cache.accounts.len() + slot_count,
+                slots_requested: slot_count,
+                ..Default::default()
+            };
+            return Ok((warm_db, stats));
         }
 
-        // Storage slots for known DeFi contracts.
-        let mut slot_count = 0usize;
-        for &addr in &addresses {
-            if let Some(slots) = crate::slots::known_slots(&addr) {
-                for &slot in slots {
-                    let p = self.provider.clone();
-                    let sem = semaphore.clone();
-                    slot_count += 1;
-                    tasks.spawn(async move {
-                        let _permit = sem.acquire().await.unwrap();
-                        fetch_storage_with_retry(&p, addr, slot, block_id).await
-                    });
+        let mut cached = match &cache {
+            Some((cache, chain_id, key)) => cache
+                .get::<CachedState>(*chain_id, rpc_cache::STATE, key)?
+                .unwrap_or_default(),
+            None => CachedState::default(),
+        };
+        let wanted = jobs.len();
+        jobs.retain(|job| !job.is_cached(&cached));
+        let cache_hits = wanted - jobs.len();
+        if cache_hits > 0 {
+            tracing::info!(cached = cache_hits, "prefetch cache hits");
+        }
+        let cached_count = cached.len();
+
+        // Drain into CacheDB, sharing one bytecode per distinct code hash.
+        let mut warm_db = CacheDB::new(EmptyDB::new());
+        cached.insert_into(&mut warm_db, &self.code_cache);
+        let store = cache.is_some().then_some(&mut cached);
+        let (fetched, failed) = self
+            .fetch_jobs(block_id, &block, jobs, &mut warm_db, store)
+            .await?;
+
+        tracing::info!(
+            %block,
+            fetched,
+            failed,
+            code_cache = self.code_cache.len(),
+            "prefetch done"
+        );
+        // Failed reads aren't stored, so the next run retries them.
+        if let Some((cache, chain_id, key)) = &cache {
+            if cached.len() > cached_count {
+                cache.put(*chain_id, rpc_cache::STATE, key, &cached)?;
+            }
+        }
+        let stats = PrefetchStats {
+            fetched,
+            failed,
+            cached: cache_hits,
+            slots_requested: slot_count,
+            ..Default::default()
+        };
+        Ok((warm_db, stats))
+    }
+
+    /// Fetch exactly the state the block touches, found by simulation
+    /// rather than the static slot table. `discover` simulates the block
+    /// against the state so far and reports every account and slot it
+    /// read: first against an empty state (senders and call targets),
+    /// then against each round's result, picking up code, storage and
+    /// nested callees, until nothing new turns up or
+    /// [`DISCOVERY_ROUNDS`] pass. Bypasses the RPC cache.
+    pub async fn prefetch_discovered(
+        &self,
+        block: impl Into<BlockId>,
+        discover: impl Fn(&WarmCacheDB) -> ArgusResult<TouchedState>,
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        let started = Instant::now();
+        let retries = self.metrics().retries;
+        let block_id = block.into();
+        let block = describe_block(block_id);
+        let mut warm_db = CacheDB::new(EmptyDB::new());
+        let mut accounts = std::collections::HashSet::new();
+        let mut slots = std::collections::HashSet::new();
+        let (mut fetched, mut failed) = (0, 0);
+        for round in 0..DISCOVERY_ROUNDS {
+            let touched = discover(&warm_db)?;
+            let mut jobs = Vec::new();
+            for (addr, touched_slots) in touched {
+                if accounts.insert(addr) {
+                    jobs.push(Job::Account(addr));
+                }
+                for slot in touched_slots {
+                    if slots.insert((addr, slot)) {
+                        jobs.push(Job::Storage(addr, slot));
+                    }
                 }
             }
+            if jobs.is_empty() {
+                break;
+            }
+            tracing::info!(%block, round, jobs = jobs.len(), "prefetching discovered state");
+            let (ok, err) = self
+                .fetch_jobs(block_id, &block, jobs, &mut warm_db, None)
+                .await?;
+            fetched += ok;
+            failed += err;
         }
+        tracing::info!(
+            %block,
+            accounts = accounts.len(),
+            slots = slots.len(),
+            fetched,
+            failed,
+            "discovery prefetch done"
+        );
+        let stats = PrefetchStats {
+            fetched,
+            failed,
+            slots_requested: slots.len(),
+            ..Default::default()
+        };
+        Ok(self.finish_stats(warm_db, stats, started, retries))
+    }
 
-        if slot_count > 0 {
-            tracing::info!(slot_count, "prefetching known DeFi slots");
+    /// Layers `warm_db` over the node's state at `block`, so accounts, code
+    /// and slots the prefetch missed are fetched on first read instead of
+    /// defaulting to zero.
+    ///
+    /// Each miss is a blocking round trip from the simulating thread. It
+    /// draws from the budget at [`Priority::High`] and retries and fails
+    /// over like the prefetch, but reads through a shared `&FallbackDB` are
+    /// not cached between transactions, so this tops up a well-warmed cache
+    /// rather than replacing the prefetch. Requests are driven by the
+    /// current multi-threaded Tokio runtime; elsewhere this fails.
+    pub fn fallback_db(
+        &self,
+        warm_db: WarmCacheDB,
+        block: impl Into<BlockId>,
+    ) -> ArgusResult<FallbackDB> {
+        let handle = tokio::runtime::Handle::try_current()
+            .ok()
+            .filter(|h| h.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread)
+            .ok_or_else(|| {
+                ArgusError::Internal("RPC fallback needs a multi-threaded Tokio runtime".into())
+            })?;
+        let node = NodeDB::new(
+            self.endpoints.clone(),
+            self.budget.clone(),
+            self.retry.clone(),
+            self.code_cache.clone(),
+            block.into(),
+        );
+        let node = WrapDatabaseAsync::with_handle(node, handle);
+        let mut db = CacheDB::new(node);
+        db.cache = warm_db.cache;
+        Ok(db)
+    }
+}
+
+impl Prefetcher {
+    /// Fetch `jobs` concurrently into `warm_db`, recording what was fetched
+    /// in `cached` when given. Returns the fetched and failed counts.
+    async fn fetch_jobs(
+        &self,
+        block_id: BlockId,
+        block: &str,
+        mut jobs: Vec<Job>,
+        warm_db: &mut WarmCacheDB,
+        mut cached: Option<&mut CachedState>,
+    ) -> ArgusResult<(usize, usize)> {
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(self.concurrency()));
+        let mut tasks = tokio::task::JoinSet::new();
+        let total = jobs.len();
+        if let Some(progress) = &self.progress {
+            progress.on_fetch_start(block, total);
+        }
+
+        // Contracts whose code an earlier block fetched only need balance
+        // and nonce.
+        let mut known = HashMap::new();
+        for job in &mut jobs {
+            if let Job::Account(addr) = *job {
+                if let Some(code) = self.code_cache.contract(&addr) {
+                    known.insert(addr, code);
+                    *job = Job::KnownCode(addr);
+                }
+            }
+        }
+        if !known.is_empty() {
+            tracing::debug!(%block, known = known.len(), "reusing cached contract code");
+        }
+
+        if self.multicall {
+            let mut reads = Vec::new();
+            jobs.retain(|&job| match job {
+                Job::Storage(addr, slot) => {
+                    reads.push((addr, slot));
+                    false
+                }
+                Job::Account(_) | Job::KnownCode(_) => true,
+            });
+            let (calls, skipped) = multicall::plan(reads);
+            jobs.extend(
+                skipped
+                    .into_iter()
+                    .map(|(addr, slot)| Job::Storage(addr, slot)),
+            );
+            for call in calls {
+                let p = self.endpoints.clone();
+                let sem = semaphore.clone();
+                let budget = self.budget.clone();
+                let retry = self.retry.clone();
+                let batch_size = self.batch_size;
+                tasks.spawn(async move {
+                    let _permit = sem.acquire().await.unwrap();
+                    fetch_multicall_with_retry(&p, &budget, &retry, call, block_id, batch_size)
+                        .await
+                });
+            }
+        }
+        if self.batch_size > 1 {
+            for batch in batches(jobs, self.batch_size) {
+                let p = self.endpoints.clone();
+                let sem = semaphore.clone();
+                let budget = self.budget.clone();
+                let retry = self.retry.clone();
+                tasks.spawn(async move {
+                    let _permit = sem.acquire().await.unwrap();
+                    fetch_batch_with_retry(&p, &budget, &retry, batch, block_id).await
+                });
+            }
+        } else {
+            for job in jobs {
+                self.spawn_job(&mut tasks, &semaphore, job, block_id);
+            }
         }
 
-        // Drain into CacheDB.
-        let mut warm_db = CacheDB::new(EmptyDB::new());
         let mut fetched = 0usize;
         let mut failed = 0usize;
 
-        while let Some(result) = tasks.join_next().await {
-            match result {
-                Ok(Ok(FetchResult::Account(addr, info))) => {
-                    warm_db.insert_account_info(addr, info);
-                    fetched += 1;
+        loop {
+            let result = tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => {
+                    tasks.abort_all();
+                    tracing::info!(%block, fetched, "prefetch cancelled");
+                    return Err(ArgusError::Cancelled);
                 }
-                Ok(Ok(FetchResult::Storage(addr, slot, value))) => {
-                    warm_db.insert_account_storage(addr, slot, value).ok();
-                    fetched += 1;
+                result = tasks.join_next() => match result {
+                    Some(result) => result,
+                    None => break,
+                },
+            };
+            let results = match result {
+                Ok(results) => results,
+                Err(e) => {
+                    tracing::warn!(error = %e, "prefetch task panicked");
+                    failed += 1;
+                    continue;
+                }
+            };
+            for result in results {
+                match result {
+                    Ok(FetchResult::Account {
+                        addr,
+                        balance,
+                        nonce,
+                        code,
+                    }) => {
+                        let (code_hash, bytecode) = match code {
+                            Some(code) => self.code_cache.intern_contract(addr, code),
+                            // A nonce of 0 means the contract isn't deployed
+                            // yet at this block (or predates EIP-161), so its
+                            // code has to be fetched after all.
+                            None => match known.get(&addr).filter(|_| nonce > 0) {
+                                Some(code) => code.clone(),
+                                None => {
+                                    self.spawn_job(
+                                        &mut tasks,
+                                        &semaphore,
+                                        Job::Account(addr),
+                                        block_id,
+                                    );
+                                    continue;
+                                }
+                            },
+                        };
+                        if let Some(cached) = cached.as_deref_mut() {
+                            let account = CachedAccount {
+                                balance,
+                                nonce,
+                                code: bytecode.original_bytes(),
+                            };
+                            cached.accounts.insert(addr, account);
+                        }
+                        let info = AccountInfo::new(balance, nonce, code_hash, bytecode);
+                        warm_db.insert_account_info(addr, info);
+                        fetched += 1;
+                    }
+                    Ok(FetchResult::Storage(addr, slot, value)) => {
+                        if let Some(cached) = cached.as_deref_mut() {
+                            cached.insert_slot(addr, slot, value);
+                        }
+                        warm_db.insert_account_storage(addr, slot, value).ok();
+                        fetched += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!(%block, error = %e, "prefetch failed");
+                        failed += 1;
+                    }
+                }
+            }
+            if let Some(progress) = &self.progress {
+                progress.on_fetch_done((fetched + failed).min(total), total);
+            }
+        }
+        Ok((fetched, failed))
+    }
+
+    /// Fetch one job on its own task.
+    fn spawn_job(
+        &self,
+        tasks: &mut tokio::task::JoinSet<Vec<Result<FetchResult, String>>>,
+        semaphore: &Arc<tokio::sync::Semaphore>,
+        job: Job,
+        block_id: BlockId,
+    ) {
+        let p = self.endpoints.clone();
+        let sem = semaphore.clone();
+        let budget = self.budget.clone();
+        let retry = self.retry.clone();
+        tasks.spawn(async move {
+            let _permit = sem.acquire().await.unwrap();
+            let result = match job {
+                Job::Account(addr) => {
+                    fetch_account_with_retry(&p, &budget, &retry, addr, true, block_id).await
+                }
+                Job::KnownCode(addr) => {
+                    fetch_account_with_retry(&p, &budget, &retry, addr, false, block_id).await
+                }
+                Job::Storage(addr, slot) => {
+                    fetch_storage_with_retry(&p, &budget, &retry, addr, slot, block_id).await
+                }
+            };
+            vec![result.map_err(|e| format!("{job}: {e}"))]
+        });
+    }
+
+    /// Chain the RPC cache is scoped to, asking the node once if unpinned.
+    async fn cache_chain_id(&self, cache: &RpcCache) -> ArgusResult<u64> {
+        cache
+            .chain_id(|| async {
+                let _permit = self.budget.acquire(Priority::Normal).await?;
+                let (idx, p) = self.endpoints.current();
+                let chain_id = p
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| ArgusError::Provider(format!("Failed to fetch chain id: {e}")))
+                    .rpc("eth_chainId")?;
+                self.endpoints.record_success(idx);
+                Ok(chain_id)
+            })
+            .await
+    }
+
+    /// Slots per contract from `eth_createAccessList` for each transaction.
+    async fn access_list_hints(
+        &self,
+        block_id: BlockId,
+        transactions: &[Transaction],
+        semaphore: &Arc<tokio::sync::Semaphore>,
+    ) -> ArgusResult<HashMap<Address, BTreeSet<U256>>> {
+        let mut tasks = tokio::task::JoinSet::new();
+        for tx in transactions {
+            let request = TransactionRequest {
+                from: Some(tx.from),
+                to: Some(tx.to.map_or(TxKind::Create, TxKind::Call)),
+                value: Some(tx.value),
+                input: TransactionInput::new(tx.input.clone()),
+                gas: Some(tx.gas),
+                ..Default::default()
+            };
+            let tx_hash = tx.hash;
+            let endpoints = self.endpoints.clone();
+            let sem = semaphore.clone();
+            let budget = self.budget.clone();
+            tasks.spawn(async move {
+                let _permit = sem.acquire().await.unwrap();
+                let _budget = budget
+                    .acquire(Priority::Normal)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let (idx, p) = endpoints.current();
+                let result = p
+                    .create_access_list(&request)
+                    .block_id(block_id)
+                    .await
+                    .map_err(|e| format!("tx {tx_hash}: {e}"))?;
+                Ok::<_, String>((idx, result.access_list))
+            });
+        }
+
+        let mut hints: HashMap<Address, BTreeSet<U256>> = HashMap::new();
+        let mut failed = 0usize;
+        loop {
+            let result = tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => {
+                    tasks.abort_all();
+                    return Err(ArgusError::Cancelled);
+                }
+                result = tasks.join_next() => match result {
+                    Some(result) => result,
+                    None => break,
+                },
+            };
+            match result {
+                Ok(Ok((idx, access_list))) => {
+                    self.endpoints.record_success(idx);
+                    for item in access_list.0 {
+                        hints.entry(item.address).or_default().extend(
+                            item.storage_keys
+                                .iter()
+                                .map(|key| U256::from_be_bytes(key.0)),
+                        );
+                    }
                 }
                 Ok(Err(e)) => {
-                    tracing::warn!(error = %e, "prefetch failed");
+                    tracing::debug!(error = %e, "access list hint failed");
                     failed += 1;
                 }
                 Err(e) => {
-                    tracing::warn!(error = %e, "prefetch task panicked");
+                    tracing::warn!(error = %e, "access list task panicked");
                     failed += 1;
                 }
             }
         }
 
-        tracing::info!(block_number, fetched, failed, "prefetch done");
+        tracing::info!(
+            txs = transactions.len(),
+            contracts = hints.len(),
+            slots = hints.values().map(BTreeSet::len).sum::<usize>(),
+            failed,
+            "collected access list hints"
+        );
+        Ok(hints)
+    }
+
+    /// [`PrefetchStrategy::Proof`]: one verified `eth_getProof` per account,
+    /// covering all of its slots.
+    async fn prefetch_proven(
+        &self,
+        block_id: BlockId,
+        block: &str,
+        jobs: Vec<Job>,
+        semaphore: &Arc<tokio::sync::Semaphore>,
+    ) -> ArgusResult<WarmCacheDB> {
+        let root = tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => return Err(ArgusError::Cancelled),
+            root = proof::state_root(&self.endpoints, &self.budget, &self.retry, block_id) => root,
+        }
+        .map_err(|e| {
+            ArgusError::Provider(format!("Failed to fetch header of block {block}: {e}"))
+        })?;
+
+        let total = jobs.len();
+        if let Some(progress) = &self.progress {
+            progress.on_fetch_start(block, total);
+        }
+        let mut accounts: BTreeMap<Address, Vec<U256>> = BTreeMap::new();
+        for job in jobs {
+            match job {
+                Job::Account(addr) | Job::KnownCode(addr) => {
+                    accounts.entry(addr).or_default();
+                }
+                Job::Storage(addr, slot) => accounts.entry(addr).or_default().push(slot),
+            }
+        }
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for (addr, slots) in accounts {
+            let p = self.endpoints.clone();
+            let sem = semaphore.clone();
+            let budget = self.budget.clone();
+            let retry = self.retry.clone();
+            tasks.spawn(async move {
+                let _permit = sem.acquire().await.unwrap();
+                proof::fetch_proven_account(&p, &budget, &retry, addr, slots, block_id, root).await
+            });
+        }
+
+        let mut warm_db = CacheDB::new(EmptyDB::new());
+        let mut slots = 0usize;
+        loop {
+            let result = tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => {
+                    tasks.abort_all();
+                    return Err(ArgusError::Cancelled);
+                }
+                result = tasks.join_next() => match result {
+                    Some(result) => result,
+                    None => break,
+                },
+            };
+            let account = match result {
+                Ok(Ok(account)) => account,
+                Ok(Err(ProofError::Invalid(e))) => {
+                    tasks.abort_all();
+                    return Err(ArgusError::Provider(format!(
+                        "state proof check failed at block {block}: {e}"
+                    )));
+                }
+                Ok(Err(ProofError::Fetch(e))) => {
+                    tasks.abort_all();
+                    return Err(ArgusError::Provider(format!(
+                        "eth_getProof at block {block} failed: {e}"
+                    )))
+                    .rpc("eth_getProof");
+                }
+                Err(e) => {
+                    tasks.abort_all();
+                    return Err(ArgusError::Internal(format!("proof task failed: {e}")));
+                }
+            };
+            let (code_hash, bytecode) = self
+                .code_cache
+                .intern_contract(account.address, account.code);
+            let info = AccountInfo::new(account.balance, account.nonce, code_hash, bytecode);
+            warm_db.insert_account_info(account.address, info);
+            for (slot, value) in account.storage {
+                warm_db
+                    .insert_account_storage(account.address, slot, value)
+                    .ok();
+                slots += 1;
+            }
+            if let Some(progress) = &self.progress {
+                let done = warm_db.cache.accounts.len() + slots;
+                progress.on_fetch_done(done.min(total), total);
+            }
+        }
+
+        tracing::info!(
+            %block,
+            %root,
+            accounts = warm_db.cache.accounts.len(),
+            slots,
+            "prefetch done, all state verified"
+        );
         Ok(warm_db)
     }
+
+    /// [`PrefetchStrategy::Prestate`]: trace the block once and keep each
+    /// account's and slot's first-seen pre-state, i.e. its value before the
+    /// first transaction that touched it.
+    async fn prefetch_prestate(
+        &self,
+        block_id: BlockId,
+        block: &str,
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        tracing::info!(%block, "prefetching state from prestateTracer");
+        // One trace call covers the whole block.
+        if let Some(progress) = &self.progress {
+            progress.on_fetch_start(block, 1);
+        }
+        let trace = trace_prestate_with_retry(&self.endpoints, &self.budget, &self.retry, block_id);
+        let traces = tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => return Err(ArgusError::Cancelled),
+            traces = trace => traces,
+        }
+        .map_err(|e| {
+            ArgusError::Provider(format!(
+                "prestateTracer on block {block} failed (needs the debug API): {e}"
+            ))
+        })?;
+
+        if let Some(progress) = &self.progress {
+            progress.on_fetch_done(1, 1);
+        }
+        let failed = traces.iter().filter(|trace| trace.error.is_some()).count();
+        for trace in &traces {
+            if let Some(error) = &trace.error {
+                tracing::warn!(%block, tx = ?trace.tx_hash, %error, "prestate trace failed");
+            }
+        }
+        let warm_db = prestate_db(&traces, &self.code_cache);
+
+        tracing::info!(
+            %block,
+            txs = traces.len(),
+            accounts = warm_db.cache.accounts.len(),
+            failed,
+            "prefetch done"
+        );
+        // Every account and slot came from the traces, so all were fetched;
+        // failures are whole transactions whose pre-state is missing.
+        let slots = warm_slots(&warm_db);
+        let stats = PrefetchStats {
+            fetched: warm_db.cache.accounts.len() + slots,
+            failed,
+            slots_requested: slots,
+            ..Default::default()
+        };
+        Ok((warm_db, stats))
+    }
+}
+
+#[async_trait]
+impl PrefetchSource for Prefetcher {
+    async fn prefetch_on(
+        &self,
+        chain: &ChainConfig,
+        block: BlockId,
+        transactions: &[Transaction],
+    ) -> ArgusResult<(WarmCacheDB, PrefetchStats)> {
+        Prefetcher::prefetch_on(self, chain, block, transactions).await
+    }
+}
+
+/// Storage slots held in `warm_db`.
+fn warm_slots(warm_db: &WarmCacheDB) -> usize {
+    warm_db
+        .cache
+        .accounts
+        .values()
+        .map(|account| account.storage.len())
+        .sum()
+}
+
+/// Merge per-transaction pre-states into the state before the block: an
+/// account or slot keeps the value from the first transaction that touched
+/// it, since later transactions see earlier ones' writes.
+fn prestate_db(traces: &[TxTrace<Prestate>], codes: &CodeCache) -> WarmCacheDB {
+    let mut warm_db = CacheDB::new(EmptyDB::new());
+    let mut seen_slots = std::collections::HashSet::new();
+    for (&addr, account) in traces
+        .iter()
+        .flat_map(|trace| trace.result.iter().flatten())
+    {
+        if !warm_db.cache.accounts.contains_key(&addr) {
+            let code = account.code.clone().unwrap_or_default();
+            let (code_hash, bytecode) = codes.intern_contract(addr, code);
+            let info = AccountInfo::new(
+                account.balance.unwrap_or_default(),
+                account.nonce.unwrap_or_default(),
+                code_hash,
+                bytecode,
+            );
+            warm_db.insert_account_info(addr, info);
+        }
+        for (slot, value) in &account.storage {
+            if seen_slots.insert((addr, *slot)) {
+                let slot = U256::from_be_bytes(slot.0);
+                let value = U256::from_be_bytes(value.0);
+                warm_db.insert_account_storage(addr, slot, value).ok();
+            }
+        }
+    }
+    warm_db
+}
+
+/// Trace `block_id` with the `prestateTracer`, retrying like the other
+/// fetches. One (heavy) request.
+async fn trace_prestate_with_retry(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    retry: &RetryPolicy,
+    block_id: BlockId,
+) -> Result<Vec<TxTrace<Prestate>>, String> {
+    let started = Instant::now();
+    for attempt in 0..retry.max_attempts() {
+        retry.backoff(attempt).await;
+
+        let _permit = budget
+            .acquire(Priority::Normal)
+            .await
+            .map_err(|e| e.to_string())?;
+        let (idx, p) = endpoints.current();
+        match trace::debug_trace_block(p, block_id, trace::PRESTATE).await {
+            Ok(traces) => {
+                endpoints.record_success(idx);
+                return Ok(traces);
+            }
+            Err(e) => {
+                if retry.on_failure(endpoints, idx, attempt, started, &e) {
+                    continue;
+                }
+                return Err(e);
+            }
+        }
+    }
+    Err("max retries exceeded".to_string())
 }
 
-/// Fetch account info with exponential backoff retry on 429.
+/// Fetch account info (without code unless `with_code`), retrying per
+/// `retry` and moving to the next endpoint when the active one is
+/// unreachable or keeps rate limiting.
 async fn fetch_account_with_retry(
-    p: &DynProvider,
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    retry: &RetryPolicy,
     addr: Address,
+    with_code: bool,
     block_id: BlockId,
 ) -> Result<FetchResult, String> {
-    for attempt in 0..=MAX_RETRIES {
-        if attempt > 0 {
-            let delay = std::time::Duration::from_millis(200 * 2u64.pow(attempt - 1));
-            tokio::time::sleep(delay).await;
-        }
+    let started = Instant::now();
+    for attempt in 0..retry.max_attempts() {
+        retry.backoff(attempt).await;
 
+        // The balance/nonce/code triple is charged as three requests.
+        let permit = budget
+            .acquire_batch(Priority::Normal, if with_code { 3 } else { 2 })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let (idx, p) = endpoints.current();
         let balance = p.get_balance(addr).block_id(block_id);
         let nonce = p.get_transaction_count(addr).block_id(block_id);
-        let code = p.get_code_at(addr).block_id(block_id);
+        let code = async {
+            match with_code {
+                true => p.get_code_at(addr).block_id(block_id).await.map(Some),
+                false => Ok(None),
+            }
+        };
 
         let (balance, nonce, code) = tokio::join!(balance, nonce, code);
+        drop(permit);
 
-        // Check for 429 and retry.
-        let is_rate_limited = balance
-            .as_ref()
-            .err()
-            .map_or(false, |e| format!("{e}").contains("429"))
-            || nonce
-                .as_ref()
-                .err()
-                .map_or(false, |e| format!("{e}").contains("429"))
-            || code
-                .as_ref()
-                .err()
-                .map_or(false, |e| format!("{e}").contains("429"));
-
-        if is_rate_limited && attempt < MAX_RETRIES {
-            continue;
+        // Retry (possibly on another endpoint) if any of the three failed
+        // for a transient reason.
+        let error = [
+            balance.as_ref().err().map(|e| e.to_string()),
+            nonce.as_ref().err().map(|e| e.to_string()),
+            code.as_ref().err().map(|e| e.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .next();
+        match error {
+            Some(e) => {
+                if retry.on_failure(endpoints, idx, attempt, started, &e) {
+                    continue;
+                }
+            }
+            None => endpoints.record_success(idx),
         }
 
         let balance = balance.map_err(|e| format!("{e}"))?;
         let nonce = nonce.map_err(|e| format!("{e}"))?;
         let code_bytes = code.map_err(|e| format!("{e}"))?;
 
-        let bytecode = Bytecode::new_raw(code_bytes.0.into());
-        let code_hash = bytecode.hash_slow();
-        let info = AccountInfo::new(balance, nonce, code_hash, bytecode);
-
-        return Ok(FetchResult::Account(addr, info));
+        // Analysis is deferred to the drain loop so shared code is analyzed once.
+        return Ok(FetchResult::Account {
+            addr,
+            balance,
+            nonce,
+            code: code_bytes.map(|code| code.0.into()),
+        });
     }
-    Err(format!("max retries exceeded for {addr}"))
+    Err("max retries exceeded".to_string())
 }
 
-/// Fetch storage slot with exponential backoff retry on 429.
+/// Fetch storage slot with the same retry and failover as
+/// [`fetch_account_with_retry`].
 async fn fetch_storage_with_retry(
-    p: &DynProvider,
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    retry: &RetryPolicy,
     addr: Address,
-    slot: alloy_primitives::U256,
+    slot: U256,
     block_id: BlockId,
 ) -> Result<FetchResult, String> {
-    for attempt in 0..=MAX_RETRIES {
-        if attempt > 0 {
-            let delay = std::time::Duration::from_millis(200 * 2u64.pow(attempt - 1));
-            tokio::time::sleep(delay).await;
-        }
+    let started = Instant::now();
+    for attempt in 0..retry.max_attempts() {
+        retry.backoff(attempt).await;
 
+        let _permit = budget
+            .acquire(Priority::Normal)
+            .await
+            .map_err(|e| e.to_string())?;
+        let (idx, p) = endpoints.current();
         match p.get_storage_at(addr, slot).block_id(block_id).await {
-            Ok(val) => return Ok(FetchResult::Storage(addr, slot, val)),
+            Ok(val) => {
+                endpoints.record_success(idx);
+                return Ok(FetchResult::Storage(addr, slot, val));
+            }
             Err(e) => {
                 let err_str = format!("{e}");
-                if err_str.contains("429") && attempt < MAX_RETRIES {
+                if retry.on_failure(endpoints, idx, attempt, started, &err_str) {
                     continue;
                 }
                 return Err(err_str);
             }
         }
     }
-    Err(format!("max retries exceeded for {addr} slot {slot}"))
+    Err("max retries exceeded".to_string())
+}
+
+/// Read the slots in `call` with one Multicall3 `eth_call`, retried like
+/// the other fetches. Contracts whose sub-call failed, or every contract if
+/// the node rejects the call, are read with `eth_getStorageAt` in batches
+/// of `batch_size`.
+async fn fetch_multicall_with_retry(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    retry: &RetryPolicy,
+    call: multicall::StorageCall,
+    block_id: BlockId,
+    batch_size: usize,
+) -> Vec<Result<FetchResult, String>> {
+    let mut results = Vec::new();
+    let mut fallback = Vec::new();
+    let started = Instant::now();
+    for attempt in 0..retry.max_attempts() {
+        retry.backoff(attempt).await;
+
+        let _permit = match budget.acquire(Priority::Normal).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                return storage_jobs(&call)
+                    .map(|job| Err(format!("{job}: {e}")))
+                    .collect();
+            }
+        };
+        let (idx, p) = endpoints.current();
+        match multicall::read_storage(p, &call, block_id).await {
+            Ok(values) => {
+                endpoints.record_success(idx);
+                for ((addr, slots), values) in call.iter().zip(values) {
+                    match values {
+                        Some(values) => results.extend(
+                            slots
+                                .iter()
+                                .zip(values)
+                                .map(|(&slot, value)| Ok(FetchResult::Storage(*addr, slot, value))),
+                        ),
+                        None => {
+                            fallback.extend(slots.iter().map(|&slot| Job::Storage(*addr, slot)))
+                        }
+                    }
+                }
+                break;
+            }
+            Err(e) => {
+                if retry.on_failure(endpoints, idx, attempt, started, &e) {
+                    continue;
+                }
+                tracing::debug!(error = %e, "multicall storage read failed; using eth_getStorageAt");
+                fallback = storage_jobs(&call).collect();
+                break;
+            }
+        }
+    }
+    for batch in batches(fallback, batch_size) {
+        results.extend(fetch_batch_with_retry(endpoints, budget, retry, batch, block_id).await);
+    }
+    results
+}
+
+fn storage_jobs(call: &multicall::StorageCall) -> impl Iterator<Item = Job> + '_ {
+    call.iter()
+        .flat_map(|(addr, slots)| slots.iter().map(|&slot| Job::Storage(*addr, slot)))
+}
+
+/// Pack `jobs` into batches of at most `size` calls (a lone account may
+/// exceed a size below 3).
+fn batches(jobs: Vec<Job>, size: usize) -> Vec<Vec<Job>> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<Job> = Vec::new();
+    let mut calls = 0;
+    for job in jobs {
+        if !batch.is_empty() && calls + job.calls() > size {
+            batches.push(std::mem::take(&mut batch));
+            calls = 0;
+        }
+        calls += job.calls();
+        batch.push(job);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+/// Fetch `jobs` as JSON-RPC batches. A failed batch is retried whole, with
+/// backoff and failover as for single calls; rate-limited entries within
+/// a delivered batch are retried on their own.
+async fn fetch_batch_with_retry(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    retry: &RetryPolicy,
+    jobs: Vec<Job>,
+    block_id: BlockId,
+) -> Vec<Result<FetchResult, String>> {
+    let mut done = Vec::with_capacity(jobs.len());
+    let mut pending = jobs;
+    let started = Instant::now();
+    for attempt in 0..retry.max_attempts() {
+        if pending.is_empty() {
+            break;
+        }
+        retry.backoff(attempt).await;
+
+        let calls = pending.iter().map(|job| job.calls() as u32).sum();
+        let permit = match budget.acquire_batch(Priority::Normal, calls).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                done.extend(pending.drain(..).map(|job| Err(format!("{job}: {e}"))));
+                break;
+            }
+        };
+        let (idx, p) = endpoints.current();
+        let outcomes = send_batch(p, &pending, block_id).await;
+        drop(permit);
+
+        let outcomes = match outcomes {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                if retry.on_failure(endpoints, idx, attempt, started, &e) {
+                    continue;
+                }
+                done.extend(pending.drain(..).map(|job| Err(format!("{job}: {e}"))));
+                break;
+            }
+        };
+        endpoints.record_success(idx);
+
+        let mut again = Vec::new();
+        for (job, outcome) in pending.drain(..).zip(outcomes) {
+            match outcome {
+                Err(e) if retry_entry(retry, endpoints, idx, attempt, started, &e) => {
+                    again.push(job);
+                }
+                outcome => done.push(outcome.map_err(|e| format!("{job}: {e}"))),
+            }
+        }
+        pending = again;
+    }
+    done
+}
+
+/// Whether a call that failed inside a delivered batch is retried: rate
+/// limits count against the endpoint, other errors only match predicates.
+fn retry_entry(
+    retry: &RetryPolicy,
+    endpoints: &Endpoints,
+    idx: usize,
+    attempt: u32,
+    started: Instant,
+    error: &str,
+) -> bool {
+    let failure = Failure::classify(error);
+    let wanted = retry.should_retry(
+        attempt,
+        started,
+        error,
+        failure,
+        failure == Failure::RateLimited,
+    );
+    if wanted {
+        endpoints.record_retry(idx);
+    }
+    wanted
+}
+
+/// Send one batch. The outer error is transport-level (the whole batch
+/// failed); inner errors are per call.
+async fn send_batch(
+    p: &DynProvider,
+    jobs: &[Job],
+    block_id: BlockId,
+) -> Result<Vec<Result<FetchResult, String>>, String> {
+    let mut batch = BatchRequest::new(p.client());
+    let mut waiters = Vec::with_capacity(jobs.len());
+    for &job in jobs {
+        let waiter = match job {
+            Job::Account(addr) | Job::KnownCode(addr) => {
+                let params = (addr, block_id);
+                let balance = batch
+                    .add_call("eth_getBalance", &params)
+                    .map_err(|e| e.to_string())?;
+                let nonce = batch
+                    .add_call("eth_getTransactionCount", &params)
+                    .map_err(|e| e.to_string())?;
+                let code = match job {
+                    Job::Account(_) => Some(
+                        batch
+                            .add_call("eth_getCode", &params)
+                            .map_err(|e| e.to_string())?,
+                    ),
+                    _ => None,
+                };
+                BatchWaiter::Account(balance, nonce, code)
+            }
+            Job::Storage(addr, slot) => BatchWaiter::Storage(
+                batch
+                    .add_call("eth_getStorageAt", &(addr, slot, block_id))
+                    .map_err(|e| e.to_string())?,
+            ),
+        };
+        waiters.push(waiter);
+    }
+    batch.send().await.map_err(|e| e.to_string())?;
+
+    let mut results = Vec::with_capacity(jobs.len());
+    for (&job, waiter) in jobs.iter().zip(waiters) {
+        let result = match (job, waiter) {
+            (
+                Job::Account(addr) | Job::KnownCode(addr),
+                BatchWaiter::Account(balance, nonce, code),
+            ) => {
+                let code = async {
+                    match code {
+                        Some(code) => code.await.map(Some),
+                        None => Ok(None),
+                    }
+                };
+                let (balance, nonce, code) = tokio::join!(balance, nonce, code);
+                balance
+                    .and_then(|balance| Ok((balance, nonce?, code?)))
+                    .map(|(balance, nonce, code)| FetchResult::Account {
+                        addr,
+                        balance,
+                        nonce: nonce.to::<u64>(),
+                        code,
+                    })
+            }
+            (Job::Storage(addr, slot), BatchWaiter::Storage(value)) => value
+                .await
+                .map(|value| FetchResult::Storage(addr, slot, value)),
+            _ => unreachable!("waiters are built in job order"),
+        };
+        results.push(result.map_err(|e| e.to_string()));
+    }
+    Ok(results)
+}
+
+/// One account or slot read.
+#[derive(Debug, Clone, Copy)]
+enum Job {
+    Account(Address),
+    /// Balance and nonce of a contract whose code the [`CodeCache`] holds.
+    KnownCode(Address),
+    Storage(Address, U256),
+}
+
+impl Job {
+    fn is_cached(self, state: &CachedState) -> bool {
+        match self {
+            Self::Account(addr) | Self::KnownCode(addr) => state.accounts.contains_key(&addr),
+            Self::Storage(addr, slot) => state.slot(&addr, slot).is_some(),
+        }
+    }
+
+    /// JSON-RPC calls needed.
+    fn calls(self) -> usize {
+        match self {
+            Self::Account(_) => 3,
+            Self::KnownCode(_) => 2,
+            Self::Storage(..) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Account(addr) | Self::KnownCode(addr) => write!(f, "account {addr}"),
+            Self::Storage(addr, slot) => write!(f, "storage {addr} slot {slot}"),
+        }
+    }
+}
+
+/// Pending responses for one [`Job`] in a batch.
+enum BatchWaiter {
+    Account(Waiter<U256>, Waiter<U64>, Option<Waiter<Bytes>>),
+    Storage(Waiter<U256>),
 }
 
 /// Internal result type for the JoinSet drain loop.
 enum FetchResult {
-    Account(Address, AccountInfo),
-    Storage(Address, alloy_primitives::U256, alloy_primitives::U256),
+    Account {
+        addr: Address,
+        balance: U256,
+        nonce: u64,
+        /// `None` for [`Job::KnownCode`].
+        code: Option<Bytes>,
+    },
+    Storage(Address, U256, U256),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(to: u8, gas: u64) -> Transaction {
+        Transaction {
+            from: Address::with_last_byte(0xee),
+            to: Some(Address::with_last_byte(to)),
+            input: Bytes::from_static(&[0xa9, 0x05, 0x9c, 0xbb]),
+            gas,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lite_targets_rank_by_calls_and_gas() {
+        let mut transfer = call(9, 21_000);
+        transfer.input = Bytes::new();
+        let txs = vec![
+            call(1, 50_000),
+            call(1, 50_000),
+            call(2, 900_000),
+            call(3, 30_000),
+            transfer,
+        ];
+
+        let top = lite_targets(&txs, 2);
+        assert_eq!(
+            top,
+            vec![Address::with_last_byte(2), Address::with_last_byte(1)]
+        );
+        assert_eq!(lite_targets(&txs, 10).len(), 3);
+    }
+
+    #[test]
+    fn prestate_keeps_first_seen_values() {
+        let json = r#"[
+            {"txHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+             "result": {"0x0000000000000000000000000000000000000001": {
+                "balance": "0x10", "nonce": 2, "code": "0x6000",
+                "storage": {"0x0000000000000000000000000000000000000000000000000000000000000000":
+                            "0x0000000000000000000000000000000000000000000000000000000000000005"}}}},
+            {"txHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+             "result": {"0x0000000000000000000000000000000000000001": {
+                "balance": "0x99", "nonce": 3,
+                "storage": {"0x0000000000000000000000000000000000000000000000000000000000000000":
+                            "0x0000000000000000000000000000000000000000000000000000000000000007",
+                            "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000009"}}}},
+            {"txHash": "0x0000000000000000000000000000000000000000000000000000000000000003",
+             "error": "execution timeout"}
+        ]"#;
+        let traces: Vec<TxTrace<Prestate>> = serde_json::from_str(json).unwrap();
+        let db = prestate_db(&traces, &CodeCache::new());
+
+        let account = &db.cache.accounts[&Address::with_last_byte(1)];
+        assert_eq!(account.info.balance, U256::from(0x10));
+        assert_eq!(account.info.nonce, 2);
+        assert_eq!(account.storage[&U256::from(0)], U256::from(5));
+        assert_eq!(account.storage[&U256::from(1)], U256::from(9));
+    }
+
+    #[test]
+    fn batches_respect_call_budget() {
+        let a = Address::with_last_byte(1);
+        let jobs = vec![
+            Job::Account(a),
+            Job::Storage(a, U256::from(0)),
+            Job::Storage(a, U256::from(1)),
+            Job::Account(Address::with_last_byte(2)),
+            Job::Storage(a, U256::from(2)),
+        ];
+        let sizes: Vec<usize> = batches(jobs.clone(), 5)
+            .iter()
+            .map(|batch| batch.iter().map(|job| job.calls()).sum())
+            .collect();
+        assert_eq!(sizes, vec![5, 4]);
+        // An account never splits, even below its own cost.
+        assert_eq!(batches(jobs, 2).len(), 5);
+    }
+
+    fn offline_prefetcher() -> Prefetcher {
+        crate::FailoverProvider::connect_http(&["http://127.0.0.1:9"])
+            .unwrap()
+            .prefetcher()
+    }
+
+    #[tokio::test]
+    async fn fallback_refuses_current_thread_runtime() {
+        let warm = WarmCacheDB::new(EmptyDB::new());
+        assert!(offline_prefetcher().fallback_db(warm, 1u64).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fallback_answers_warm_state_locally() {
+        use revm::database_interface::DatabaseRef;
+
+        let addr = Address::with_last_byte(1);
+        let mut warm = WarmCacheDB::new(EmptyDB::new());
+        warm.insert_account_storage(addr, U256::from(1), U256::from(7))
+            .unwrap();
+        let db = offline_prefetcher().fallback_db(warm, 1u64).unwrap();
+        // Nothing listens on the endpoint, so this only passes if the warm
+        // slot never reaches it.
+        assert_eq!(db.storage_ref(addr, U256::from(1)).unwrap(), U256::from(7));
+    }
 }
@@ -0,0 +1,29 @@
+//! Progress callbacks for prefetch and simulation.
+//!
+//! Register a [`ProgressHandler`] with [`Prefetcher::with_progress`]
+//! (and the simulator's `ProgressObserver` in `argus-analyzer`) to drive
+//! progress bars or dashboards without parsing tracing output. To stop
+//! early, fire the `CancellationToken` shared with the prefetcher or
+//! pipeline from inside a callback.
+//!
+//! [`Prefetcher::with_progress`]: crate::Prefetcher::with_progress
+
+/// Receives progress events. Every method defaults to doing nothing.
+///
+/// Callbacks run on the fetching task or simulation worker thread that
+/// produced the event, so they should return quickly.
+pub trait ProgressHandler: Send + Sync {
+    /// A prefetch is about to issue `total` reads (accounts and slots).
+    /// Discovery prefetch calls this once per round; prestate prefetch
+    /// counts its single trace call as one read.
+    fn on_fetch_start(&self, _block: &str, _total: usize) {}
+
+    /// `done` of the `total` reads announced by
+    /// [`on_fetch_start`](Self::on_fetch_start) have finished, fetched or
+    /// failed.
+    fn on_fetch_done(&self, _done: usize, _total: usize) {}
+
+    /// The transaction at block position `index` finished simulating;
+    /// `done` of `total` have so far. Calls arrive in completion order.
+    fn on_tx_simulated(&self, _index: usize, _done: usize, _total: usize) {}
+}
@@ -0,0 +1,286 @@
+//! `eth_getProof` fetches verified against the block's state root
+//! ([`PrefetchStrategy::Proof`](crate::PrefetchStrategy::Proof)).
+//!
+//! The account proof ties balance, nonce, code hash and storage root to the
+//! header's `stateRoot`; each storage proof ties a slot value to that
+//! storage root; the code returned by `eth_getCode` must hash to the proven
+//! code hash. Absent accounts and zero slots are proven by exclusion.
+
+use crate::budget::{Priority, RpcBudget};
+use crate::failover::Endpoints;
+use crate::retry::RetryPolicy;
+use alloy_eips::BlockId;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use alloy_trie::proof::verify_proof;
+use alloy_trie::{Nibbles, TrieAccount, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// One account with its slots, proven against a state root.
+#[derive(Debug)]
+pub(crate) struct ProvenAccount {
+    pub address: Address,
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+    pub storage: Vec<(U256, U256)>,
+}
+
+/// Why a fetch failed: the node couldn't answer, or answered with state
+/// that doesn't match the root.
+#[derive(Debug)]
+pub(crate) enum ProofError {
+    Fetch(String),
+    Invalid(String),
+}
+
+/// `stateRoot` of the header `block_id` refers to.
+pub(crate) async fn state_root(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    policy: &RetryPolicy,
+    block_id: BlockId,
+) -> Result<B256, String> {
+    retry(endpoints, budget, policy, 1, |p| async move {
+        match p.get_block(block_id).await {
+            Ok(Some(block)) => Ok(block.header.state_root),
+            Ok(None) => Err(ProofError::Fetch("block not found".into())),
+            Err(e) => Err(ProofError::Fetch(e.to_string())),
+        }
+    })
+    .await
+    .map_err(|e| match e {
+        ProofError::Fetch(e) | ProofError::Invalid(e) => e,
+    })
+}
+
+/// Fetch `address` and `slots` with `eth_getProof` and `eth_getCode` and
+/// verify them against `root`.
+pub(crate) async fn fetch_proven_account(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    policy: &RetryPolicy,
+    address: Address,
+    slots: Vec<U256>,
+    block_id: BlockId,
+    root: B256,
+) -> Result<ProvenAccount, ProofError> {
+    let keys: Vec<B256> = slots.iter().map(|slot| B256::from(*slot)).collect();
+    let (proof, code) = retry(endpoints, budget, policy, 2, |p| {
+        let keys = keys.clone();
+        async move {
+            let proof = p.get_proof(address, keys).block_id(block_id);
+            let code = p.get_code_at(address).block_id(block_id);
+            let (proof, code) = tokio::join!(proof, code);
+            let proof = proof.map_err(|e| ProofError::Fetch(e.to_string()))?;
+            let code = code.map_err(|e| ProofError::Fetch(e.to_string()))?;
+            Ok((proof, code))
+        }
+    })
+    .await?;
+    verify_account(root, address, &proof, &slots, &code).map_err(ProofError::Invalid)?;
+    Ok(ProvenAccount {
+        address,
+        balance: proof.balance,
+        nonce: proof.nonce,
+        code,
+        storage: slots
+            .into_iter()
+            .zip(proof.storage_proof.iter().map(|p| p.value))
+            .collect(),
+    })
+}
+
+/// Check `proof` (for `address` and `slots`, in request order) and `code`
+/// against `root`.
+pub(crate) fn verify_account(
+    root: B256,
+    address: Address,
+    proof: &EIP1186AccountProofResponse,
+    slots: &[U256],
+    code: &Bytes,
+) -> Result<(), String> {
+    if proof.address != address {
+        return Err(format!(
+            "requested a proof of {address}, node returned one of {}",
+            proof.address
+        ));
+    }
+    let code_hash = non_zero(proof.code_hash, KECCAK_EMPTY);
+    let storage_root = non_zero(proof.storage_hash, EMPTY_ROOT_HASH);
+    let account = TrieAccount {
+        nonce: proof.nonce,
+        balance: proof.balance,
+        storage_root,
+        code_hash,
+    };
+    // Empty accounts are not in the trie (EIP-161).
+    let empty = account.nonce == 0
+        && account.balance.is_zero()
+        && code_hash == KECCAK_EMPTY
+        && storage_root == EMPTY_ROOT_HASH;
+    let expected = (!empty).then(|| alloy_rlp::encode(account));
+    verify_proof(
+        root,
+        Nibbles::unpack(keccak256(address)),
+        expected,
+        &proof.account_proof,
+    )
+    .map_err(|e| format!("account {address} does not match state root {root}: {e}"))?;
+
+    if keccak256(code) != code_hash {
+        return Err(format!(
+            "code of {address} does not match its proven code hash {code_hash}"
+        ));
+    }
+
+    if proof.storage_proof.len() != slots.len() {
+        return Err(format!(
+            "{} storage proofs for {} requested slots of {address}",
+            proof.storage_proof.len(),
+            slots.len()
+        ));
+    }
+    for (slot, slot_proof) in slots.iter().zip(&proof.storage_proof) {
+        let value = slot_proof.value;
+        let expected = (!value.is_zero()).then(|| alloy_rlp::encode(value));
+        verify_proof(
+            storage_root,
+            Nibbles::unpack(keccak256(B256::from(*slot))),
+            expected,
+            &slot_proof.proof,
+        )
+        .map_err(|e| {
+            format!("slot {slot} of {address} does not match storage root {storage_root}: {e}")
+        })?;
+    }
+    Ok(())
+}
+
+/// Some nodes report a zero hash for absent accounts' code and storage.
+fn non_zero(hash: B256, empty: B256) -> B256 {
+    if hash.is_zero() {
+        empty
+    } else {
+        hash
+    }
+}
+
+/// Run `call` on the active endpoint, retrying per `policy` with failover
+/// and charging `cost` requests per attempt. Verification errors are never
+/// retried.
+async fn retry<T, F, Fut>(
+    endpoints: &Endpoints,
+    budget: &Arc<RpcBudget>,
+    policy: &RetryPolicy,
+    cost: u32,
+    call: F,
+) -> Result<T, ProofError>
+where
+    F: Fn(alloy_provider::DynProvider) -> Fut,
+    Fut: std::future::Future<Output = Result<T, ProofError>>,
+{
+    let started = Instant::now();
+    for attempt in 0..policy.max_attempts() {
+        policy.backoff(attempt).await;
+
+        let _permit = budget
+            .acquire_batch(Priority::Normal, cost)
+            .await
+            .map_err(|e| ProofError::Fetch(e.to_string()))?;
+        let (idx, p) = endpoints.current();
+        match call(p.clone()).await {
+            Ok(value) => {
+                endpoints.record_success(idx);
+                return Ok(value);
+            }
+            Err(ProofError::Fetch(e)) => {
+                if policy.on_failure(endpoints, idx, attempt, started, &e) {
+                    continue;
+                }
+                return Err(ProofError::Fetch(e));
+            }
+            Err(invalid) => return Err(invalid),
+        }
+    }
+    Err(ProofError::Fetch("max retries exceeded".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: Address = Address::with_last_byte(1);
+
+    /// An account proof against the empty trie: every key is absent.
+    fn proof(balance: u64, slot_value: u64) -> EIP1186AccountProofResponse {
+        serde_json::from_value(serde_json::json!({
+            "address": ADDRESS,
+            "balance": U256::from(balance),
+            "codeHash": KECCAK_EMPTY,
+            "nonce": "0x0",
+            "storageHash": EMPTY_ROOT_HASH,
+            "accountProof": [],
+            "storageProof": [{
+                "key": B256::ZERO,
+                "value": U256::from(slot_value),
+                "proof": []
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn absent_account_and_slot_verify() {
+        let slots = [U256::ZERO];
+        verify_account(
+            EMPTY_ROOT_HASH,
+            ADDRESS,
+            &proof(0, 0),
+            &slots,
+            &Bytes::new(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn forged_values_are_rejected() {
+        let slots = [U256::ZERO];
+        let err = verify_account(
+            EMPTY_ROOT_HASH,
+            ADDRESS,
+            &proof(5, 0),
+            &slots,
+            &Bytes::new(),
+        );
+        assert!(err.unwrap_err().contains("does not match state root"));
+        let err = verify_account(
+            EMPTY_ROOT_HASH,
+            ADDRESS,
+            &proof(0, 7),
+            &slots,
+            &Bytes::new(),
+        );
+        assert!(err.unwrap_err().contains("does not match storage root"));
+        let err = verify_account(
+            EMPTY_ROOT_HASH,
+            ADDRESS,
+            &proof(0, 0),
+            &slots,
+            &Bytes::from_static(&[0x00]),
+        );
+        assert!(err.unwrap_err().contains("code hash"));
+        let err = verify_account(EMPTY_ROOT_HASH, ADDRESS, &proof(0, 0), &[], &Bytes::new());
+        assert!(err.unwrap_err().contains("storage proofs"));
+    }
+
+    #[test]
+    fn proof_of_another_account_is_rejected() {
+        let slots = [U256::ZERO];
+        let other = Address::with_last_byte(2);
+        let err = verify_account(EMPTY_ROOT_HASH, other, &proof(0, 0), &slots, &Bytes::new());
+        assert!(err.unwrap_err().contains("node returned one of"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Retry policy for prefetch requests.
+//!
+//! Every prefetch fetch (accounts, slots, batches, traces, proofs) retries
+//! through one [`RetryPolicy`]: exponential backoff from a base delay,
+//! capped per attempt and optionally jittered, bounded by an attempt count
+//! and a total time budget. By default rate-limited (HTTP 429) and
+//! unreachable endpoints are retried, the latter only when failover has
+//! another endpoint to move to; [`RetryPolicy::retry_if`] adds errors of
+//! your own, such as a lagging node's "header not found".
+//!
+//! ```ignore
+//! let retry = RetryPolicy::default()
+//!     .with_max_attempts(8)
+//!     .with_base_delay(Duration::from_millis(500))
+//!     .with_jitter(0.2)
+//!     .with_max_elapsed(Duration::from_secs(60))
+//!     .retry_if(|error| error.contains("header not found"));
+//! let prefetcher = Prefetcher::new(provider).with_retry_policy(retry);
+//! ```
+
+use crate::failover::{Endpoints, Failure};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Extra condition under which a failed request is retried, given its
+/// error message.
+pub type RetryPredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// How prefetch requests are retried. See the [module docs](self).
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    max_elapsed: Option<Duration>,
+    rate_limited: bool,
+    unreachable: bool,
+    predicates: Vec<RetryPredicate>,
+}
+
+impl Default for RetryPolicy {
+    /// Four attempts, 200ms doubling to at most 10s, no jitter, no time limit.
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.0,
+            max_elapsed: None,
+            rate_limited: true,
+            unreachable: true,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("max_elapsed", &self.max_elapsed)
+            .field("rate_limited", &self.rate_limited)
+            .field("unreachable", &self.unreachable)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Give up after the first failure.
+    pub fn never() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Tries per request, the first included (at least 1).
+    pub fn with_max_attempts(mut self, n: u32) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+
+    /// Delay before the first retry; each further retry doubles it.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Cap on a single delay.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Spread each delay randomly by up to this fraction either way (0.0
+    /// to 1.0), so concurrent tasks don't retry in lockstep.
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Stop retrying once this long has passed since the first attempt.
+    pub fn with_max_elapsed(mut self, limit: Duration) -> Self {
+        self.max_elapsed = Some(limit);
+        self
+    }
+
+    /// Retry HTTP 429 responses (default: yes).
+    pub fn retry_rate_limited(mut self, enabled: bool) -> Self {
+        self.rate_limited = enabled;
+        self
+    }
+
+    /// Retry connection errors, timeouts and gateway errors on the next
+    /// endpoint (default: yes).
+    pub fn retry_unreachable(mut self, enabled: bool) -> Self {
+        self.unreachable = enabled;
+        self
+    }
+
+    /// Also retry errors whose message matches `predicate`, on any
+    /// endpoint count.
+    pub fn retry_if(mut self, predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Delay before retry `retry` (1 for the first), before jitter.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Sleep before attempt `attempt` (0-based; no-op for the first).
+    pub(crate) async fn backoff(&self, attempt: u32) {
+        if attempt == 0 {
+            return;
+        }
+        let delay = self.delay(attempt);
+        let delay = if self.jitter > 0.0 {
+            delay.mul_f64(1.0 + self.jitter * (2.0 * unit_random() - 1.0))
+        } else {
+            delay
+        };
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Whether attempt `attempt` (0-based), which failed with `error` of
+    /// kind `failure`, gets another try. `failover` is what endpoint
+    /// rotation suggests for the built-in kinds.
+    pub(crate) fn should_retry(
+        &self,
+        attempt: u32,
+        started: Instant,
+        error: &str,
+        failure: Failure,
+        failover: bool,
+    ) -> bool {
+        if attempt + 1 >= self.max_attempts {
+            return false;
+        }
+        if self
+            .max_elapsed
+            .is_some_and(|limit| started.elapsed() + self.delay(attempt + 1) > limit)
+        {
+            return false;
+        }
+        if self.predicates.iter().any(|predicate| predicate(error)) {
+            return true;
+        }
+        failover
+            && match failure {
+                Failure::RateLimited => self.rate_limited,
+                Failure::Unreachable => self.unreachable,
+                Failure::Other => false,
+            }
+    }
+
+    /// Record `error` on endpoint `idx` (rotating when warranted) and
+    /// decide whether to retry.
+    pub(crate) fn on_failure(
+        &self,
+        endpoints: &Endpoints,
+        idx: usize,
+        attempt: u32,
+        started: Instant,
+        error: &str,
+    ) -> bool {
+        let failure = Failure::classify(error);
+        let failover = endpoints.record_failure(idx, failure);
+        let retry = self.should_retry(attempt, started, error, failure, failover);
+        if retry {
+            endpoints.record_retry(idx);
+        }
+        retry
+    }
+}
+
+/// Uniform in `[0, 1)`, from the std hasher's random keys.
+fn unit_random() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    hasher.write_u128(now.map_or(0, |d| d.as_nanos()));
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_double_up_to_the_cap() {
+        let policy = RetryPolicy::default().with_max_delay(Duration::from_millis(700));
+        let delays: Vec<u128> = (1..=4).map(|r| policy.delay(r).as_millis()).collect();
+        assert_eq!(delays, [200, 400, 700, 700]);
+        assert_eq!(policy.delay(200), Duration::from_millis(700));
+    }
+
+    #[test]
+    fn retries_by_kind_attempts_and_predicates() {
+        let now = Instant::now();
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(0, now, "429", Failure::RateLimited, true));
+        assert!(!policy.should_retry(3, now, "429", Failure::RateLimited, true));
+        // Failover has nowhere to go.
+        assert!(!policy.should_retry(0, now, "refused", Failure::Unreachable, false));
+        assert!(!policy.should_retry(0, now, "header not found", Failure::Other, false));
+
+        let policy = RetryPolicy::default()
+            .retry_rate_limited(false)
+            .retry_if(|e| e.contains("header not found"));
+        assert!(!policy.should_retry(0, now, "429", Failure::RateLimited, true));
+        assert!(policy.should_retry(0, now, "header not found", Failure::Other, false));
+
+        assert!(!RetryPolicy::never().should_retry(0, now, "429", Failure::RateLimited, true));
+        let policy = RetryPolicy::default().with_max_elapsed(Duration::from_millis(100));
+        assert!(!policy.should_retry(0, now, "429", Failure::RateLimited, true));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        for _ in 0..100 {
+            let r = unit_random();
+            assert!((0.0..1.0).contains(&r));
+        }
+    }
+}
@@ -1,82 +1,725 @@
 //! JSON-RPC provider backed by alloy-rs.
 
-use crate::DataProvider;
+use crate::budget::{Priority, RpcBudget};
+use crate::metrics::{MetricsLayer, MetricsSnapshot, ProviderMetrics};
+use crate::{describe_block, trace, BlockRef, DataProvider};
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{Address, Bytes, B256, U128, U256, U64, U8};
 use alloy_provider::{DynProvider, Provider, ProviderBuilder};
-use argus_core::error::{ArgusError, ArgusResult};
-use argus_core::Transaction;
+use alloy_rpc_client::ClientBuilder;
+use argus_core::chain::{self, ChainFamily};
+use argus_core::error::{ArgusError, ArgusResult, ResultExt};
+use argus_core::{AccessList, Authorization, BlockContext, DeclaredAccess, Transaction, TxReceipt};
 use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 /// Fetches blockchain data from an Ethereum JSON-RPC endpoint.
 ///
 /// ```ignore
 /// let provider = RpcProvider::connect("https://mainnet.infura.io/v3/KEY").await?;
+/// // Co-located node, with the `ipc` feature:
+/// let provider = RpcProvider::connect("/var/lib/geth/geth.ipc").await?;
 /// ```
 pub struct RpcProvider {
     provider: DynProvider,
     rpc_url: String,
+    budget: Arc<RpcBudget>,
+    /// Fetched once; selects how blocks are decoded.
+    chain_id: OnceCell<u64>,
+    /// Fed by a transport layer, so it also counts prefetch traffic.
+    metrics: Arc<ProviderMetrics>,
 }
 
 impl RpcProvider {
+    /// Connects to an HTTP(S) or WebSocket URL, or a local IPC socket: a
+    /// path ending in `.ipc` or prefixed with `ipc://`.
     pub async fn connect(rpc_url: &str) -> ArgusResult<Self> {
         if rpc_url.is_empty() {
             return Err(ArgusError::InvalidInput("RPC URL must not be empty".into()));
         }
 
-        let provider = ProviderBuilder::new()
-            .connect(rpc_url)
-            .await
-            .map_err(|e| ArgusError::Provider(format!("Failed to connect to {rpc_url}: {e}")))?;
+        let metrics = ProviderMetrics::new();
+        let layer = MetricsLayer(metrics.clone());
+        let provider = match ipc_path(rpc_url) {
+            Some(path) => connect_ipc(path, layer).await?,
+            None => {
+                let client = ClientBuilder::default()
+                    .layer(layer)
+                    .connect(rpc_url)
+                    .await
+                    .map_err(|e| {
+                        ArgusError::Provider(format!("Failed to connect to {rpc_url}: {e}"))
+                    })?;
+                ProviderBuilder::new().connect_client(client).erased()
+            }
+        };
 
         tracing::info!(rpc_url, "connected");
 
         Ok(Self {
-            provider: provider.erased(),
+            provider,
             rpc_url: rpc_url.to_string(),
+            budget: RpcBudget::unlimited(),
+            chain_id: OnceCell::new(),
+            metrics,
         })
     }
 
+    /// Synchronously builds an HTTP(S) provider; no request is made until
+    /// the first fetch.
+    pub fn connect_http(rpc_url: &str) -> ArgusResult<Self> {
+        if rpc_url.is_empty() {
+            return Err(ArgusError::InvalidInput("RPC URL must not be empty".into()));
+        }
+
+        let url: url::Url = rpc_url
+            .parse()
+            .map_err(|e| ArgusError::InvalidInput(format!("Invalid RPC URL {rpc_url}: {e}")))?;
+
+        let metrics = ProviderMetrics::new();
+        let client = ClientBuilder::default()
+            .layer(MetricsLayer(metrics.clone()))
+            .http(url);
+        Ok(Self {
+            provider: ProviderBuilder::new().connect_client(client).erased(),
+            rpc_url: rpc_url.to_string(),
+            budget: RpcBudget::unlimited(),
+            chain_id: OnceCell::new(),
+            metrics,
+        })
+    }
+
+    /// Draw every request from a shared budget. Block and header fetches
+    /// run at [`Priority::High`], pending-block polls at [`Priority::Low`].
+    pub fn with_budget(mut self, budget: Arc<RpcBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// The budget this provider draws from (shared with a [`Prefetcher`](crate::Prefetcher)).
+    pub fn budget(&self) -> Arc<RpcBudget> {
+        self.budget.clone()
+    }
+
+    /// Request counters for this endpoint, shared with every clone of
+    /// [`provider`](Self::provider).
+    pub fn metrics(&self) -> Arc<ProviderMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Requests by method, errors, 429s, bytes and latency so far.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Returns a handle to the underlying `DynProvider` (cheap `Arc` clone).
+    pub fn provider(&self) -> DynProvider {
+        self.provider.clone()
+    }
+
     /// Returns the underlying `DynProvider` for use with `AlloyDB`.
     pub fn into_provider(self) -> DynProvider {
         self.provider
     }
+
+    /// Whether the endpoint's chain has transaction types beyond Ethereum's
+    /// (OP Stack deposits, Arbitrum system and retryable transactions).
+    async fn has_custom_tx_types(&self) -> ArgusResult<bool> {
+        let chain_id = self.chain_id().await?;
+        Ok(chain::lookup(chain_id).is_some_and(|c| c.family != ChainFamily::Ethereum))
+    }
+
+    /// Full block with transactions decoded field by field rather than as
+    /// typed envelopes, so L2 transaction types parse.
+    async fn get_loose_block(&self, block: BlockId) -> ArgusResult<Option<LooseBlock>> {
+        let label = describe_block(block);
+        let method = get_block_method(block);
+        let request = match block {
+            BlockId::Hash(hash) => {
+                self.provider
+                    .raw_request(method.into(), (hash.block_hash, true))
+                    .await
+            }
+            BlockId::Number(number) => {
+                self.provider
+                    .raw_request(method.into(), (number, true))
+                    .await
+            }
+        };
+        request
+            .map_err(|e| ArgusError::Provider(format!("Failed to fetch block {label}: {e}")))
+            .rpc(method)
+    }
+
+    async fn get_header(&self, block: BlockId) -> ArgusResult<alloy_rpc_types::Header> {
+        let _permit = self.budget.acquire(Priority::High).await?;
+        let label = describe_block(block);
+        let block = self
+            .provider
+            .get_block(block)
+            .await
+            .map_err(|e| ArgusError::Provider(format!("Failed to fetch block {label}: {e}")))
+            .rpc(get_block_method(block))?
+            .ok_or_else(|| ArgusError::Provider(format!("Block {label} not found")))?;
+        Ok(block.header)
+    }
+}
+
+/// Heads buffered between the subscription and a slow consumer.
+#[cfg(feature = "ws")]
+const HEAD_BUFFER: usize = 64;
+
+#[cfg(feature = "ws")]
+impl RpcProvider {
+    /// Subscribes to `eth_subscribe("newHeads")`, yielding each block as the
+    /// node announces it. Requires a `ws://` or `wss://` endpoint opened with
+    /// [`connect`](Self::connect).
+    ///
+    /// Heads are forwarded as-is, so a reorg shows up as a repeated or lower
+    /// number with a new hash. The channel closes when the socket does;
+    /// dropping the receiver stops the forwarding task.
+    pub async fn subscribe_heads(&self) -> ArgusResult<tokio::sync::mpsc::Receiver<BlockRef>> {
+        use tokio::sync::broadcast::error::RecvError;
+
+        if !is_ws_url(&self.rpc_url) {
+            return Err(ArgusError::InvalidInput(format!(
+                "newHeads needs a ws:// or wss:// endpoint, got {}",
+                self.rpc_url
+            )));
+        }
+
+        let mut sub = self
+            .provider
+            .subscribe_blocks()
+            .await
+            .map_err(|e| ArgusError::Provider(format!("Failed to subscribe to newHeads: {e}")))
+            .rpc("eth_subscribe")?;
+        tracing::info!(rpc_url = %self.rpc_url, "subscribed to newHeads");
+
+        let (tx, rx) = tokio::sync::mpsc::channel(HEAD_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                match sub.recv().await {
+                    Ok(header) => {
+                        let head = BlockRef {
+                            number: header.number,
+                            hash: header.hash,
+                        };
+                        if tx.send(head).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "newHeads subscription lagged");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+#[cfg(feature = "ws")]
+fn is_ws_url(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
 }
 
 #[async_trait]
 impl DataProvider for RpcProvider {
-    async fn get_block_transactions(&self, block_number: u64) -> ArgusResult<Vec<Transaction>> {
-        use alloy_consensus::transaction::Transaction as TxTrait;
+    async fn get_block_transactions(&self, block: BlockId) -> ArgusResult<Vec<Transaction>> {
+        let label = describe_block(block);
+        tracing::debug!(block = %label, rpc_url = %self.rpc_url, "fetching block");
+        let custom_types = self.has_custom_tx_types().await?;
+        let _permit = self.budget.acquire(Priority::High).await?;
 
-        tracing::debug!(block_number, rpc_url = %self.rpc_url, "fetching block");
+        if custom_types {
+            let block = self
+                .get_loose_block(block)
+                .await?
+                .ok_or_else(|| ArgusError::Provider(format!("Block {label} not found")))?;
+            let transactions = block.into_transactions();
+            tracing::info!(block = %label, txs = transactions.len(), "fetched block");
+            return Ok(transactions);
+        }
 
+        let method = get_block_method(block);
         let block = self
             .provider
-            .get_block_by_number(block_number.into())
+            .get_block(block)
             .full()
             .await
-            .map_err(|e| {
-                ArgusError::Provider(format!("Failed to fetch block {block_number}: {e}"))
-            })?
-            .ok_or_else(|| ArgusError::Provider(format!("Block {block_number} not found")))?;
+            .map_err(|e| ArgusError::Provider(format!("Failed to fetch block {label}: {e}")))
+            .rpc(method)?
+            .ok_or_else(|| ArgusError::Provider(format!("Block {label} not found")))?;
+        let block_number = block.header.number;
 
         let transactions: Vec<Transaction> = block
             .transactions
             .into_transactions()
-            .map(|tx| Transaction {
-                hash: *tx.inner.tx_hash(),
-                from: tx.inner.signer(),
-                to: tx.to(),
-                input: tx.input().clone(),
-                value: tx.value(),
-                gas: tx.gas_limit(),
-            })
+            .map(to_argus_tx)
             .collect();
 
         tracing::info!(block_number, txs = transactions.len(), "fetched block");
         Ok(transactions)
     }
 
+    /// Fetches the node's `pending` block view of the mempool.
     async fn get_pending_transactions(&self) -> ArgusResult<Vec<Transaction>> {
-        tracing::warn!("get_pending_transactions not implemented");
-        Ok(Vec::new())
+        tracing::debug!(rpc_url = %self.rpc_url, "fetching pending block");
+        let custom_types = self.has_custom_tx_types().await?;
+        let _permit = self.budget.acquire(Priority::Low).await?;
+
+        if custom_types {
+            let pending = BlockId::Number(BlockNumberOrTag::Pending);
+            return Ok(self
+                .get_loose_block(pending)
+                .await?
+                .map(LooseBlock::into_transactions)
+                .unwrap_or_default());
+        }
+
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Pending)
+            .full()
+            .await
+            .map_err(|e| ArgusError::Provider(format!("Failed to fetch pending block: {e}")))
+            .rpc("eth_getBlockByNumber")?;
+
+        let Some(block) = block else {
+            return Ok(Vec::new());
+        };
+
+        Ok(block
+            .transactions
+            .into_transactions()
+            .map(to_argus_tx)
+            .collect())
+    }
+
+    async fn chain_id(&self) -> ArgusResult<u64> {
+        self.chain_id
+            .get_or_try_init(|| async {
+                let _permit = self.budget.acquire(Priority::High).await?;
+                self.provider
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| ArgusError::Provider(format!("Failed to fetch chain id: {e}")))
+                    .rpc("eth_chainId")
+            })
+            .await
+            .copied()
+    }
+
+    async fn get_block_hash(&self, block: BlockId) -> ArgusResult<B256> {
+        Ok(self.get_header(block).await?.hash)
+    }
+
+    async fn get_block_timestamp(&self, block: BlockId) -> ArgusResult<u64> {
+        Ok(self.get_header(block).await?.timestamp)
+    }
+
+    async fn get_block_context(&self, block: BlockId) -> ArgusResult<BlockContext> {
+        Ok(block_context(&self.get_header(block).await?))
+    }
+
+    async fn resolve_block(&self, block: BlockId) -> ArgusResult<BlockRef> {
+        let header = self.get_header(block).await?;
+        Ok(BlockRef {
+            number: header.number,
+            hash: header.hash,
+        })
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> ArgusResult<Vec<TxReceipt>> {
+        let label = describe_block(block);
+        let _permit = self.budget.acquire(Priority::High).await?;
+        let receipts = self
+            .provider
+            .get_block_receipts(block)
+            .await
+            .map_err(|e| {
+                ArgusError::Provider(format!("Failed to fetch receipts for block {label}: {e}"))
+            })
+            .rpc("eth_getBlockReceipts")?
+            .ok_or_else(|| ArgusError::Provider(format!("Block {label} not found")))?;
+        Ok(receipts
+            .into_iter()
+            .map(|receipt| TxReceipt {
+                tx_hash: receipt.transaction_hash,
+                gas_used: receipt.gas_used,
+                success: receipt.status(),
+            })
+            .collect())
+    }
+
+    /// Two `prestateTracer` traces of the block (default and diff mode);
+    /// needs the debug API.
+    async fn trace_access_lists(&self, block: BlockId) -> ArgusResult<Vec<AccessList>> {
+        let label = describe_block(block);
+        let _permit = self.budget.acquire_batch(Priority::High, 2).await?;
+        let (touched, diffs) = tokio::join!(
+            trace::debug_trace_block(&self.provider, block, trace::PRESTATE),
+            trace::debug_trace_block(&self.provider, block, trace::PRESTATE_DIFF),
+        );
+        let method = match block {
+            BlockId::Hash(_) => "debug_traceBlockByHash",
+            BlockId::Number(_) => "debug_traceBlockByNumber",
+        };
+        let lists = touched
+            .and_then(|touched| trace::access_lists(touched, diffs?))
+            .map_err(|e| ArgusError::Provider(format!("Failed to trace block {label}: {e}")))
+            .rpc(method)?;
+
+        tracing::info!(block = %label, txs = lists.len(), "traced access lists");
+        Ok(lists)
+    }
+}
+
+/// The socket path if `rpc_url` names a local IPC endpoint.
+fn ipc_path(rpc_url: &str) -> Option<&str> {
+    if let Some(path) = rpc_url.strip_prefix("ipc://") {
+        return Some(path);
+    }
+    (rpc_url.ends_with(".ipc") && !rpc_url.contains("://")).then_some(rpc_url)
+}
+
+#[cfg(feature = "ipc")]
+async fn connect_ipc(path: &str, layer: MetricsLayer) -> ArgusResult<DynProvider> {
+    let ipc = alloy_rpc_client::IpcConnect::new(std::path::PathBuf::from(path));
+    let client = ClientBuilder::default()
+        .layer(layer)
+        .ipc(ipc)
+        .await
+        .map_err(|e| ArgusError::Provider(format!("Failed to connect to IPC {path}: {e}")))?;
+    Ok(ProviderBuilder::new().connect_client(client).erased())
+}
+
+#[cfg(not(feature = "ipc"))]
+async fn connect_ipc(path: &str, _layer: MetricsLayer) -> ArgusResult<DynProvider> {
+    Err(ArgusError::InvalidInput(format!(
+        "{path} is an IPC endpoint; rebuild with the `ipc` feature"
+    )))
+}
+
+/// Execution-visible fields of `header`. `mixHash` carries PREVRANDAO only
+/// once difficulty is zero (the merge); Arbitrum keeps a difficulty of 1.
+fn block_context(header: &alloy_rpc_types::Header) -> BlockContext {
+    BlockContext {
+        number: header.number,
+        timestamp: header.timestamp,
+        base_fee: header.base_fee_per_gas,
+        gas_limit: header.gas_limit,
+        coinbase: header.beneficiary,
+        prevrandao: header.difficulty.is_zero().then_some(header.mix_hash),
+        difficulty: header.difficulty,
+        excess_blob_gas: header.excess_blob_gas,
+    }
+}
+
+/// The JSON-RPC method alloy uses to fetch `block`.
+fn get_block_method(block: BlockId) -> &'static str {
+    match block {
+        BlockId::Hash(_) => "eth_getBlockByHash",
+        BlockId::Number(_) => "eth_getBlockByNumber",
+    }
+}
+
+/// Highest EIP-2718 type defined on Ethereum (EIP-7702).
+const MAX_ETHEREUM_TX_TYPE: u8 = 4;
+
+/// A block reduced to the transaction fields Argus reads, in any
+/// transaction type.
+#[derive(Deserialize)]
+struct LooseBlock {
+    transactions: Vec<LooseTransaction>,
+}
+
+/// Arbitrum internal transactions carry no gas, and some L2 types omit
+/// fields Ethereum transactions always have.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LooseTransaction {
+    hash: B256,
+    from: Address,
+    #[serde(default)]
+    to: Option<Address>,
+    #[serde(default)]
+    input: Bytes,
+    #[serde(default)]
+    value: U256,
+    #[serde(default)]
+    gas: U64,
+    #[serde(rename = "type")]
+    tx_type: U8,
+    #[serde(default)]
+    blob_versioned_hashes: Vec<B256>,
+    #[serde(default)]
+    max_fee_per_blob_gas: Option<U128>,
+    #[serde(default)]
+    gas_price: Option<U128>,
+    #[serde(default)]
+    max_fee_per_gas: Option<U128>,
+    #[serde(default)]
+    max_priority_fee_per_gas: Option<U128>,
+    #[serde(default)]
+    access_list: Vec<DeclaredAccess>,
+    #[serde(default)]
+    authorization_list: Vec<LooseAuthorization>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LooseAuthorization {
+    chain_id: U256,
+    address: Address,
+    nonce: U64,
+    y_parity: U8,
+    r: U256,
+    s: U256,
+}
+
+impl LooseBlock {
+    /// L2 transactions are kept: deposits and retryables execute like calls,
+    /// and protocol transactions (the L1 attributes deposit, ArbOS internal
+    /// transactions) are dropped downstream as system transactions.
+    fn into_transactions(self) -> Vec<Transaction> {
+        let l2_typed = self
+            .transactions
+            .iter()
+            .filter(|tx| tx.tx_type.to::<u8>() > MAX_ETHEREUM_TX_TYPE)
+            .count();
+        if l2_typed > 0 {
+            tracing::debug!(l2_typed, "decoded L2 transaction types");
+        }
+        self.transactions
+            .into_iter()
+            .map(|tx| Transaction {
+                hash: tx.hash,
+                from: tx.from,
+                to: tx.to,
+                input: tx.input,
+                value: tx.value,
+                gas: tx.gas.to(),
+                blob_hashes: tx.blob_versioned_hashes,
+                max_fee_per_blob_gas: tx.max_fee_per_blob_gas.map(|fee| fee.to()),
+                // Nodes also report the effective `gasPrice` on EIP-1559
+                // transactions; the fee cap is what execution sees.
+                gas_price: tx
+                    .gas_price
+                    .filter(|_| tx.max_fee_per_gas.is_none())
+                    .map(|fee| fee.to()),
+                max_fee_per_gas: tx.max_fee_per_gas.map(|fee| fee.to()),
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.to()),
+                access_list: tx.access_list,
+                authorization_list: tx
+                    .authorization_list
+                    .into_iter()
+                    .map(|auth| Authorization {
+                        chain_id: auth.chain_id,
+                        address: auth.address,
+                        nonce: auth.nonce.to(),
+                        y_parity: auth.y_parity.to(),
+                        r: auth.r,
+                        s: auth.s,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Maps an RPC transaction to the analyzer's lightweight representation.
+fn to_argus_tx(tx: alloy_rpc_types::Transaction) -> Transaction {
+    use alloy_consensus::transaction::Transaction as TxTrait;
+
+    Transaction {
+        hash: *tx.inner.tx_hash(),
+        from: tx.inner.signer(),
+        to: tx.to(),
+        input: tx.input().clone(),
+        value: tx.value(),
+        gas: tx.gas_limit(),
+        blob_hashes: tx.blob_versioned_hashes().unwrap_or_default().to_vec(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas(),
+        gas_price: tx.gas_price(),
+        max_fee_per_gas: tx.is_dynamic_fee().then(|| tx.max_fee_per_gas()),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas(),
+        access_list: declared_access(tx.access_list()),
+        authorization_list: authorizations(tx.authorization_list()),
+    }
+}
+
+/// Signed EIP-7702 authorizations in Argus's representation.
+pub(crate) fn authorizations(
+    list: Option<&[alloy_eips::eip7702::SignedAuthorization]>,
+) -> Vec<Authorization> {
+    list.unwrap_or_default()
+        .iter()
+        .map(|auth| Authorization {
+            chain_id: auth.chain_id,
+            address: auth.address,
+            nonce: auth.nonce,
+            y_parity: auth.y_parity(),
+            r: auth.r(),
+            s: auth.s(),
+        })
+        .collect()
+}
+
+/// A declared EIP-2930 access list in Argus's representation.
+pub(crate) fn declared_access(
+    list: Option<&alloy_eips::eip2930::AccessList>,
+) -> Vec<DeclaredAccess> {
+    list.map(|list| {
+        list.iter()
+            .map(|item| DeclaredAccess {
+                address: item.address,
+                storage_keys: item.storage_keys.clone(),
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_op_stack_deposits() {
+        let block: LooseBlock = serde_json::from_str(
+            r#"{"number": "0x1", "transactions": [
+                {"type": "0x7e", "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+                 "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+                 "to": "0x4200000000000000000000000000000000000015",
+                 "input": "0x098999be", "value": "0x0", "gas": "0xf4240",
+                 "mint": "0x0", "sourceHash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+                 "isSystemTx": false},
+                {"type": "0x2", "hash": "0x0303030303030303030303030303030303030303030303030303030303030303",
+                 "from": "0x0000000000000000000000000000000000000001", "to": null,
+                 "input": "0x", "value": "0x10", "gas": "0x5208", "nonce": "0x0",
+                 "accessList": [{"address": "0x0000000000000000000000000000000000000009",
+                                 "storageKeys": ["0x0505050505050505050505050505050505050505050505050505050505050505"]}]}
+            ]}"#,
+        )
+        .unwrap();
+        let txs = block.into_transactions();
+        assert_eq!(txs.len(), 2);
+        assert!(chain::BASE.is_system_account(&txs[0].from));
+        assert_eq!(txs[0].gas, 1_000_000);
+        assert_eq!(txs[1].to, None);
+        assert_eq!(txs[1].value, U256::from(16));
+        assert_eq!(txs[1].access_list[0].address, Address::with_last_byte(9));
+        assert_eq!(txs[1].access_list[0].storage_keys, [B256::repeat_byte(5)]);
+    }
+
+    #[test]
+    fn decodes_arbitrum_tx_types() {
+        let block: LooseBlock = serde_json::from_str(
+            r#"{"transactions": [
+                {"type": "0x6a", "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+                 "from": "0x00000000000000000000000000000000000a4b05",
+                 "to": "0x0000000000000000000000000000000000000064",
+                 "input": "0x6bf6a42d", "value": "0x0", "gas": "0x0"},
+                {"type": "0x69", "hash": "0x0303030303030303030303030303030303030303030303030303030303030303",
+                 "from": "0x0000000000000000000000000000000000000002",
+                 "to": "0x000000000000000000000000000000000000006e",
+                 "input": "0x", "value": "0x5", "gas": "0x186a0",
+                 "requestId": "0x0404040404040404040404040404040404040404040404040404040404040404",
+                 "l1BaseFee": "0x1", "depositValue": "0x5", "retryTo": null}
+            ]}"#,
+        )
+        .unwrap();
+        let txs = block.into_transactions();
+        assert_eq!(txs.len(), 2);
+        assert!(chain::ARBITRUM_ONE.is_system_account(&txs[0].from));
+        assert_eq!(txs[1].gas, 100_000);
+        assert_eq!(txs[1].value, U256::from(5));
+    }
+
+    #[test]
+    fn decodes_set_code_authorizations() {
+        let block: LooseBlock = serde_json::from_str(
+            r#"{"transactions": [
+                {"type": "0x4", "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+                 "from": "0x0000000000000000000000000000000000000001",
+                 "to": "0x0000000000000000000000000000000000000001",
+                 "input": "0x", "value": "0x0", "gas": "0x186a0",
+                 "authorizationList": [{"chainId": "0x1",
+                                        "address": "0x0000000000000000000000000000000000000007",
+                                        "nonce": "0x3", "yParity": "0x1",
+                                        "r": "0x2", "s": "0x3"}]}
+            ]}"#,
+        )
+        .unwrap();
+        let txs = block.into_transactions();
+        let auth = &txs[0].authorization_list[0];
+        assert_eq!(auth.chain_id, U256::from(1));
+        assert_eq!(auth.address, Address::with_last_byte(7));
+        assert_eq!((auth.nonce, auth.y_parity), (3, 1));
+        assert_eq!((auth.r, auth.s), (U256::from(2), U256::from(3)));
+    }
+
+    #[test]
+    fn prevrandao_only_after_the_merge() {
+        let header = |difficulty: u64| alloy_rpc_types::Header {
+            hash: B256::ZERO,
+            inner: alloy_consensus::Header {
+                number: 7,
+                timestamp: 84,
+                gas_limit: 30_000_000,
+                base_fee_per_gas: Some(9),
+                beneficiary: Address::with_last_byte(1),
+                mix_hash: B256::repeat_byte(2),
+                difficulty: U256::from(difficulty),
+                ..Default::default()
+            },
+            total_difficulty: None,
+            size: None,
+        };
+        let merged = block_context(&header(0));
+        assert_eq!(merged.prevrandao, Some(B256::repeat_byte(2)));
+        assert_eq!((merged.number, merged.timestamp), (7, 84));
+        assert_eq!(merged.base_fee, Some(9));
+        assert_eq!(merged.coinbase, Address::with_last_byte(1));
+        assert_eq!(block_context(&header(1)).prevrandao, None);
+    }
+
+    #[test]
+    fn recognizes_ipc_paths() {
+        assert_eq!(
+            ipc_path("/var/lib/geth/geth.ipc"),
+            Some("/var/lib/geth/geth.ipc")
+        );
+        assert_eq!(ipc_path("ipc:///tmp/reth.sock"), Some("/tmp/reth.sock"));
+        assert_eq!(ipc_path("https://node.example/rpc.ipc"), None);
+        assert_eq!(ipc_path("http://127.0.0.1:8545"), None);
+    }
+
+    #[cfg(not(feature = "ipc"))]
+    #[tokio::test]
+    async fn ipc_needs_feature() {
+        let err = RpcProvider::connect("/tmp/geth.ipc").await.err().unwrap();
+        assert!(matches!(err, ArgusError::InvalidInput(_)));
+    }
+
+    #[cfg(feature = "ws")]
+    #[test]
+    fn only_websocket_urls_subscribe() {
+        assert!(is_ws_url("wss://eth.example/ws"));
+        assert!(is_ws_url("ws://127.0.0.1:8546"));
+        assert!(!is_ws_url("https://eth.example"));
+    }
+
+    #[cfg(feature = "ws")]
+    #[tokio::test]
+    async fn http_endpoint_cannot_subscribe() {
+        let provider = RpcProvider::connect_http("http://127.0.0.1:8545").unwrap();
+        let err = provider.subscribe_heads().await.unwrap_err();
+        assert!(matches!(err, ArgusError::InvalidInput(_)));
     }
 }
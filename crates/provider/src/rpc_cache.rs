@@ -0,0 +1,229 @@
+//! On-disk cache of RPC responses.
+//!
+//! Historical blocks never change, so a block's transactions, header fields
+//! and prefetched state are kept across runs and re-analyzing the block
+//! reads them from disk instead of the node. Entries are JSON files:
+//!
+//! ```text
+//! <dir>/<chain_id>/<kind>/<block>.json   transactions, receipts, hash, ...
+//! <dir>/<chain_id>/state/<block>.json    accounts and (address, slot) values
+//! ```
+//!
+//! `<block>` is the number or hash that was asked for. Tags such as `latest`
+//! move and are never cached. A number names whichever block is canonical
+//! when it is first fetched, so a block cached within reorg depth of the
+//! head can outlive a reorg; analyze recent blocks by hash when that matters.
+
+use crate::code::CodeCache;
+use crate::{BlockId, BlockNumberOrTag, WarmCacheDB};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use argus_core::error::ArgusResult;
+use argus_core::json_file;
+use revm::state::AccountInfo;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::sync::OnceCell;
+
+/// Entry kind for prefetched state.
+pub(crate) const STATE: &str = "state";
+
+/// Account fields as fetched by the prefetcher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+}
+
+/// Prefetched state of one block: accounts, and slot values keyed by
+/// `(address, slot)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedState {
+    pub accounts: BTreeMap<Address, CachedAccount>,
+    pub storage: BTreeMap<Address, BTreeMap<B256, U256>>,
+}
+
+impl CachedState {
+    pub fn slot(&self, addr: &Address, slot: U256) -> Option<U256> {
+        self.storage.get(addr)?.get(&B256::from(slot)).copied()
+    }
+
+    pub fn insert_slot(&mut self, addr: Address, slot: U256, value: U256) {
+        self.storage
+            .entry(addr)
+            .or_default()
+            .insert(B256::from(slot), value);
+    }
+
+    /// Number of accounts plus slots.
+    pub fn len(&self) -> usize {
+        self.accounts.len() + self.storage.values().map(BTreeMap::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Load every entry into `db`.
+    pub(crate) fn insert_into(&self, db: &mut WarmCacheDB, codes: &CodeCache) {
+        for (&addr, account) in &self.accounts {
+            let (code_hash, bytecode) = codes.intern_contract(addr, account.code.clone());
+            let info = AccountInfo::new(account.balance, account.nonce, code_hash, bytecode);
+            db.insert_account_info(addr, info);
+        }
+        for (&addr, slots) in &self.storage {
+            for (slot, value) in slots {
+                db.insert_account_storage(addr, U256::from_be_bytes(slot.0), *value)
+                    .ok();
+            }
+        }
+    }
+}
+
+/// Filesystem-backed RPC response cache, for one chain.
+///
+/// ```ignore
+/// let cache = Arc::new(RpcCache::new("/var/cache/argus-rpc"));
+/// let provider = FailoverProvider::connect_http(&[url])?.with_rpc_cache(cache);
+/// ```
+#[derive(Debug)]
+pub struct RpcCache {
+    dir: PathBuf,
+    chain_id: OnceCell<u64>,
+}
+
+impl RpcCache {
+    /// The chain id is asked from the node on first use (once per process).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            chain_id: OnceCell::new(),
+        }
+    }
+
+    /// Pin the chain id, so a fully cached block needs no request at all.
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = OnceCell::new_with(Some(chain_id));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File name stem for `block`, or `None` for tags.
+    pub fn block_key(block: BlockId) -> Option<String> {
+        match block {
+            BlockId::Number(BlockNumberOrTag::Number(n)) => Some(n.to_string()),
+            BlockId::Number(_) => None,
+            BlockId::Hash(hash) => Some(hash.block_hash.to_string()),
+        }
+    }
+
+    /// The pinned chain id, else the one `fetch` returns.
+    pub(crate) async fn chain_id<F, Fut>(&self, fetch: F) -> ArgusResult<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ArgusResult<u64>>,
+    {
+        self.chain_id.get_or_try_init(fetch).await.copied()
+    }
+
+    pub fn path_for(&self, chain_id: u64, kind: &str, key: &str) -> PathBuf {
+        self.dir
+            .join(chain_id.to_string())
+            .join(kind)
+            .join(format!("{key}.json"))
+    }
+
+    /// Returns `Ok(None)` on a miss. A corrupt entry is treated as a miss.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        chain_id: u64,
+        kind: &str,
+        key: &str,
+    ) -> ArgusResult<Option<T>> {
+        json_file::read(&self.path_for(chain_id, kind, key), "RPC cache entry")
+    }
+
+    /// Store an entry atomically (see [`json_file`]).
+    pub fn put<T: Serialize>(
+        &self,
+        chain_id: u64,
+        kind: &str,
+        key: &str,
+        value: &T,
+    ) -> ArgusResult<()> {
+        json_file::write(
+            &self.path_for(chain_id, kind, key),
+            value,
+            "RPC cache entry",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::database::{CacheDB, EmptyDB};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("argus-rpc-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn tags_are_never_keyed() {
+        assert_eq!(
+            RpcCache::block_key(BlockId::number(7)).as_deref(),
+            Some("7")
+        );
+        assert_eq!(RpcCache::block_key(BlockId::latest()), None);
+        let hash = B256::repeat_byte(0xab);
+        assert_eq!(
+            RpcCache::block_key(BlockId::from(hash)),
+            Some(hash.to_string())
+        );
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let dir = temp_dir("state");
+        let cache = RpcCache::new(&dir);
+        let addr = Address::with_last_byte(1);
+        let mut state = CachedState::default();
+        state.accounts.insert(
+            addr,
+            CachedAccount {
+                balance: U256::from(5),
+                nonce: 2,
+                code: Bytes::from_static(&[0x60, 0x00]),
+            },
+        );
+        state.insert_slot(addr, U256::from(3), U256::from(9));
+
+        assert!(cache.get::<CachedState>(1, STATE, "7").unwrap().is_none());
+        cache.put(1, STATE, "7", &state).unwrap();
+        let loaded: CachedState = cache.get(1, STATE, "7").unwrap().unwrap();
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.slot(&addr, U256::from(3)), Some(U256::from(9)));
+        assert_eq!(loaded.len(), 2);
+        // Other chains don't share entries.
+        assert!(cache.get::<CachedState>(10, STATE, "7").unwrap().is_none());
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        loaded.insert_into(&mut db, &CodeCache::new());
+        let account = &db.cache.accounts[&addr];
+        assert_eq!(account.info.nonce, 2);
+        assert_eq!(account.storage[&U256::from(3)], U256::from(9));
+
+        std::fs::write(cache.path_for(1, STATE, "7"), b"{not json").unwrap();
+        assert!(cache.get::<CachedState>(1, STATE, "7").unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
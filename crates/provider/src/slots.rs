@@ -2,8 +2,14 @@
 //!
 //! Used by the [`Prefetcher`](super::prefetcher::Prefetcher) to proactively
 //! warm cache with high-touch storage slots before simulation.
+//!
+//! For well-known ERC-20s the slots a call will touch follow from its
+//! calldata: `transfer`, `transferFrom` and `approve` name the accounts
+//! whose balance and allowance mapping entries change
+//! ([`calldata_slots_in`]).
 
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use argus_core::chain::SlotPack;
 
 const UNISWAP_V2_SLOTS: &[U256] = &[
     U256::from_limbs([6, 0, 0, 0]),  // reserve0 + reserve1 (packed)
@@ -26,51 +32,154 @@ const ERC20_SLOTS: &[U256] = &[
     U256::from_limbs([2, 0, 0, 0]), // totalSupply (OpenZeppelin default)
 ];
 
-static KNOWN_CONTRACTS: std::sync::LazyLock<
-    std::collections::HashMap<Address, &'static [U256]>,
-> = std::sync::LazyLock::new(|| {
-    use std::collections::HashMap;
-    let mut m = HashMap::new();
-
-    // Uniswap V2 high-volume pairs
-    m.insert(
-        "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".parse::<Address>().unwrap(),
-        UNISWAP_V2_SLOTS as &[U256],
-    );
-    m.insert(
-        "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852".parse::<Address>().unwrap(),
-        UNISWAP_V2_SLOTS,
-    );
-
-    // Uniswap V3 high-volume pools
-    m.insert(
-        "0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8".parse::<Address>().unwrap(),
-        UNISWAP_V3_SLOTS,
-    );
-    m.insert(
-        "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".parse::<Address>().unwrap(),
-        UNISWAP_V3_SLOTS,
-    );
-    m.insert(
-        "0xCBCdF9626bC03E24f779434178A73a0B4bad62eD".parse::<Address>().unwrap(),
-        UNISWAP_V3_SLOTS,
-    );
-
-    m
-});
+static KNOWN_CONTRACTS: std::sync::LazyLock<std::collections::HashMap<Address, &'static [U256]>> =
+    std::sync::LazyLock::new(|| {
+        use std::collections::HashMap;
+        let mut m = HashMap::new();
+
+        // Uniswap V2 high-volume pairs
+        m.insert(
+            "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"
+                .parse::<Address>()
+                .unwrap(),
+            UNISWAP_V2_SLOTS as &[U256],
+        );
+        m.insert(
+            "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852"
+                .parse::<Address>()
+                .unwrap(),
+            UNISWAP_V2_SLOTS,
+        );
+
+        // Uniswap V3 high-volume pools
+        m.insert(
+            "0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8"
+                .parse::<Address>()
+                .unwrap(),
+            UNISWAP_V3_SLOTS,
+        );
+        m.insert(
+            "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"
+                .parse::<Address>()
+                .unwrap(),
+            UNISWAP_V3_SLOTS,
+        );
+        m.insert(
+            "0xCBCdF9626bC03E24f779434178A73a0B4bad62eD"
+                .parse::<Address>()
+                .unwrap(),
+            UNISWAP_V3_SLOTS,
+        );
+
+        m
+    });
+
+/// Storage positions of an ERC-20's `balanceOf` and `allowance` mappings.
+#[derive(Debug, Clone, Copy)]
+struct TokenLayout {
+    balances: u64,
+    allowances: u64,
+}
+
+static KNOWN_TOKENS: std::sync::LazyLock<std::collections::HashMap<Address, TokenLayout>> =
+    std::sync::LazyLock::new(|| {
+        let token = |addr: &str, balances, allowances| {
+            (
+                addr.parse::<Address>().unwrap(),
+                TokenLayout {
+                    balances,
+                    allowances,
+                },
+            )
+        };
+        [
+            // USDC (FiatTokenV2 behind a proxy)
+            token("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 9, 10),
+            // USDT
+            token("0xdAC17F958D2ee523a2206206994597C13D831ec7", 2, 5),
+            // WETH9
+            token("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 3, 4),
+            // DAI
+            token("0x6B175474E89094C44Da98b954EedeAC495271d0F", 2, 3),
+        ]
+        .into_iter()
+        .collect()
+    });
+
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+
+/// Slot of `key`'s entry in the Solidity mapping at `slot`:
+/// `keccak256(key . slot)`.
+pub fn mapping_slot(key: B256, slot: U256) -> U256 {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(key.as_slice());
+    preimage[32..].copy_from_slice(&slot.to_be_bytes::<32>());
+    keccak256(preimage).into()
+}
+
+/// Balance and allowance slots a call from `from` to known token `to`
+/// with `input` will touch. Empty for other contracts, other functions and
+/// malformed calldata.
+pub fn calldata_slots_in(pack: SlotPack, to: &Address, from: &Address, input: &[u8]) -> Vec<U256> {
+    let layout = match pack {
+        SlotPack::Ethereum => KNOWN_TOKENS.get(to),
+        SlotPack::None => None,
+    };
+    let (Some(layout), Some(selector)) = (layout, input.get(..4)) else {
+        return Vec::new();
+    };
+    // The n-th argument, as an address.
+    let arg = |n: usize| {
+        input
+            .get(4 + 32 * n..4 + 32 * (n + 1))
+            .map(|word| B256::from_slice(word))
+    };
+    let balance = |owner: B256| mapping_slot(owner, U256::from(layout.balances));
+    let allowance = |owner: B256, spender: B256| {
+        mapping_slot(spender, mapping_slot(owner, U256::from(layout.allowances)))
+    };
+    let from = from.into_word();
+    match selector {
+        s if s == TRANSFER => arg(0)
+            .map(|recipient| vec![balance(from), balance(recipient)])
+            .unwrap_or_default(),
+        s if s == TRANSFER_FROM => match (arg(0), arg(1)) {
+            (Some(owner), Some(recipient)) => {
+                vec![balance(owner), balance(recipient), allowance(owner, from)]
+            }
+            _ => Vec::new(),
+        },
+        s if s == APPROVE => arg(0)
+            .map(|spender| vec![allowance(from, spender)])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
 
 /// Returns known hot storage slots for a contract, if any.
 pub fn known_slots(address: &Address) -> Option<&'static [U256]> {
     KNOWN_CONTRACTS.get(address).copied()
 }
 
+/// Returns known hot storage slots for a contract in `pack`, if any.
+pub fn known_slots_in(pack: SlotPack, address: &Address) -> Option<&'static [U256]> {
+    match pack {
+        SlotPack::Ethereum => known_slots(address),
+        SlotPack::None => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn known_uniswap_v3_pool() {
-        let usdc_weth: Address = "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".parse().unwrap();
+        let usdc_weth: Address = "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"
+            .parse()
+            .unwrap();
         let slots = known_slots(&usdc_weth).unwrap();
         assert_eq!(slots.len(), 5);
         assert_eq!(slots[0], U256::ZERO); // slot0
@@ -80,4 +189,53 @@ mod tests {
     fn unknown_address_returns_none() {
         assert!(known_slots(&Address::ZERO).is_none());
     }
+
+    #[test]
+    fn token_calls_name_balance_and_allowance_slots() {
+        let usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            .parse()
+            .unwrap();
+        let sender = Address::repeat_byte(0x11);
+        let other = Address::repeat_byte(0x22);
+        let call = |selector: [u8; 4], args: &[Address]| {
+            let mut input = selector.to_vec();
+            for arg in args {
+                input.extend_from_slice(arg.into_word().as_slice());
+            }
+            input.extend_from_slice(&[0u8; 32]); // amount
+            input
+        };
+        let balance = |a: Address| mapping_slot(a.into_word(), U256::from(9));
+        let allowance = |owner: Address, spender: Address| {
+            mapping_slot(
+                spender.into_word(),
+                mapping_slot(owner.into_word(), U256::from(10)),
+            )
+        };
+
+        let slots = |input: &[u8]| calldata_slots_in(SlotPack::Ethereum, &usdc, &sender, input);
+        assert_eq!(
+            slots(&call(TRANSFER, &[other])),
+            [balance(sender), balance(other)]
+        );
+        assert_eq!(
+            slots(&call(TRANSFER_FROM, &[other, usdc])),
+            [balance(other), balance(usdc), allowance(other, sender)]
+        );
+        assert_eq!(slots(&call(APPROVE, &[other])), [allowance(sender, other)]);
+        assert!(slots(&TRANSFER).is_empty());
+        assert!(slots(&call([0xde, 0xad, 0xbe, 0xef], &[other])).is_empty());
+        let input = call(TRANSFER, &[other]);
+        assert!(calldata_slots_in(SlotPack::Ethereum, &other, &sender, &input).is_empty());
+        assert!(calldata_slots_in(SlotPack::None, &usdc, &sender, &input).is_empty());
+    }
+
+    #[test]
+    fn empty_pack_has_no_slots() {
+        let usdc_weth: Address = "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"
+            .parse()
+            .unwrap();
+        assert!(known_slots_in(SlotPack::Ethereum, &usdc_weth).is_some());
+        assert!(known_slots_in(SlotPack::None, &usdc_weth).is_none());
+    }
 }
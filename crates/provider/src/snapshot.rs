@@ -0,0 +1,283 @@
+//! Save and restore a prefetched [`WarmCacheDB`].
+//!
+//! Prefetch is the slowest stage and deterministic for historical blocks, so
+//! a snapshot taken once can back any number of simulation runs.
+//!
+//! Binary layout (big-endian, accounts and slots sorted for stable output):
+//!
+//! ```text
+//! magic "ARGS" | version u8 | block_number u64 | n_accounts u32
+//! per account:
+//!   address [20] | balance [32] | nonce u64 | code_hash [32]
+//!   code_len u32 | code [code_len]
+//!   n_slots u32 | (slot [32] | value [32]) * n_slots
+//! ```
+
+use crate::code::CodeInterner;
+use crate::WarmCacheDB;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use argus_core::error::{ArgusError, ArgusResult};
+use revm::database::{CacheDB, EmptyDB};
+use revm::state::{AccountInfo, Bytecode};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ARGS";
+const FORMAT_VERSION: u8 = 1;
+
+/// Write `db` as a snapshot of `block_number`.
+pub fn write_snapshot<W: Write>(db: &WarmCacheDB, block_number: u64, w: W) -> ArgusResult<()> {
+    let mut w = BufWriter::new(w);
+    let mut accounts: Vec<_> = db.cache.accounts.iter().collect();
+    accounts.sort_unstable_by_key(|(addr, _)| **addr);
+
+    w.write_all(MAGIC).map_err(io_err)?;
+    w.write_all(&[FORMAT_VERSION]).map_err(io_err)?;
+    w.write_all(&block_number.to_be_bytes()).map_err(io_err)?;
+    write_len(&mut w, accounts.len())?;
+
+    for (addr, account) in accounts {
+        let info = &account.info;
+        let code = info
+            .code
+            .as_ref()
+            .map(Bytecode::original_bytes)
+            .unwrap_or_default();
+
+        w.write_all(addr.as_slice()).map_err(io_err)?;
+        w.write_all(&info.balance.to_be_bytes::<32>())
+            .map_err(io_err)?;
+        w.write_all(&info.nonce.to_be_bytes()).map_err(io_err)?;
+        w.write_all(info.code_hash.as_slice()).map_err(io_err)?;
+        write_len(&mut w, code.len())?;
+        w.write_all(&code).map_err(io_err)?;
+
+        let mut slots: Vec<_> = account.storage.iter().collect();
+        slots.sort_unstable_by_key(|(slot, _)| **slot);
+        write_len(&mut w, slots.len())?;
+        for (slot, value) in slots {
+            w.write_all(&slot.to_be_bytes::<32>()).map_err(io_err)?;
+            w.write_all(&value.to_be_bytes::<32>()).map_err(io_err)?;
+        }
+    }
+
+    w.flush().map_err(io_err)
+}
+
+/// Read a snapshot, returning its block number and the restored state.
+pub fn read_snapshot<R: Read>(r: R) -> ArgusResult<(u64, WarmCacheDB)> {
+    let mut r = BufReader::new(r);
+
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic).map_err(io_err)?;
+    if &magic != MAGIC {
+        return Err(ArgusError::InvalidInput(
+            "not an argus state snapshot".into(),
+        ));
+    }
+    let version = read_array::<1>(&mut r)?[0];
+    if version != FORMAT_VERSION {
+        return Err(ArgusError::InvalidInput(format!(
+            "unsupported snapshot version {version}"
+        )));
+    }
+
+    let block_number = u64::from_be_bytes(read_array(&mut r)?);
+    let n_accounts = read_len(&mut r)?;
+
+    let mut db = CacheDB::new(EmptyDB::new());
+    let mut codes = CodeInterner::new();
+    for _ in 0..n_accounts {
+        let addr = Address::from(read_array::<20>(&mut r)?);
+        let balance = U256::from_be_bytes(read_array::<32>(&mut r)?);
+        let nonce = u64::from_be_bytes(read_array(&mut r)?);
+        let code_hash = B256::from(read_array::<32>(&mut r)?);
+
+        let code = Bytes::from(read_bytes(&mut r, read_len(&mut r)?)?);
+        if keccak256(&code) != code_hash {
+            return Err(ArgusError::InvalidInput(format!(
+                "code of {addr} does not hash to its code_hash {code_hash}"
+            )));
+        }
+        let bytecode = codes
+            .try_intern_with_hash(code_hash, code)
+            .map_err(|e| ArgusError::InvalidInput(format!("code of {addr}: {e}")))?;
+        db.insert_account_info(addr, AccountInfo::new(balance, nonce, code_hash, bytecode));
+
+        for _ in 0..read_len(&mut r)? {
+            let slot = U256::from_be_bytes(read_array::<32>(&mut r)?);
+            let value = U256::from_be_bytes(read_array::<32>(&mut r)?);
+            db.insert_account_storage(addr, slot, value).ok();
+        }
+    }
+
+    Ok((block_number, db))
+}
+
+/// Write a snapshot to `path`, creating or truncating the file.
+pub fn save_snapshot(db: &WarmCacheDB, block_number: u64, path: &Path) -> ArgusResult<()> {
+    let file = std::fs::File::create(path).map_err(io_err)?;
+    write_snapshot(db, block_number, file)?;
+    tracing::info!(
+        path = %path.display(),
+        block_number,
+        accounts = db.cache.accounts.len(),
+        "state snapshot saved"
+    );
+    Ok(())
+}
+
+/// Read a snapshot from `path`.
+pub fn load_snapshot(path: &Path) -> ArgusResult<(u64, WarmCacheDB)> {
+    let file = std::fs::File::open(path).map_err(io_err)?;
+    let (block_number, db) = read_snapshot(file)?;
+    tracing::info!(
+        path = %path.display(),
+        block_number,
+        accounts = db.cache.accounts.len(),
+        "state snapshot loaded"
+    );
+    Ok((block_number, db))
+}
+
+fn write_len<W: Write>(w: &mut W, len: usize) -> ArgusResult<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| ArgusError::Internal(format!("snapshot section too large: {len}")))?;
+    w.write_all(&len.to_be_bytes()).map_err(io_err)
+}
+
+fn read_len<R: Read>(r: &mut R) -> ArgusResult<usize> {
+    Ok(u32::from_be_bytes(read_array(r)?) as usize)
+}
+
+/// Read `len` bytes, failing if fewer are left. The buffer only grows as
+/// data arrives, so a corrupt length can't allocate past the input's end.
+fn read_bytes<R: Read>(r: &mut R, len: usize) -> ArgusResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    r.take(len as u64).read_to_end(&mut buf).map_err(io_err)?;
+    if buf.len() < len {
+        return Err(ArgusError::InvalidInput(format!(
+            "truncated snapshot: code_len {len} but {} bytes left",
+            buf.len()
+        )));
+    }
+    Ok(buf)
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> ArgusResult<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(buf)
+}
+
+fn io_err(e: std::io::Error) -> ArgusError {
+    ArgusError::Internal(format!("state snapshot I/O: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::database::DatabaseRef;
+
+    #[test]
+    fn roundtrip_preserves_accounts_and_storage() {
+        let addr = Address::repeat_byte(0xAB);
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x54]));
+        let info = AccountInfo::new(U256::from(1_000), 7, code.hash_slow(), code);
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(addr, info);
+        db.insert_account_storage(addr, U256::from(3), U256::from(42))
+            .unwrap();
+        db.insert_account_info(
+            Address::repeat_byte(0x01),
+            AccountInfo::new(U256::ZERO, 0, Default::default(), Bytecode::default()),
+        );
+
+        let mut buf = Vec::new();
+        write_snapshot(&db, 21_000_000, &mut buf).unwrap();
+        let (block, restored) = read_snapshot(buf.as_slice()).unwrap();
+
+        assert_eq!(block, 21_000_000);
+        assert_eq!(restored.cache.accounts.len(), 2);
+        let got = restored.basic_ref(addr).unwrap().unwrap();
+        assert_eq!(got.balance, U256::from(1_000));
+        assert_eq!(got.nonce, 7);
+        assert_eq!(
+            restored.storage_ref(addr, U256::from(3)).unwrap(),
+            U256::from(42)
+        );
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        let mut db = CacheDB::new(EmptyDB::new());
+        for b in [3u8, 1, 2] {
+            let addr = Address::repeat_byte(b);
+            db.insert_account_info(addr, AccountInfo::default());
+            db.insert_account_storage(addr, U256::from(b), U256::from(1))
+                .unwrap();
+        }
+
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        write_snapshot(&db, 1, &mut a).unwrap();
+        write_snapshot(&db.clone(), 1, &mut b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        assert!(matches!(
+            read_snapshot(&b"NOPE\x01"[..]),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_code_past_the_end() {
+        let mut buf = Vec::new();
+        write_snapshot(&CacheDB::new(EmptyDB::new()), 1, &mut buf).unwrap();
+        // One account whose code_len claims far more than follows.
+        buf[13..17].copy_from_slice(&1u32.to_be_bytes());
+        buf.extend_from_slice(&[0; 20 + 32 + 8 + 32]);
+        buf.extend_from_slice(&u32::MAX.to_be_bytes());
+        buf.extend_from_slice(&[0x60, 0x00]);
+        assert!(matches!(
+            read_snapshot(buf.as_slice()),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    /// A snapshot holding one account with `code` under `code_hash`.
+    fn with_code(code_hash: B256, code: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_snapshot(&CacheDB::new(EmptyDB::new()), 1, &mut buf).unwrap();
+        buf[13..17].copy_from_slice(&1u32.to_be_bytes());
+        buf.extend_from_slice(&[0; 20 + 32 + 8]);
+        buf.extend_from_slice(code_hash.as_slice());
+        buf.extend_from_slice(&(code.len() as u32).to_be_bytes());
+        buf.extend_from_slice(code);
+        buf.extend_from_slice(&0u32.to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn rejects_code_that_does_not_match_its_hash() {
+        let buf = with_code(keccak256([0x00]), &[0x60, 0x00]);
+        assert!(matches!(
+            read_snapshot(buf.as_slice()),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_delegation() {
+        // EIP-7702 magic, but not a 23-byte designator.
+        let code = [0xef, 0x01, 0x00, 0x11];
+        let buf = with_code(keccak256(code), &code);
+        assert!(matches!(
+            read_snapshot(buf.as_slice()),
+            Err(ArgusError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Export a [`WarmCacheDB`] as JSON state for other EVM tools.
+//!
+//! Lets the exact state Argus simulated against be loaded elsewhere to
+//! cross-validate results:
+//!
+//! - [`StateFormat::Anvil`]: `anvil --load-state state.json`
+//! - [`StateFormat::Alloc`]: a genesis `alloc` map, e.g. for
+//!   `vm.loadAllocs` in Foundry tests or a custom `genesis.json`.
+//!
+//! Accounts and slots are sorted, so the same state always produces the
+//! same bytes. Storage keys and values are written as 32-byte hex.
+
+use crate::WarmCacheDB;
+use alloy_primitives::{Bytes, B256};
+use argus_core::error::{ArgusError, ArgusResult};
+use revm::state::Bytecode;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+/// Layout of the exported JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateFormat {
+    /// `{"accounts": {addr: {nonce, balance, code, storage}}}`, nonce as a number.
+    Anvil,
+    /// `{addr: {nonce, balance, code, storage}}`, nonce as a hex quantity.
+    Alloc,
+}
+
+impl FromStr for StateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "anvil" => Ok(Self::Anvil),
+            "alloc" => Ok(Self::Alloc),
+            other => Err(format!(
+                "unknown state format {other:?} (expected anvil or alloc)"
+            )),
+        }
+    }
+}
+
+/// Write `db` as JSON in `format`.
+pub fn write_state_json<W: Write>(db: &WarmCacheDB, format: StateFormat, w: W) -> ArgusResult<()> {
+    let mut w = BufWriter::new(w);
+    let mut accounts: Vec<_> = db.cache.accounts.iter().collect();
+    accounts.sort_unstable_by_key(|(addr, _)| **addr);
+
+    let indent = match format {
+        StateFormat::Anvil => {
+            w.write_all(b"{\n  \"accounts\": {").map_err(io_err)?;
+            "    "
+        }
+        StateFormat::Alloc => {
+            w.write_all(b"{").map_err(io_err)?;
+            "  "
+        }
+    };
+
+    for (i, (addr, account)) in accounts.into_iter().enumerate() {
+        let info = &account.info;
+        let code = info
+            .code
+            .as_ref()
+            .map(Bytecode::original_bytes)
+            .unwrap_or_default();
+        let nonce = match format {
+            StateFormat::Anvil => info.nonce.to_string(),
+            StateFormat::Alloc => format!("\"{:#x}\"", info.nonce),
+        };
+        let sep = if i == 0 { "" } else { "," };
+        write!(
+            w,
+            "{sep}\n{indent}\"{addr:?}\": {{\n\
+             {indent}  \"nonce\": {nonce},\n\
+             {indent}  \"balance\": \"{:#x}\",\n\
+             {indent}  \"code\": \"{}\",\n\
+             {indent}  \"storage\": {{",
+            info.balance,
+            Bytes::from(code),
+        )
+        .map_err(io_err)?;
+
+        let mut slots: Vec<_> = account.storage.iter().collect();
+        slots.sort_unstable_by_key(|(slot, _)| **slot);
+        for (j, (slot, value)) in slots.into_iter().enumerate() {
+            let sep = if j == 0 { "" } else { "," };
+            write!(
+                w,
+                "{sep}\n{indent}    \"{}\": \"{}\"",
+                B256::from(*slot),
+                B256::from(*value)
+            )
+            .map_err(io_err)?;
+        }
+        if !account.storage.is_empty() {
+            write!(w, "\n{indent}  ").map_err(io_err)?;
+        }
+        write!(w, "}}\n{indent}}}").map_err(io_err)?;
+    }
+
+    match format {
+        StateFormat::Anvil => w.write_all(b"\n  }\n}\n"),
+        StateFormat::Alloc => w.write_all(b"\n}\n"),
+    }
+    .map_err(io_err)?;
+    w.flush().map_err(io_err)
+}
+
+fn io_err(e: std::io::Error) -> ArgusError {
+    ArgusError::Internal(format!("state export I/O: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use revm::database::{CacheDB, EmptyDB};
+    use revm::state::AccountInfo;
+
+    fn db() -> WarmCacheDB {
+        let mut db = CacheDB::new(EmptyDB::new());
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        db.insert_account_info(
+            Address::with_last_byte(2),
+            AccountInfo::new(U256::from(255), 7, code.hash_slow(), code),
+        );
+        db.insert_account_storage(Address::with_last_byte(2), U256::from(1), U256::from(16))
+            .unwrap();
+        db.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo::from_balance(U256::from(1)),
+        );
+        db
+    }
+
+    fn export(format: StateFormat) -> String {
+        let mut out = Vec::new();
+        write_state_json(&db(), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn anvil_layout_is_sorted_and_padded() {
+        let json = export(StateFormat::Anvil);
+        assert!(json.starts_with("{\n  \"accounts\": {"));
+        let first = json.find("0x0000000000000000000000000000000000000001");
+        let second = json.find("0x0000000000000000000000000000000000000002");
+        assert!(first < second);
+        assert!(json.contains("\"nonce\": 7,"));
+        assert!(json.contains("\"balance\": \"0xff\","));
+        assert!(json.contains("\"code\": \"0x6000\","));
+        assert!(json.contains(&format!(
+            "\"{}\": \"{}\"",
+            B256::with_last_byte(1),
+            B256::with_last_byte(16)
+        )));
+    }
+
+    #[test]
+    fn alloc_uses_hex_nonce_at_top_level() {
+        let json = export(StateFormat::Alloc);
+        assert!(!json.contains("accounts"));
+        assert!(json.contains("\"nonce\": \"0x7\","));
+        assert_eq!("alloc".parse::<StateFormat>(), Ok(StateFormat::Alloc));
+    }
+}
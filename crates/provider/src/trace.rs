@@ -0,0 +1,198 @@
+//! Block traces from a node's debug API (geth's `prestateTracer`).
+//!
+//! Used to prefetch exactly the state a block touches
+//! ([`PrefetchStrategy::Prestate`](crate::PrefetchStrategy::Prestate)) and to
+//! derive access lists without local simulation
+//! ([`DataProvider::trace_access_lists`](crate::DataProvider::trace_access_lists)).
+
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_provider::{DynProvider, Provider};
+use argus_core::{AccessEntry, AccessList, AccessMode, StorageLocation};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// `prestateTracer` output for one transaction of a block trace.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TxTrace<T> {
+    #[serde(default)]
+    pub tx_hash: Option<B256>,
+    #[serde(default)]
+    pub result: Option<T>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Accounts as they were before the transaction, in default mode.
+pub(crate) type Prestate = BTreeMap<Address, PrestateAccount>;
+
+/// Accounts the transaction modified, before and after, in diff mode.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PrestateDiff {
+    #[serde(default)]
+    pub pre: Prestate,
+    #[serde(default)]
+    pub post: Prestate,
+}
+
+/// One account in a prestate trace. Zero fields are omitted.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PrestateAccount {
+    #[serde(default)]
+    pub balance: Option<U256>,
+    #[serde(default)]
+    pub nonce: Option<u64>,
+    #[serde(default)]
+    pub code: Option<Bytes>,
+    #[serde(default)]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TracerOptions {
+    tracer: &'static str,
+    tracer_config: TracerConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TracerConfig {
+    diff_mode: bool,
+}
+
+/// Pre-state of everything each transaction touches.
+pub(crate) const PRESTATE: TracerOptions = TracerOptions {
+    tracer: "prestateTracer",
+    tracer_config: TracerConfig { diff_mode: false },
+};
+
+/// Pre- and post-state of what each transaction modifies.
+pub(crate) const PRESTATE_DIFF: TracerOptions = TracerOptions {
+    tracer: "prestateTracer",
+    tracer_config: TracerConfig { diff_mode: true },
+};
+
+/// `debug_traceBlockByHash` or `debug_traceBlockByNumber` with `options`.
+pub(crate) async fn debug_trace_block<T>(
+    p: &DynProvider,
+    block: BlockId,
+    options: TracerOptions,
+) -> Result<Vec<TxTrace<T>>, String>
+where
+    T: DeserializeOwned + Debug + Send + Sync + Unpin + 'static,
+{
+    match block {
+        BlockId::Hash(hash) => {
+            p.raw_request("debug_traceBlockByHash".into(), (hash.block_hash, options))
+                .await
+        }
+        BlockId::Number(number) => {
+            p.raw_request("debug_traceBlockByNumber".into(), (number, options))
+                .await
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Per-transaction access lists from a block's default and diff-mode
+/// prestate traces: every slot in the prestate was accessed, and the ones
+/// in the diff were written. A write that leaves a slot unchanged shows up
+/// as a read. Transactions whose trace failed are skipped.
+pub(crate) fn access_lists(
+    touched: Vec<TxTrace<Prestate>>,
+    diffs: Vec<TxTrace<PrestateDiff>>,
+) -> Result<Vec<AccessList>, String> {
+    if touched.len() != diffs.len() {
+        return Err(format!(
+            "trace lengths differ: {} prestate vs {} diff",
+            touched.len(),
+            diffs.len()
+        ));
+    }
+
+    let mut lists = Vec::with_capacity(touched.len());
+    for (i, (touched, diff)) in touched.into_iter().zip(diffs).enumerate() {
+        let tx_hash = touched
+            .tx_hash
+            .ok_or_else(|| format!("trace {i} has no txHash (node too old?)"))?;
+        let (Some(touched), Some(diff)) = (touched.result, diff.result) else {
+            tracing::warn!(%tx_hash, "skipping transaction whose trace failed");
+            continue;
+        };
+
+        let mut entries: BTreeMap<StorageLocation, AccessMode> = BTreeMap::new();
+        for (address, account) in touched {
+            for slot in account.storage.into_keys() {
+                entries.insert(StorageLocation { address, slot }, AccessMode::Read);
+            }
+        }
+        for (address, account) in diff.pre.into_iter().chain(diff.post) {
+            for slot in account.storage.into_keys() {
+                entries.insert(StorageLocation { address, slot }, AccessMode::Write);
+            }
+        }
+        lists.push(AccessList {
+            tx_hash,
+            entries: entries
+                .into_iter()
+                .map(|(location, mode)| AccessEntry {
+                    location,
+                    mode,
+                    count: 1,
+                })
+                .collect(),
+            transient: Default::default(),
+            logs: Vec::new(),
+            outcome: Default::default(),
+            gas_used: 0,
+            writes: Vec::new(),
+        });
+    }
+    Ok(lists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_writes() {
+        let touched: Vec<TxTrace<Prestate>> = serde_json::from_str(
+            r#"[{"txHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                 "result": {"0x0000000000000000000000000000000000000001": {"balance": "0x0",
+                    "storage": {"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                                "0x0000000000000000000000000000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000000000000000000000000000005"}}}},
+                {"txHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                 "error": "execution timeout"}]"#,
+        )
+        .unwrap();
+        let diffs: Vec<TxTrace<PrestateDiff>> = serde_json::from_str(
+            r#"[{"txHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                 "result": {"pre": {"0x0000000000000000000000000000000000000001": {
+                    "storage": {"0x0000000000000000000000000000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000000000000000000000000000005"}}},
+                            "post": {}}},
+                {"txHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                 "error": "execution timeout"}]"#,
+        )
+        .unwrap();
+
+        let lists = access_lists(touched, diffs).unwrap();
+        assert_eq!(lists.len(), 1);
+        let modes: Vec<_> = lists[0]
+            .entries
+            .iter()
+            .map(|e| (e.location.slot, e.mode))
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                (B256::with_last_byte(1), AccessMode::Read),
+                (B256::with_last_byte(2), AccessMode::Write),
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Python bindings for the Argus conflict analyzer.
+//!
+//! Build with `maturin develop -m crates/py/Cargo.toml`, then:
+//!
+//! ```python
+//! import argus
+//! a = argus.analyze_block("https://eth.llamarpc.com", 21_000_000)
+//! a.summary()                   # dict
+//! a.contention_events()         # list[dict]
+//! a.contention_events_df()      # pandas.DataFrame
+//! ```
+//!
+//! Rows cross the boundary as plain dicts (via the sink row schemas), so the
+//! Python side sees the same columns as the NDJSON sink.
+
+use argus::{AccessList, ConflictGraph, Report};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+/// Result of analyzing one block.
+#[pyclass(module = "argus", frozen)]
+struct Analysis {
+    access_lists: Vec<AccessList>,
+    graph: ConflictGraph,
+    report: Report,
+}
+
+#[pymethods]
+impl Analysis {
+    #[getter]
+    fn block_number(&self) -> u64 {
+        self.report.block_number
+    }
+
+    #[getter]
+    fn total_conflicts(&self) -> usize {
+        self.report.total_conflicts
+    }
+
+    /// Block summary row as a dict.
+    fn summary(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (summary, _) = self.report.to_rows();
+        to_py(py, &summary)
+    }
+
+    /// Per-tx access lists: `[{"tx_hash": ..., "entries": [...]}, ...]`.
+    fn access_lists(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.access_lists)
+    }
+
+    /// One dict per conflict edge (denormalized, labeled).
+    fn conflicts(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (_, rows) = self.report.to_rows_from_graph(&self.graph);
+        to_py(py, &rows)
+    }
+
+    /// One dict per (contract, slot, hazard) with density and severity.
+    fn contention_events(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.report.to_contention_events(&self.graph))
+    }
+
+    /// `conflicts()` as a pandas DataFrame (requires pandas).
+    fn conflicts_df(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_dataframe(py, self.conflicts(py)?)
+    }
+
+    /// `contention_events()` as a pandas DataFrame (requires pandas).
+    fn contention_events_df(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_dataframe(py, self.contention_events(py)?)
+    }
+
+    /// The rendered text report, as printed by the CLI.
+    fn render(&self) -> String {
+        self.report.render(&self.graph)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Analysis(block_number={}, txs={}, conflicts={})",
+            self.report.block_number, self.report.total_txs, self.report.total_conflicts
+        )
+    }
+}
+
+/// Fetch, simulate, and analyze a block. Releases the GIL while running.
+#[pyfunction]
+#[pyo3(signature = (rpc_url, block, dry_run = false))]
+fn analyze_block(py: Python<'_>, rpc_url: String, block: u64, dry_run: bool) -> PyResult<Analysis> {
+    py.allow_threads(|| {
+        let rt = tokio::runtime::Runtime::new()
+            .map_err(|e| PyRuntimeError::new_err(format!("tokio runtime: {e}")))?;
+        let argus = argus::Argus::builder()
+            .rpc_url(rpc_url)
+            .dry_run(dry_run)
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        let result = rt
+            .block_on(argus.analyze_block(block))
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(Analysis {
+            access_lists: result.access_lists,
+            graph: result.graph,
+            report: result.report,
+        })
+    })
+}
+
+/// Serialize through JSON into native Python objects.
+fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| PyRuntimeError::new_err(format!("serialize: {e}")))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn to_dataframe(py: Python<'_>, records: PyObject) -> PyResult<PyObject> {
+    Ok(py
+        .import("pandas")?
+        .getattr("DataFrame")?
+        .call_method1("from_records", (records,))?
+        .unbind())
+}
+
+#[pymodule]
+#[pyo3(name = "argus")]
+fn argus_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(analyze_block, m)?)?;
+    m.add_class::<Analysis>()?;
+    Ok(())
+}